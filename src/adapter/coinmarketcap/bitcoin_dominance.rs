@@ -1,40 +1,86 @@
 use super::prelude::*;
+use crate::config::live::LiveConfig;
 
-fn signal_info() -> SignalInfo {
+pub fn signal_info() -> SignalInfo {
     SignalInfo {
-        id: 2,
+        id: SignalInfo::UNREGISTERED,
         signal_type: "btc_dominance".to_string(),
         data_type: SignalDataType::Scalar,
         source: SOURCE.to_string(),
         description: "Bitcoin Dominance Index".to_string(),
         is_atomic: true,
+        parameters: Default::default(),
     }
 }
 
-// TODO set up the global source to replace the config
-pub async fn run_bitcoin_dominance(
-    config: Config,
-    period: tokio::time::Duration,
-) -> AnySignalResult<()> {
-    let signal = signal_info();
-    let mut interval = tokio::time::interval(period);
-    let client = reqwest::Client::new();
-    let url = "https://pro-api.coinmarketcap.com/v1/global-metrics/quotes/latest";
+/// Polls the latest global metrics every `coinmarketcap_poll_interval_secs`;
+/// each poll yields one row stamped with the quote's `last_updated`.
+pub struct BitcoinDominanceSignalSource {
+    api_key: String,
+    config: LiveConfig,
+}
+
+impl BitcoinDominanceSignalSource {
+    pub fn new(api_key: String, config: LiveConfig) -> Self {
+        Self { api_key, config }
+    }
+}
 
-    let key_cmc = config.get_api_key("coinmarketcap")?;
+impl PollingSignalSource for BitcoinDominanceSignalSource {
+    fn get_signal_info(&self) -> SignalInfo {
+        signal_info()
+    }
 
-    loop {
-        interval.tick().await; // Wait for the next tick
+    fn poll_interval_duration(&self) -> Duration {
+        Duration::from_secs(self.config.get().coinmarketcap_poll_interval_secs)
+    }
 
-        let _response = client
+    async fn get_signals(&self) -> AnySignalResult<Vec<Signal>> {
+        let url = "https://pro-api.coinmarketcap.com/v1/global-metrics/quotes/latest";
+        let response = reqwest::Client::new()
             .get(url)
-            .header(KEY, &key_cmc)
+            .header(KEY, &self.api_key)
             .send()
             .await?
-            .text()
+            .error_for_status()?
+            .json::<GlobalMetricsResponse>()
             .await?;
+        Ok(vec![response.data.signal()])
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GlobalMetricsResponse {
+    data: GlobalMetricsData,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GlobalMetricsData {
+    btc_dominance: f64,
+    last_updated: chrono::DateTime<chrono::Utc>,
+}
+
+impl GlobalMetricsData {
+    fn signal(&self) -> Signal {
+        Signal {
+            info_id: SignalInfo::UNREGISTERED,
+            timestamp_us: self.last_updated.timestamp_micros(),
+            data: SignalData::Scalar(self.btc_dominance),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // write a parser for the response
-        tracing::debug!(description = ?signal.description, "bitcoin dominance tick");
+    #[test]
+    fn parses_global_metrics() {
+        let body = r#"{"status":{"error_code":0},"data":{"btc_dominance":54.21,
+            "eth_dominance":17.3,"last_updated":"2024-11-18T00:04:59.999Z"}}"#;
+        let response: GlobalMetricsResponse = serde_json::from_str(body).unwrap();
+        let signal = response.data.signal();
+        assert_eq!(signal.data, SignalData::Scalar(54.21));
+        assert_eq!(signal.timestamp_us, 1_731_888_299_999_000);
     }
 }
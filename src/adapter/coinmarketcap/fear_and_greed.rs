@@ -1,4 +1,5 @@
 use super::prelude::*;
+use crate::config::live::LiveConfig;
 use crate::model::signal::{Signal, SignalData};
 use rand::Rng;
 use serde::Deserialize;
@@ -6,38 +7,38 @@ use tokio::time::Duration;
 
 // TODO generalize into a SignalFetcher Trait and implement the fetcher for each signal
 
-fn fear_and_greed_signal_info() -> SignalInfo {
+pub fn signal_info() -> SignalInfo {
     SignalInfo {
-        id: 0,
+        id: SignalInfo::UNREGISTERED,
         signal_type: "fear_and_greed".to_string(),
         data_type: SignalDataType::Scalar,
         source: SOURCE.to_string(),
         description: "Fear and Greed Index".to_string(),
         is_atomic: true,
+        parameters: Default::default(),
     }
 }
 
+/// Polls the index every `coinmarketcap_poll_interval_secs`, re-read from
+/// the live config on each iteration.
 pub struct FearAndGreedSignalSource {
     api_key: String,
-    poll_interval_duration: Duration,
+    config: LiveConfig,
 }
 
 impl FearAndGreedSignalSource {
-    pub fn new(api_key: String, poll_interval_duration: Duration) -> Self {
-        Self {
-            api_key,
-            poll_interval_duration,
-        }
+    pub fn new(api_key: String, config: LiveConfig) -> Self {
+        Self { api_key, config }
     }
 }
 /// set up polling signal source trait
 impl PollingSignalSource for FearAndGreedSignalSource {
     fn get_signal_info(&self) -> SignalInfo {
-        fear_and_greed_signal_info()
+        signal_info()
     }
 
     fn poll_interval_duration(&self) -> Duration {
-        self.poll_interval_duration
+        Duration::from_secs(self.config.get().coinmarketcap_poll_interval_secs)
     }
 
     async fn get_signals(&self) -> AnySignalResult<Vec<Signal>> {
@@ -48,7 +49,7 @@ impl PollingSignalSource for FearAndGreedSignalSource {
             let signal = Signal {
                 timestamp_us: data.timestamp_micros().unwrap_or_default(),
                 data: SignalData::Scalar(data.value),
-                info_id: self.get_signal_info().id,
+                info_id: SignalInfo::UNREGISTERED,
             };
             signals.push(signal);
         }
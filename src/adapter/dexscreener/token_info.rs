@@ -128,7 +128,7 @@ mod tests {
         use super::*;
         let chain_id = "solana";
         let token_id = "6p6xgHyF7AeE6TZkSmFsko444wqoP15icUSqi2jfGiPN";
-        let result = fetch_token_info(&chain_id, &token_id).await.unwrap();
+        let result = fetch_token_info(chain_id, token_id).await.unwrap();
         assert!(!result.is_empty());
         println!("result: {:#?}", result.len())
     }
@@ -137,7 +137,7 @@ mod tests {
     async fn test_seach_pairs() {
         use super::*;
         let keyword = "TRUMP";
-        let result = search_pairs(&keyword).await.unwrap();
+        let result = search_pairs(keyword).await.unwrap();
         assert!(!result.pairs.is_empty());
         let result = result
             .pairs
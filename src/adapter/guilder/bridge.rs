@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use futures_util::stream::SelectAll;
+use futures_util::StreamExt;
+use guilder_abstraction::{AssetContext, BoxStream, Fill, GetMarketData, SubscribeMarketData};
+use rust_decimal::prelude::ToPrimitive;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tracing::warn;
+
+use crate::engine::{MarketKey, MarketListing, MarketType, Update};
+
+/// Market data event variants used inside the bridge run loop.
+enum Ev {
+    Fill(Fill),
+    AssetCtx(AssetContext),
+}
+
+/// Bridges market data from any `guilder-abstraction` client into the
+/// [`MarketStateScheduler`] via an [`UnboundedSender<Update>`].
+///
+/// Every update is tagged with the bridge's `venue`, so several bridges (one
+/// per exchange) can feed the same scheduler. Venue names follow
+/// `guilder-abstraction`'s `PredictedFunding::venue` naming (`HlPerp`,
+/// `BinPerp`, `BybitPerp`).
+///
+/// On [`run`], the bridge:
+/// 1. Calls `get_all_asset_contexts()` to fetch all perp snapshots in one request.
+/// 2. Seeds each coin in the engine from that snapshot.
+/// 3. Opens `subscribe_fill` and `subscribe_asset_context` streams per coin.
+/// 4. Dispatches incoming events as [`Update`]s to the engine channel.
+/// 5. Subscribes to any market it has not seen yet whenever a listing batch
+///    arrives on `listings_rx` (fed by pollers such as [`SymbolPoller`]).
+///
+/// Listings map exchange symbols to engine keys, so markets the guilder client
+/// does not enumerate (e.g. Hyperliquid spot `@107` → `HlSpot` / `HYPE/USDC`)
+/// can still have their fills streamed and attributed correctly. Listings with
+/// `stream_context == false` get fills only; their snapshots come from the
+/// poller that announced them.
+///
+/// REST-only data such as predicted funding is not polled here; it is fed to
+/// the scheduler by separate pollers (see [`crate::poller`]).
+///
+/// [`SymbolPoller`]: super::SymbolPoller
+/// [`run`]: GuilderBridge::run
+/// [`MarketStateScheduler`]: crate::engine::MarketStateScheduler
+pub struct GuilderBridge<C> {
+    client: C,
+    venue: String,
+    tx: UnboundedSender<Update>,
+}
+
+impl<C: GetMarketData + SubscribeMarketData> GuilderBridge<C> {
+    pub fn new(client: C, venue: impl Into<String>, tx: UnboundedSender<Update>) -> Self {
+        Self {
+            client,
+            venue: venue.into(),
+            tx,
+        }
+    }
+
+    pub fn venue(&self) -> &str {
+        &self.venue
+    }
+
+    // -----------------------------------------------------------------------
+    // Internal helpers
+    // -----------------------------------------------------------------------
+
+    fn send(&self, update: Update) -> bool {
+        self.tx.send(update).is_ok()
+    }
+
+    fn d2f(d: rust_decimal::Decimal) -> f64 {
+        d.to_f64().unwrap_or(0.0)
+    }
+
+    /// Attribute a fill to the listing of its exchange symbol, falling back to
+    /// `(self.venue, symbol)` for symbols seeded from the guilder client.
+    fn on_fill(&self, fill: Fill, known: &HashMap<String, MarketKey>) {
+        let key = known
+            .get(&fill.symbol)
+            .cloned()
+            .unwrap_or_else(|| MarketKey::new(&self.venue, &fill.symbol));
+        self.send(Update::Trade {
+            venue: key.venue,
+            coin: key.coin,
+            volume: Self::d2f(fill.volume),
+            count: 1,
+            time_ms: fill.timestamp_ms,
+        });
+    }
+
+    fn on_asset_context(&self, ctx: AssetContext) {
+        let mark_px = Self::d2f(ctx.mark_price);
+        self.send(Update::AssetContext {
+            venue: self.venue.clone(),
+            coin: ctx.symbol,
+            market_type: MarketType::Perp,
+            dex: String::new(),
+            oracle_px: ctx.oracle_price.map(Self::d2f).unwrap_or(mark_px),
+            mark_px,
+            mid_px: ctx.mid_price.map(Self::d2f),
+            open_interest: Self::d2f(ctx.open_interest),
+            funding_rate: Self::d2f(ctx.funding_rate),
+            volume_24h_usd: Self::d2f(ctx.day_volume),
+        });
+    }
+
+    fn subscribe(
+        &self,
+        symbol: &str,
+        stream_context: bool,
+        streams: &mut SelectAll<BoxStream<Ev>>,
+    ) {
+        streams.push(Box::pin(
+            self.client.subscribe_fill(symbol.to_string()).map(Ev::Fill),
+        ));
+        if stream_context {
+            streams.push(Box::pin(
+                self.client
+                    .subscribe_asset_context(symbol.to_string())
+                    .map(Ev::AssetCtx),
+            ));
+        }
+    }
+
+    /// Fetch all symbols, seed the engine with REST snapshots, subscribe to
+    /// streams for each coin, and return the seeded symbols keyed for
+    /// [`Self::on_fill`].
+    async fn initialize(
+        &self,
+        streams: &mut SelectAll<BoxStream<Ev>>,
+    ) -> HashMap<String, MarketKey> {
+        let ctxs = match self.client.get_all_asset_contexts().await {
+            Ok(c) => c,
+            Err(e) => {
+                warn!(
+                    venue = self.venue.as_str(),
+                    "get_all_asset_contexts failed: {e}"
+                );
+                return HashMap::new();
+            }
+        };
+        tracing::info!(
+            venue = self.venue.as_str(),
+            total = ctxs.len(),
+            "guilder bridge: fetching initial snapshots"
+        );
+
+        let mut seeded = HashMap::new();
+        for ctx in ctxs {
+            let symbol = ctx.symbol.clone();
+            self.on_asset_context(ctx);
+            seeded.insert(symbol.clone(), MarketKey::new(&self.venue, symbol));
+        }
+
+        for symbol in seeded.keys() {
+            self.subscribe(symbol, true, streams);
+        }
+
+        tracing::info!(
+            venue = self.venue.as_str(),
+            symbols = seeded.len(),
+            "guilder bridge initialised"
+        );
+        seeded
+    }
+
+    /// Subscribe to any listings not already tracked.
+    async fn subscribe_new_listings(
+        &self,
+        listings: Vec<MarketListing>,
+        known: &mut HashMap<String, MarketKey>,
+        streams: &mut SelectAll<BoxStream<Ev>>,
+    ) {
+        let new: Vec<MarketListing> = listings
+            .into_iter()
+            .filter(|l| !known.contains_key(&l.symbol))
+            .collect();
+        if new.is_empty() {
+            return;
+        }
+
+        tracing::info!(
+            venue = self.venue.as_str(),
+            count = new.len(),
+            "new markets listed"
+        );
+
+        for listing in new {
+            if listing.stream_context {
+                match self.client.get_asset_context(listing.symbol.clone()).await {
+                    Ok(ctx) => self.on_asset_context(ctx),
+                    Err(e) => {
+                        warn!(
+                            symbol = listing.symbol.as_str(),
+                            "new listing: get_asset_context failed: {e}"
+                        );
+                        continue;
+                    }
+                }
+            }
+            self.subscribe(&listing.symbol, listing.stream_context, streams);
+            known.insert(listing.symbol, listing.key);
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Public entry point
+    // -----------------------------------------------------------------------
+
+    /// Run the bridge loop until all WS streams close or the engine channel drops.
+    ///
+    /// Exits when `streams.next()` returns `None` (all subscriptions ended).
+    /// The caller is responsible for restarting if reconnect is desired.
+    pub async fn run(self, mut listings_rx: UnboundedReceiver<Vec<MarketListing>>) {
+        let mut streams: SelectAll<BoxStream<Ev>> = SelectAll::new();
+        let mut known = self.initialize(&mut streams).await;
+        // Signal to the scheduler that this venue is fully seeded.
+        let _ = self.tx.send(Update::EngineReady {
+            venue: self.venue.clone(),
+        });
+
+        loop {
+            tokio::select! {
+                biased;
+                maybe_ev = streams.next() => {
+                    let Some(ev) = maybe_ev else {
+                        warn!(venue = self.venue.as_str(), "guilder bridge: all streams closed");
+                        break;
+                    };
+                    if self.tx.is_closed() { break; }
+                    match ev {
+                        Ev::Fill(f)     => self.on_fill(f, &known),
+                        Ev::AssetCtx(c) => self.on_asset_context(c),
+                    }
+                }
+                Some(listings) = listings_rx.recv() => {
+                    self.subscribe_new_listings(listings, &mut known, &mut streams).await;
+                }
+            }
+        }
+    }
+}
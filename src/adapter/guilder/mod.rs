@@ -0,0 +1,96 @@
+/// Generic WebSocket bridge — subscribes to fills + asset contexts for all coins
+/// of one venue through `guilder-abstraction`.
+pub mod bridge;
+/// REST pollers (predicted funding, symbol list) built on [`crate::poller`].
+pub mod pollers;
+
+pub use bridge::GuilderBridge;
+pub use pollers::{predicted_funding_quotes, PredictedFundingPoller, SymbolPoller};
+
+use std::time::Duration;
+
+use guilder_client_hyperliquid::HyperliquidClient;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::task::JoinHandle;
+
+use crate::adapter::hyperliquid_info::{HyperliquidInfo, UniversePoller, UniverseSink};
+use crate::engine::Update;
+use crate::poller::{run_poller, PollerConfig, PollerRegistry};
+
+/// Native Hyperliquid perp venue (all perp DEXes, including builder-deployed).
+pub const HL_PERP: &str = "HlPerp";
+
+/// Hyperliquid spot venue. Fed by the [`HL_PERP`] bridge, since both share one
+/// Hyperliquid connection.
+pub const HL_SPOT: &str = "HlSpot";
+
+/// Venues with a `guilder-abstraction` client wired into [`spawn_bridge`].
+pub const SUPPORTED_VENUES: &[&str] = &[HL_PERP];
+
+/// How often to poll for predicted funding rates.
+const PREDICTED_FUNDING_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often to re-fetch the symbol list to pick up newly listed coins.
+const SYMBOL_REFETCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often to refresh spot + builder-DEX snapshots. These markets have no
+/// streamed asset contexts, so this bounds the staleness of their prices.
+const UNIVERSE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Spread REST calls of different pollers / venues apart.
+const POLLER_JITTER: Duration = Duration::from_secs(5);
+
+/// Spawn a [`GuilderBridge`] for `venue` feeding `tx`, together with its
+/// registered pollers:
+/// - `<venue>/predicted_funding` — sends [`Update::PredictedFundings`] to `tx`.
+/// - `<venue>/symbols` — sends the symbol list to the bridge, which subscribes
+///   to newly listed coins.
+/// - `HlPerp/universe` (Hyperliquid only) — sends spot + builder-DEX snapshots
+///   to `tx` and their listings to the bridge, which streams their fills.
+///
+/// Returns `None` when no guilder client is available for the venue. Adding an
+/// exchange only requires a new match arm here once its client crate exists.
+pub fn spawn_bridge(
+    venue: &str,
+    tx: UnboundedSender<Update>,
+    pollers: &PollerRegistry,
+) -> Option<JoinHandle<()>> {
+    match venue {
+        HL_PERP => {
+            let (listings_tx, listings_rx) = mpsc::unbounded_channel();
+
+            tokio::spawn(run_poller(
+                PredictedFundingPoller::new(HyperliquidClient::new()),
+                PollerConfig::new(
+                    format!("{venue}/predicted_funding"),
+                    PREDICTED_FUNDING_INTERVAL,
+                )
+                .with_jitter(POLLER_JITTER),
+                tx.clone(),
+                pollers.clone(),
+            ));
+            tokio::spawn(run_poller(
+                SymbolPoller::new(HyperliquidClient::new(), venue),
+                PollerConfig::new(format!("{venue}/symbols"), SYMBOL_REFETCH_INTERVAL)
+                    .with_jitter(POLLER_JITTER)
+                    .with_poll_on_start(false),
+                listings_tx.clone(),
+                pollers.clone(),
+            ));
+            tokio::spawn(run_poller(
+                UniversePoller::new(HyperliquidInfo::new()),
+                PollerConfig::new(format!("{venue}/universe"), UNIVERSE_INTERVAL)
+                    .with_jitter(POLLER_JITTER),
+                UniverseSink {
+                    updates: tx.clone(),
+                    listings: listings_tx,
+                },
+                pollers.clone(),
+            ));
+
+            let bridge = GuilderBridge::new(HyperliquidClient::new(), HL_PERP, tx);
+            Some(tokio::spawn(bridge.run(listings_rx)))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashSet;
+
+use guilder_abstraction::{GetMarketData, PredictedFunding};
+use rust_decimal::prelude::ToPrimitive;
+use tracing::warn;
+
+use crate::engine::{MarketKey, MarketListing, MarketType, PredictedFundingQuote, Update};
+use crate::poller::Poller;
+
+/// Polls `get_predicted_fundings` and yields one [`Update::PredictedFundings`]
+/// batch covering every venue the client reports.
+pub struct PredictedFundingPoller<C> {
+    client: C,
+}
+
+impl<C> PredictedFundingPoller<C> {
+    pub fn new(client: C) -> Self {
+        Self { client }
+    }
+}
+
+impl<C: GetMarketData> Poller for PredictedFundingPoller<C> {
+    type Output = Update;
+
+    async fn poll(&mut self) -> Result<Update, String> {
+        let fundings = self.client.get_predicted_fundings().await?;
+        let quotes = predicted_funding_quotes(fundings);
+        tracing::debug!(quotes = quotes.len(), "predicted funding rates refreshed");
+        Ok(Update::PredictedFundings { quotes })
+    }
+}
+
+/// Polls `get_symbol` and yields one [`MarketListing`] per symbol; the bridge
+/// diffs the list against the markets it already tracks.
+pub struct SymbolPoller<C> {
+    client: C,
+    venue: String,
+}
+
+impl<C> SymbolPoller<C> {
+    pub fn new(client: C, venue: impl Into<String>) -> Self {
+        Self {
+            client,
+            venue: venue.into(),
+        }
+    }
+}
+
+impl<C: GetMarketData> Poller for SymbolPoller<C> {
+    type Output = Vec<MarketListing>;
+
+    async fn poll(&mut self) -> Result<Vec<MarketListing>, String> {
+        let symbols = self.client.get_symbol().await?;
+        Ok(symbols
+            .into_iter()
+            .map(|symbol| MarketListing {
+                key: MarketKey::new(&self.venue, &symbol),
+                symbol,
+                market_type: MarketType::Perp,
+                dex: String::new(),
+                stream_context: true,
+            })
+            .collect())
+    }
+}
+
+/// Convert `get_predicted_fundings` output into engine quotes, keeping the
+/// first entry per `(venue, coin)` and skipping unparsable rates.
+pub fn predicted_funding_quotes(fundings: Vec<PredictedFunding>) -> Vec<PredictedFundingQuote> {
+    let mut seen = HashSet::new();
+    let mut quotes = Vec::with_capacity(fundings.len());
+    for pf in fundings {
+        if !seen.insert((pf.venue.clone(), pf.symbol.clone())) {
+            continue;
+        }
+        let Some(rate) = pf.funding_rate.to_f64() else {
+            warn!(venue = %pf.venue, coin = %pf.symbol, "failed to convert predicted funding rate");
+            continue;
+        };
+        quotes.push(PredictedFundingQuote {
+            venue: pf.venue,
+            coin: pf.symbol,
+            rate,
+            next_funding_time_ms: pf.next_funding_time_ms,
+        });
+    }
+    quotes
+}
@@ -0,0 +1,7 @@
+/// Poller feeding spot + builder-DEX markets into the realtime engine.
+pub mod poller;
+/// Spot + builder-DEX (HIP-3) market universe over the REST `info` endpoint.
+pub mod universe;
+
+pub use poller::{UniversePoller, UniverseSink};
+pub use universe::{classify_coin, HyperliquidInfo, MarketSnapshot};
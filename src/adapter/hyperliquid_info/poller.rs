@@ -0,0 +1,133 @@
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::{HyperliquidInfo, MarketSnapshot};
+use crate::adapter::guilder::{HL_PERP, HL_SPOT};
+use crate::engine::{MarketKey, MarketListing, MarketType, Update};
+use crate::poller::{Poller, PollerSink};
+
+/// Polls spot pairs and builder-DEX perps via [`HyperliquidInfo`].
+///
+/// The guilder client only enumerates the default perp DEX, so these markets
+/// get their snapshots from this poller and only their fills from the WS
+/// bridge.
+#[derive(Default)]
+pub struct UniversePoller {
+    info: HyperliquidInfo,
+}
+
+impl UniversePoller {
+    pub fn new(info: HyperliquidInfo) -> Self {
+        Self { info }
+    }
+}
+
+impl Poller for UniversePoller {
+    type Output = Vec<MarketSnapshot>;
+
+    async fn poll(&mut self) -> Result<Vec<MarketSnapshot>, String> {
+        self.info
+            .extended_snapshots()
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Splits each [`UniversePoller`] result between the engine (one
+/// [`Update::Batch`] of asset contexts) and the Hyperliquid bridge (the
+/// listings to subscribe fills for).
+pub struct UniverseSink {
+    pub updates: UnboundedSender<Update>,
+    pub listings: UnboundedSender<Vec<MarketListing>>,
+}
+
+/// Engine venue for a Hyperliquid market: spot pairs are tracked under
+/// [`HL_SPOT`], every perp DEX under [`HL_PERP`].
+fn venue_of(market_type: MarketType) -> &'static str {
+    match market_type {
+        MarketType::Spot => HL_SPOT,
+        MarketType::Perp => HL_PERP,
+    }
+}
+
+/// Build the listing + asset-context update for one snapshot.
+pub fn snapshot_update(snap: MarketSnapshot) -> (MarketListing, Update) {
+    let venue = venue_of(snap.market_type);
+    let listing = MarketListing {
+        symbol: snap.symbol,
+        key: MarketKey::new(venue, &snap.coin),
+        market_type: snap.market_type,
+        dex: snap.dex.clone(),
+        stream_context: false,
+    };
+    let update = Update::AssetContext {
+        venue: venue.to_string(),
+        coin: snap.coin,
+        market_type: snap.market_type,
+        dex: snap.dex,
+        oracle_px: snap.oracle_px.unwrap_or(snap.mark_px),
+        mark_px: snap.mark_px,
+        mid_px: snap.mid_px,
+        open_interest: snap.open_interest,
+        funding_rate: snap.funding,
+        volume_24h_usd: snap.day_ntl_vlm,
+    };
+    (listing, update)
+}
+
+impl PollerSink<Vec<MarketSnapshot>> for UniverseSink {
+    fn deliver(&self, snapshots: Vec<MarketSnapshot>) -> bool {
+        let (listings, updates): (Vec<_>, Vec<_>) =
+            snapshots.into_iter().map(snapshot_update).unzip();
+        // The bridge may have exited (e.g. all streams closed); snapshots are
+        // still worth recording, so a closed listings channel is not fatal.
+        let _ = self.listings.send(listings);
+        self.updates.send(Update::Batch(updates)).is_ok()
+    }
+
+    fn is_closed(&self) -> bool {
+        self.updates.is_closed()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spot_snapshot_maps_to_spot_venue() {
+        let snap = MarketSnapshot {
+            symbol: "@107".into(),
+            coin: "HYPE/USDC".into(),
+            market_type: MarketType::Spot,
+            dex: String::new(),
+            mark_px: 40.5,
+            mid_px: Some(40.4),
+            oracle_px: None,
+            open_interest: 0.0,
+            funding: 0.0,
+            day_ntl_vlm: 1000.0,
+        };
+        let (listing, update) = snapshot_update(snap);
+
+        assert_eq!(listing.symbol, "@107");
+        assert_eq!(listing.key, MarketKey::new(HL_SPOT, "HYPE/USDC"));
+        assert!(!listing.stream_context);
+        match update {
+            Update::AssetContext {
+                venue,
+                coin,
+                oracle_px,
+                ..
+            } => {
+                assert_eq!(venue, HL_SPOT);
+                assert_eq!(coin, "HYPE/USDC");
+                assert_eq!(oracle_px, 40.5);
+            }
+            other => panic!("unexpected update: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::adapter::AdapterError;
+use crate::engine::MarketType;
+use crate::error::AnySignalResult;
+
+const INFO_URL: &str = "https://api.hyperliquid.xyz/info";
+
+/// Asset context snapshot for one spot pair or builder-deployed perp, fetched
+/// over Hyperliquid's REST `info` endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketSnapshot {
+    /// Name used by Hyperliquid's WS `trades` channel, e.g. `"@107"`,
+    /// `"PURR/USDC"` or `"xyz:XYZ100"`.
+    pub symbol: String,
+    /// Readable name: `"HYPE/USDC"` for spot, unchanged for perps.
+    pub coin: String,
+    pub market_type: MarketType,
+    /// Perp DEX name; empty for spot and the default perp DEX.
+    pub dex: String,
+    pub mark_px: f64,
+    pub mid_px: Option<f64>,
+    /// Spot pairs have no oracle price.
+    pub oracle_px: Option<f64>,
+    /// Zero for spot.
+    pub open_interest: f64,
+    /// Zero for spot.
+    pub funding: f64,
+    pub day_ntl_vlm: f64,
+}
+
+/// Classify a Hyperliquid coin name by its naming convention.
+///
+/// - `@<index>` and `BASE/QUOTE` → spot.
+/// - `<dex>:<COIN>` → perp on builder-deployed DEX `<dex>`.
+/// - anything else → perp on the default DEX (empty `dex`).
+pub fn classify_coin(coin: &str) -> (MarketType, String) {
+    if coin.starts_with('@') || coin.contains('/') {
+        (MarketType::Spot, String::new())
+    } else if let Some((dex, _)) = coin.split_once(':') {
+        (MarketType::Perp, dex.to_string())
+    } else {
+        (MarketType::Perp, String::new())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Wire types
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize)]
+struct SpotToken {
+    name: String,
+    index: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpotPair {
+    name: String,
+    /// `[base_token_index, quote_token_index]`.
+    tokens: [usize; 2],
+}
+
+#[derive(Debug, Deserialize)]
+struct SpotMeta {
+    tokens: Vec<SpotToken>,
+    universe: Vec<SpotPair>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpotCtx {
+    coin: String,
+    mark_px: String,
+    mid_px: Option<String>,
+    day_ntl_vlm: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PerpAsset {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PerpMeta {
+    universe: Vec<PerpAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PerpCtx {
+    funding: String,
+    open_interest: String,
+    day_ntl_vlm: String,
+    oracle_px: String,
+    mark_px: String,
+    mid_px: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PerpDex {
+    name: String,
+}
+
+fn parse_f64(s: &str) -> f64 {
+    s.parse().unwrap_or(0.0)
+}
+
+/// `"@107"` → `"HYPE/USDC"`. Pairs already named readably (e.g. the canonical
+/// `"PURR/USDC"`) map to themselves.
+fn spot_names(meta: &SpotMeta) -> HashMap<String, String> {
+    let tokens: HashMap<usize, &str> = meta
+        .tokens
+        .iter()
+        .map(|t| (t.index, t.name.as_str()))
+        .collect();
+    meta.universe
+        .iter()
+        .map(|pair| {
+            let readable = match (tokens.get(&pair.tokens[0]), tokens.get(&pair.tokens[1])) {
+                (Some(base), Some(quote)) if pair.name.starts_with('@') => {
+                    format!("{base}/{quote}")
+                }
+                _ => pair.name.clone(),
+            };
+            (pair.name.clone(), readable)
+        })
+        .collect()
+}
+
+fn spot_snapshots(meta: &SpotMeta, ctxs: Vec<SpotCtx>) -> Vec<MarketSnapshot> {
+    let names = spot_names(meta);
+    ctxs.into_iter()
+        .map(|ctx| MarketSnapshot {
+            coin: names
+                .get(&ctx.coin)
+                .cloned()
+                .unwrap_or_else(|| ctx.coin.clone()),
+            symbol: ctx.coin,
+            market_type: MarketType::Spot,
+            dex: String::new(),
+            mark_px: parse_f64(&ctx.mark_px),
+            mid_px: ctx.mid_px.as_deref().map(parse_f64),
+            oracle_px: None,
+            open_interest: 0.0,
+            funding: 0.0,
+            day_ntl_vlm: parse_f64(&ctx.day_ntl_vlm),
+        })
+        .collect()
+}
+
+fn perp_snapshots(dex: &str, meta: PerpMeta, ctxs: Vec<PerpCtx>) -> Vec<MarketSnapshot> {
+    meta.universe
+        .into_iter()
+        .zip(ctxs)
+        .map(|(asset, ctx)| MarketSnapshot {
+            coin: asset.name.clone(),
+            symbol: asset.name,
+            market_type: MarketType::Perp,
+            dex: dex.to_string(),
+            mark_px: parse_f64(&ctx.mark_px),
+            mid_px: ctx.mid_px.as_deref().map(parse_f64),
+            oracle_px: Some(parse_f64(&ctx.oracle_px)),
+            open_interest: parse_f64(&ctx.open_interest),
+            funding: parse_f64(&ctx.funding),
+            day_ntl_vlm: parse_f64(&ctx.day_ntl_vlm),
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Client
+// ---------------------------------------------------------------------------
+
+/// Minimal client for the Hyperliquid REST `info` endpoint, covering the
+/// markets `guilder-abstraction` does not enumerate: spot pairs and perps on
+/// builder-deployed (HIP-3) DEXes.
+#[derive(Clone, Default)]
+pub struct HyperliquidInfo {
+    client: reqwest::Client,
+}
+
+impl HyperliquidInfo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    async fn info<T: serde::de::DeserializeOwned>(
+        &self,
+        body: serde_json::Value,
+    ) -> AnySignalResult<T> {
+        let resp = self.client.post(INFO_URL).json(&body).send().await?;
+        let resp = resp.error_for_status()?;
+        resp.json::<T>()
+            .await
+            .map_err(|e| AdapterError::FetchError(format!("info {body}: {e}")).into())
+    }
+
+    /// Map of spot `@index` names to readable `BASE/QUOTE` names.
+    pub async fn spot_names(&self) -> AnySignalResult<HashMap<String, String>> {
+        let meta: SpotMeta = self.info(serde_json::json!({"type": "spotMeta"})).await?;
+        Ok(spot_names(&meta))
+    }
+
+    /// Snapshots for every spot pair.
+    pub async fn spot_snapshots(&self) -> AnySignalResult<Vec<MarketSnapshot>> {
+        let (meta, ctxs): (SpotMeta, Vec<SpotCtx>) = self
+            .info(serde_json::json!({"type": "spotMetaAndAssetCtxs"}))
+            .await?;
+        Ok(spot_snapshots(&meta, ctxs))
+    }
+
+    /// Names of all builder-deployed perp DEXes (the default DEX is excluded).
+    pub async fn perp_dexs(&self) -> AnySignalResult<Vec<String>> {
+        let dexs: Vec<Option<PerpDex>> = self.info(serde_json::json!({"type": "perpDexs"})).await?;
+        Ok(dexs.into_iter().flatten().map(|d| d.name).collect())
+    }
+
+    /// Snapshots for every perp listed on builder DEX `dex`.
+    pub async fn dex_snapshots(&self, dex: &str) -> AnySignalResult<Vec<MarketSnapshot>> {
+        let (meta, ctxs): (PerpMeta, Vec<PerpCtx>) = self
+            .info(serde_json::json!({"type": "metaAndAssetCtxs", "dex": dex}))
+            .await?;
+        Ok(perp_snapshots(dex, meta, ctxs))
+    }
+
+    /// Spot pairs plus the perps of every builder DEX.
+    pub async fn extended_snapshots(&self) -> AnySignalResult<Vec<MarketSnapshot>> {
+        let mut all = self.spot_snapshots().await?;
+        for dex in self.perp_dexs().await? {
+            all.extend(self.dex_snapshots(&dex).await?);
+        }
+        Ok(all)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_by_naming() {
+        assert_eq!(classify_coin("BTC"), (MarketType::Perp, String::new()));
+        assert_eq!(
+            classify_coin("xyz:XYZ100"),
+            (MarketType::Perp, "xyz".to_string())
+        );
+        assert_eq!(classify_coin("@107"), (MarketType::Spot, String::new()));
+        assert_eq!(
+            classify_coin("PURR/USDC"),
+            (MarketType::Spot, String::new())
+        );
+    }
+
+    #[test]
+    fn spot_index_names_resolve_to_pairs() {
+        let json = serde_json::json!([
+            {
+                "tokens": [
+                    {"name": "USDC", "index": 0},
+                    {"name": "PURR", "index": 1},
+                    {"name": "HYPE", "index": 150}
+                ],
+                "universe": [
+                    {"name": "PURR/USDC", "tokens": [1, 0], "index": 0},
+                    {"name": "@107", "tokens": [150, 0], "index": 107}
+                ]
+            },
+            [
+                {"coin": "PURR/USDC", "markPx": "0.2", "midPx": null, "dayNtlVlm": "10"},
+                {"coin": "@107", "markPx": "40.5", "midPx": "40.4", "dayNtlVlm": "1000"}
+            ]
+        ]);
+        let (meta, ctxs): (SpotMeta, Vec<SpotCtx>) = serde_json::from_value(json).unwrap();
+        let snaps = spot_snapshots(&meta, ctxs);
+
+        assert_eq!(snaps[0].coin, "PURR/USDC");
+        assert_eq!(snaps[1].symbol, "@107");
+        assert_eq!(snaps[1].coin, "HYPE/USDC");
+        assert_eq!(snaps[1].market_type, MarketType::Spot);
+        assert_eq!(snaps[1].mid_px, Some(40.4));
+        assert_eq!(snaps[1].oracle_px, None);
+    }
+
+    #[test]
+    fn dex_perps_carry_dex_name() {
+        let json = serde_json::json!([
+            {"universe": [{"name": "xyz:XYZ100", "szDecimals": 4}]},
+            [{
+                "funding": "0.00001", "openInterest": "12", "dayNtlVlm": "500",
+                "oraclePx": "100", "markPx": "100.5", "midPx": "100.4", "premium": "0"
+            }]
+        ]);
+        let (meta, ctxs): (PerpMeta, Vec<PerpCtx>) = serde_json::from_value(json).unwrap();
+        let snaps = perp_snapshots("xyz", meta, ctxs);
+
+        assert_eq!(snaps.len(), 1);
+        assert_eq!(snaps[0].coin, "xyz:XYZ100");
+        assert_eq!(snaps[0].dex, "xyz");
+        assert_eq!(snaps[0].market_type, MarketType::Perp);
+        assert_eq!(snaps[0].oracle_px, Some(100.0));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires network access to api.hyperliquid.xyz"]
+    async fn fetch_extended_snapshots() {
+        let snaps = HyperliquidInfo::new().extended_snapshots().await.unwrap();
+        assert!(snaps.iter().any(|s| s.market_type == MarketType::Spot));
+    }
+}
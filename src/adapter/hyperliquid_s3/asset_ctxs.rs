@@ -4,6 +4,10 @@ use aws_sdk_s3::config::Region;
 use aws_sdk_s3::Client;
 use aws_smithy_types::byte_stream::AggregatedBytes;
 use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::adapter::hyperliquid_info::classify_coin;
+use crate::engine::MarketType;
 
 const BUCKET: &str = "hyperliquid-archive";
 const BUCKET_REGION: &str = "us-east-1";
@@ -33,6 +37,29 @@ pub struct AssetCtxRow {
     pub impact_bid_px: Option<f64>,
     #[serde(default, deserialize_with = "de_opt_f64")]
     pub impact_ask_px: Option<f64>,
+    /// Not in the archive; derived from `coin` by [`resolve_markets`].
+    #[serde(default)]
+    pub market_type: MarketType,
+    /// Perp DEX name; empty for spot and the default DEX. Derived by
+    /// [`resolve_markets`].
+    #[serde(default)]
+    pub dex: String,
+}
+
+/// Classify every row by its coin name and rename spot `@index` coins to their
+/// readable `BASE/QUOTE` names from `spot_names` (see
+/// [`HyperliquidInfo::spot_names`]). Unknown indices keep their raw name.
+///
+/// [`HyperliquidInfo::spot_names`]: crate::adapter::hyperliquid_info::HyperliquidInfo::spot_names
+pub fn resolve_markets(rows: &mut [AssetCtxRow], spot_names: &HashMap<String, String>) {
+    for row in rows {
+        let (market_type, dex) = classify_coin(&row.coin);
+        if let Some(name) = spot_names.get(&row.coin) {
+            row.coin = name.clone();
+        }
+        row.market_type = market_type;
+        row.dex = dex;
+    }
 }
 
 /// Deserialise an optional float from a CSV field that may be an empty
@@ -213,6 +240,32 @@ time,coin,funding,open_interest,prev_day_px,day_ntl_vlm,premium,oracle_px,mark_p
         assert!(rows.is_empty());
     }
 
+    #[test]
+    fn resolve_markets_classifies_and_renames() {
+        let csv = "\
+time,coin,funding,open_interest,prev_day_px,day_ntl_vlm,premium,oracle_px,mark_px,mid_px,impact_bid_px,impact_ask_px
+2025-01-01T00:00:00Z,BTC,0.0001,100.0,42000.0,5000000.0,,42100.0,42050.0,,,
+2025-01-01T00:00:00Z,@107,0.0,0.0,40.0,1000.0,,40.0,40.5,,,
+2025-01-01T00:00:00Z,xyz:XYZ100,0.0,12.0,100.0,500.0,,100.0,100.5,,,
+";
+        let mut rows = AssetCtxs::parse_csv(csv).expect("should parse");
+        assert_eq!(rows[0].market_type, MarketType::Perp);
+
+        let spot_names = HashMap::from([("@107".to_string(), "HYPE/USDC".to_string())]);
+        resolve_markets(&mut rows, &spot_names);
+
+        assert_eq!(
+            (rows[0].coin.as_str(), rows[0].market_type),
+            ("BTC", MarketType::Perp)
+        );
+        assert_eq!(
+            (rows[1].coin.as_str(), rows[1].market_type),
+            ("HYPE/USDC", MarketType::Spot)
+        );
+        assert_eq!(rows[2].dex, "xyz");
+        assert_eq!(rows[2].coin, "xyz:XYZ100");
+    }
+
     // -----------------------------------------------------------------------
     // decompress_lz4
     // -----------------------------------------------------------------------
@@ -183,6 +183,7 @@ mod tests {
     use super::*;
     use aws_sdk_s3::config::{BehaviorVersion, Builder as S3ConfigBuilder};
 
+    #[allow(dead_code)]
     fn dummy_fetcher() -> MarketData {
         let s3_config = S3ConfigBuilder::new()
             .behavior_version(BehaviorVersion::latest())
@@ -1,43 +1,84 @@
-use crate::error::AnySignalResult;
-use futures::SinkExt;
-use futures::StreamExt;
+use super::prelude::*;
+use crate::adapter::AdapterError;
+use crate::config::live::LiveConfig;
+use chrono::{DateTime, NaiveDate};
+use regex::Regex;
+use reqwest::header::USER_AGENT;
 use serde::{Deserialize, Serialize};
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use serde_json::Value;
+use std::time::Duration;
 
-pub async fn run_polygonio_stock(api_key: String) -> AnySignalResult<()> {
-    let url = "wss://delayed.polygon.io/stocks";
+const PURCHASES_URL: &str = "https://www.strategy.com/purchases";
 
-    // Establish WebSocket connection
-    let stream = connect_async(url).await?.0;
-    let (mut tx, mut rx) = stream.split();
-    tracing::info!("established connection");
+pub fn signal_info() -> SignalInfo {
+    SignalInfo {
+        id: SignalInfo::UNREGISTERED,
+        signal_type: "btc_purchase".to_string(),
+        data_type: SignalDataType::Scalar,
+        source: SOURCE.to_string(),
+        description: "Bitcoin bought per purchase".to_string(),
+        is_atomic: true,
+        parameters: Default::default(),
+    }
+}
+
+/// Scrapes the purchase history every `microstrategy_poll_interval_secs`;
+/// each purchase becomes a row with the BTC count, stamped with its date.
+pub struct BtcPurchaseSignalSource {
+    config: LiveConfig,
+}
 
-    // auth
-    {
-        let message = format!("{{\"action\":\"auth\",\"params\":\"{}\"}}", api_key);
-        let message = Message::Text(message.into());
-        tx.send(message).await?;
+impl BtcPurchaseSignalSource {
+    pub fn new(config: LiveConfig) -> Self {
+        Self { config }
     }
-    tracing::info!("auth granted");
+}
 
-    // request
-    {
-        let message = "{\"action\":\"subscribe\",\"params\":\"AM.LPL,AM.MSFT,AM.TSLA\"}";
-        let message = Message::Text(message.into());
-        tx.send(message).await?;
+impl PollingSignalSource for BtcPurchaseSignalSource {
+    fn get_signal_info(&self) -> SignalInfo {
+        signal_info()
     }
-    tracing::info!("requested");
-
-    // receive and print response
-    loop {
-        match rx.next().await {
-            Some(Ok(msg)) => tracing::debug!(msg = ?msg, "received"),
-            Some(Err(e)) => {
-                tracing::error!(error = ?e, "error receiving message");
-            }
-            None => continue,
-        };
+
+    fn poll_interval_duration(&self) -> Duration {
+        Duration::from_secs(self.config.get().microstrategy_poll_interval_secs)
     }
+
+    async fn get_signals(&self) -> AnySignalResult<Vec<Signal>> {
+        let purchases = fetch_purchases().await?;
+        Ok(purchases
+            .iter()
+            .filter_map(BtcPurchaseInfo::signal)
+            .collect())
+    }
+}
+
+/// Every purchase listed on the purchases page, read from its embedded
+/// `__NEXT_DATA__` JSON.
+pub async fn fetch_purchases() -> AnySignalResult<Vec<BtcPurchaseInfo>> {
+    let html_text = reqwest::Client::new()
+        .get(PURCHASES_URL)
+        .header(USER_AGENT, AGENT)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    parse_purchases(&html_text)
+}
+
+fn parse_purchases(html_text: &str) -> AnySignalResult<Vec<BtcPurchaseInfo>> {
+    let re = Regex::new(
+        r#"(?s)<script id="__NEXT_DATA__"[^>]*type="application/json"[^>]*>(.*?)</script>"#,
+    )
+    .map_err(|_| AdapterError::Parser)?;
+    let json_str = re
+        .captures(html_text)
+        .and_then(|caps| caps.get(1))
+        .ok_or(AdapterError::Parser)?
+        .as_str();
+    let mut parsed: Value = serde_json::from_str(json_str)?;
+    let bitcoin_data = parsed["props"]["pageProps"]["bitcoinData"].take();
+    serde_json::from_value(bitcoin_data).map_err(|_| AdapterError::Parser.into())
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -47,34 +88,63 @@ pub struct BtcPurchaseInfo {
     date_of_purchase: String,
 }
 
-mod tests {
-
-    #[tokio::test]
-    async fn test() {
-        use super::*;
-        use regex::Regex;
-        use serde_json::from_value;
-        use serde_json::Value;
-
-        let url = "https://www.strategy.com/purchases";
+impl BtcPurchaseInfo {
+    /// Midnight UTC of the purchase date; the page has used ISO, US and
+    /// RFC 3339 dates.
+    pub fn timestamp_micros(&self) -> Option<i64> {
+        let date = self.date_of_purchase.trim();
+        if let Ok(dt) = DateTime::parse_from_rfc3339(date) {
+            return Some(dt.timestamp_micros());
+        }
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .or_else(|_| NaiveDate::parse_from_str(date, "%m/%d/%Y"))
+            .ok()?;
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp_micros())
+    }
 
-        let html_text = reqwest::get(url).await.unwrap().text().await.unwrap();
+    /// The BTC count at the purchase date; `None` if that does not parse.
+    pub fn signal(&self) -> Option<Signal> {
+        Some(Signal {
+            info_id: SignalInfo::UNREGISTERED,
+            timestamp_us: self.timestamp_micros()?,
+            data: SignalData::Scalar(self.count),
+        })
+    }
+}
 
-        let re = Regex::new(
-            r#"<script id="__NEXT_DATA__"[^>]*type="application/json"[^>]*>(.*?)</script>"#,
-        )
-        .unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let Some(caps) = re.captures(&html_text) else {
-            eprintln!("No match found for __NEXT_DATA__ script!");
-            return;
-        };
-        let json_str = caps.get(1).unwrap().as_str();
-        let parsed: Value = serde_json::from_str(json_str).unwrap();
-        let bitcoin_data = &parsed["props"]["pageProps"]["bitcoinData"];
+    #[test]
+    fn parses_purchase_page() {
+        let html = r#"<html><script id="__NEXT_DATA__" type="application/json">
+            {"props":{"pageProps":{"bitcoinData":[
+                {"purchase_price":97000.5,"count":10107.0,"date_of_purchase":"2025-01-21"},
+                {"purchase_price":90000.0,"count":1070.0,"date_of_purchase":"02/10/2025"},
+                {"purchase_price":1.0,"count":1.0,"date_of_purchase":"soon"}
+            ]}}}</script></html>"#;
+        let purchases = parse_purchases(html).unwrap();
+        let signals: Vec<Signal> = purchases
+            .iter()
+            .filter_map(BtcPurchaseInfo::signal)
+            .collect();
+        assert_eq!(signals.len(), 2);
+        assert_eq!(signals[0].timestamp_us, 1_737_417_600_000_000);
+        assert_eq!(signals[0].data, SignalData::Scalar(10107.0));
+        assert_eq!(signals[1].timestamp_us, 1_739_145_600_000_000);
 
-        let bitcoin_data: Vec<BtcPurchaseInfo> = from_value(bitcoin_data.clone()).unwrap();
+        assert!(parse_purchases("<html></html>").is_err());
+    }
 
-        dbg!(bitcoin_data);
+    #[tokio::test]
+    #[ignore = "requires network access to strategy.com"]
+    async fn fetches_live_purchases() {
+        let purchases = fetch_purchases().await.unwrap();
+        assert!(!purchases.is_empty());
+        assert!(purchases
+            .iter()
+            .all(|p| p.count > 0.0 && p.purchase_price > 0.0));
+        assert!(purchases.iter().filter_map(BtcPurchaseInfo::signal).count() > 0);
     }
 }
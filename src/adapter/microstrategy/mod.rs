@@ -1,10 +1,12 @@
 pub mod btc_holding;
 
-pub use btc_holding::run_polygonio_stock;
+pub use btc_holding::BtcPurchaseSignalSource;
 
 pub mod prelude {
+    pub use crate::adapter::polling::PollingSignalSource;
     pub use crate::config::Config;
     pub use crate::error::AnySignalResult;
+    pub use crate::model::signal::*;
     pub const SOURCE: &str = "Microstrategy";
     pub const AGENT: &str = "Signals";
 }
@@ -10,17 +10,35 @@ pub mod youtube;
 pub mod microstrategy;
 // adapter error
 pub mod error;
+// polling runtime shared by the HTTP signal adapters
+pub mod polling;
 // PolygonIO
 pub mod polygonio;
 // SEC API
 pub mod secapi;
 // Hyperliquid S3
 pub mod hyperliquid_s3;
-// Hyperliquid WebSocket / realtime
-pub mod hyperliquid_ws;
+// guilder-abstraction realtime bridges + pollers (any exchange)
+pub mod guilder;
+// Hyperliquid REST info (spot + builder-deployed perp DEXes)
+pub mod hyperliquid_info;
 
 pub use error::{AdapterError, AdapterResult};
 
+use crate::model::signal::SignalInfo;
+
+/// Signals the built-in adapters write, registered at startup so their ids
+/// are stable and listed by `GET /signals`. Per-video YouTube caption
+/// signals are not included.
+pub fn signal_infos() -> Vec<SignalInfo> {
+    vec![
+        coinmarketcap::fear_and_greed::signal_info(),
+        coinmarketcap::bitcoin_dominance::signal_info(),
+        newsapi::keyword::signal_info(),
+        microstrategy::btc_holding::signal_info(),
+    ]
+}
+
 #[derive(Clone, Debug)]
 pub enum DataSourceType {
     Historic,
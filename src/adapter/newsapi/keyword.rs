@@ -1,49 +1,77 @@
 use crate::adapter::AdapterError;
 
 use super::prelude::*;
+use crate::config::live::LiveConfig;
 use chrono::{DateTime, NaiveDateTime};
 use reqwest::header::USER_AGENT;
 use serde::Deserialize;
+use std::time::Duration;
 
 // TODO generalize into a SignalFetcher Trait and implement the fetcher for each signal
 pub fn signal_info() -> SignalInfo {
     SignalInfo {
-        id: 4,
+        id: SignalInfo::UNREGISTERED,
         signal_type: "news".to_string(),
         data_type: SignalDataType::Text,
         source: SOURCE.to_string(),
         description: "News Aritcle".to_string(),
         is_atomic: true,
+        parameters: Default::default(),
     }
 }
 
-pub async fn run_news_fetcher(
-    config: Config,
-    period: tokio::time::Duration,
-) -> AnySignalResult<()> {
-    let mut interval = tokio::time::interval(period);
-    let api_key = config.get_api_key("newsapi")?;
+/// Polls NewsAPI for `newsapi_keywords` every `newsapi_poll_interval_secs`.
+/// Both are re-read from the live config before each request; every article
+/// becomes a text row with its title, stamped with its publication time.
+pub struct NewsApiSignalSource {
+    api_key: String,
+    config: LiveConfig,
+}
 
-    loop {
-        // Wait for the next tick
-        interval.tick().await;
+impl NewsApiSignalSource {
+    pub fn new(api_key: String, config: LiveConfig) -> Self {
+        Self { api_key, config }
+    }
+}
+
+impl PollingSignalSource for NewsApiSignalSource {
+    fn get_signal_info(&self) -> SignalInfo {
+        signal_info()
+    }
 
-        tracing::info!("Fetching NewsAPI");
-        let response = fetch(&api_key).await?;
-        tracing::debug!(articles = ?response.articles, "fetched articles");
+    fn poll_interval_duration(&self) -> Duration {
+        Duration::from_secs(self.config.get().newsapi_poll_interval_secs)
+    }
+
+    async fn get_signals(&self) -> AnySignalResult<Vec<Signal>> {
+        let keywords = self.config.get().newsapi_keywords.clone();
+        tracing::info!(keywords = ?keywords, "Fetching NewsAPI");
+        let response = fetch(&self.api_key, &keywords).await?;
+        Ok(response
+            .articles
+            .iter()
+            .filter_map(NewsArticle::signal)
+            .collect())
     }
 }
 
-async fn fetch(api_key: &str) -> AnySignalResult<NewsApiResponse> {
+async fn fetch(api_key: &str, keywords: &[String]) -> AnySignalResult<NewsApiResponse> {
     let client = reqwest::Client::new();
-    let url = "https://newsapi.org/v2/everything?q=bitcoin&sortBy=publishedAt&apiKey=";
+    let url = "https://newsapi.org/v2/everything";
+    let query = keywords.join(" OR ");
 
     // Dispatch request
     let response = client
-        .get(format!("{}{}", url, api_key))
+        .get(url)
+        .query(&[
+            ("q", query.as_str()),
+            ("sortBy", "publishedAt"),
+            ("apiKey", api_key),
+        ])
         .header(USER_AGENT, AGENT)
         .send()
-        .await?;
+        .await?
+        .error_for_status()?;
     let response = response.text().await?;
     tracing::debug!(response = ?response, "raw NewsAPI response");
     serde_json::from_str::<NewsApiResponse>(&response).map_err(|_| AdapterError::Parser.into())
@@ -70,15 +98,26 @@ impl NewsArticle {
             .ok()
             .map(|dt| dt.naive_utc())
     }
+
+    /// The title at the publication time; `None` if that does not parse.
+    pub fn signal(&self) -> Option<Signal> {
+        Some(Signal {
+            info_id: SignalInfo::UNREGISTERED,
+            timestamp_us: self.get_time()?.and_utc().timestamp_micros(),
+            data: SignalData::Text(self.title.clone()),
+        })
+    }
 }
 
+#[cfg(test)]
 mod tests {
     #[tokio::test]
+    #[ignore = "requires NEWSAPI_API_KEY and network access to newsapi.org"]
     async fn test_news_fetcher() {
         use super::*;
         let config: Config = Config::from_env();
         let api_key = config.get_api_key("newsapi").unwrap();
-        let data = fetch(&api_key).await.unwrap();
-        println!("{data:#?}");
+        let data = fetch(&api_key, &config.newsapi_keywords).await.unwrap();
+        assert!(data.articles.iter().all(|a| a.signal().is_some()));
     }
 }
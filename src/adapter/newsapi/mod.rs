@@ -1,9 +1,9 @@
 pub mod keyword;
 
-// pub use bitcoin_dominance::run_bitcoin_dominance;
-pub use keyword::run_news_fetcher;
+pub use keyword::NewsApiSignalSource;
 
 pub mod prelude {
+    pub use crate::adapter::polling::PollingSignalSource;
     pub use crate::config::Config;
     pub use crate::error::AnySignalResult;
     pub use crate::model::signal::*;
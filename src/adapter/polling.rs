@@ -0,0 +1,244 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::DateTime;
+
+use crate::database::registry::SignalRegistry;
+use crate::database::signal_data::signal_query;
+use crate::database::{insert_signal_rows, select_signal_db, QuestDbClient};
+use crate::error::{AnySignalError, AnySignalResult};
+use crate::model::signal::{Signal, SignalData, SignalInfo};
+use crate::poller::{run_poller, Discard, Poller, PollerConfig, PollerRegistry};
+
+/// An HTTP signal adapter. Each poll returns what the API currently offers,
+/// usually a window of recent history; [`run_signal_source`] registers the
+/// signal, retries failed polls with backoff, records health under
+/// `GET /pollers`, and writes only the rows not already stored.
+pub trait PollingSignalSource: Send + Sync {
+    /// The signal this source writes, as [`SignalInfo::UNREGISTERED`].
+    fn get_signal_info(&self) -> SignalInfo;
+
+    /// Delay between polls; re-read after every poll so reloaded settings
+    /// apply.
+    fn poll_interval_duration(&self) -> Duration;
+
+    /// The signals on offer, in any order. `info_id` is set by the runtime.
+    fn get_signals(&self) -> impl Future<Output = AnySignalResult<Vec<Signal>>> + Send;
+
+    /// Poller name shown in `GET /pollers`; `<source>/<signal_type>` unless
+    /// overridden.
+    fn name(&self) -> String {
+        let info = self.get_signal_info();
+        format!("{}/{}", info.source, info.signal_type)
+    }
+}
+
+/// Drive `source` forever: poll it per its interval, write new signals to
+/// the `signal_*` table of its data type through `db`, and record health in
+/// `pollers`. Failed polls (API, registry or QuestDB errors) are retried
+/// with backoff and never end the loop.
+pub async fn run_signal_source<S: PollingSignalSource>(
+    source: S,
+    db: Arc<QuestDbClient>,
+    signals: SignalRegistry,
+    pollers: PollerRegistry,
+) {
+    let cfg = PollerConfig::new(source.name(), source.poll_interval_duration());
+    let poller = SignalPoller {
+        source,
+        db,
+        signals,
+        info: None,
+    };
+    run_poller(poller, cfg, Discard, pollers).await;
+}
+
+/// [`Poller`] adapter around one [`PollingSignalSource`]. Its registry entry
+/// is resolved on the first successful poll, so a source started while
+/// QuestDB is down catches up once it is back.
+struct SignalPoller<S> {
+    source: S,
+    db: Arc<QuestDbClient>,
+    signals: SignalRegistry,
+    info: Option<SignalInfo>,
+}
+
+impl<S: PollingSignalSource> Poller for SignalPoller<S> {
+    /// Rows written.
+    type Output = usize;
+
+    async fn poll(&mut self) -> Result<usize, String> {
+        self.poll_once().await.map_err(|e| e.to_string())
+    }
+
+    fn interval(&self) -> Option<Duration> {
+        Some(self.source.poll_interval_duration())
+    }
+}
+
+impl<S: PollingSignalSource> SignalPoller<S> {
+    async fn poll_once(&mut self) -> AnySignalResult<usize> {
+        let info = match &self.info {
+            Some(info) => info.clone(),
+            None => {
+                let info = self
+                    .signals
+                    .register(&self.db, &self.source.get_signal_info())
+                    .await?;
+                self.info = Some(info.clone());
+                info
+            }
+        };
+        let table = self.db.table_name(info.data_type.table());
+        let polled = distinct_signals(self.source.get_signals().await?, &info);
+        let (Some(oldest), Some(newest)) = (polled.first(), polled.last()) else {
+            return Ok(0);
+        };
+        let stored =
+            stored_signals(&self.db, &info, oldest.timestamp_us, newest.timestamp_us).await?;
+        let fresh = unstored(polled, &stored);
+        if fresh.is_empty() {
+            return Ok(0);
+        }
+        let written = self
+            .db
+            .write_rows(&table, &fresh, insert_signal_rows)
+            .await?;
+        tracing::debug!(signal_id = info.id, written, "signal source poll");
+        Ok(written)
+    }
+}
+
+/// Rows of `info` stored from `from_us` to `to_us` inclusive: the window a
+/// poll covers, however late its rows arrive.
+async fn stored_signals(
+    db: &QuestDbClient,
+    info: &SignalInfo,
+    from_us: i64,
+    to_us: i64,
+) -> AnySignalResult<Vec<Signal>> {
+    let at = |t: i64| DateTime::from_timestamp_micros(t).unwrap_or_default();
+    let query = signal_query(db, info).time_range("timestamp", at(from_us), at(to_us + 1));
+    match select_signal_db(db, info.data_type, &query).await {
+        Ok(rows) => Ok(rows),
+        Err(AnySignalError::QuestQuery(e)) if e.is_missing_table() => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Signals of `info`'s data type stamped with its id, oldest first, each
+/// timestamp and content once. Rows sharing a timestamp but not their
+/// content, e.g. two articles published the same second, are all kept.
+fn distinct_signals(signals: Vec<Signal>, info: &SignalInfo) -> Vec<Signal> {
+    let mut polled: Vec<Signal> = signals
+        .into_iter()
+        .filter(|s| {
+            let matches = s.data.data_type() == info.data_type;
+            if !matches {
+                tracing::warn!(
+                    signal_id = info.id,
+                    expected = info.data_type.name(),
+                    got = s.data.data_type().name(),
+                    "dropping signal of the wrong data type"
+                );
+            }
+            matches
+        })
+        .map(|s| Signal {
+            info_id: info.id,
+            ..s
+        })
+        .collect();
+    polled.sort_by_key(|s| s.timestamp_us);
+    let mut distinct: Vec<Signal> = Vec::with_capacity(polled.len());
+    for signal in polled {
+        if !contains(&distinct, &signal) {
+            distinct.push(signal);
+        }
+    }
+    distinct
+}
+
+/// `polled` (oldest first) without the rows already in `stored`.
+fn unstored(polled: Vec<Signal>, stored: &[Signal]) -> Vec<Signal> {
+    let mut by_time: BTreeMap<i64, Vec<&SignalData>> = BTreeMap::new();
+    for s in stored {
+        by_time.entry(s.timestamp_us).or_default().push(&s.data);
+    }
+    polled
+        .into_iter()
+        .filter(|s| {
+            by_time
+                .get(&s.timestamp_us)
+                .is_none_or(|data| !data.contains(&&s.data))
+        })
+        .collect()
+}
+
+/// Whether `signals` (oldest first) ends with a row equal to `signal` at
+/// its timestamp.
+fn contains(signals: &[Signal], signal: &Signal) -> bool {
+    signals
+        .iter()
+        .rev()
+        .take_while(|s| s.timestamp_us == signal.timestamp_us)
+        .any(|s| s.data == signal.data)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(timestamp_us: i64, value: f64) -> Signal {
+        Signal {
+            info_id: 0,
+            timestamp_us,
+            data: SignalData::Scalar(value),
+        }
+    }
+
+    #[test]
+    fn polled_signals_keep_same_second_rows_and_skip_stored_ones() {
+        let info = SignalInfo {
+            id: 7,
+            ..SignalInfo::dummy()
+        };
+        let polled = vec![
+            scalar(30, 3.0),
+            scalar(10, 1.0),
+            scalar(20, 2.0),
+            Signal {
+                data: SignalData::Text("wrong type".to_string()),
+                ..scalar(40, 0.0)
+            },
+            scalar(30, 3.5),
+            scalar(30, 3.0),
+        ];
+
+        let polled = distinct_signals(polled, &info);
+        let got: Vec<(i64, i64, f64)> = polled
+            .iter()
+            .map(|s| match s.data {
+                SignalData::Scalar(v) => (s.info_id, s.timestamp_us, v),
+                _ => (s.info_id, s.timestamp_us, f64::NAN),
+            })
+            .collect();
+        assert_eq!(
+            got,
+            [(7, 10, 1.0), (7, 20, 2.0), (7, 30, 3.0), (7, 30, 3.5)]
+        );
+
+        // stored rows are skipped however new they are; a late row is not
+        let stored = [scalar(30, 3.0), scalar(20, 2.0)];
+        let fresh = unstored(polled, &stored);
+        let got: Vec<i64> = fresh.iter().map(|s| s.timestamp_us).collect();
+        assert_eq!(got, [10, 30]);
+        assert_eq!(fresh[1].data, SignalData::Scalar(3.5));
+    }
+}
@@ -1,9 +1,46 @@
+use crate::config::live::LiveConfig;
 use crate::error::AnySignalResult;
+use crate::poller::RetryPolicy;
 use futures::SinkExt;
 use futures::StreamExt;
+use std::time::{Duration, Instant};
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 
-pub async fn run_polygonio_stock(api_key: String) -> AnySignalResult<()> {
+/// How often the live config is checked for a new ticker list.
+const TICKER_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Longest wait before reconnecting.
+const RECONNECT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Stream Polygon aggregates for `polygonio_tickers`. When the ticker list
+/// is reloaded, removed channels are unsubscribed and new ones subscribed on
+/// the open connection. A connection that fails or closes is reopened after
+/// a backoff, reset once a connection has stayed up longer than the cap.
+pub async fn run_polygonio_stock(api_key: String, config: LiveConfig) -> AnySignalResult<()> {
+    let retry = RetryPolicy {
+        max_backoff: RECONNECT_MAX_BACKOFF,
+        ..RetryPolicy::default()
+    };
+    let mut attempt = 0;
+    loop {
+        let connected = Instant::now();
+        match stream_aggregates(&api_key, &config).await {
+            Ok(()) => tracing::warn!("polygon stream closed"),
+            Err(e) => tracing::warn!(error = ?e, "polygon stream failed"),
+        }
+        if connected.elapsed() > retry.max_backoff {
+            attempt = 0;
+        }
+        let backoff = retry.backoff(attempt);
+        attempt = attempt.saturating_add(1);
+        tracing::info!(?backoff, "reconnecting to polygon");
+        tokio::time::sleep(backoff).await;
+    }
+}
+
+/// One connection: subscribe and follow ticker reloads until the stream
+/// ends (`Ok`) or connecting or a send fails.
+async fn stream_aggregates(api_key: &str, config: &LiveConfig) -> AnySignalResult<()> {
     let url = "wss://delayed.polygon.io/stocks";
 
     // Establish WebSocket connection
@@ -20,33 +57,55 @@ pub async fn run_polygonio_stock(api_key: String) -> AnySignalResult<()> {
     tracing::info!("auth granted");
 
     // request
-    {
-        let message = "{\"action\":\"subscribe\",\"params\":\"AM.LPL,AM.MSFT,AM.TSLA\"}";
-        let message = Message::Text(message.into());
-        tx.send(message).await?;
-    }
-    tracing::info!("requested");
+    let mut tickers = config.get().polygonio_tickers;
+    tx.send(ticker_message("subscribe", &tickers)).await?;
+    tracing::info!(tickers = ?tickers, "requested");
 
-    // receive and print response
+    // receive and print response, following ticker reloads
+    let mut check = tokio::time::interval(TICKER_CHECK_INTERVAL);
     loop {
-        match rx.next().await {
-            Some(Ok(msg)) => tracing::debug!(msg = ?msg, "received"),
-            Some(Err(e)) => {
-                tracing::error!(error = ?e, "error receiving message");
+        tokio::select! {
+            msg = rx.next() => match msg {
+                Some(Ok(msg)) => tracing::debug!(msg = ?msg, "received"),
+                Some(Err(e)) => {
+                    tracing::error!(error = ?e, "error receiving message");
+                }
+                None => return Ok(()),
+            },
+            _ = check.tick() => {
+                let next = config.get().polygonio_tickers;
+                if next == tickers {
+                    continue;
+                }
+                let removed: Vec<String> = tickers.iter().filter(|t| !next.contains(t)).cloned().collect();
+                let added: Vec<String> = next.iter().filter(|t| !tickers.contains(t)).cloned().collect();
+                if !removed.is_empty() {
+                    tx.send(ticker_message("unsubscribe", &removed)).await?;
+                }
+                if !added.is_empty() {
+                    tx.send(ticker_message("subscribe", &added)).await?;
+                }
+                tracing::info!(added = ?added, removed = ?removed, "polygon tickers reloaded");
+                tickers = next;
             }
-            None => continue,
-        };
+        }
     }
 }
 
+/// `{"action":"<action>","params":"AM.MSFT,AM.TSLA"}`
+fn ticker_message(action: &str, tickers: &[String]) -> Message {
+    let message = serde_json::json!({ "action": action, "params": tickers.join(",") });
+    Message::Text(message.to_string().into())
+}
+
+#[cfg(test)]
 mod tests {
     #[tokio::test]
+    #[ignore = "requires network access to delayed.polygon.io"]
     async fn test_stock() {
-        //
         use super::*;
         let url = "wss://delayed.polygon.io/stocks";
         let result = connect_async(url).await;
-        dbg!(&result);
         assert!(result.is_ok());
     }
 }
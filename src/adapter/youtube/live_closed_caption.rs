@@ -1,7 +1,10 @@
 // polling HTTP live caption data
 use super::prelude::*;
 use crate::extension::reqwest::ResponseExt;
+use chrono::Utc;
 use serde::Deserialize;
+use std::sync::Mutex;
+use std::time::Duration;
 use yup_oauth2::{read_service_account_key, ServiceAccountAuthenticator};
 
 #[derive(Deserialize, Debug)]
@@ -31,29 +34,62 @@ pub struct YouTubeClosedCaptionRequirement {
 
 pub fn signal_info(requirement: &YouTubeClosedCaptionRequirement) -> SignalInfo {
     SignalInfo {
-        id: 4,
-        signal_type: format!("youtube_closed_caption_{}", requirement.url),
+        id: SignalInfo::UNREGISTERED,
+        signal_type: "youtube_closed_caption".to_string(),
         data_type: SignalDataType::Text,
         source: SOURCE.to_string(),
         description: "YouTube live closed caption".to_string(),
         is_atomic: true,
+        parameters: [("url".to_string(), requirement.url.clone())].into(),
     }
 }
 
-pub async fn run_live_closed_caption_fetcher(
-    config: Config,
-    requirement: &YouTubeClosedCaptionRequirement,
-) -> AnySignalResult<()> {
-    let mut interval = tokio::time::interval(requirement.polling_period);
-    let api_key = config.get_api_key("youtube_data_v3")?;
+/// Downloads the captions of one video every `polling_period`. A row with
+/// the full caption text, stamped with the poll time, is written only when
+/// the text differs from the previous poll.
+pub struct LiveClosedCaptionSource {
+    api_key: String,
+    requirement: YouTubeClosedCaptionRequirement,
+    last: Mutex<Option<String>>,
+}
+
+impl LiveClosedCaptionSource {
+    pub fn new(api_key: String, requirement: YouTubeClosedCaptionRequirement) -> Self {
+        Self {
+            api_key,
+            requirement,
+            last: Mutex::new(None),
+        }
+    }
+}
+
+impl PollingSignalSource for LiveClosedCaptionSource {
+    fn get_signal_info(&self) -> SignalInfo {
+        signal_info(&self.requirement)
+    }
 
-    loop {
-        // Wait for the next tick
-        interval.tick().await;
+    fn poll_interval_duration(&self) -> Duration {
+        self.requirement.polling_period
+    }
+
+    async fn get_signals(&self) -> AnySignalResult<Vec<Signal>> {
+        tracing::info!(url = %self.requirement.url, "Fetching youtube live closed caption");
+        let captions = fetch(&self.api_key, &self.requirement).await?;
+        let mut last = self.last.lock().unwrap_or_else(|p| p.into_inner());
+        if last.as_deref() == Some(captions.as_str()) {
+            return Ok(Vec::new());
+        }
+        *last = Some(captions.clone());
+        Ok(vec![Signal {
+            info_id: SignalInfo::UNREGISTERED,
+            timestamp_us: Utc::now().timestamp_micros(),
+            data: SignalData::Text(captions),
+        }])
+    }
 
-        tracing::info!(url = %requirement.url, "Fetching youtube live closed caption");
-        let response = fetch(&api_key, requirement).await?;
-        tracing::debug!(response = ?response, "fetched captions");
+    fn name(&self) -> String {
+        let video_id = extract_video_id(&self.requirement.url).unwrap_or(&self.requirement.url);
+        format!("{SOURCE}/closed_caption/{video_id}")
     }
 }
 
@@ -111,15 +147,14 @@ pub async fn fetch(
 
     let url_caption_download = format!("{}/captions/{}", url_base, caption_id);
 
-    dbg!(&url_caption_download);
-
     // Use 'tfmt=vtt' for WebVTT format
     let caption_response = reqwest::Client::new()
         .get(url_caption_download)
         .bearer_auth(token.token().unwrap_or_default())
         .query(&[("key", api_key), ("tfmt", "srt")])
         .send()
-        .await?;
+        .await?
+        .error_for_status()?;
 
     Ok(caption_response.text().await?)
 }
@@ -0,0 +1,422 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Utc};
+
+use super::series::{price_at, read_bars, BarInterval, Bars, Series};
+use crate::database::QuestDbClient;
+use crate::error::AnySignalResult;
+
+/// Values of every series on the bars they share, keyed by bar start in
+/// microseconds, in series order.
+pub type Aligned = BTreeMap<i64, Vec<f64>>;
+
+/// Fewest points a correlation is computed from.
+const MIN_POINTS: usize = 3;
+
+/// Align `series` on their common bars. Without `fill`, bars any series
+/// lacks are dropped; with it, each series carries its last value forward
+/// over empty bars, from the first bar every series has a value.
+pub fn align(series: &[Bars], fill: bool) -> Aligned {
+    let Some((first, rest)) = series.split_first() else {
+        return Aligned::new();
+    };
+    if !fill {
+        return first
+            .iter()
+            .filter_map(|(bar, v)| {
+                let mut row = vec![*v];
+                for s in rest {
+                    row.push(*s.get(bar)?);
+                }
+                Some((*bar, row))
+            })
+            .collect();
+    }
+    let Some(start) = series
+        .iter()
+        .map(|s| s.first_key_value().map(|(bar, _)| *bar))
+        .collect::<Option<Vec<i64>>>()
+        .and_then(|firsts| firsts.into_iter().max())
+    else {
+        return Aligned::new();
+    };
+    let bars: BTreeSet<i64> = series
+        .iter()
+        .flat_map(|s| s.range(start..).map(|(bar, _)| *bar))
+        .collect();
+    bars.into_iter()
+        .filter_map(|bar| {
+            let row = series
+                .iter()
+                .map(|s| price_at(s, bar))
+                .collect::<Option<Vec<f64>>>()?;
+            Some((bar, row))
+        })
+        .collect()
+}
+
+/// Pearson correlation; `None` below three points or when either side is
+/// constant.
+pub fn pearson(x: &[f64], y: &[f64]) -> Option<f64> {
+    let n = x.len().min(y.len());
+    if n < MIN_POINTS {
+        return None;
+    }
+    let (x, y) = (&x[..n], &y[..n]);
+    let mean = |v: &[f64]| v.iter().sum::<f64>() / n as f64;
+    let (mx, my) = (mean(x), mean(y));
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (a, b) in x.iter().zip(y) {
+        sxy += (a - mx) * (b - my);
+        sxx += (a - mx).powi(2);
+        syy += (b - my).powi(2);
+    }
+    let r = sxy / (sxx * syy).sqrt();
+    r.is_finite().then_some(r.clamp(-1.0, 1.0))
+}
+
+/// 1-based ranks, ties sharing their average rank.
+fn ranks(x: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..x.len()).collect();
+    order.sort_by(|a, b| x[*a].total_cmp(&x[*b]));
+    let mut ranks = vec![0.0; x.len()];
+    let mut i = 0;
+    while i < order.len() {
+        let mut j = i;
+        while j + 1 < order.len() && x[order[j + 1]] == x[order[i]] {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        for k in &order[i..=j] {
+            ranks[*k] = rank;
+        }
+        i = j + 1;
+    }
+    ranks
+}
+
+/// Spearman rank correlation: Pearson over ranks.
+pub fn spearman(x: &[f64], y: &[f64]) -> Option<f64> {
+    let n = x.len().min(y.len());
+    pearson(&ranks(&x[..n]), &ranks(&y[..n]))
+}
+
+/// How series are aligned and correlated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    pub interval: BarInterval,
+    /// Carry values forward over empty bars; see [`align`].
+    pub fill: bool,
+    /// Bars per rolling correlation; 0 for none.
+    pub window: usize,
+    /// Lead-lag profile from `-max_lag` to `max_lag` bars.
+    pub max_lag: u32,
+}
+
+/// Correlation of `a` at each bar with `b` `lag` bars later.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LagCorrelation {
+    /// Positive when `a` leads `b`.
+    pub lag: i64,
+    pub points: usize,
+    pub correlation: Option<f64>,
+}
+
+/// Correlations between two of the series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairCorrelation {
+    /// Indexes into the series.
+    pub a: usize,
+    pub b: usize,
+    pub points: usize,
+    pub pearson: Option<f64>,
+    pub spearman: Option<f64>,
+    /// Pearson correlation of the `window` aligned bars ending at each bar.
+    pub rolling: Vec<(i64, Option<f64>)>,
+    /// One per lag from `-max_lag` to `max_lag`.
+    pub lead_lag: Vec<LagCorrelation>,
+}
+
+impl PairCorrelation {
+    /// The lag with the strongest correlation of either sign.
+    pub fn best_lag(&self) -> Option<&LagCorrelation> {
+        self.lead_lag
+            .iter()
+            .filter(|l| l.correlation.is_some())
+            .max_by(|x, y| {
+                let abs = |l: &LagCorrelation| l.correlation.unwrap_or_default().abs();
+                abs(x).total_cmp(&abs(y))
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Correlation {
+    pub settings: Settings,
+    /// Aligned bars.
+    pub points: usize,
+    /// Every pair, `a < b`, in series order.
+    pub pairs: Vec<PairCorrelation>,
+}
+
+fn column(aligned: &Aligned, i: usize) -> Vec<f64> {
+    aligned.values().map(|row| row[i]).collect()
+}
+
+/// Running sums of a window of pairs, taken relative to an origin pair to
+/// keep the rounding error of the sums of squares small.
+struct Sums {
+    origin: (f64, f64),
+    n: f64,
+    x: f64,
+    y: f64,
+    xx: f64,
+    yy: f64,
+    xy: f64,
+}
+
+impl Sums {
+    fn new(x: &[f64], y: &[f64]) -> Self {
+        let mut sums = Sums {
+            origin: (x[0], y[0]),
+            n: 0.0,
+            x: 0.0,
+            y: 0.0,
+            xx: 0.0,
+            yy: 0.0,
+            xy: 0.0,
+        };
+        for (a, b) in x.iter().zip(y) {
+            sums.add(*a, *b, 1.0);
+        }
+        sums
+    }
+
+    /// Add the pair with `sign` 1, remove it with -1.
+    fn add(&mut self, a: f64, b: f64, sign: f64) {
+        let (a, b) = (a - self.origin.0, b - self.origin.1);
+        self.n += sign;
+        self.x += sign * a;
+        self.y += sign * b;
+        self.xx += sign * a * a;
+        self.yy += sign * b * b;
+        self.xy += sign * a * b;
+    }
+
+    /// Pearson correlation of the window, as [`pearson`].
+    fn pearson(&self) -> Option<f64> {
+        if self.n < MIN_POINTS as f64 {
+            return None;
+        }
+        let vx = self.n * self.xx - self.x * self.x;
+        let vy = self.n * self.yy - self.y * self.y;
+        // a constant side leaves only rounding error
+        if vx <= self.n * self.xx * 1e-12 || vy <= self.n * self.yy * 1e-12 {
+            return None;
+        }
+        let r = (self.n * self.xy - self.x * self.y) / (vx * vy).sqrt();
+        r.is_finite().then_some(r.clamp(-1.0, 1.0))
+    }
+}
+
+/// Rolling Pearson correlation of series `a` and `b` over `window` aligned
+/// bars, stamped with the last bar of each window. The sums slide one bar at
+/// a time and are recomputed every `window` bars so rounding error does not
+/// build up, which keeps the whole profile linear in the bars.
+pub fn rolling(aligned: &Aligned, a: usize, b: usize, window: usize) -> Vec<(i64, Option<f64>)> {
+    if window == 0 {
+        return Vec::new();
+    }
+    let bars: Vec<i64> = aligned.keys().copied().collect();
+    let (x, y) = (column(aligned, a), column(aligned, b));
+    let mut out = Vec::with_capacity((bars.len() + 1).saturating_sub(window));
+    let mut sums: Option<Sums> = None;
+    for end in window..=bars.len() {
+        let start = end - window;
+        let sums = match sums.as_mut() {
+            Some(s) if !start.is_multiple_of(window) => {
+                s.add(x[start - 1], y[start - 1], -1.0);
+                s.add(x[end - 1], y[end - 1], 1.0);
+                s
+            }
+            _ => sums.insert(Sums::new(&x[start..end], &y[start..end])),
+        };
+        out.push((bars[end - 1], sums.pearson()));
+    }
+    out
+}
+
+/// Cross-correlation of `a` against `b` shifted by `-max_lag..=max_lag`
+/// bars, pairing bars by time so gaps in the alignment are respected.
+pub fn lead_lag(
+    aligned: &Aligned,
+    interval: &BarInterval,
+    a: usize,
+    b: usize,
+    max_lag: u32,
+) -> Vec<LagCorrelation> {
+    let max_lag = i64::from(max_lag);
+    (-max_lag..=max_lag)
+        .map(|lag| {
+            let (x, y): (Vec<f64>, Vec<f64>) = aligned
+                .iter()
+                .filter_map(|(bar, row)| {
+                    let later = aligned.get(&(bar + lag * interval.micros))?;
+                    Some((row[a], later[b]))
+                })
+                .unzip();
+            LagCorrelation {
+                lag,
+                points: x.len(),
+                correlation: pearson(&x, &y),
+            }
+        })
+        .collect()
+}
+
+/// Correlate every pair of `series`, aligned per [`align`].
+pub fn correlate(series: &[Bars], settings: Settings) -> Correlation {
+    let aligned = align(series, settings.fill);
+    let mut pairs = Vec::new();
+    for a in 0..series.len() {
+        for b in a + 1..series.len() {
+            let (x, y) = (column(&aligned, a), column(&aligned, b));
+            pairs.push(PairCorrelation {
+                a,
+                b,
+                points: x.len(),
+                pearson: pearson(&x, &y),
+                spearman: spearman(&x, &y),
+                rolling: rolling(&aligned, a, b, settings.window),
+                lead_lag: lead_lag(&aligned, &settings.interval, a, b, settings.max_lag),
+            });
+        }
+    }
+    Correlation {
+        settings,
+        points: aligned.len(),
+        pairs,
+    }
+}
+
+/// Read `series` over `[from, to)`, the last value per bar, and
+/// [`correlate`] them on the blocking pool.
+pub async fn run_correlation(
+    db: &QuestDbClient,
+    series: &[Series],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    settings: Settings,
+) -> AnySignalResult<Correlation> {
+    let mut bars = Vec::with_capacity(series.len());
+    for s in series {
+        bars.push(read_bars(db, s, &settings.interval, from, to).await?);
+    }
+    let correlation = tokio::task::spawn_blocking(move || correlate(&bars, settings))
+        .await
+        .map_err(|e| eyre::eyre!("correlation task: {e}"))?;
+    Ok(correlation)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(got: Option<f64>, want: f64) -> bool {
+        got.is_some_and(|g| (g - want).abs() < 1e-9)
+    }
+
+    #[test]
+    fn pearson_and_spearman() {
+        let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert!(close(pearson(&x, &[2.0, 4.0, 6.0, 8.0, 10.0]), 1.0));
+        assert!(close(pearson(&x, &[5.0, 4.0, 3.0, 2.0, 1.0]), -1.0));
+        // monotonic but not linear
+        let cubes = x.map(|v: f64| v.powi(3));
+        assert!(pearson(&x, &cubes).unwrap() < 1.0);
+        assert!(close(spearman(&x, &cubes), 1.0));
+        assert_eq!(ranks(&[3.0, 1.0, 3.0, 2.0]), [3.5, 1.0, 3.5, 2.0]);
+        assert_eq!(pearson(&x, &[1.0; 5]), None);
+        assert_eq!(pearson(&x[..2], &x[..2]), None);
+    }
+
+    #[test]
+    fn rolling_matches_pearson_per_window() {
+        // levels far from zero, a constant stretch, and more bars than two
+        // windows so the sums are both slid and recomputed
+        let x: Vec<f64> = (0..50)
+            .map(|i| 60_000.0 + ((i * 37) % 11) as f64 * 0.5)
+            .collect();
+        let y: Vec<f64> = (0..50)
+            .map(|i| {
+                if (20..30).contains(&i) {
+                    3.0
+                } else {
+                    ((i * i) % 13) as f64
+                }
+            })
+            .collect();
+        let aligned: Aligned = (0..50).map(|i| (i as i64, vec![x[i], y[i]])).collect();
+        let window = 8;
+        let got = rolling(&aligned, 0, 1, window);
+        assert_eq!(got.len(), 50 - window + 1);
+        for (i, (bar, r)) in got.into_iter().enumerate() {
+            assert_eq!(bar, (i + window - 1) as i64);
+            let want = pearson(&x[i..i + window], &y[i..i + window]);
+            match want {
+                Some(w) => assert!(close(r, w), "bar {bar}: {r:?} != {w}"),
+                None => assert_eq!(r, None, "bar {bar}"),
+            }
+        }
+        assert!(rolling(&aligned, 0, 1, 0).is_empty());
+    }
+
+    #[test]
+    fn aligns_with_and_without_fill() {
+        let a: Bars = [(1, 1.0), (2, 2.0), (3, 3.0), (4, 4.0)].into();
+        let b: Bars = [(2, 20.0), (4, 40.0), (5, 50.0)].into();
+        let inner = align(&[a.clone(), b.clone()], false);
+        assert_eq!(inner.keys().copied().collect::<Vec<_>>(), [2, 4]);
+        let filled = align(&[a, b], true);
+        assert_eq!(
+            filled.into_iter().collect::<Vec<_>>(),
+            [
+                (2, vec![2.0, 20.0]),
+                (3, vec![3.0, 20.0]),
+                (4, vec![4.0, 40.0]),
+                (5, vec![4.0, 50.0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_the_lead() {
+        let hour: BarInterval = "1h".parse().unwrap();
+        let h = hour.micros;
+        // b repeats a two bars later
+        let signal: Vec<f64> = (0..40).map(|i| ((i * i) % 7) as f64).collect();
+        let a: Bars = (0..40).map(|i| (i * h, signal[i as usize])).collect();
+        let b: Bars = (2..42).map(|i| (i * h, signal[i as usize - 2])).collect();
+        let settings = Settings {
+            interval: hour,
+            fill: false,
+            window: 10,
+            max_lag: 3,
+        };
+        let result = correlate(&[a, b], settings);
+        assert_eq!(result.points, 38);
+        let pair = &result.pairs[0];
+        assert_eq!((pair.a, pair.b), (0, 1));
+        assert_eq!(pair.lead_lag.len(), 7);
+        let best = pair.best_lag().unwrap();
+        assert_eq!(best.lag, 2);
+        assert!(close(best.correlation, 1.0));
+        assert_eq!(best.points, 36);
+        assert_eq!(pair.rolling.len(), 38 - 10 + 1);
+        assert_eq!(pair.rolling[0].0, 11 * h);
+    }
+}
@@ -0,0 +1,531 @@
+use std::ops::RangeInclusive;
+
+use chrono::{DateTime, Utc};
+
+use super::series::{price_at, read_bars, BarInterval, Bars, Series};
+use crate::database::calendar::is_tombstone;
+use crate::database::signal_data::{signal_query, MAX_LIMIT};
+use crate::database::{select_signal_db, QuestDbClient};
+use crate::error::AnySignalResult;
+use crate::model::signal::{FieldValue, SignalData, SignalInfo};
+
+/// Most events one study takes; each costs a price read per series.
+pub const MAX_EVENTS: usize = 500;
+/// Bars without a price are filled with the previous one for up to this long
+/// (weekends and holidays of daily equity prices).
+const FILL_GAP_US: i64 = 7 * 86_400_000_000;
+
+/// Bars studied around each event. Offset 0 is the bar containing the event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    /// Bars before the event, offsets `-pre..0`.
+    pub pre: u32,
+    /// Bars after the event, offsets `1..=post`.
+    pub post: u32,
+    /// Bars before the window whose mean return is the normal return when
+    /// there is no benchmark.
+    pub estimation: u32,
+}
+
+impl Window {
+    /// Upper bound on `pre + post + estimation`.
+    pub const MAX_BARS: u32 = 10_000;
+
+    pub fn validate(&self) -> Result<(), String> {
+        let bars = u64::from(self.pre) + u64::from(self.post) + u64::from(self.estimation);
+        if bars > u64::from(Self::MAX_BARS) {
+            return Err(format!(
+                "pre + post + estimation must be at most {} bars",
+                Self::MAX_BARS
+            ));
+        }
+        Ok(())
+    }
+
+    /// `-pre..=post`.
+    pub fn offsets(&self) -> RangeInclusive<i64> {
+        -i64::from(self.pre)..=i64::from(self.post)
+    }
+}
+
+/// How the normal return an abnormal return is measured against is found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    /// The benchmark's return over the same bar.
+    MarketAdjusted,
+    /// The mean return over the estimation bars.
+    MeanAdjusted,
+    /// Zero: no benchmark and no estimation bars.
+    Raw,
+}
+
+impl Model {
+    pub fn new(benchmark: bool, window: &Window) -> Self {
+        match (benchmark, window.estimation) {
+            (true, _) => Self::MarketAdjusted,
+            (false, 0) => Self::Raw,
+            (false, _) => Self::MeanAdjusted,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MarketAdjusted => "market_adjusted",
+            Self::MeanAdjusted => "mean_adjusted",
+            Self::Raw => "raw",
+        }
+    }
+}
+
+/// One event of a study.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StudyEvent {
+    pub time: DateTime<Utc>,
+    /// e.g. the calendar event's title; may be empty.
+    pub label: String,
+}
+
+/// Returns around one event, one per window offset, `-pre` first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventReturns {
+    pub event: StudyEvent,
+    /// Start of the offset-0 bar, in microseconds.
+    pub anchor_us: i64,
+    pub returns: Vec<f64>,
+    pub abnormal: Vec<f64>,
+    /// Abnormal returns summed from `-pre`.
+    pub cumulative: Vec<f64>,
+}
+
+/// An event left out of a study, e.g. for lack of prices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedEvent {
+    pub event: StudyEvent,
+    pub reason: String,
+}
+
+/// Cross-sectional statistics of one window offset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OffsetStats {
+    pub offset: i64,
+    pub events: usize,
+    pub mean_return: f64,
+    /// Average abnormal return.
+    pub aar: f64,
+    /// Cumulative average abnormal return.
+    pub caar: f64,
+    /// `aar` over its standard error; `None` below two events or without
+    /// dispersion.
+    pub t_aar: Option<f64>,
+    pub t_caar: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventStudy {
+    pub interval: BarInterval,
+    pub window: Window,
+    pub model: Model,
+    pub events: Vec<EventReturns>,
+    pub skipped: Vec<SkippedEvent>,
+    pub offsets: Vec<OffsetStats>,
+}
+
+/// Simple returns of `bars` over `offsets` around `anchor`, each from the
+/// previous bar's price. Empty bars carry the previous price forward.
+fn bar_returns(
+    bars: &Bars,
+    interval: &BarInterval,
+    anchor: i64,
+    offsets: RangeInclusive<i64>,
+) -> Result<Vec<f64>, String> {
+    let bar = |k: i64| anchor + k * interval.micros;
+    if bars
+        .last_key_value()
+        .is_none_or(|(last, _)| *last < bar(*offsets.end()))
+    {
+        return Err("no prices after the window".to_string());
+    }
+    let mut prev = price_at(bars, bar(offsets.start() - 1))
+        .ok_or_else(|| "no prices before the window".to_string())?;
+    offsets
+        .map(|k| {
+            let price = price_at(bars, bar(k)).unwrap_or(prev);
+            if prev <= 0.0 {
+                return Err(format!("non-positive price {prev}"));
+            }
+            let r = price / prev - 1.0;
+            prev = price;
+            Ok(r)
+        })
+        .collect()
+}
+
+/// Returns, abnormal returns and their running sum around `event`.
+pub fn event_returns(
+    event: StudyEvent,
+    prices: &Bars,
+    benchmark: Option<&Bars>,
+    interval: &BarInterval,
+    window: &Window,
+) -> Result<EventReturns, SkippedEvent> {
+    let anchor = interval.bar(event.time.timestamp_micros());
+    let (returns, abnormal) = match abnormal_returns(prices, benchmark, interval, window, anchor) {
+        Ok(r) => r,
+        Err(reason) => return Err(SkippedEvent { event, reason }),
+    };
+    let cumulative = abnormal
+        .iter()
+        .scan(0.0, |sum, ar| {
+            *sum += ar;
+            Some(*sum)
+        })
+        .collect();
+    Ok(EventReturns {
+        event,
+        anchor_us: anchor,
+        returns,
+        abnormal,
+        cumulative,
+    })
+}
+
+/// Returns and abnormal returns over the window around `anchor`, per the
+/// [`Model`] `benchmark` and `window` imply.
+fn abnormal_returns(
+    prices: &Bars,
+    benchmark: Option<&Bars>,
+    interval: &BarInterval,
+    window: &Window,
+    anchor: i64,
+) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let offsets = window.offsets();
+    if let Some(benchmark) = benchmark {
+        let returns = bar_returns(prices, interval, anchor, offsets.clone())?;
+        let normal = bar_returns(benchmark, interval, anchor, offsets)
+            .map_err(|e| format!("benchmark: {e}"))?;
+        let abnormal = returns.iter().zip(&normal).map(|(r, n)| r - n).collect();
+        return Ok((returns, abnormal));
+    }
+    let estimation = window.estimation as usize;
+    let first = offsets.start() - i64::from(window.estimation);
+    let mut returns = bar_returns(prices, interval, anchor, first..=*offsets.end())?;
+    let normal = match estimation {
+        0 => 0.0,
+        n => mean(&returns[..n]),
+    };
+    let returns = returns.split_off(estimation);
+    let abnormal = returns.iter().map(|r| r - normal).collect();
+    Ok((returns, abnormal))
+}
+
+/// Mean over its standard error, from the sample standard deviation.
+fn t_stat(xs: &[f64]) -> Option<f64> {
+    if xs.len() < 2 {
+        return None;
+    }
+    let n = xs.len() as f64;
+    let mean = xs.iter().sum::<f64>() / n;
+    let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let se = (var / n).sqrt();
+    (se > 0.0 && se.is_finite()).then(|| mean / se)
+}
+
+fn mean(xs: &[f64]) -> f64 {
+    xs.iter().sum::<f64>() / xs.len() as f64
+}
+
+/// Per-offset averages and t-stats across `events`; empty without events.
+pub fn aggregate(events: &[EventReturns], window: &Window) -> Vec<OffsetStats> {
+    if events.is_empty() {
+        return Vec::new();
+    }
+    window
+        .offsets()
+        .enumerate()
+        .map(|(i, offset)| {
+            let column = |f: fn(&EventReturns) -> &[f64]| -> Vec<f64> {
+                events.iter().map(|e| f(e)[i]).collect()
+            };
+            let returns = column(|e| &e.returns);
+            let abnormal = column(|e| &e.abnormal);
+            let cumulative = column(|e| &e.cumulative);
+            OffsetStats {
+                offset,
+                events: events.len(),
+                mean_return: mean(&returns),
+                aar: mean(&abnormal),
+                caar: mean(&cumulative),
+                t_aar: t_stat(&abnormal),
+                t_caar: t_stat(&cumulative),
+            }
+        })
+        .collect()
+}
+
+/// Study `events` against `price`, and `benchmark` when given. Prices are
+/// read per event; events without enough of them are skipped.
+pub async fn run_event_study(
+    db: &QuestDbClient,
+    events: Vec<StudyEvent>,
+    price: &Series,
+    benchmark: Option<&Series>,
+    interval: BarInterval,
+    window: Window,
+) -> AnySignalResult<EventStudy> {
+    let model = Model::new(benchmark.is_some(), &window);
+    let mut studied = Vec::new();
+    let mut skipped = Vec::new();
+    for event in events {
+        let anchor = interval.bar(event.time.timestamp_micros());
+        let first = window.offsets().start() - i64::from(window.estimation) - 1;
+        let (from, to) = fetch_range(anchor, first, *window.offsets().end(), &interval)?;
+        let prices = read_bars(db, price, &interval, from, to).await?;
+        let bench = match benchmark {
+            Some(b) => Some(read_bars(db, b, &interval, from, to).await?),
+            None => None,
+        };
+        match event_returns(event, &prices, bench.as_ref(), &interval, &window) {
+            Ok(e) => studied.push(e),
+            Err(s) => skipped.push(s),
+        }
+    }
+    let offsets = aggregate(&studied, &window);
+    Ok(EventStudy {
+        interval,
+        window,
+        model,
+        events: studied,
+        skipped,
+        offsets,
+    })
+}
+
+/// Prices to read for bars `first..=last` around `anchor`, with room to fill
+/// gaps at either end.
+fn fetch_range(
+    anchor: i64,
+    first: i64,
+    last: i64,
+    interval: &BarInterval,
+) -> AnySignalResult<(DateTime<Utc>, DateTime<Utc>)> {
+    let at = |k: i64, gap: i64| {
+        k.checked_mul(interval.micros)
+            .and_then(|d| anchor.checked_add(d))
+            .and_then(|t| t.checked_add(gap))
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(|| eyre::eyre!("event window around {anchor}us is out of range"))
+    };
+    Ok((at(first, -FILL_GAP_US)?, at(last + 1, FILL_GAP_US)?))
+}
+
+/// Events from the rows of `info` over `[from, to)`, one per timestamp.
+/// Calendar tombstones are skipped; text signals and calendar events are
+/// labelled with their text and title.
+pub async fn signal_events(
+    db: &QuestDbClient,
+    info: &SignalInfo,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> AnySignalResult<Vec<StudyEvent>> {
+    let query = signal_query(db, info)
+        .time_range("timestamp", from, to)
+        .limit(MAX_LIMIT);
+    let mut events: Vec<StudyEvent> = select_signal_db(db, info.data_type, &query)
+        .await?
+        .into_iter()
+        .filter(|s| !is_tombstone(s))
+        .filter_map(|s| {
+            let label = match s.data {
+                SignalData::Text(text) => text,
+                SignalData::Structured(mut fields) => match fields.remove("title") {
+                    Some(FieldValue::Text(title)) => title,
+                    _ => String::new(),
+                },
+                _ => String::new(),
+            };
+            Some(StudyEvent {
+                time: DateTime::from_timestamp_micros(s.timestamp_us)?,
+                label,
+            })
+        })
+        .collect();
+    events.dedup_by_key(|e| e.time);
+    Ok(events)
+}
+
+/// `study` as CSV: one row per event and offset, then one `average` row per
+/// offset carrying the cross-sectional averages and t-stats.
+pub fn to_csv(study: &EventStudy) -> AnySignalResult<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let csv_err = |e: csv::Error| eyre::eyre!("writing event study CSV: {e}");
+    writer
+        .write_record([
+            "event",
+            "label",
+            "event_time",
+            "offset",
+            "bar_time",
+            "return",
+            "abnormal_return",
+            "cumulative_abnormal_return",
+            "events",
+            "t_stat",
+            "t_stat_cumulative",
+        ])
+        .map_err(csv_err)?;
+    let opt = |x: Option<f64>| x.map(|x| x.to_string()).unwrap_or_default();
+    for (n, e) in study.events.iter().enumerate() {
+        for (i, offset) in study.window.offsets().enumerate() {
+            let bar = e.anchor_us + offset * study.interval.micros;
+            writer
+                .write_record([
+                    (n + 1).to_string(),
+                    e.event.label.clone(),
+                    e.event.time.to_rfc3339(),
+                    offset.to_string(),
+                    DateTime::from_timestamp_micros(bar)
+                        .map(|t| t.to_rfc3339())
+                        .unwrap_or_default(),
+                    e.returns[i].to_string(),
+                    e.abnormal[i].to_string(),
+                    e.cumulative[i].to_string(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                ])
+                .map_err(csv_err)?;
+        }
+    }
+    for s in &study.offsets {
+        writer
+            .write_record([
+                "average".to_string(),
+                String::new(),
+                String::new(),
+                s.offset.to_string(),
+                String::new(),
+                s.mean_return.to_string(),
+                s.aar.to_string(),
+                s.caar.to_string(),
+                s.events.to_string(),
+                opt(s.t_aar),
+                opt(s.t_caar),
+            ])
+            .map_err(csv_err)?;
+    }
+    let bytes = writer.into_inner().map_err(|e| eyre::eyre!("{e}"))?;
+    Ok(String::from_utf8(bytes).map_err(|e| eyre::eyre!("{e}"))?)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400_000_000;
+
+    fn event(day: i64) -> StudyEvent {
+        StudyEvent {
+            time: DateTime::from_timestamp_micros(day * DAY + 3_600_000_000).unwrap(),
+            label: format!("day {day}"),
+        }
+    }
+
+    fn bars(prices: &[(i64, f64)]) -> Bars {
+        prices.iter().map(|(d, p)| (d * DAY, *p)).collect()
+    }
+
+    #[test]
+    fn abnormal_returns_against_a_benchmark_or_the_estimation_mean() {
+        let day: BarInterval = "1d".parse().unwrap();
+        let window = Window {
+            pre: 1,
+            post: 1,
+            estimation: 2,
+        };
+        // day 13 is empty and carries day 12's price forward
+        let prices = bars(&[
+            (7, 100.0),
+            (8, 101.0),
+            (9, 102.01),
+            (10, 100.0),
+            (11, 110.0),
+            (12, 99.0),
+            (14, 99.0),
+        ]);
+        let benchmark = bars(&[(9, 50.0), (10, 50.0), (11, 51.0), (12, 51.0)]);
+
+        let e = event_returns(event(11), &prices, Some(&benchmark), &day, &window).unwrap();
+        assert_eq!(e.anchor_us, 11 * DAY);
+        let expected = [100.0 / 102.01 - 1.0, 0.1, -0.1];
+        for (got, want) in e.returns.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-12);
+        }
+        assert!((e.abnormal[1] - (0.1 - 0.02)).abs() < 1e-12);
+        assert!((e.cumulative[2] - e.abnormal.iter().sum::<f64>()).abs() < 1e-12);
+
+        // estimation bars 9 and 10 both returned 1%
+        let e = event_returns(event(11), &prices, None, &day, &window).unwrap();
+        assert!((e.abnormal[1] - 0.09).abs() < 1e-12);
+
+        let e = event_returns(event(13), &prices, None, &day, &window).unwrap();
+        assert_eq!(e.returns[1], 0.0);
+
+        let skipped = event_returns(event(14), &prices, None, &day, &window).unwrap_err();
+        assert_eq!(skipped.reason, "no prices after the window");
+        let skipped = event_returns(event(8), &prices, None, &day, &window).unwrap_err();
+        assert_eq!(skipped.reason, "no prices before the window");
+        let skipped = event_returns(event(13), &prices, Some(&benchmark), &day, &window);
+        assert_eq!(
+            skipped.unwrap_err().reason,
+            "benchmark: no prices after the window"
+        );
+    }
+
+    #[test]
+    fn aggregates_across_events() {
+        let window = Window {
+            pre: 0,
+            post: 1,
+            estimation: 0,
+        };
+        let returns = |abnormal: [f64; 2]| EventReturns {
+            event: event(0),
+            anchor_us: 0,
+            returns: abnormal.to_vec(),
+            abnormal: abnormal.to_vec(),
+            cumulative: vec![abnormal[0], abnormal[0] + abnormal[1]],
+        };
+        let events = [returns([0.01, 0.02]), returns([0.03, 0.02])];
+        let stats = aggregate(&events, &window);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].offset, 0);
+        assert!((stats[0].aar - 0.02).abs() < 1e-12);
+        // mean 0.02, sample sd 0.01414…, so t = 0.02 / 0.01 = 2
+        assert!((stats[0].t_aar.unwrap() - 2.0).abs() < 1e-9);
+        assert!((stats[1].caar - 0.04).abs() < 1e-12);
+        assert_eq!(stats[1].t_aar, None);
+        assert!(aggregate(&[], &window).is_empty());
+
+        let study = EventStudy {
+            interval: "1d".parse().unwrap(),
+            window,
+            model: Model::new(false, &window),
+            events: events.to_vec(),
+            skipped: Vec::new(),
+            offsets: stats,
+        };
+        let csv = to_csv(&study).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 1 + 4 + 2);
+        assert!(
+            lines[1].starts_with("1,day 0,1970-01-01T01:00:00+00:00,0,1970-01-01T00:00:00+00:00,")
+        );
+        assert!(lines[5].starts_with("average,,,0,,"));
+        // no dispersion at offset 1, so no t_aar
+        assert!(lines[6].contains(",2,,"));
+    }
+}
@@ -0,0 +1,6 @@
+/// correlation and lead-lag between series
+pub mod correlation;
+/// abnormal returns around event timestamps
+pub mod event_study;
+/// price series sampled onto a regular bar grid
+pub mod series;
@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::database::query::Query;
+use crate::database::registry::SignalRegistry;
+use crate::database::schema::{self, ColumnType};
+use crate::database::signal_data::{
+    interval_micros, resample_query, Aggregate, Resample, ScalarPoint,
+};
+use crate::database::QuestDbClient;
+use crate::error::AnySignalResult;
+use crate::model::signal::{SignalDataType, SignalInfo};
+
+/// Bar length of a study: a `SAMPLE BY` interval such as `1h` or `1d`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BarInterval {
+    pub spec: String,
+    pub micros: i64,
+}
+
+impl FromStr for BarInterval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let micros = interval_micros(s).ok_or_else(|| {
+            format!("interval '{s}': must be a positive count of s, m, h or d, e.g. 1h")
+        })?;
+        Ok(Self {
+            spec: s.to_string(),
+            micros,
+        })
+    }
+}
+
+impl BarInterval {
+    /// Start of the bar containing `t_us`.
+    pub fn bar(&self, t_us: i64) -> i64 {
+        t_us.div_euclid(self.micros) * self.micros
+    }
+}
+
+/// Last price of each bar, keyed by bar start in microseconds. Bars without
+/// a price are absent.
+pub type Bars = BTreeMap<i64, f64>;
+
+/// The latest price at or before `bar`, carried forward over empty bars.
+pub fn price_at(bars: &Bars, bar: i64) -> Option<f64> {
+    bars.range(..=bar).next_back().map(|(_, p)| *p)
+}
+
+/// Tables whose numeric columns can be read per coin.
+const MARKET_TABLES: &[&str] = &["market_state_1m", "market_state_rt_1m"];
+/// Venue of `market_state_rt_1m` rows when a spec names none.
+const DEFAULT_VENUE: &str = "HlPerp";
+
+/// A numeric series to study.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Series {
+    /// A numeric column of a market state table, for one coin (and, in
+    /// `market_state_rt_1m`, one venue).
+    Market {
+        table: &'static str,
+        coin: String,
+        column: &'static str,
+        venue: Option<String>,
+    },
+    /// A registered scalar signal.
+    Signal(SignalInfo),
+}
+
+impl Series {
+    /// The price series named by exactly one of `coin` (its `price_mid` in
+    /// `market_state_1m`) or `signal_id`.
+    pub fn price(
+        signals: &SignalRegistry,
+        coin: Option<&str>,
+        signal_id: Option<i64>,
+    ) -> Result<Self, String> {
+        let coin = coin.map(str::trim).filter(|c| !c.is_empty());
+        match (coin, signal_id) {
+            (Some(coin), None) => Ok(Self::Market {
+                table: "market_state_1m",
+                coin: coin.to_string(),
+                column: "price_mid",
+                venue: None,
+            }),
+            (None, Some(id)) => Self::signal(signals, id),
+            _ => Err("give exactly one of coin or signal_id".to_string()),
+        }
+    }
+
+    /// Series from a spec: `signal:<id>` for a scalar signal, or
+    /// `<table>:<column>:<coin>` for a numeric column of `market_state_1m`
+    /// or `market_state_rt_1m`, the latter optionally followed by
+    /// `@<venue>` (default `HlPerp`). Coins may contain `:`, e.g. `xyz:TSLA`.
+    pub fn parse(signals: &SignalRegistry, spec: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = spec.splitn(3, ':').map(str::trim).collect();
+        if let ["signal", id] = parts[..] {
+            let id = id
+                .parse()
+                .map_err(|_| format!("series '{spec}': '{id}' is not a signal id"))?;
+            return Self::signal(signals, id);
+        }
+        let [table, column, coin] = parts[..] else {
+            return Err(format!(
+                "series '{spec}': expected 'signal:<id>' or '<table>:<column>:<coin>'"
+            ));
+        };
+        let (coin, venue) = match coin.rsplit_once('@') {
+            Some((coin, venue)) => (coin, Some(venue)),
+            None => (coin, None),
+        };
+        let table = MARKET_TABLES
+            .iter()
+            .find(|t| **t == table)
+            .ok_or_else(|| format!("series '{spec}': table must be one of {MARKET_TABLES:?}"))?;
+        if coin.is_empty() {
+            return Err(format!("series '{spec}': missing coin"));
+        }
+        let column = schema::table(table)
+            .map(|t| t.columns())
+            .unwrap_or_default()
+            .into_iter()
+            .find(|c| {
+                c.name == column
+                    && matches!(
+                        c.ty,
+                        ColumnType::Double | ColumnType::Long | ColumnType::Int
+                    )
+            })
+            .ok_or_else(|| format!("series '{spec}': {table} has no numeric column '{column}'"))?
+            .name;
+        let venue = match (*table, venue) {
+            ("market_state_rt_1m", venue) => {
+                Some(venue.filter(|v| !v.is_empty()).unwrap_or(DEFAULT_VENUE))
+            }
+            (_, None) => None,
+            (_, Some(_)) => {
+                return Err(format!(
+                    "series '{spec}': only market_state_rt_1m has venues"
+                ))
+            }
+        };
+        Ok(Self::Market {
+            table,
+            coin: coin.to_string(),
+            column,
+            venue: venue.map(str::to_string),
+        })
+    }
+
+    fn signal(signals: &SignalRegistry, id: i64) -> Result<Self, String> {
+        let info = signals
+            .get(id)
+            .ok_or_else(|| format!("unknown signal id {id}"))?;
+        if info.data_type != SignalDataType::Scalar {
+            return Err(format!(
+                "signal {} is {}; only scalar signals are series",
+                info.id,
+                info.data_type.name()
+            ));
+        }
+        Ok(Self::Signal(info))
+    }
+
+    /// The spec [`Series::parse`] reads back, e.g.
+    /// `market_state_1m:price_mid:BTC` or `signal:12`.
+    pub fn label(&self) -> String {
+        match self {
+            Self::Market {
+                table,
+                coin,
+                column,
+                venue,
+            } => match venue {
+                Some(venue) => format!("{table}:{column}:{coin}@{venue}"),
+                None => format!("{table}:{column}:{coin}"),
+            },
+            Self::Signal(info) => format!("signal:{}", info.id),
+        }
+    }
+}
+
+/// `series` over `[from, to)`, the last value of each bar.
+pub async fn read_bars(
+    db: &QuestDbClient,
+    series: &Series,
+    interval: &BarInterval,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> AnySignalResult<Bars> {
+    let points: Vec<(DateTime<Utc>, Option<f64>)> = match series {
+        Series::Market {
+            table,
+            coin,
+            column,
+            venue,
+        } => {
+            #[derive(Deserialize)]
+            struct Row {
+                ts: DateTime<Utc>,
+                value: Option<f64>,
+            }
+            let query = Query::select(&["ts"])
+                .select_expr(&format!("last({column}) value"))
+                .from(&db.table_name(table))
+                .eq("coin", coin.as_str());
+            let query = match venue {
+                Some(venue) => query.eq("venue", venue.as_str()),
+                None => query,
+            };
+            let query = query.time_range("ts", from, to).sample_by(&interval.spec);
+            db.query::<Row>(&query)
+                .await?
+                .into_iter()
+                .map(|r| (r.ts, r.value))
+                .collect()
+        }
+        Series::Signal(info) => {
+            let resample = Resample {
+                aggregate: Aggregate::Last,
+                interval: interval.spec.clone(),
+            };
+            db.query::<ScalarPoint>(&resample_query(db, info, &resample, from, to))
+                .await?
+                .into_iter()
+                .map(|p| (p.timestamp, Some(p.value)))
+                .collect()
+        }
+    };
+    Ok(points
+        .into_iter()
+        .filter_map(|(t, p)| Some((interval.bar(t.timestamp_micros()), p?)))
+        .filter(|(_, p)| p.is_finite())
+        .collect())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_align_and_carry_prices_forward() {
+        let hour: BarInterval = "1h".parse().unwrap();
+        assert_eq!(hour.bar(3_600_000_000 + 5), 3_600_000_000);
+        assert_eq!(hour.bar(-1), -3_600_000_000);
+        assert!("1w".parse::<BarInterval>().is_err());
+
+        let bars: Bars = [(0, 10.0), (2, 12.0)].into();
+        assert_eq!(price_at(&bars, -1), None);
+        assert_eq!(price_at(&bars, 1), Some(10.0));
+        assert_eq!(price_at(&bars, 5), Some(12.0));
+    }
+
+    #[test]
+    fn parses_series_specs() {
+        let signals = SignalRegistry::default();
+        let series = Series::parse(&signals, "market_state_rt_1m:funding_rate:BTC").unwrap();
+        assert_eq!(series.label(), "market_state_rt_1m:funding_rate:BTC@HlPerp");
+        let series = Series::parse(&signals, "market_state_rt_1m:price_mid:xyz:TSLA@HlPerp");
+        assert!(matches!(series, Ok(Series::Market { ref coin, .. }) if coin == "xyz:TSLA"));
+        let series = Series::parse(&signals, "market_state_1m:trade_count:ETH").unwrap();
+        assert_eq!(Series::parse(&signals, &series.label()), Ok(series));
+        assert_eq!(
+            Series::price(&signals, Some("BTC"), None).unwrap().label(),
+            "market_state_1m:price_mid:BTC"
+        );
+        for bad in [
+            "signal:x",
+            "signal:7",
+            "market_data:price:BTC",
+            "market_state_1m:coin:BTC",
+            "market_state_1m:price_mid:BTC@HlPerp",
+            "market_state_1m:price_mid:",
+            "BTC",
+        ] {
+            assert!(Series::parse(&signals, bad).is_err(), "{bad}");
+        }
+        assert!(Series::price(&signals, Some("BTC"), Some(1)).is_err());
+    }
+}
@@ -1,3 +1,11 @@
+use crate::analysis::correlation::{
+    run_correlation, Correlation, LagCorrelation, Settings as CorrelationSettings,
+};
+use crate::analysis::event_study::{
+    run_event_study, signal_events, to_csv, EventReturns, OffsetStats, SkippedEvent, StudyEvent,
+    Window, MAX_EVENTS,
+};
+use crate::analysis::series::{BarInterval, Series};
 use crate::backfill::asset_ctxs::AssetCtxsSource;
 use crate::backfill::l2_orderbook::{L2PartitionKey, L2SnapshotSource};
 use crate::backfill::market_state_1m::{MarketState1mHourKey, MarketState1mSource};
@@ -8,15 +16,52 @@ use crate::backfill::node_fills_legacy_1m_aggregate::{NodeFillsLegacy1mAggregate
 use crate::backfill::run_backfill;
 use crate::backfill::tracker::BackfillTracker;
 use crate::backfill::PartitionedSource;
+use crate::backtest::jobs::BacktestJob;
+use crate::backtest::simulator::{Costs, Settings as BacktestSettings, Spec as BacktestSpec};
+use crate::backtest::{run_backtest, Backtest, BacktestJobs};
 use crate::config::Config;
+use crate::database::calendar::{
+    parse_csv, parse_event_time, Event, EventCalendar, EventFilter, EventSpec,
+};
+use crate::database::export::{export, export_query, ExportFormat};
+use crate::database::migration::schema_report;
+use crate::database::query::Query as SqlQuery;
+use crate::database::registry::SignalRegistry;
+use crate::database::retention::{
+    run_once, RetentionManager, RetentionPolicy, RetentionRun, RetentionSettings,
+};
+use crate::database::schema;
+use crate::database::signal_data::{read_signal, Resample, SignalPoints, DEFAULT_LIMIT, MAX_LIMIT};
+use crate::database::spool::SpoolStatus;
 use crate::database::QuestDbClient;
+use crate::error::AnySignalResult;
 use crate::metadata::cargo_package_version;
+use crate::model::instance::{InstanceStatus, PaperInstance};
+use crate::model::signal::{FieldValue, SignalData, SignalDataType, SignalInfo};
+use crate::poller::PollerRegistry;
+use crate::sink::{self, Sink};
+use crate::strategy::{builtin, StrategyRegistry};
 use chrono::{NaiveDate, NaiveDateTime, Timelike};
+use futures::TryStreamExt;
+use poem::Body;
 use poem_openapi::{
-    param::Query,
-    payload::{Json, PlainText},
-    ApiResponse, Enum, Object, OpenApi,
+    param::{Path, Query},
+    payload::{Binary, Json, PlainText},
+    ApiRequest, ApiResponse, Enum, Object, OpenApi,
 };
+use std::sync::Arc;
+
+/// Most series one `GET /analysis/correlation` takes.
+const MAX_SERIES: usize = 8;
+/// Largest `max_lag` of `GET /analysis/correlation`, in bars.
+const MAX_LAG: u32 = 1000;
+/// Largest rolling `window` of `GET /analysis/correlation`, in bars.
+const MAX_WINDOW: u32 = 1000;
+/// Largest `bars × (2·max_lag + 1)` of `GET /analysis/correlation`, the
+/// points one pair's lead-lag profile pairs up.
+const MAX_LAG_POINTS: i64 = 5_000_000;
+/// Longest range one `POST /backtest` replays, in days.
+const MAX_BACKTEST_DAYS: i64 = 366;
 
 /// Parse a datetime string in either `YYYY-MM-DDTHH:MM:SS` or `YYYY-MM-DD` format.
 /// Date-only strings are treated as midnight (`T00:00:00`).
@@ -34,7 +79,16 @@ fn parse_flexible_datetime(s: &str) -> Result<NaiveDateTime, String> {
 
 pub struct Endpoint {
     pub config: Config,
+    /// Shared by every request; its ILP senders and pgwire connections are
+    /// pooled.
+    pub db: Arc<QuestDbClient>,
     pub tracker: BackfillTracker,
+    pub pollers: PollerRegistry,
+    pub retention: RetentionManager,
+    pub signals: SignalRegistry,
+    pub events: EventCalendar,
+    pub strategies: StrategyRegistry,
+    pub backtests: BacktestJobs,
 }
 
 // ---------------------------------------------------------------------------
@@ -95,7 +149,7 @@ enum BackfillSource {
     /// **Data available from:** `2025-05-25T14:00:00` **to** `2025-07-27T08:00:00`
     ///
     /// Note: `2025-07-27` hour 8 exists in both this dataset and
-    /// `HyperliquidNodeFills`.  The `partition_exists` check prevents
+    /// `HyperliquidNodeFills`.  The partition presence check prevents
     /// double-ingestion — whichever source is backfilled first wins.
     HyperliquidNodeFillsLegacy,
 
@@ -115,12 +169,12 @@ enum BackfillSource {
     HyperliquidNodeFillsLegacy1mAggregate,
 
     /// Compute `market_state_1m` by joining `hyperliquid_fill_1m_aggregate`
-    /// (minute-level fill stats) with `market_data` (daily price/market
-    /// snapshots).  No S3 access — pure DB-to-DB computation.
+    /// (minute-level fill stats) with `asset_ctx` (price/market snapshots).
+    /// No S3 access — pure DB-to-DB computation.
     ///
     /// Per minute bucket and coin the row contains:
-    /// - `price_oracle`, `price_mark`, `price_mid` — from `market_data` daily snapshot
-    /// - `open_interest`, `funding_rate`, `volume_24h_usd` — from `market_data` daily snapshot
+    /// - `price_oracle`, `price_mark`, `price_mid` — from the `asset_ctx` snapshot
+    /// - `open_interest`, `funding_rate`, `volume_24h_usd` — from the `asset_ctx` snapshot
     /// - `trade_volume`, `trade_count` — buy-side fills only (pairs cancel out)
     /// - `liquidation_long/short_volume/count` — fills with category in
     ///   (`Liquidated Isolated Long`, `Liquidated Cross Long`, …Short)
@@ -193,12 +247,39 @@ struct TableStats {
     disk_size_bytes: i64,
 }
 
+/// ILP buffers waiting in the write-ahead spool for QuestDB to accept them.
+#[derive(Debug, Object)]
+struct SpoolStats {
+    files: u64,
+    bytes: u64,
+    /// Cap from `QUESTDB_SPOOL_MAX_MB`; failed writes error once reached.
+    max_bytes: u64,
+    /// RFC 3339 UTC timestamp of when the oldest pending buffer was spooled.
+    oldest: Option<String>,
+    /// Pending buffers per table.
+    tables: std::collections::BTreeMap<String, u64>,
+}
+
+impl From<SpoolStatus> for SpoolStats {
+    fn from(s: SpoolStatus) -> Self {
+        Self {
+            files: s.files as u64,
+            bytes: s.bytes,
+            max_bytes: s.max_bytes,
+            oldest: s.oldest.map(|t| t.to_rfc3339()),
+            tables: s.tables.into_iter().map(|(t, n)| (t, n as u64)).collect(),
+        }
+    }
+}
+
 /// Response for `GET /database`.
 #[derive(Debug, Object)]
 struct DatabaseStats {
     tables: Vec<TableStats>,
     /// Sum of `disk_size_bytes` across all tables.
     total_disk_size_bytes: i64,
+    /// Absent when the spool is disabled.
+    spool: Option<SpoolStats>,
 }
 
 #[derive(ApiResponse)]
@@ -209,47 +290,1170 @@ enum DatabaseApiResponse {
     InternalError(PlainText<String>),
 }
 
+// ---------------------------------------------------------------------------
+// Schema types
+// ---------------------------------------------------------------------------
+
+/// A column whose live type differs from the expected one.
+#[derive(Debug, Object)]
+struct ColumnTypeMismatch {
+    column: String,
+    expected: String,
+    actual: String,
+}
+
+/// Expected vs live shape of one managed table.
+#[derive(Debug, Object)]
+struct TableSchemaStatus {
+    /// Table name (with `_dev` suffix in dev mode).
+    table: String,
+    /// `false` when the table does not exist yet.
+    exists: bool,
+    /// `true` when the table matches its expected schema exactly.
+    in_sync: bool,
+    /// Latest migration version defined for the table.
+    expected_version: u32,
+    /// Highest migration version recorded in `schema_migrations`.
+    applied_version: u32,
+    /// Expected columns missing from the live table.
+    missing_columns: Vec<String>,
+    /// Live columns not defined by the schema.
+    unexpected_columns: Vec<String>,
+    type_mismatches: Vec<ColumnTypeMismatch>,
+    expected_timestamp: Option<String>,
+    /// Live designated timestamp column.
+    actual_timestamp: Option<String>,
+    /// Natural key declared via `DEDUP UPSERT KEYS`.
+    expected_dedup_keys: Vec<String>,
+    /// Live upsert keys; empty when dedup is disabled.
+    actual_dedup_keys: Vec<String>,
+}
+
+/// Response for `GET /schema`.
+#[derive(Debug, Object)]
+struct SchemaStatus {
+    /// `true` when every managed table is in sync.
+    in_sync: bool,
+    tables: Vec<TableSchemaStatus>,
+}
+
+#[derive(ApiResponse)]
+enum SchemaApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<SchemaStatus>),
+    #[oai(status = 500)]
+    InternalError(PlainText<String>),
+}
+
 // ---------------------------------------------------------------------------
 // Coverage types
 // ---------------------------------------------------------------------------
 
-/// One contiguous range of missing partitions (both bounds inclusive).
+/// One contiguous range of missing partitions (both bounds inclusive).
+#[derive(Debug, Object)]
+struct CoverageGap {
+    /// First missing partition key, e.g. `"2025-07-28T12:00:00"`.
+    from: String,
+    /// Last missing partition key, e.g. `"2025-07-28T14:00:00"`.
+    to: String,
+}
+
+/// Response for `GET /coverage`.
+#[derive(Debug, Object)]
+struct CoverageResult {
+    source: String,
+    /// Requested range start.
+    from: String,
+    /// Requested range end.
+    to: String,
+    /// Total number of partition keys in the requested range.
+    total_partitions: u64,
+    /// Number of partitions present in QuestDB.
+    present_count: u64,
+    /// Number of partitions missing from QuestDB.
+    missing_count: u64,
+    /// Contiguous ranges of missing partitions (from/to both inclusive).
+    gaps: Vec<CoverageGap>,
+}
+
+#[derive(ApiResponse)]
+enum CoverageApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<CoverageResult>),
+    #[oai(status = 400)]
+    BadRequest(PlainText<String>),
+    #[oai(status = 500)]
+    InternalError(PlainText<String>),
+}
+
+// ---------------------------------------------------------------------------
+// Poller types
+// ---------------------------------------------------------------------------
+
+/// Health of one periodic REST poller.
+#[derive(Debug, Object)]
+struct PollerHealthEntry {
+    /// Poller name, e.g. `"HlPerp/predicted_funding"`.
+    name: String,
+    /// `Pending`, `Healthy`, `Failing`, or `Stopped`.
+    status: String,
+    interval_ms: u64,
+    jitter_ms: u64,
+    max_retries: u32,
+    /// RFC 3339 UTC timestamp of the last successful poll.
+    last_success_at: Option<String>,
+    last_error: Option<String>,
+    /// RFC 3339 UTC timestamp of the last failed attempt.
+    last_error_at: Option<String>,
+    /// Failed attempts since the last success (retries included).
+    consecutive_failures: u64,
+    total_successes: u64,
+    total_failures: u64,
+}
+
+// ---------------------------------------------------------------------------
+// Export types
+// ---------------------------------------------------------------------------
+
+/// File format for `GET /export`.
+#[derive(Debug, Clone, Copy, Enum)]
+#[oai(rename_all = "lowercase")]
+enum ExportFileFormat {
+    /// Snappy-compressed Parquet, one row group per fetched batch.
+    Parquet,
+    /// CSV with a header row; timestamps in RFC 3339.
+    Csv,
+    /// Arrow IPC streaming format.
+    Arrow,
+}
+
+impl From<ExportFileFormat> for ExportFormat {
+    fn from(f: ExportFileFormat) -> Self {
+        match f {
+            ExportFileFormat::Parquet => ExportFormat::Parquet,
+            ExportFileFormat::Csv => ExportFormat::Csv,
+            ExportFileFormat::Arrow => ExportFormat::Arrow,
+        }
+    }
+}
+
+#[derive(ApiResponse)]
+enum ExportApiResponse {
+    #[oai(status = 200, content_type = "application/vnd.apache.parquet")]
+    Parquet(Binary<Body>, #[oai(header = "Content-Disposition")] String),
+    #[oai(status = 200, content_type = "text/csv")]
+    Csv(Binary<Body>, #[oai(header = "Content-Disposition")] String),
+    #[oai(status = 200, content_type = "application/vnd.apache.arrow.stream")]
+    Arrow(Binary<Body>, #[oai(header = "Content-Disposition")] String),
+    #[oai(status = 400)]
+    BadRequest(PlainText<String>),
+    #[oai(status = 500)]
+    InternalError(PlainText<String>),
+}
+
+// ---------------------------------------------------------------------------
+// Retention types
+// ---------------------------------------------------------------------------
+
+/// Retention policy for one raw table.
+#[derive(Debug, Object)]
+struct RetentionPolicyEntry {
+    /// Base table name, e.g. `l2_orderbook`.
+    table: String,
+    /// Partitions whose newest row is older than this many days are dropped.
+    keep_days: u32,
+    /// Rollup tables filled from a partition before it is dropped:
+    /// `l2_orderbook_1m`, `l2_orderbook_1h` (for `l2_orderbook`),
+    /// `hyperliquid_fill_1m_aggregate`, `hyperliquid_fill_1h_aggregate`
+    /// (for `hyperliquid_fill`).
+    #[oai(default)]
+    rollups: Vec<String>,
+}
+
+/// Body of `PUT /retention`; also part of `GET /retention`.
+#[derive(Debug, Object)]
+struct RetentionSettingsBody {
+    /// Only report what would be dropped.
+    dry_run: bool,
+    policies: Vec<RetentionPolicyEntry>,
+}
+
+/// One partition dropped, or due to be dropped.
+#[derive(Debug, Object)]
+struct RetentionActionEntry {
+    table: String,
+    /// QuestDB partition name, e.g. `"2025-06-01T12"`.
+    partition: String,
+    /// RFC 3339 UTC timestamp of the oldest row.
+    min_timestamp: Option<String>,
+    /// RFC 3339 UTC timestamp of the newest row.
+    max_timestamp: Option<String>,
+    num_rows: i64,
+    rollups: Vec<String>,
+    /// `false` in dry-run or when the rollup or drop failed.
+    dropped: bool,
+}
+
+/// One pass over every policy.
+#[derive(Debug, Object)]
+struct RetentionRunEntry {
+    /// RFC 3339 UTC timestamp.
+    started_at: String,
+    dry_run: bool,
+    actions: Vec<RetentionActionEntry>,
+    errors: Vec<String>,
+}
+
+/// Response for `GET`/`PUT /retention`.
+#[derive(Debug, Object)]
+struct RetentionStatus {
+    settings: RetentionSettingsBody,
+    /// What a run right now would drop (always a dry run).
+    plan: RetentionRunEntry,
+    /// Last scheduled run; `null` until the `retention` runner has run once.
+    last_run: Option<RetentionRunEntry>,
+}
+
+#[derive(ApiResponse)]
+enum RetentionApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<RetentionStatus>),
+    #[oai(status = 400)]
+    BadRequest(PlainText<String>),
+}
+
+impl From<RetentionSettings> for RetentionSettingsBody {
+    fn from(s: RetentionSettings) -> Self {
+        Self {
+            dry_run: s.dry_run,
+            policies: s
+                .policies
+                .into_iter()
+                .map(|p| RetentionPolicyEntry {
+                    table: p.table,
+                    keep_days: p.keep_days,
+                    rollups: p.rollups,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<RetentionSettingsBody> for RetentionSettings {
+    fn from(s: RetentionSettingsBody) -> Self {
+        Self {
+            dry_run: s.dry_run,
+            policies: s
+                .policies
+                .into_iter()
+                .map(|p| RetentionPolicy {
+                    table: p.table,
+                    keep_days: p.keep_days,
+                    rollups: p.rollups,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<RetentionRun> for RetentionRunEntry {
+    fn from(r: RetentionRun) -> Self {
+        let rfc3339 = |t: chrono::DateTime<chrono::Utc>| t.to_rfc3339();
+        Self {
+            started_at: rfc3339(r.started_at),
+            dry_run: r.dry_run,
+            actions: r
+                .actions
+                .into_iter()
+                .map(|a| RetentionActionEntry {
+                    table: a.table,
+                    partition: a.partition,
+                    min_timestamp: a.min_timestamp.map(rfc3339),
+                    max_timestamp: a.max_timestamp.map(rfc3339),
+                    num_rows: a.num_rows,
+                    rollups: a.rollups,
+                    dropped: a.dropped,
+                })
+                .collect(),
+            errors: r.errors,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Signal types
+// ---------------------------------------------------------------------------
+
+/// A registered signal.
+#[derive(Debug, Object)]
+struct SignalEntry {
+    /// Registry id, stored as `info_id` in the `signal_*` tables.
+    id: i64,
+    signal_type: String,
+    /// `simple`, `binary`, `scalar`, `text`, `vector` or `structured`; picks
+    /// the `signal_*` table.
+    data_type: String,
+    source: String,
+    description: String,
+    /// Raw and indivisible, rather than derived from other signals.
+    is_atomic: bool,
+    /// What distinguishes signals of the same type and source.
+    parameters: std::collections::BTreeMap<String, String>,
+}
+
+#[derive(ApiResponse)]
+enum SignalsApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<SignalEntry>>),
+}
+
+#[derive(ApiResponse)]
+enum SignalApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<SignalEntry>),
+    #[oai(status = 404)]
+    NotFound(PlainText<String>),
+}
+
+/// One row of a signal, or one interval when resampled. Only the fields of
+/// the signal's data type (or of the resample) are present.
+#[derive(Debug, Default, Object)]
+struct SignalPointEntry {
+    /// RFC 3339 UTC timestamp; the interval start when resampled.
+    timestamp: String,
+    /// `scalar` signals, and `last` / `mean` resamples.
+    #[oai(skip_serializing_if_is_none)]
+    value: Option<f64>,
+    /// `binary` signals.
+    #[oai(skip_serializing_if_is_none)]
+    flag: Option<bool>,
+    /// `text` signals.
+    #[oai(skip_serializing_if_is_none)]
+    text: Option<String>,
+    /// `vector` signals.
+    #[oai(skip_serializing_if_is_none)]
+    values: Option<Vec<f64>>,
+    /// `structured` signals: field name to number or string.
+    #[oai(skip_serializing_if_is_none)]
+    fields: Option<std::collections::BTreeMap<String, serde_json::Value>>,
+    /// `ohlc` resamples.
+    #[oai(skip_serializing_if_is_none)]
+    open: Option<f64>,
+    #[oai(skip_serializing_if_is_none)]
+    high: Option<f64>,
+    #[oai(skip_serializing_if_is_none)]
+    low: Option<f64>,
+    #[oai(skip_serializing_if_is_none)]
+    close: Option<f64>,
+}
+
+/// Response for `GET /signals/{id}/data`.
+#[derive(Debug, Object)]
+struct SignalDataResult {
+    signal: SignalEntry,
+    /// The `resample` parameter, when set.
+    resample: Option<String>,
+    points: Vec<SignalPointEntry>,
+}
+
+#[derive(ApiResponse)]
+enum SignalDataApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<SignalDataResult>),
+    #[oai(status = 400)]
+    BadRequest(PlainText<String>),
+    #[oai(status = 404)]
+    NotFound(PlainText<String>),
+    #[oai(status = 500)]
+    InternalError(PlainText<String>),
+}
+
+impl From<SignalPoints> for Vec<SignalPointEntry> {
+    fn from(points: SignalPoints) -> Self {
+        let rfc3339 = |t: chrono::DateTime<chrono::Utc>| t.to_rfc3339();
+        match points {
+            SignalPoints::Raw(signals) => signals
+                .into_iter()
+                .map(|s| {
+                    let timestamp = chrono::DateTime::from_timestamp_micros(s.timestamp_us)
+                        .map(rfc3339)
+                        .unwrap_or_default();
+                    let entry = SignalPointEntry {
+                        timestamp,
+                        ..Default::default()
+                    };
+                    match s.data {
+                        SignalData::Simple => entry,
+                        SignalData::Binary(v) => SignalPointEntry {
+                            flag: Some(v),
+                            ..entry
+                        },
+                        SignalData::Scalar(v) => SignalPointEntry {
+                            value: Some(v),
+                            ..entry
+                        },
+                        SignalData::Text(v) => SignalPointEntry {
+                            text: Some(v),
+                            ..entry
+                        },
+                        SignalData::Vector(v) => SignalPointEntry {
+                            values: Some(v),
+                            ..entry
+                        },
+                        SignalData::Structured(fields) => SignalPointEntry {
+                            fields: Some(
+                                fields
+                                    .into_iter()
+                                    .map(|(k, v)| {
+                                        let v = match v {
+                                            FieldValue::Number(n) => serde_json::json!(n),
+                                            FieldValue::Text(t) => serde_json::Value::String(t),
+                                        };
+                                        (k, v)
+                                    })
+                                    .collect(),
+                            ),
+                            ..entry
+                        },
+                    }
+                })
+                .collect(),
+            SignalPoints::Scalar(points) => points
+                .into_iter()
+                .map(|p| SignalPointEntry {
+                    timestamp: rfc3339(p.timestamp),
+                    value: Some(p.value),
+                    ..Default::default()
+                })
+                .collect(),
+            SignalPoints::Ohlc(points) => points
+                .into_iter()
+                .map(|p| SignalPointEntry {
+                    timestamp: rfc3339(p.timestamp),
+                    open: Some(p.open),
+                    high: Some(p.high),
+                    low: Some(p.low),
+                    close: Some(p.close),
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<SignalInfo> for SignalEntry {
+    fn from(i: SignalInfo) -> Self {
+        Self {
+            id: i.id,
+            signal_type: i.signal_type,
+            data_type: i.data_type.name().to_string(),
+            source: i.source,
+            description: i.description,
+            is_atomic: i.is_atomic,
+            parameters: i.parameters,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Event types
+// ---------------------------------------------------------------------------
+
+/// Body of `POST /events` and `PUT /events/{id}`.
+#[derive(Debug, Object)]
+struct EventBody {
+    /// `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM:SS` (UTC) or RFC 3339.
+    time: String,
+    /// e.g. `NVDA`; omit for market-wide events.
+    ticker: Option<String>,
+    /// e.g. `conference`, `product_launch`, `earnings`; stored lowercase.
+    event_type: String,
+    title: String,
+    #[oai(default)]
+    tags: Vec<String>,
+}
+
+impl EventBody {
+    fn spec(&self) -> Result<EventSpec, String> {
+        EventSpec::new(
+            parse_event_time(&self.time)?,
+            self.ticker.as_deref(),
+            &self.event_type,
+            &self.title,
+            &self.tags,
+        )
+    }
+}
+
+/// A calendar event.
+#[derive(Debug, Object)]
+struct EventEntry {
+    id: i64,
+    /// Registry id of the calendar signal, the event rows' `info_id` in
+    /// `signal_structured`; their `key` is the event id.
+    signal_id: i64,
+    /// RFC 3339 UTC.
+    time: String,
+    #[oai(skip_serializing_if_is_none)]
+    ticker: Option<String>,
+    event_type: String,
+    title: String,
+    tags: Vec<String>,
+}
+
+impl From<Event> for EventEntry {
+    fn from(e: Event) -> Self {
+        Self {
+            id: e.id,
+            signal_id: e.signal_id,
+            time: e.spec.time.to_rfc3339(),
+            ticker: e.spec.ticker,
+            event_type: e.spec.event_type,
+            title: e.spec.title,
+            tags: e.spec.tags,
+        }
+    }
+}
+
+/// Body of `POST /events/import`.
+#[derive(ApiRequest)]
+enum EventCsvBody {
+    /// Header `time,ticker,event_type,title,tags`; tags separated by `;`.
+    #[oai(content_type = "text/csv")]
+    Csv(PlainText<String>),
+}
+
+#[derive(ApiResponse)]
+enum EventsApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<EventEntry>>),
+    #[oai(status = 400)]
+    BadRequest(PlainText<String>),
+    #[oai(status = 500)]
+    InternalError(PlainText<String>),
+}
+
+#[derive(ApiResponse)]
+enum EventApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<EventEntry>),
+    #[oai(status = 400)]
+    BadRequest(PlainText<String>),
+    #[oai(status = 404)]
+    NotFound(PlainText<String>),
+    #[oai(status = 500)]
+    InternalError(PlainText<String>),
+}
+
+impl EventApiResponse {
+    fn found(id: i64, result: AnySignalResult<Option<Event>>) -> Self {
+        match result {
+            Ok(Some(event)) => Self::Ok(Json(event.into())),
+            Ok(None) => Self::NotFound(PlainText(format!("unknown event id {id}"))),
+            Err(e) => Self::InternalError(PlainText(format!("Event calendar error: {e}"))),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Analysis types
+// ---------------------------------------------------------------------------
+
+/// A price series: set exactly one field.
+#[derive(Debug, Object)]
+struct SeriesBody {
+    /// Coin in `market_state_1m`, e.g. `BTC`; its `price_mid` is used.
+    coin: Option<String>,
+    /// Any registered scalar signal.
+    signal_id: Option<i64>,
+}
+
+impl SeriesBody {
+    fn resolve(&self, signals: &SignalRegistry) -> Result<Series, String> {
+        Series::price(signals, self.coin.as_deref(), self.signal_id)
+    }
+}
+
+/// Calendar events to study; unset filters match everything.
+#[derive(Debug, Object)]
+struct CalendarEventsBody {
+    ticker: Option<String>,
+    event_type: Option<String>,
+    tag: Option<String>,
+}
+
+/// Response format of an analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[oai(rename_all = "lowercase")]
+enum AnalysisFormat {
+    Json,
+    Csv,
+}
+
+/// Body of `POST /analysis/event_study`.
+#[derive(Debug, Object)]
+struct EventStudyBody {
+    /// Signal whose rows are the events, e.g. MicroStrategy purchases.
+    event_signal_id: Option<i64>,
+    /// Calendar events; use instead of `event_signal_id`.
+    events: Option<CalendarEventsBody>,
+    /// Events from, **inclusive**: `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM:SS` or
+    /// RFC 3339.
+    from: Option<String>,
+    /// Events until, **exclusive**.
+    to: Option<String>,
+    price: SeriesBody,
+    /// Normal returns are the benchmark's; without one they are the mean
+    /// return over the `estimation` bars before the window.
+    benchmark: Option<SeriesBody>,
+    /// Bar length, a count of `s`, `m`, `h` or `d`; defaults to `1d`.
+    interval: Option<String>,
+    /// Bars before the event bar; defaults to 5.
+    pre: Option<u32>,
+    /// Bars after the event bar; defaults to 10.
+    post: Option<u32>,
+    /// Estimation bars before the window; defaults to 30.
+    estimation: Option<u32>,
+    /// `json` (default) or `csv`.
+    format: Option<AnalysisFormat>,
+}
+
+/// Returns around one event, one per offset from `-pre` to `post`.
+#[derive(Debug, Object)]
+struct EventReturnsEntry {
+    /// RFC 3339 UTC.
+    time: String,
+    #[oai(skip_serializing_if_is_none)]
+    label: Option<String>,
+    /// Start of the event's bar (offset 0), RFC 3339 UTC.
+    anchor: String,
+    returns: Vec<f64>,
+    abnormal_returns: Vec<f64>,
+    cumulative_abnormal_returns: Vec<f64>,
+}
+
+impl From<EventReturns> for EventReturnsEntry {
+    fn from(e: EventReturns) -> Self {
+        Self {
+            time: e.event.time.to_rfc3339(),
+            label: Some(e.event.label).filter(|l| !l.is_empty()),
+            anchor: chrono::DateTime::from_timestamp_micros(e.anchor_us)
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default(),
+            returns: e.returns,
+            abnormal_returns: e.abnormal,
+            cumulative_abnormal_returns: e.cumulative,
+        }
+    }
+}
+
+/// An event left out, e.g. for lack of prices around it.
+#[derive(Debug, Object)]
+struct SkippedEventEntry {
+    /// RFC 3339 UTC.
+    time: String,
+    #[oai(skip_serializing_if_is_none)]
+    label: Option<String>,
+    reason: String,
+}
+
+impl From<SkippedEvent> for SkippedEventEntry {
+    fn from(s: SkippedEvent) -> Self {
+        Self {
+            time: s.event.time.to_rfc3339(),
+            label: Some(s.event.label).filter(|l| !l.is_empty()),
+            reason: s.reason,
+        }
+    }
+}
+
+/// Cross-sectional statistics of one offset.
+#[derive(Debug, Object)]
+struct OffsetStatsEntry {
+    offset: i64,
+    events: u64,
+    mean_return: f64,
+    /// Average abnormal return.
+    aar: f64,
+    /// Cumulative average abnormal return from `-pre`.
+    caar: f64,
+    /// Absent below two events.
+    #[oai(skip_serializing_if_is_none)]
+    t_aar: Option<f64>,
+    #[oai(skip_serializing_if_is_none)]
+    t_caar: Option<f64>,
+}
+
+impl From<OffsetStats> for OffsetStatsEntry {
+    fn from(s: OffsetStats) -> Self {
+        Self {
+            offset: s.offset,
+            events: s.events as u64,
+            mean_return: s.mean_return,
+            aar: s.aar,
+            caar: s.caar,
+            t_aar: s.t_aar,
+            t_caar: s.t_caar,
+        }
+    }
+}
+
+#[derive(Debug, Object)]
+struct EventStudyResult {
+    /// Series spec, e.g. `market_state_1m:price_mid:BTC` or `signal:12`.
+    price: String,
+    #[oai(skip_serializing_if_is_none)]
+    benchmark: Option<String>,
+    /// `market_adjusted`, `mean_adjusted` or `raw`.
+    model: String,
+    interval: String,
+    pre: u32,
+    post: u32,
+    estimation: u32,
+    events: Vec<EventReturnsEntry>,
+    skipped: Vec<SkippedEventEntry>,
+    /// One per offset from `-pre` to `post`; empty when no event was studied.
+    offsets: Vec<OffsetStatsEntry>,
+}
+
+#[derive(ApiResponse)]
+enum EventStudyApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<EventStudyResult>),
+    #[oai(status = 200, content_type = "text/csv")]
+    Csv(PlainText<String>),
+    #[oai(status = 400)]
+    BadRequest(PlainText<String>),
+    #[oai(status = 404)]
+    NotFound(PlainText<String>),
+    #[oai(status = 500)]
+    InternalError(PlainText<String>),
+}
+
+/// Correlation of `a` at each bar with `b` `lag` bars later.
+#[derive(Debug, Object)]
+struct LagCorrelationEntry {
+    /// Positive when `a` leads `b`.
+    lag: i64,
+    points: u64,
+    /// Absent below three points or when either side is constant.
+    #[oai(skip_serializing_if_is_none)]
+    correlation: Option<f64>,
+}
+
+impl From<&LagCorrelation> for LagCorrelationEntry {
+    fn from(l: &LagCorrelation) -> Self {
+        Self {
+            lag: l.lag,
+            points: l.points as u64,
+            correlation: l.correlation,
+        }
+    }
+}
+
+/// Rolling correlation of the `window` bars ending at `timestamp`.
+#[derive(Debug, Object)]
+struct RollingCorrelationEntry {
+    /// Start of the window's last bar, RFC 3339 UTC.
+    timestamp: String,
+    #[oai(skip_serializing_if_is_none)]
+    correlation: Option<f64>,
+}
+
+/// Correlations between two of the requested series.
+#[derive(Debug, Object)]
+struct PairCorrelationEntry {
+    a: String,
+    b: String,
+    /// Aligned bars both are correlated over.
+    points: u64,
+    #[oai(skip_serializing_if_is_none)]
+    pearson: Option<f64>,
+    #[oai(skip_serializing_if_is_none)]
+    spearman: Option<f64>,
+    /// The lag with the strongest correlation of either sign.
+    #[oai(skip_serializing_if_is_none)]
+    best_lag: Option<LagCorrelationEntry>,
+    /// One per lag from `-max_lag` to `max_lag`.
+    lead_lag: Vec<LagCorrelationEntry>,
+    rolling: Vec<RollingCorrelationEntry>,
+}
+
+#[derive(Debug, Object)]
+struct CorrelationResult {
+    series: Vec<String>,
+    interval: String,
+    fill: bool,
+    window: u64,
+    max_lag: u32,
+    /// Bars every series has a value on.
+    points: u64,
+    pairs: Vec<PairCorrelationEntry>,
+}
+
+impl CorrelationResult {
+    fn new(series: &[Series], c: Correlation) -> Self {
+        let labels: Vec<String> = series.iter().map(Series::label).collect();
+        let pairs = c
+            .pairs
+            .iter()
+            .map(|p| PairCorrelationEntry {
+                a: labels[p.a].clone(),
+                b: labels[p.b].clone(),
+                points: p.points as u64,
+                pearson: p.pearson,
+                spearman: p.spearman,
+                best_lag: p.best_lag().map(Into::into),
+                lead_lag: p.lead_lag.iter().map(Into::into).collect(),
+                rolling: p
+                    .rolling
+                    .iter()
+                    .map(|(bar, correlation)| RollingCorrelationEntry {
+                        timestamp: chrono::DateTime::from_timestamp_micros(*bar)
+                            .map(|t| t.to_rfc3339())
+                            .unwrap_or_default(),
+                        correlation: *correlation,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self {
+            series: labels,
+            interval: c.settings.interval.spec,
+            fill: c.settings.fill,
+            window: c.settings.window as u64,
+            max_lag: c.settings.max_lag,
+            points: c.points as u64,
+            pairs,
+        }
+    }
+}
+
+#[derive(ApiResponse)]
+enum CorrelationApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<CorrelationResult>),
+    #[oai(status = 400)]
+    BadRequest(PlainText<String>),
+    #[oai(status = 500)]
+    InternalError(PlainText<String>),
+}
+
+// ---------------------------------------------------------------------------
+// Strategy types
+// ---------------------------------------------------------------------------
+
+/// A strategy run by the `strategy` runner.
+#[derive(Debug, Object)]
+struct StrategyEntry {
+    id: u64,
+    name: String,
+    description: String,
+    creator: String,
+    open_instances: u64,
+}
+
+/// One leg of an instance.
+#[derive(Debug, Object)]
+struct LegEntry {
+    venue: String,
+    coin: String,
+    /// `long` or `short`.
+    side: String,
+    /// Base units.
+    size: f64,
+    entry_price: f64,
+    /// Latest mark; the exit price once the instance is closed.
+    mark_price: f64,
+    pnl: f64,
+}
+
+/// An instance spawned by a strategy, traded on paper.
+#[derive(Debug, Object)]
+struct InstanceEntry {
+    id: u64,
+    strategy_id: u64,
+    /// `open`, `closed` or `terminated`.
+    status: String,
+    legs: Vec<LegEntry>,
+    pnl: f64,
+    /// Why it was opened, then why it was closed.
+    reason: String,
+    /// RFC 3339 UTC.
+    opened_at: String,
+    #[oai(skip_serializing_if_is_none)]
+    closed_at: Option<String>,
+}
+
+impl From<PaperInstance> for InstanceEntry {
+    fn from(i: PaperInstance) -> Self {
+        Self {
+            id: i.id,
+            strategy_id: i.strategy_id,
+            status: i.status.name().to_string(),
+            pnl: i.pnl(),
+            legs: i
+                .legs
+                .into_iter()
+                .map(|l| LegEntry {
+                    pnl: l.pnl(),
+                    venue: l.market.venue,
+                    coin: l.market.coin,
+                    side: l.side.name().to_string(),
+                    size: l.size,
+                    entry_price: l.entry_price,
+                    mark_price: l.mark_price,
+                })
+                .collect(),
+            reason: i.reason,
+            opened_at: i.opened_at.to_rfc3339(),
+            closed_at: i.closed_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(ApiResponse)]
+enum InstancesApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<Vec<InstanceEntry>>),
+    #[oai(status = 400)]
+    BadRequest(PlainText<String>),
+}
+
+#[derive(ApiResponse)]
+enum InstanceApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<InstanceEntry>),
+    #[oai(status = 400)]
+    BadRequest(PlainText<String>),
+    #[oai(status = 404)]
+    NotFound(PlainText<String>),
+    #[oai(status = 500)]
+    InternalError(PlainText<String>),
+}
+
+// ---------------------------------------------------------------------------
+// Backtest types
+// ---------------------------------------------------------------------------
+
+/// Body of `POST /backtest`.
+#[derive(Debug, Object)]
+struct BacktestBody {
+    /// Built-in strategy to replay, e.g. `funding_carry`.
+    strategy: String,
+    /// Replay from, **inclusive**: `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM:SS` or
+    /// RFC 3339.
+    from: String,
+    /// Replay until, **exclusive**; at most 366 days after `from`.
+    to: String,
+    /// Coins to replay market data of, e.g. `["BTC", "ETH"]`; every coin
+    /// when empty.
+    #[oai(default)]
+    coins: Vec<String>,
+    /// Fee on traded notional in basis points; defaults to 4.5.
+    fee_bps: Option<f64>,
+    /// Fill distance from the mid in basis points; defaults to 1.
+    slippage_bps: Option<f64>,
+    /// Settle hourly funding on open legs; defaults to true.
+    funding: Option<bool>,
+    /// Quote currency; defaults to 10000.
+    initial_capital: Option<f64>,
+    /// Equity curve spacing, a count of `s`, `m`, `h` or `d`; defaults to
+    /// `1h`.
+    equity_interval: Option<String>,
+}
+
+/// One simulated fill.
+#[derive(Debug, Object)]
+struct TradeEntry {
+    /// RFC 3339 UTC.
+    time: String,
+    instance_id: u64,
+    venue: String,
+    coin: String,
+    /// Side of the leg: `long` or `short`.
+    side: String,
+    /// `open` or `close`.
+    action: String,
+    size: f64,
+    price: f64,
+    fee: f64,
+}
+
+/// An instance spawned during a backtest.
+#[derive(Debug, Object)]
+struct BacktestInstanceEntry {
+    /// `pnl` is the legs' price pnl; still open instances are at their
+    /// last marks.
+    instance: InstanceEntry,
+    fees: f64,
+    /// Funding received, negative when paid.
+    funding: f64,
+    /// `pnl` net of fees and funding.
+    net_pnl: f64,
+}
+
+#[derive(Debug, Object)]
+struct EquityEntry {
+    /// RFC 3339 UTC.
+    time: String,
+    equity: f64,
+}
+
+#[derive(Debug, Object)]
+struct BacktestSummaryEntry {
+    initial_capital: f64,
+    final_equity: f64,
+    total_return: f64,
+    /// Largest fall from a peak of the equity curve, as a fraction of it.
+    max_drawdown: f64,
+    /// Annualised, at a zero risk-free rate.
+    #[oai(skip_serializing_if_is_none)]
+    sharpe: Option<f64>,
+    trades: u64,
+    instances: u64,
+    closed: u64,
+    /// Share of closed instances with a positive net pnl.
+    #[oai(skip_serializing_if_is_none)]
+    win_rate: Option<f64>,
+    fees: f64,
+    funding: f64,
+    /// Actions that could not be carried out, e.g. orders without a price.
+    rejected: u64,
+}
+
+#[derive(Debug, Object)]
+struct BacktestResult {
+    strategy_id: u64,
+    events: u64,
+    summary: BacktestSummaryEntry,
+    trades: Vec<TradeEntry>,
+    instances: Vec<BacktestInstanceEntry>,
+    equity: Vec<EquityEntry>,
+}
+
+impl From<&Backtest> for BacktestResult {
+    fn from(b: &Backtest) -> Self {
+        let s = &b.summary;
+        Self {
+            strategy_id: b.strategy.id,
+            events: b.events,
+            summary: BacktestSummaryEntry {
+                initial_capital: s.initial_capital,
+                final_equity: s.final_equity,
+                total_return: s.total_return,
+                max_drawdown: s.max_drawdown,
+                sharpe: s.sharpe,
+                trades: s.trades as u64,
+                instances: s.instances as u64,
+                closed: s.closed as u64,
+                win_rate: s.win_rate,
+                fees: s.fees,
+                funding: s.funding,
+                rejected: s.rejected as u64,
+            },
+            trades: b
+                .trades
+                .iter()
+                .map(|t| TradeEntry {
+                    time: t.time.to_rfc3339(),
+                    instance_id: t.instance_id,
+                    venue: t.market.venue.clone(),
+                    coin: t.market.coin.clone(),
+                    side: t.side.name().to_string(),
+                    action: if t.opening { "open" } else { "close" }.to_string(),
+                    size: t.size,
+                    price: t.price,
+                    fee: t.fee,
+                })
+                .collect(),
+            instances: b
+                .instances
+                .iter()
+                .map(|i| BacktestInstanceEntry {
+                    instance: i.instance.clone().into(),
+                    fees: i.fees,
+                    funding: i.funding,
+                    net_pnl: i.pnl(),
+                })
+                .collect(),
+            equity: b
+                .equity
+                .iter()
+                .map(|p| EquityEntry {
+                    time: p.time.to_rfc3339(),
+                    equity: p.equity,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A backtest started through `POST /backtest`.
 #[derive(Debug, Object)]
-struct CoverageGap {
-    /// First missing partition key, e.g. `"2025-07-28T12:00:00"`.
+struct BacktestJobEntry {
+    id: u64,
+    strategy: String,
+    /// RFC 3339 UTC.
     from: String,
-    /// Last missing partition key, e.g. `"2025-07-28T14:00:00"`.
     to: String,
+    /// `running`, `done` or `failed`.
+    status: String,
+    /// Share of the range replayed, from 0 to 1.
+    progress: f64,
+    /// Events replayed so far.
+    events: u64,
+    started_at: String,
+    #[oai(skip_serializing_if_is_none)]
+    finished_at: Option<String>,
+    #[oai(skip_serializing_if_is_none)]
+    error: Option<String>,
+    /// Only returned by `GET /backtest/{id}` once done.
+    #[oai(skip_serializing_if_is_none)]
+    result: Option<Box<BacktestResult>>,
 }
 
-/// Response for `GET /coverage`.
-#[derive(Debug, Object)]
-struct CoverageResult {
-    source: String,
-    /// Requested range start.
-    from: String,
-    /// Requested range end.
-    to: String,
-    /// Total number of partition keys in the requested range.
-    total_partitions: u64,
-    /// Number of partitions present in QuestDB.
-    present_count: u64,
-    /// Number of partitions missing from QuestDB.
-    missing_count: u64,
-    /// Contiguous ranges of missing partitions (from/to both inclusive).
-    gaps: Vec<CoverageGap>,
+impl BacktestJobEntry {
+    fn new(job: &BacktestJob, with_result: bool) -> Self {
+        Self {
+            id: job.id,
+            strategy: job.strategy.clone(),
+            from: job.from.to_rfc3339(),
+            to: job.to.to_rfc3339(),
+            status: job.status.name().to_string(),
+            progress: job.progress(),
+            events: job.events,
+            started_at: job.started_at.to_rfc3339(),
+            finished_at: job.finished_at.map(|t| t.to_rfc3339()),
+            error: job.error.clone(),
+            result: job
+                .result
+                .as_deref()
+                .filter(|_| with_result)
+                .map(|b| Box::new(b.into())),
+        }
+    }
 }
 
 #[derive(ApiResponse)]
-enum CoverageApiResponse {
-    #[oai(status = 200)]
-    Ok(Json<CoverageResult>),
+enum BacktestStartApiResponse {
+    #[oai(status = 202)]
+    Accepted(Json<BacktestJobEntry>),
     #[oai(status = 400)]
     BadRequest(PlainText<String>),
+    #[oai(status = 429)]
+    TooManyRequests(PlainText<String>),
     #[oai(status = 500)]
     InternalError(PlainText<String>),
 }
 
+#[derive(ApiResponse)]
+enum BacktestApiResponse {
+    #[oai(status = 200)]
+    Ok(Json<BacktestJobEntry>),
+    #[oai(status = 404)]
+    NotFound(PlainText<String>),
+}
 
 // ---------------------------------------------------------------------------
 // Endpoints
@@ -301,7 +1505,7 @@ impl Endpoint {
     ///
     /// | `source`                                  | Steps  | Table                            | Available from         | Available to           | Extra params       |
     /// |-------------------------------------------|--------|----------------------------------|------------------------|------------------------|--------------------|
-    /// | `HyperliquidAssetCtxs`                    | daily  | `market_data`                    | 2023-05-20             | present                | —                  |
+    /// | `HyperliquidAssetCtxs`                    | daily  | `market_data`, `asset_ctx`       | 2023-05-20             | present                | —                  |
     /// | `HyperliquidL2Orderbook`                  | hourly | `l2_orderbook`                   | 2023-04-15             | present                | `coins` (required) |
     /// | `HyperliquidNodeFillsLegacy`              | hourly | `hyperliquid_fill`               | 2025-05-25T14:00:00    | 2025-07-27T08:00:00    | —                  |
     /// | `HyperliquidNodeFillsLegacy1mAggregate`   | hourly | `hyperliquid_fill_1m_aggregate`  | 2025-05-25T14:00:00    | 2025-07-27T08:00:00    | —                  |
@@ -360,11 +1564,13 @@ impl Endpoint {
             ));
         }
 
-        let db = match QuestDbClient::new(&self.config) {
-            Ok(c) => c,
+        let db = self.db.clone();
+        let sink = match sink::from_config(&self.config, db.clone()).await {
+            Ok(s) => s,
             Err(e) => {
                 return BackfillApiResponse::InternalError(PlainText(format!(
-                    "Failed to connect to QuestDB: {e}"
+                    "Failed to open {} sink: {e}",
+                    self.config.sink
                 )))
             }
         };
@@ -395,7 +1601,17 @@ impl Endpoint {
                     keys
                 };
 
-                match run_backfill(&source, &db, keys, force, Some((&self.tracker, "HyperliquidAssetCtxs"))).await {
+                match run_backfill(
+                    &source,
+                    &db,
+                    sink.as_ref(),
+                    keys,
+                    force,
+                    self.config.backfill_concurrency,
+                    Some((&self.tracker, "HyperliquidAssetCtxs")),
+                )
+                .await
+                {
                     Ok(stats) => BackfillApiResponse::Ok(Json(stats.into())),
                     Err(msg) => BackfillApiResponse::InternalError(PlainText(msg)),
                 }
@@ -438,7 +1654,17 @@ impl Endpoint {
                     keys
                 };
 
-                match run_backfill(&source, &db, keys, force, Some((&self.tracker, "HyperliquidL2Orderbook"))).await {
+                match run_backfill(
+                    &source,
+                    &db,
+                    sink.as_ref(),
+                    keys,
+                    force,
+                    self.config.backfill_concurrency,
+                    Some((&self.tracker, "HyperliquidL2Orderbook")),
+                )
+                .await
+                {
                     Ok(stats) => BackfillApiResponse::Ok(Json(stats.into())),
                     Err(msg) => BackfillApiResponse::InternalError(PlainText(msg)),
                 }
@@ -465,7 +1691,17 @@ impl Endpoint {
                     keys
                 };
 
-                match run_backfill(&source, &db, keys, force, Some((&self.tracker, "HyperliquidNodeFills"))).await {
+                match run_backfill(
+                    &source,
+                    &db,
+                    sink.as_ref(),
+                    keys,
+                    force,
+                    self.config.backfill_concurrency,
+                    Some((&self.tracker, "HyperliquidNodeFills")),
+                )
+                .await
+                {
                     Ok(stats) => BackfillApiResponse::Ok(Json(stats.into())),
                     Err(msg) => BackfillApiResponse::InternalError(PlainText(msg)),
                 }
@@ -493,7 +1729,17 @@ impl Endpoint {
                     keys
                 };
 
-                match run_backfill(&source, &db, keys, force, Some((&self.tracker, "HyperliquidNodeFills1mAggregate"))).await {
+                match run_backfill(
+                    &source,
+                    &db,
+                    sink.as_ref(),
+                    keys,
+                    force,
+                    self.config.backfill_concurrency,
+                    Some((&self.tracker, "HyperliquidNodeFills1mAggregate")),
+                )
+                .await
+                {
                     Ok(stats) => BackfillApiResponse::Ok(Json(stats.into())),
                     Err(msg) => BackfillApiResponse::InternalError(PlainText(msg)),
                 }
@@ -521,7 +1767,17 @@ impl Endpoint {
                     keys
                 };
 
-                match run_backfill(&source, &db, keys, force, Some((&self.tracker, "HyperliquidNodeFillsLegacy"))).await {
+                match run_backfill(
+                    &source,
+                    &db,
+                    sink.as_ref(),
+                    keys,
+                    force,
+                    self.config.backfill_concurrency,
+                    Some((&self.tracker, "HyperliquidNodeFillsLegacy")),
+                )
+                .await
+                {
                     Ok(stats) => BackfillApiResponse::Ok(Json(stats.into())),
                     Err(msg) => BackfillApiResponse::InternalError(PlainText(msg)),
                 }
@@ -549,7 +1805,17 @@ impl Endpoint {
                     keys
                 };
 
-                match run_backfill(&source, &db, keys, force, Some((&self.tracker, "HyperliquidNodeFillsLegacy1mAggregate"))).await {
+                match run_backfill(
+                    &source,
+                    &db,
+                    sink.as_ref(),
+                    keys,
+                    force,
+                    self.config.backfill_concurrency,
+                    Some((&self.tracker, "HyperliquidNodeFillsLegacy1mAggregate")),
+                )
+                .await
+                {
                     Ok(stats) => BackfillApiResponse::Ok(Json(stats.into())),
                     Err(msg) => BackfillApiResponse::InternalError(PlainText(msg)),
                 }
@@ -559,7 +1825,7 @@ impl Endpoint {
                 let source = MarketState1mSource::new();
 
                 // Build hour-by-hour key iterator.
-                // Requires hyperliquid_fill_1m_aggregate and market_data to be
+                // Requires hyperliquid_fill_1m_aggregate and asset_ctx to be
                 // backfilled for the requested range first.
                 let keys = {
                     let mut keys = Vec::new();
@@ -571,7 +1837,17 @@ impl Endpoint {
                     keys
                 };
 
-                match run_backfill(&source, &db, keys, force, Some((&self.tracker, "MarketState1m"))).await {
+                match run_backfill(
+                    &source,
+                    &db,
+                    sink.as_ref(),
+                    keys,
+                    force,
+                    self.config.backfill_concurrency,
+                    Some((&self.tracker, "MarketState1m")),
+                )
+                .await
+                {
                     Ok(stats) => BackfillApiResponse::Ok(Json(stats.into())),
                     Err(msg) => BackfillApiResponse::InternalError(PlainText(msg)),
                 }
@@ -585,20 +1861,23 @@ impl Endpoint {
     /// `diskSize` and `numRows` from `table_partitions()` for each one.
     #[oai(path = "/database", method = "get")]
     async fn database(&self) -> DatabaseApiResponse {
-        let db = match QuestDbClient::new(&self.config) {
-            Ok(c) => c,
-            Err(e) => {
-                return DatabaseApiResponse::InternalError(PlainText(format!(
-                    "Failed to connect to QuestDB: {e}"
-                )))
-            }
-        };
+        let db = &self.db;
 
-        let tables_json = match db
-            .query_json("SELECT table_name FROM tables() ORDER BY table_name")
-            .await
-        {
-            Ok(j) => j,
+        #[derive(serde::Deserialize)]
+        struct TableName {
+            table_name: String,
+        }
+        #[derive(serde::Deserialize)]
+        struct PartitionTotals {
+            disk_size: Option<i64>,
+            num_rows: Option<i64>,
+        }
+
+        let list = SqlQuery::select(&["table_name"])
+            .from_function("tables", &[])
+            .order_by("table_name");
+        let table_names: Vec<TableName> = match db.query(&list).await {
+            Ok(rows) => rows,
             Err(e) => {
                 return DatabaseApiResponse::InternalError(PlainText(format!(
                     "Failed to list tables: {e}"
@@ -606,32 +1885,23 @@ impl Endpoint {
             }
         };
 
-        let table_names: Vec<String> = tables_json["dataset"]
-            .as_array()
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|row| row[0].as_str().map(String::from))
-                    .collect()
-            })
-            .unwrap_or_default();
-
         let mut tables = Vec::with_capacity(table_names.len());
         let mut total_disk_size_bytes: i64 = 0;
 
-        for name in table_names {
-            let sql = format!(
-                "SELECT sum(diskSize), sum(numRows) FROM table_partitions('{name}')"
-            );
-            let json = match db.query_json(&sql).await {
-                Ok(j) => j,
+        for TableName { table_name: name } in table_names {
+            let totals = SqlQuery::default()
+                .select_expr("sum(diskSize) disk_size")
+                .select_expr("sum(numRows) num_rows")
+                .from_function("table_partitions", &[name.as_str().into()]);
+            let first_row = match db.query::<PartitionTotals>(&totals).await {
+                Ok(rows) => match rows.into_iter().next() {
+                    Some(row) => row,
+                    None => continue,
+                },
                 Err(_) => continue,
             };
-            let first_row = match json["dataset"].as_array().and_then(|d| d.first()) {
-                Some(row) => row.clone(),
-                None => continue,
-            };
-            let disk_size_bytes = first_row[0].as_i64().unwrap_or(0);
-            let row_count = first_row[1].as_i64().unwrap_or(0);
+            let disk_size_bytes = first_row.disk_size.unwrap_or(0);
+            let row_count = first_row.num_rows.unwrap_or(0);
             total_disk_size_bytes += disk_size_bytes;
             tables.push(TableStats {
                 name,
@@ -643,6 +1913,58 @@ impl Endpoint {
         DatabaseApiResponse::Ok(Json(DatabaseStats {
             tables,
             total_disk_size_bytes,
+            spool: db.spool().map(|s| s.status().into()),
+        }))
+    }
+
+    /// Diff every table owned by the schema migrations against its live shape.
+    ///
+    /// Reads `table_columns()` for each managed table and reports missing,
+    /// unexpected and mistyped columns, the designated timestamp, dedup upsert
+    /// keys, and the applied vs latest migration version. Migrations run on startup, so a
+    /// table out of sync usually predates them (created by ILP with guessed
+    /// types) or was altered by hand.
+    #[oai(path = "/schema", method = "get")]
+    async fn schema(&self) -> SchemaApiResponse {
+        let db = &self.db;
+        let report = match schema_report(db).await {
+            Ok(r) => r,
+            Err(e) => {
+                return SchemaApiResponse::InternalError(PlainText(format!(
+                    "Failed to read schema: {e}"
+                )))
+            }
+        };
+
+        let tables: Vec<TableSchemaStatus> = report
+            .into_iter()
+            .map(|d| TableSchemaStatus {
+                in_sync: d.in_sync(),
+                table: d.table,
+                exists: d.exists,
+                expected_version: d.expected_version,
+                applied_version: d.applied_version,
+                missing_columns: d.missing_columns,
+                unexpected_columns: d.unexpected_columns,
+                type_mismatches: d
+                    .type_mismatches
+                    .into_iter()
+                    .map(|m| ColumnTypeMismatch {
+                        column: m.column,
+                        expected: m.expected,
+                        actual: m.actual,
+                    })
+                    .collect(),
+                expected_timestamp: d.expected_timestamp,
+                actual_timestamp: d.actual_timestamp,
+                expected_dedup_keys: d.expected_dedup_keys,
+                actual_dedup_keys: d.actual_dedup_keys,
+            })
+            .collect();
+
+        SchemaApiResponse::Ok(Json(SchemaStatus {
+            in_sync: tables.iter().all(|t| t.in_sync),
+            tables,
         }))
     }
 
@@ -668,11 +1990,40 @@ impl Endpoint {
         Json(entries)
     }
 
+    /// List every registered periodic REST poller and its health.
+    ///
+    /// Pollers are registered by the `realtime` runner (predicted funding and
+    /// symbol refetch per venue). Returns an empty array when it is not running.
+    #[oai(path = "/pollers", method = "get")]
+    async fn pollers(&self) -> Json<Vec<PollerHealthEntry>> {
+        let to_rfc3339 =
+            |t: chrono::DateTime<chrono::Utc>| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let entries = self
+            .pollers
+            .list()
+            .into_iter()
+            .map(|h| PollerHealthEntry {
+                name: h.name,
+                status: format!("{:?}", h.status),
+                interval_ms: h.interval_ms,
+                jitter_ms: h.jitter_ms,
+                max_retries: h.max_retries,
+                last_success_at: h.last_success_at.map(to_rfc3339),
+                last_error: h.last_error,
+                last_error_at: h.last_error_at.map(to_rfc3339),
+                consecutive_failures: h.consecutive_failures,
+                total_successes: h.total_successes,
+                total_failures: h.total_failures,
+            })
+            .collect();
+        Json(entries)
+    }
 
-    /// Check which partitions are present or missing in QuestDB for a source and date range.
+    /// Check which partitions are present or missing in the configured sink for a
+    /// source and date range.
     ///
     /// Iterates the same partition keys as `GET /backfill` and tests each one against
-    /// QuestDB.  Returns a list of contiguous **gaps** (missing ranges), plus summary
+    /// the sink, as a backfill does before ingesting.  Returns a list of contiguous **gaps** (missing ranges), plus summary
     /// counts.  The `from`/`to` in each gap are the first and last missing partition
     /// key labels (both inclusive), using the same format as the source's partition key
     /// (hourly: `"YYYY-MM-DDTHH:00:00"`, daily: `"YYYY-MM-DD"`).
@@ -707,11 +2058,13 @@ impl Endpoint {
             ));
         }
 
-        let db = match QuestDbClient::new(&self.config) {
-            Ok(c) => c,
+        let db = self.db.clone();
+        let sink = match sink::from_config(&self.config, db).await {
+            Ok(s) => s,
             Err(e) => {
                 return CoverageApiResponse::InternalError(PlainText(format!(
-                    "Failed to connect to QuestDB: {e}"
+                    "Failed to open {} sink: {e}",
+                    self.config.sink
                 )))
             }
         };
@@ -721,7 +2074,7 @@ impl Endpoint {
         let checked: Vec<(String, bool)> = match source.0 {
             BackfillSource::HyperliquidAssetCtxs => {
                 let keys = day_range(from.date(), to.date());
-                check_coverage::<AssetCtxsSource, _>(&db, keys).await
+                check_coverage::<AssetCtxsSource, _>(sink.as_ref(), keys).await
             }
 
             BackfillSource::HyperliquidL2Orderbook => {
@@ -744,7 +2097,7 @@ impl Endpoint {
                             .collect::<Vec<_>>()
                     })
                     .collect();
-                check_coverage::<L2SnapshotSource, _>(&db, keys).await
+                check_coverage::<L2SnapshotSource, _>(sink.as_ref(), keys).await
             }
 
             BackfillSource::HyperliquidNodeFills => {
@@ -752,7 +2105,7 @@ impl Endpoint {
                     .into_iter()
                     .map(|h| NodeFillsHourKey { hour: h })
                     .collect();
-                check_coverage::<NodeFillsSource, _>(&db, keys).await
+                check_coverage::<NodeFillsSource, _>(sink.as_ref(), keys).await
             }
 
             BackfillSource::HyperliquidNodeFills1mAggregate => {
@@ -760,7 +2113,7 @@ impl Endpoint {
                     .into_iter()
                     .map(|h| NodeFills1mAggregateHourKey { hour: h })
                     .collect();
-                check_coverage::<NodeFills1mAggregateSource, _>(&db, keys).await
+                check_coverage::<NodeFills1mAggregateSource, _>(sink.as_ref(), keys).await
             }
 
             BackfillSource::HyperliquidNodeFillsLegacy => {
@@ -768,7 +2121,7 @@ impl Endpoint {
                     .into_iter()
                     .map(|h| NodeFillsLegacyHourKey { hour: h })
                     .collect();
-                check_coverage::<NodeFillsLegacySource, _>(&db, keys).await
+                check_coverage::<NodeFillsLegacySource, _>(sink.as_ref(), keys).await
             }
 
             BackfillSource::HyperliquidNodeFillsLegacy1mAggregate => {
@@ -776,7 +2129,7 @@ impl Endpoint {
                     .into_iter()
                     .map(|h| NodeFillsLegacy1mAggregateHourKey { hour: h })
                     .collect();
-                check_coverage::<NodeFillsLegacy1mAggregateSource, _>(&db, keys).await
+                check_coverage::<NodeFillsLegacy1mAggregateSource, _>(sink.as_ref(), keys).await
             }
 
             BackfillSource::MarketState1m => {
@@ -784,7 +2137,7 @@ impl Endpoint {
                     .into_iter()
                     .map(|h| MarketState1mHourKey { hour: h })
                     .collect();
-                check_coverage::<MarketState1mSource, _>(&db, keys).await
+                check_coverage::<MarketState1mSource, _>(sink.as_ref(), keys).await
             }
         };
 
@@ -807,6 +2160,764 @@ impl Endpoint {
             gaps,
         }))
     }
+
+    /// Download a time range of one table as a file.
+    ///
+    /// `table` is any managed table (`market_state_1m`,
+    /// `hyperliquid_fill_1m_aggregate`, `l2_orderbook`, `market_data`,
+    /// `signal_*`, … — see `GET /schema`); the dev suffix is added as for
+    /// every other read. Rows are read over PG wire and encoded batch by batch
+    /// as they arrive, so the range is never held in memory and there is no
+    /// `/exec` row limit. All schema columns are exported, oldest row first.
+    ///
+    /// If QuestDB fails mid-way the response ends early and the file is
+    /// truncated (an incomplete Parquet file has no footer and will not open).
+    #[oai(path = "/export", method = "get")]
+    async fn export(
+        &self,
+        /// Base table name, e.g. `market_state_1m`.
+        table: Query<String>,
+        /// Range start, **inclusive**.
+        from: Query<String>,
+        /// Range end, **exclusive**.
+        to: Query<String>,
+        /// Comma-separated coins, matched against `coin` (or `ticker` on
+        /// `market_data` / `l2_orderbook`). Not valid for `signal_*` tables.
+        coins: Query<Option<String>>,
+        /// Output format; defaults to `parquet`.
+        format: Query<Option<ExportFileFormat>>,
+    ) -> ExportApiResponse {
+        let Some(table_schema) = schema::table(&table.0) else {
+            return ExportApiResponse::BadRequest(PlainText(format!(
+                "Unknown table '{}'; see GET /schema for managed tables.",
+                table.0
+            )));
+        };
+        let from = match parse_flexible_datetime(&from.0) {
+            Ok(dt) => dt,
+            Err(e) => return ExportApiResponse::BadRequest(PlainText(e)),
+        };
+        let to = match parse_flexible_datetime(&to.0) {
+            Ok(dt) => dt,
+            Err(e) => return ExportApiResponse::BadRequest(PlainText(e)),
+        };
+        if from >= to {
+            return ExportApiResponse::BadRequest(PlainText(
+                "'from' must be before 'to'.".to_string(),
+            ));
+        }
+        let coin_list: Option<Vec<String>> = coins
+            .0
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.split(',').map(|c| c.trim().to_string()).collect());
+        let format = format.0.unwrap_or(ExportFileFormat::Parquet);
+
+        let db = &self.db;
+        let query = match export_query(db, table_schema, from, to, coin_list.as_deref()) {
+            Ok(q) => q,
+            Err(e) => return ExportApiResponse::BadRequest(PlainText(e.to_string())),
+        };
+        let stream = match export(db, table_schema, &query, format.into()) {
+            Ok(s) => s,
+            Err(e) => {
+                return ExportApiResponse::InternalError(PlainText(format!(
+                    "Failed to start export: {e}"
+                )))
+            }
+        };
+        let table_name = table.0;
+        let body = Body::from_bytes_stream(stream.map_err(move |e| {
+            tracing::error!(table = %table_name, error = %e, "export aborted");
+            std::io::Error::other(e.to_string())
+        }));
+        let disposition = format!(
+            "attachment; filename=\"{}_{}_{}.{}\"",
+            db.table_name(table_schema.name),
+            from.format("%Y%m%dT%H%M%S"),
+            to.format("%Y%m%dT%H%M%S"),
+            ExportFormat::from(format).extension()
+        );
+        match format {
+            ExportFileFormat::Parquet => ExportApiResponse::Parquet(Binary(body), disposition),
+            ExportFileFormat::Csv => ExportApiResponse::Csv(Binary(body), disposition),
+            ExportFileFormat::Arrow => ExportApiResponse::Arrow(Binary(body), disposition),
+        }
+    }
+
+    /// Show the retention policies, what they would drop right now, and the
+    /// last scheduled run.
+    ///
+    /// The `retention` runner applies the policies every
+    /// `RETENTION_INTERVAL_SECS`: for every partition of a policy's table whose
+    /// newest row is older than `keep_days`, it fills the policy's rollup
+    /// tables from the partition, drops it with `ALTER TABLE … DROP
+    /// PARTITION`, and records it in `retention_log`. With `dry_run` set
+    /// nothing is rolled up or dropped.
+    #[oai(path = "/retention", method = "get")]
+    async fn retention(&self) -> RetentionApiResponse {
+        self.retention_status().await
+    }
+
+    /// Replace the retention policies.
+    ///
+    /// The new settings are validated, saved to `RETENTION_POLICY_PATH`, and
+    /// used from the next scheduled run. Tables without a policy are never
+    /// touched.
+    #[oai(path = "/retention", method = "put")]
+    async fn put_retention(&self, body: Json<RetentionSettingsBody>) -> RetentionApiResponse {
+        if let Err(e) = self.retention.update(body.0.into()) {
+            return RetentionApiResponse::BadRequest(PlainText(e));
+        }
+        self.retention_status().await
+    }
+
+    /// List every registered signal, by id.
+    ///
+    /// Signals are registered in the `signal_info` table: the built-in
+    /// adapters register theirs at startup, and a signal keeps its id as long
+    /// as its type, source and parameters are unchanged.
+    #[oai(path = "/signals", method = "get")]
+    async fn signals(&self) -> SignalsApiResponse {
+        SignalsApiResponse::Ok(Json(
+            self.signals.list().into_iter().map(Into::into).collect(),
+        ))
+    }
+
+    /// Look up one registered signal by id.
+    #[oai(path = "/signals/:id", method = "get")]
+    async fn signal(&self, id: Path<i64>) -> SignalApiResponse {
+        match self.signals.get(id.0) {
+            Some(info) => SignalApiResponse::Ok(Json(info.into())),
+            None => SignalApiResponse::NotFound(PlainText(format!("unknown signal id {}", id.0))),
+        }
+    }
+
+    /// Read a registered signal's rows over a time range, oldest first.
+    ///
+    /// The signal's data type picks the table (`signal_simple`,
+    /// `signal_binary`, `signal_scalar`, `signal_text`, `signal_vector` or
+    /// `signal_structured`, with the dev suffix in dev mode); `limit` counts
+    /// table rows, one per element or field for the last two. Scalar signals can be resampled with
+    /// `resample=<aggregate>:<interval>`: `last`, `mean` or `ohlc` per `s`,
+    /// `m`, `h` or `d` interval, e.g. `mean:1h`; each point is then one
+    /// interval, stamped with its start.
+    #[oai(path = "/signals/:id/data", method = "get")]
+    async fn signal_data(
+        &self,
+        id: Path<i64>,
+        /// Range start, **inclusive**.
+        from: Query<String>,
+        /// Range end, **exclusive**.
+        to: Query<String>,
+        /// Maximum number of points; defaults to 10000, capped at 100000.
+        limit: Query<Option<u64>>,
+        /// `<last|mean|ohlc>:<interval>`, scalar signals only.
+        resample: Query<Option<String>>,
+    ) -> SignalDataApiResponse {
+        let Some(info) = self.signals.get(id.0) else {
+            return SignalDataApiResponse::NotFound(PlainText(format!(
+                "unknown signal id {}",
+                id.0
+            )));
+        };
+        let from = match parse_flexible_datetime(&from.0) {
+            Ok(dt) => dt.and_utc(),
+            Err(e) => return SignalDataApiResponse::BadRequest(PlainText(e)),
+        };
+        let to = match parse_flexible_datetime(&to.0) {
+            Ok(dt) => dt.and_utc(),
+            Err(e) => return SignalDataApiResponse::BadRequest(PlainText(e)),
+        };
+        if from >= to {
+            return SignalDataApiResponse::BadRequest(PlainText(
+                "'from' must be before 'to'.".to_string(),
+            ));
+        }
+        let limit = limit.0.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return SignalDataApiResponse::BadRequest(PlainText(format!(
+                "'limit' must be between 1 and {MAX_LIMIT}."
+            )));
+        }
+        let resample_param = resample.0.filter(|s| !s.trim().is_empty());
+        let resample = match resample_param.as_deref().map(str::parse::<Resample>) {
+            None => None,
+            Some(Ok(r)) => Some(r),
+            Some(Err(e)) => return SignalDataApiResponse::BadRequest(PlainText(e)),
+        };
+        if resample.is_some() && info.data_type != SignalDataType::Scalar {
+            return SignalDataApiResponse::BadRequest(PlainText(format!(
+                "signal {} is {}; only scalar signals can be resampled.",
+                info.id,
+                info.data_type.name()
+            )));
+        }
+
+        let db = &self.db;
+        match read_signal(db, &info, from, to, limit, resample.as_ref()).await {
+            Ok(points) => SignalDataApiResponse::Ok(Json(SignalDataResult {
+                signal: info.into(),
+                resample: resample_param,
+                points: points.into(),
+            })),
+            Err(e) => SignalDataApiResponse::InternalError(PlainText(format!(
+                "Failed to read signal: {e}"
+            ))),
+        }
+    }
+
+    /// List calendar events, by time.
+    ///
+    /// Events are entered by hand (or imported from CSV) and stored as
+    /// `structured` signals, one per event, so studies can join them
+    /// against price data in QuestDB.
+    #[oai(path = "/events", method = "get")]
+    async fn events(
+        &self,
+        /// Range start, **inclusive**.
+        from: Query<Option<String>>,
+        /// Range end, **exclusive**.
+        to: Query<Option<String>>,
+        ticker: Query<Option<String>>,
+        event_type: Query<Option<String>>,
+        tag: Query<Option<String>>,
+    ) -> EventsApiResponse {
+        let parse = |s: Option<String>| s.as_deref().map(parse_event_time).transpose();
+        let (from, to) = match (parse(from.0), parse(to.0)) {
+            (Ok(from), Ok(to)) => (from, to),
+            (Err(e), _) | (_, Err(e)) => return EventsApiResponse::BadRequest(PlainText(e)),
+        };
+        let filter = EventFilter {
+            from,
+            to,
+            ticker: ticker.0,
+            event_type: event_type.0,
+            tag: tag.0,
+        };
+        let result = self.events.list(&self.db, &filter).await;
+        match result {
+            Ok(events) => EventsApiResponse::Ok(Json(events.into_iter().map(Into::into).collect())),
+            Err(e) => {
+                EventsApiResponse::InternalError(PlainText(format!("Event calendar error: {e}")))
+            }
+        }
+    }
+
+    /// Add an event.
+    #[oai(path = "/events", method = "post")]
+    async fn create_event(&self, body: Json<EventBody>) -> EventApiResponse {
+        let spec = match body.0.spec() {
+            Ok(spec) => spec,
+            Err(e) => return EventApiResponse::BadRequest(PlainText(e)),
+        };
+        let result = self.events.create(&self.db, vec![spec]).await;
+        EventApiResponse::found(0, result.map(|events| events.into_iter().next()))
+    }
+
+    /// Import events from CSV.
+    ///
+    /// The body is CSV with the header `time,ticker,event_type,title,tags`;
+    /// `ticker` and `tags` may be empty and tags are separated by `;`. Every
+    /// row is validated first: if any is invalid nothing is imported and all
+    /// errors are returned, one per line.
+    #[oai(path = "/events/import", method = "post")]
+    async fn import_events(&self, body: EventCsvBody) -> EventsApiResponse {
+        let EventCsvBody::Csv(PlainText(text)) = body;
+        let specs = match parse_csv(&text) {
+            Ok(specs) => specs,
+            Err(e) => return EventsApiResponse::BadRequest(PlainText(e)),
+        };
+        let result = self.events.create(&self.db, specs).await;
+        match result {
+            Ok(events) => EventsApiResponse::Ok(Json(events.into_iter().map(Into::into).collect())),
+            Err(e) => {
+                EventsApiResponse::InternalError(PlainText(format!("Event calendar error: {e}")))
+            }
+        }
+    }
+
+    /// Look up one event.
+    #[oai(path = "/events/:id", method = "get")]
+    async fn event(&self, id: Path<i64>) -> EventApiResponse {
+        let result = self.events.get(&self.db, id.0).await;
+        EventApiResponse::found(id.0, result)
+    }
+
+    /// Replace an event. Its id and signal are kept.
+    #[oai(path = "/events/:id", method = "put")]
+    async fn update_event(&self, id: Path<i64>, body: Json<EventBody>) -> EventApiResponse {
+        let spec = match body.0.spec() {
+            Ok(spec) => spec,
+            Err(e) => return EventApiResponse::BadRequest(PlainText(e)),
+        };
+        let result = self.events.update(&self.db, id.0, spec).await;
+        EventApiResponse::found(id.0, result)
+    }
+
+    /// Delete an event and return it.
+    ///
+    /// QuestDB cannot delete rows, so the event's rows in
+    /// `signal_structured` are rewritten with `status = deleted`.
+    #[oai(path = "/events/:id", method = "delete")]
+    async fn delete_event(&self, id: Path<i64>) -> EventApiResponse {
+        let result = self.events.delete(&self.db, id.0).await;
+        EventApiResponse::found(id.0, result)
+    }
+
+    /// Run an event study: returns of a price series around each event.
+    ///
+    /// Events are the rows of `event_signal_id` or the calendar events
+    /// matching `events`, within `from`/`to`; at most 500. Each event's bar
+    /// (offset 0) is the `interval` bar containing it, and its window spans
+    /// `pre` bars before to `post` bars after. Returns are simple returns of
+    /// the last price per bar; bars without a price carry the previous one
+    /// for up to a week. Abnormal returns subtract the `benchmark`'s return
+    /// over the same bar or, without one, the mean return over the
+    /// `estimation` bars before the window. Events without prices for their
+    /// whole window are listed under `skipped`.
+    ///
+    /// Per offset, the response averages abnormal returns (AAR) and their
+    /// running sums (CAAR) across events, with cross-sectional t-stats.
+    /// `format=csv` returns one row per event and offset, then one `average`
+    /// row per offset.
+    #[oai(path = "/analysis/event_study", method = "post")]
+    async fn event_study(&self, body: Json<EventStudyBody>) -> EventStudyApiResponse {
+        let body = body.0;
+        let parse = |s: &Option<String>| s.as_deref().map(parse_event_time).transpose();
+        let (from, to) = match (parse(&body.from), parse(&body.to)) {
+            (Ok(from), Ok(to)) => (from, to),
+            (Err(e), _) | (_, Err(e)) => return EventStudyApiResponse::BadRequest(PlainText(e)),
+        };
+        let interval = match body
+            .interval
+            .as_deref()
+            .unwrap_or("1d")
+            .parse::<BarInterval>()
+        {
+            Ok(i) => i,
+            Err(e) => return EventStudyApiResponse::BadRequest(PlainText(e)),
+        };
+        let window = Window {
+            pre: body.pre.unwrap_or(5),
+            post: body.post.unwrap_or(10),
+            estimation: body.estimation.unwrap_or(30),
+        };
+        if let Err(e) = window.validate() {
+            return EventStudyApiResponse::BadRequest(PlainText(e));
+        }
+        let price = match body.price.resolve(&self.signals) {
+            Ok(p) => p,
+            Err(e) => return EventStudyApiResponse::BadRequest(PlainText(format!("price: {e}"))),
+        };
+        let benchmark = match body.benchmark.as_ref().map(|b| b.resolve(&self.signals)) {
+            None => None,
+            Some(Ok(b)) => Some(b),
+            Some(Err(e)) => {
+                return EventStudyApiResponse::BadRequest(PlainText(format!("benchmark: {e}")))
+            }
+        };
+
+        let db = &self.db;
+        let events = match (body.event_signal_id, body.events) {
+            (Some(id), None) => {
+                let Some(info) = self.signals.get(id) else {
+                    return EventStudyApiResponse::NotFound(PlainText(format!(
+                        "unknown signal id {id}"
+                    )));
+                };
+                let from = from.unwrap_or(chrono::DateTime::UNIX_EPOCH);
+                let to = to.unwrap_or_else(chrono::Utc::now);
+                signal_events(db, &info, from, to).await
+            }
+            (None, Some(f)) => {
+                let filter = EventFilter {
+                    from,
+                    to,
+                    ticker: f.ticker,
+                    event_type: f.event_type,
+                    tag: f.tag,
+                };
+                self.events.list(db, &filter).await.map(|events| {
+                    events
+                        .into_iter()
+                        .map(|e| StudyEvent {
+                            time: e.spec.time,
+                            label: e.spec.title,
+                        })
+                        .collect()
+                })
+            }
+            _ => {
+                return EventStudyApiResponse::BadRequest(PlainText(
+                    "give exactly one of event_signal_id or events".to_string(),
+                ))
+            }
+        };
+        let events = match events {
+            Ok(events) if events.len() > MAX_EVENTS => {
+                return EventStudyApiResponse::BadRequest(PlainText(format!(
+                    "{} events match; narrow them to at most {MAX_EVENTS} with from/to",
+                    events.len()
+                )))
+            }
+            Ok(events) => events,
+            Err(e) => {
+                return EventStudyApiResponse::InternalError(PlainText(format!(
+                    "Failed to read events: {e}"
+                )))
+            }
+        };
+
+        let study =
+            match run_event_study(db, events, &price, benchmark.as_ref(), interval, window).await {
+                Ok(study) => study,
+                Err(e) => {
+                    return EventStudyApiResponse::InternalError(PlainText(format!(
+                        "Event study failed: {e}"
+                    )))
+                }
+            };
+        if body.format == Some(AnalysisFormat::Csv) {
+            return match to_csv(&study) {
+                Ok(csv) => EventStudyApiResponse::Csv(PlainText(csv)),
+                Err(e) => EventStudyApiResponse::InternalError(PlainText(e.to_string())),
+            };
+        }
+        EventStudyApiResponse::Ok(Json(EventStudyResult {
+            price: price.label(),
+            benchmark: benchmark.map(|b| b.label()),
+            model: study.model.name().to_string(),
+            interval: study.interval.spec,
+            pre: study.window.pre,
+            post: study.window.post,
+            estimation: study.window.estimation,
+            events: study.events.into_iter().map(Into::into).collect(),
+            skipped: study.skipped.into_iter().map(Into::into).collect(),
+            offsets: study.offsets.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    /// Correlate two or more series: Pearson, Spearman, rolling and
+    /// lead-lag correlation.
+    ///
+    /// Each `series` is `signal:<id>` for a scalar signal, or
+    /// `<table>:<column>:<coin>` for a numeric column of `market_state_1m` or
+    /// `market_state_rt_1m` (optionally suffixed `@<venue>`, default
+    /// `HlPerp`), e.g. `market_state_rt_1m:funding_rate:BTC`. Series are
+    /// resampled to the last value per `interval` bar and aligned on the bars
+    /// they all have; with `fill=true` each instead carries its last value
+    /// forward, which lines up slow signals (e.g. the daily fear and greed
+    /// index) with fast ones.
+    ///
+    /// For every pair `a`, `b`, the lead-lag profile correlates `a` with `b`
+    /// `lag` bars later for each lag within `max_lag`: a peak at a positive
+    /// lag means `a` leads `b`.
+    #[oai(path = "/analysis/correlation", method = "get")]
+    #[allow(clippy::too_many_arguments)]
+    async fn correlation(
+        &self,
+        /// Two to eight series specs.
+        series: Query<Vec<String>>,
+        /// Range start, **inclusive**.
+        from: Query<String>,
+        /// Range end, **exclusive**.
+        to: Query<String>,
+        /// Bar length, a count of `s`, `m`, `h` or `d`; defaults to `1h`.
+        interval: Query<Option<String>>,
+        /// Carry values forward over empty bars; defaults to false.
+        fill: Query<Option<bool>>,
+        /// Bars per rolling correlation, 0 for none, at most 1000 and the bars
+        /// in range; defaults to 30, or every bar of a shorter range.
+        window: Query<Option<u32>>,
+        /// Largest lead or lag in bars, at most 1000 and with bars ×
+        /// (2·max_lag + 1) at most 5,000,000; defaults to 24.
+        max_lag: Query<Option<u32>>,
+    ) -> CorrelationApiResponse {
+        if !(2..=MAX_SERIES).contains(&series.0.len()) {
+            return CorrelationApiResponse::BadRequest(PlainText(format!(
+                "give 2 to {MAX_SERIES} series."
+            )));
+        }
+        let series = match series
+            .0
+            .iter()
+            .map(|s| Series::parse(&self.signals, s))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(series) => series,
+            Err(e) => return CorrelationApiResponse::BadRequest(PlainText(e)),
+        };
+        let (from, to) = match (
+            parse_flexible_datetime(&from.0),
+            parse_flexible_datetime(&to.0),
+        ) {
+            (Ok(from), Ok(to)) => (from.and_utc(), to.and_utc()),
+            (Err(e), _) | (_, Err(e)) => return CorrelationApiResponse::BadRequest(PlainText(e)),
+        };
+        if from >= to {
+            return CorrelationApiResponse::BadRequest(PlainText(
+                "'from' must be before 'to'.".to_string(),
+            ));
+        }
+        let interval = match interval.0.as_deref().unwrap_or("1h").parse::<BarInterval>() {
+            Ok(i) => i,
+            Err(e) => return CorrelationApiResponse::BadRequest(PlainText(e)),
+        };
+        let bars = (to - from).num_microseconds().unwrap_or(i64::MAX) / interval.micros;
+        if bars > MAX_LIMIT as i64 {
+            return CorrelationApiResponse::BadRequest(PlainText(format!(
+                "{bars} bars of {} requested; at most {MAX_LIMIT}.",
+                interval.spec
+            )));
+        }
+        let max_lag = max_lag.0.unwrap_or(24);
+        if max_lag > MAX_LAG {
+            return CorrelationApiResponse::BadRequest(PlainText(format!(
+                "'max_lag' must be at most {MAX_LAG}."
+            )));
+        }
+        let lag_points = bars.saturating_mul(2 * i64::from(max_lag) + 1);
+        if lag_points > MAX_LAG_POINTS {
+            return CorrelationApiResponse::BadRequest(PlainText(format!(
+                "{bars} bars with 'max_lag' {max_lag} pair {lag_points} points per lead-lag \
+                 profile; at most {MAX_LAG_POINTS}. Shorten the range, widen the interval or \
+                 lower 'max_lag'."
+            )));
+        }
+        let window = window
+            .0
+            .unwrap_or(30.min(u32::try_from(bars).unwrap_or(u32::MAX)));
+        if window > MAX_WINDOW || i64::from(window) > bars {
+            return CorrelationApiResponse::BadRequest(PlainText(format!(
+                "'window' must be at most {MAX_WINDOW} and the {bars} bars requested."
+            )));
+        }
+        let settings = CorrelationSettings {
+            interval,
+            fill: fill.0.unwrap_or(false),
+            window: window as usize,
+            max_lag,
+        };
+
+        let db = &self.db;
+        match run_correlation(db, &series, from, to, settings).await {
+            Ok(c) => CorrelationApiResponse::Ok(Json(CorrelationResult::new(&series, c))),
+            Err(e) => CorrelationApiResponse::InternalError(PlainText(format!(
+                "Failed to read series: {e}"
+            ))),
+        }
+    }
+
+    /// List the strategies paper-traded by the `strategy` runner. Returns an
+    /// empty array when it is not running.
+    #[oai(path = "/strategies", method = "get")]
+    async fn strategies(&self) -> Json<Vec<StrategyEntry>> {
+        let entries = self
+            .strategies
+            .strategies()
+            .into_iter()
+            .map(|s| StrategyEntry {
+                open_instances: self.strategies.open_instances(s.id).len() as u64,
+                id: s.id,
+                name: s.name,
+                description: s.description,
+                creator: s.creator,
+            })
+            .collect();
+        Json(entries)
+    }
+
+    /// List strategy instances, newest first, including those stored in
+    /// `strategy_instance` by earlier runs.
+    ///
+    /// Open instances are marked at the latest `price_mid` of their legs'
+    /// markets while the `strategy` runner is running.
+    #[oai(path = "/instances", method = "get")]
+    async fn instances(
+        &self,
+        strategy_id: Query<Option<u64>>,
+        /// `open`, `closed` or `terminated`.
+        status: Query<Option<String>>,
+    ) -> InstancesApiResponse {
+        let status = match status.0.as_deref().map(InstanceStatus::from_name) {
+            None => None,
+            Some(Some(status)) => Some(status),
+            Some(None) => {
+                return InstancesApiResponse::BadRequest(PlainText(
+                    "status must be open, closed or terminated".to_string(),
+                ))
+            }
+        };
+        let entries = self
+            .strategies
+            .instances()
+            .into_iter()
+            .rev()
+            .filter(|i| strategy_id.0.is_none_or(|id| i.strategy_id == id))
+            .filter(|i| status.is_none_or(|s| i.status == s))
+            .map(Into::into)
+            .collect();
+        InstancesApiResponse::Ok(Json(entries))
+    }
+
+    /// Terminate an open instance: its legs exit at their latest marks and
+    /// the instance is stored as `terminated`.
+    #[oai(path = "/instances/:id/terminate", method = "post")]
+    async fn terminate_instance(&self, id: Path<u64>) -> InstanceApiResponse {
+        match self.strategies.get(id.0) {
+            None => {
+                return InstanceApiResponse::NotFound(PlainText(format!(
+                    "unknown instance id {}",
+                    id.0
+                )))
+            }
+            Some(instance) if !instance.is_open() => {
+                return InstanceApiResponse::BadRequest(PlainText(format!(
+                    "instance {} is already {}",
+                    id.0,
+                    instance.status.name()
+                )))
+            }
+            Some(_) => {}
+        }
+        let result = self.strategies.terminate(&self.db, id.0).await;
+        match result {
+            Ok(Some(instance)) => InstanceApiResponse::Ok(Json(instance.into())),
+            Ok(None) => {
+                InstanceApiResponse::NotFound(PlainText(format!("unknown instance id {}", id.0)))
+            }
+            Err(e) => InstanceApiResponse::InternalError(PlainText(format!(
+                "Failed to terminate instance: {e}"
+            ))),
+        }
+    }
+
+    /// Start a backtest: replay stored history through a built-in strategy
+    /// in the background. Poll `GET /backtest/{id}` for its result.
+    ///
+    /// `market_state_1m` rows are replayed at the end of their minute, the
+    /// rows of subscribed signals at their timestamp, in time order;
+    /// `hyperliquid_fill_1m_aggregate` and book metrics from
+    /// `l2_orderbook_1m` too when the strategy asks for them. Orders
+    /// fill at the latest `price_mid` moved by `slippage_bps` against the
+    /// order and pay `fee_bps` on their notional; open legs settle funding at
+    /// the `funding_rate` of every hour crossed. At most two backtests run at
+    /// once.
+    #[oai(path = "/backtest", method = "post")]
+    async fn start_backtest(&self, body: Json<BacktestBody>) -> BacktestStartApiResponse {
+        let body = body.0;
+        let Some(strategy) = builtin::build(&body.strategy) else {
+            return BacktestStartApiResponse::BadRequest(PlainText(format!(
+                "unknown strategy '{}', expected one of: {}",
+                body.strategy,
+                builtin::STRATEGIES.join(", ")
+            )));
+        };
+        let (from, to) = match (parse_event_time(&body.from), parse_event_time(&body.to)) {
+            (Ok(from), Ok(to)) => (from, to),
+            (Err(e), _) | (_, Err(e)) => return BacktestStartApiResponse::BadRequest(PlainText(e)),
+        };
+        if from >= to || to - from > chrono::Duration::days(MAX_BACKTEST_DAYS) {
+            return BacktestStartApiResponse::BadRequest(PlainText(format!(
+                "to must be after from and at most {MAX_BACKTEST_DAYS} days later"
+            )));
+        }
+        let equity_interval = match body.equity_interval.as_deref().unwrap_or("1h").parse() {
+            Ok(i) => i,
+            Err(e) => return BacktestStartApiResponse::BadRequest(PlainText(e)),
+        };
+        let costs = Costs {
+            fee_bps: body.fee_bps.unwrap_or(4.5),
+            slippage_bps: body.slippage_bps.unwrap_or(1.0),
+        };
+        if let Err(e) = costs.validate() {
+            return BacktestStartApiResponse::BadRequest(PlainText(e));
+        }
+        let initial_capital = body.initial_capital.unwrap_or(10_000.0);
+        if !(initial_capital.is_finite() && initial_capital > 0.0) {
+            return BacktestStartApiResponse::BadRequest(PlainText(
+                "initial_capital must be positive".to_string(),
+            ));
+        }
+        let spec = BacktestSpec {
+            from,
+            to,
+            coins: body
+                .coins
+                .iter()
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty())
+                .collect(),
+            settings: BacktestSettings {
+                costs,
+                funding: body.funding.unwrap_or(true),
+                initial_capital,
+                equity_interval,
+            },
+        };
+
+        let db = self.db.clone();
+        let id = match self.backtests.start(&body.strategy, from, to) {
+            Ok(id) => id,
+            Err(e) => return BacktestStartApiResponse::TooManyRequests(PlainText(e)),
+        };
+        let jobs = self.backtests.clone();
+        let signals = self.signals.clone();
+        self.backtests.spawn(id, async move {
+            let progress = |reached, events| jobs.progress(id, reached, events);
+            let result = run_backtest(&db, &signals, strategy, spec, progress).await;
+            if let Err(e) = &result {
+                tracing::warn!(id, error = %e, "backtest failed");
+            }
+            result.map_err(|e| e.to_string())
+        });
+        match self.backtests.get(id) {
+            Some(job) => {
+                BacktestStartApiResponse::Accepted(Json(BacktestJobEntry::new(&job, false)))
+            }
+            None => BacktestStartApiResponse::InternalError(PlainText(format!(
+                "backtest {id} was forgotten"
+            ))),
+        }
+    }
+
+    /// List backtests started since the service started, newest first,
+    /// without their results.
+    #[oai(path = "/backtest", method = "get")]
+    async fn backtests(&self) -> Json<Vec<BacktestJobEntry>> {
+        Json(
+            self.backtests
+                .list()
+                .iter()
+                .map(|j| BacktestJobEntry::new(j, false))
+                .collect(),
+        )
+    }
+
+    /// Status of a backtest and, once done, its trades, instances, equity
+    /// curve and summary statistics.
+    #[oai(path = "/backtest/:id", method = "get")]
+    async fn backtest(&self, id: Path<u64>) -> BacktestApiResponse {
+        match self.backtests.get(id.0) {
+            Some(job) => BacktestApiResponse::Ok(Json(BacktestJobEntry::new(&job, true))),
+            None => {
+                BacktestApiResponse::NotFound(PlainText(format!("unknown backtest id {}", id.0)))
+            }
+        }
+    }
+}
+
+impl Endpoint {
+    async fn retention_status(&self) -> RetentionApiResponse {
+        let db = &self.db;
+        let settings = self.retention.settings();
+        let plan = run_once(db, &settings, true).await;
+        RetentionApiResponse::Ok(Json(RetentionStatus {
+            settings: settings.into(),
+            plan: plan.into(),
+            last_run: self.retention.last_run().map(Into::into),
+        }))
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -860,9 +2971,9 @@ fn hour_range(from: NaiveDateTime, to: NaiveDateTime) -> Vec<NaiveDateTime> {
     hours
 }
 
-/// Check existence of every key in `keys` against QuestDB concurrently (16 at a time).
+/// Check existence of every key in `keys` against `sink` concurrently (16 at a time).
 /// Returns `(label, is_present)` pairs in **arbitrary** order.
-async fn check_coverage<S, K>(db: &QuestDbClient, keys: Vec<K>) -> Vec<(String, bool)>
+async fn check_coverage<S, K>(sink: &dyn Sink, keys: Vec<K>) -> Vec<(String, bool)>
 where
     S: PartitionedSource<Key = K>,
     K: crate::backfill::PartitionKey + Send + 'static,
@@ -871,7 +2982,10 @@ where
     futures::stream::iter(keys)
         .map(|key| async move {
             let label = key.to_string();
-            let present = S::partition_exists(db, &key).await.unwrap_or(false);
+            let present = sink
+                .is_partition_present(&S::partition(&key))
+                .await
+                .unwrap_or(false);
             (label, present)
         })
         .buffer_unordered(16)
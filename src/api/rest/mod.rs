@@ -1,11 +1,19 @@
 mod endpoint;
 use crate::backfill::tracker::BackfillTracker;
+use crate::backtest::BacktestJobs;
 use crate::config::Config;
+use crate::database::calendar::EventCalendar;
+use crate::database::registry::SignalRegistry;
+use crate::database::retention::RetentionManager;
+use crate::database::QuestDbClient;
 use crate::metadata::cargo_package_version;
+use crate::poller::PollerRegistry;
+use crate::strategy::StrategyRegistry;
 use endpoint::Endpoint;
 use poem::{listener::TcpListener, middleware::Cors, EndpointExt, Route, Server};
 use poem_openapi::{OpenApiService, ServerObject};
 use serde_json::Error as SerdeJsonError;
+use std::sync::Arc;
 use thiserror::Error as ThisError;
 use tokio::task::JoinHandle;
 #[derive(Debug, ThisError)]
@@ -16,11 +24,23 @@ pub enum ApiError {
     Reqwest(#[from] reqwest::Error),
     #[error("API Error: {0}")]
     SerdeJsonError(#[from] SerdeJsonError),
+    #[error("API Error: {0}")]
+    QuestDb(#[from] questdb::Error),
 }
 
 // host a rest api server
-pub async fn host_rest_api_server(config: Config) -> Result<(), ApiError> {
+pub async fn host_rest_api_server(
+    config: Config,
+    pollers: PollerRegistry,
+    retention: RetentionManager,
+    signals: SignalRegistry,
+    strategies: StrategyRegistry,
+) -> Result<(), ApiError> {
     let url = config.api_base_url.clone();
+    let port = config.api_port;
+
+    // one client for every request, built before the server accepts any
+    let db = Arc::new(QuestDbClient::new(&config)?);
 
     let desciption = "signal indexer";
 
@@ -28,7 +48,17 @@ pub async fn host_rest_api_server(config: Config) -> Result<(), ApiError> {
 
     // stable
     let service_api_root = {
-        let all_ep = Endpoint { config, tracker: BackfillTracker::new() };
+        let all_ep = Endpoint {
+            config,
+            db,
+            tracker: BackfillTracker::new(),
+            pollers,
+            retention,
+            events: EventCalendar::new(signals.clone()),
+            signals,
+            strategies,
+            backtests: BacktestJobs::default(),
+        };
         OpenApiService::new(all_ep, title, cargo_package_version())
             .server(ServerObject::new(&url))
             .description(desciption)
@@ -51,12 +81,20 @@ pub async fn host_rest_api_server(config: Config) -> Result<(), ApiError> {
         .nest("/yaml", ep_yaml)
         .with(Cors::new());
 
-    Server::new(TcpListener::bind(("0.0.0.0", 3000)))
+    Server::new(TcpListener::bind(("0.0.0.0", port)))
         .run(route)
         .await
         .map_err(|i| i.into())
 }
 
-pub async fn run_web(config: Config) -> JoinHandle<Result<(), ApiError>> {
-    tokio::spawn(async move { host_rest_api_server(config).await })
+pub async fn run_web(
+    config: Config,
+    pollers: PollerRegistry,
+    retention: RetentionManager,
+    signals: SignalRegistry,
+    strategies: StrategyRegistry,
+) -> JoinHandle<Result<(), ApiError>> {
+    tokio::spawn(async move {
+        host_rest_api_server(config, pollers, retention, signals, strategies).await
+    })
 }
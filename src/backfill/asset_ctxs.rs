@@ -1,21 +1,40 @@
 use super::{PartitionKey, PartitionedSource, PartitionStats};
-use crate::adapter::hyperliquid_s3::asset_ctxs::AssetCtxs;
-use crate::database::{insert_asset_ctxs, QuestDbClient};
+use crate::adapter::hyperliquid_info::HyperliquidInfo;
+use crate::adapter::hyperliquid_s3::asset_ctxs::{resolve_markets, AssetCtxs};
+use crate::database::QuestDbClient;
 use crate::error::AnySignalResult;
-use chrono::NaiveDate;
+use crate::sink::{PartitionFilter, RowBatch, Sink};
+use chrono::{NaiveDate, NaiveTime};
+use std::collections::HashMap;
 
 // `NaiveDate` already implements `Display` ("YYYY-MM-DD").
 impl PartitionKey for NaiveDate {}
 
 /// Hyperliquid asset_ctxs source — one partition = one calendar day.
+///
+/// Each day is written twice: exploded into `market_data` (one row per
+/// metric) and as one wide `asset_ctx` row per `(ts, coin)`.
+///
+/// Rows are tagged with market type and DEX; spot `@index` coins are renamed
+/// to readable pairs using the current `spotMeta`.
 pub struct AssetCtxsSource {
     fetcher: AssetCtxs,
+    spot_names: HashMap<String, String>,
 }
 
 impl AssetCtxsSource {
     pub async fn new() -> AnySignalResult<Self> {
+        // Best effort: without spot names, `@index` coins are kept verbatim.
+        let spot_names = HyperliquidInfo::new()
+            .spot_names()
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("asset_ctxs: failed to fetch spot names: {e}");
+                HashMap::new()
+            });
         Ok(Self {
             fetcher: AssetCtxs::new().await?,
+            spot_names,
         })
     }
 }
@@ -24,34 +43,27 @@ impl AssetCtxsSource {
 impl PartitionedSource for AssetCtxsSource {
     type Key = NaiveDate;
 
-    async fn partition_exists(db: &QuestDbClient, key: &NaiveDate) -> AnySignalResult<bool> {
-        let next_day = key.succ_opt().unwrap_or(*key);
-        let table = db.table_name("market_data");
-        let sql = format!(
-            "SELECT count() FROM {table} \
-             WHERE source = 'HYPERLIQUID_S3' \
-             AND ts >= '{}T00:00:00Z' \
-             AND ts < '{}T00:00:00Z'",
-            key.format("%Y-%m-%d"),
-            next_day.format("%Y-%m-%d"),
-        );
-        Ok(db.count(&sql).await? > 0)
+    fn partition(key: &NaiveDate) -> PartitionFilter {
+        let day = key.and_time(NaiveTime::MIN).and_utc();
+        PartitionFilter::new("asset_ctx", day, day + chrono::Duration::days(1))
     }
 
     async fn ingest_partition(
         &self,
-        db: &QuestDbClient,
+        _db: &QuestDbClient,
+        sink: &dyn Sink,
         key: &NaiveDate,
     ) -> AnySignalResult<PartitionStats> {
         let t_fetch = std::time::Instant::now();
         let csv_text = self.fetcher.fetch_and_decompress(*key).await?;
-        let rows = AssetCtxs::parse_csv(&csv_text)?;
+        let mut rows = AssetCtxs::parse_csv(&csv_text)?;
+        resolve_markets(&mut rows, &self.spot_names);
         let fetch_ms = t_fetch.elapsed().as_millis();
 
         let t_insert = std::time::Instant::now();
         let rows_n = rows.len() as u64;
-        let table = db.table_name("market_data");
-        db.with_sender(|s| insert_asset_ctxs(s, &table, &rows))?;
+        sink.write(RowBatch::AssetCtxs(&rows)).await?;
+        sink.write(RowBatch::AssetCtxWide(&rows)).await?;
         let insert_ms = t_insert.elapsed().as_millis();
 
         Ok(PartitionStats { rows: rows_n, fetch_ms, insert_ms })
@@ -1,7 +1,8 @@
 use super::{PartitionKey, PartitionedSource, PartitionStats};
 use crate::adapter::hyperliquid_s3::market_data::MarketData;
-use crate::database::{insert_l2_snapshots, QuestDbClient};
+use crate::database::QuestDbClient;
 use crate::error::AnySignalResult;
+use crate::sink::{PartitionFilter, RowBatch, Sink};
 use chrono::{NaiveDateTime, Timelike};
 
 // ---------------------------------------------------------------------------
@@ -43,24 +44,16 @@ impl L2SnapshotSource {
 impl PartitionedSource for L2SnapshotSource {
     type Key = L2PartitionKey;
 
-    async fn partition_exists(db: &QuestDbClient, key: &L2PartitionKey) -> AnySignalResult<bool> {
-        let hour_end = key.hour + chrono::Duration::hours(1);
-        let table = db.table_name("l2_orderbook");
-        let sql = format!(
-            "SELECT count() FROM {table} \
-             WHERE ticker = '{}' \
-             AND ts >= '{}Z' \
-             AND ts < '{}Z'",
-            key.coin,
-            key.hour.format("%Y-%m-%dT%H:%M:%S"),
-            hour_end.format("%Y-%m-%dT%H:%M:%S"),
-        );
-        Ok(db.count(&sql).await? > 0)
+    fn partition(key: &L2PartitionKey) -> PartitionFilter {
+        let hour = key.hour.and_utc();
+        PartitionFilter::new("l2_orderbook", hour, hour + chrono::Duration::hours(1))
+            .eq("ticker", &key.coin)
     }
 
     async fn ingest_partition(
         &self,
-        db: &QuestDbClient,
+        _db: &QuestDbClient,
+        sink: &dyn Sink,
         key: &L2PartitionKey,
     ) -> AnySignalResult<PartitionStats> {
         let date = key.hour.date();
@@ -71,8 +64,7 @@ impl PartitionedSource for L2SnapshotSource {
         let fetch_ms = t_fetch.elapsed().as_millis();
 
         let t_insert = std::time::Instant::now();
-        let table = db.table_name("l2_orderbook");
-        let rows = db.with_sender(|s| insert_l2_snapshots(s, &table, &snapshots))? as u64;
+        let rows = sink.write(RowBatch::L2Snapshots(&snapshots)).await? as u64;
         let insert_ms = t_insert.elapsed().as_millis();
 
         Ok(PartitionStats { rows, fetch_ms, insert_ms })
@@ -1,8 +1,11 @@
 use super::{PartitionKey, PartitionedSource, PartitionStats};
-use crate::adapter::error::AdapterError;
-use crate::database::{insert_market_state_1m, MarketStateRow, QuestDbClient};
-use crate::error::{AnySignalError, AnySignalResult};
-use chrono::NaiveDateTime;
+use crate::database::query::{Query, ReadPath};
+use crate::database::{MarketStateRow, QuestDbClient};
+use crate::error::AnySignalResult;
+use crate::sink::{PartitionFilter, RowBatch, Sink};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures::TryStreamExt;
+use serde::Deserialize;
 use std::collections::HashMap;
 
 const LIQUIDATION_LONG: &[&str] =
@@ -36,9 +39,9 @@ impl PartitionKey for MarketState1mHourKey {}
 ///
 /// - `hyperliquid_fill_1m_aggregate` — minute-level fill stats (trade volume,
 ///   trade count, liquidation volumes).
-/// - `market_data` — per-minute price/market snapshots per coin (oracle/mark
+/// - `asset_ctx` — per-minute price/market snapshots per coin (oracle/mark
 ///   price, open interest, funding rate, 24h volume).  Each minute bucket is
-///   joined to the exact same-minute snapshot from `market_data`.
+///   joined to the exact same-minute snapshot from `asset_ctx`.
 ///
 /// No S3 access is required — this is a pure DB-to-DB computation.
 pub struct MarketState1mSource;
@@ -49,35 +52,32 @@ impl MarketState1mSource {
     }
 }
 
+impl Default for MarketState1mSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait::async_trait]
 impl PartitionedSource for MarketState1mSource {
     type Key = MarketState1mHourKey;
 
-    /// Run only 2 partitions concurrently — each one fires 2 QuestDB SELECT
-    /// queries plus an ILP write, so the default of 8 would issue 16
+    /// Run only 2 partitions concurrently — each one opens 2 QuestDB PG wire
+    /// cursors plus an ILP write, so the default of 8 would issue 16
     /// simultaneous reads and overwhelm a small QuestDB instance.
-    fn concurrency() -> usize {
-        2
+    fn concurrency() -> Option<usize> {
+        Some(2)
     }
 
-    async fn partition_exists(
-        db: &QuestDbClient,
-        key: &MarketState1mHourKey,
-    ) -> AnySignalResult<bool> {
-        let hour_end = key.hour + chrono::Duration::hours(1);
-        let table = db.table_name("market_state_1m");
-        let sql = format!(
-            "SELECT count() FROM {table} \
-             WHERE ts >= '{}Z' AND ts < '{}Z'",
-            key.hour.format("%Y-%m-%dT%H:%M:%S"),
-            hour_end.format("%Y-%m-%dT%H:%M:%S"),
-        );
-        Ok(db.count(&sql).await? > 0)
+    fn partition(key: &MarketState1mHourKey) -> PartitionFilter {
+        let hour = key.hour.and_utc();
+        PartitionFilter::new("market_state_1m", hour, hour + chrono::Duration::hours(1))
     }
 
     async fn ingest_partition(
         &self,
         db: &QuestDbClient,
+        sink: &dyn Sink,
         key: &MarketState1mHourKey,
     ) -> AnySignalResult<PartitionStats> {
         let hour_end = key.hour + chrono::Duration::hours(1);
@@ -85,90 +85,89 @@ impl PartitionedSource for MarketState1mSource {
         let t_fetch = std::time::Instant::now();
 
         // Fetch 1-minute fill aggregates for [H, H+1h).
-        let fill_table = db.table_name("hyperliquid_fill_1m_aggregate");
-        let fill_sql = format!(
-            "SELECT ts, coin, category, buy_side, quantity, trade_count \
-             FROM {fill_table} \
-             WHERE ts >= '{}Z' AND ts < '{}Z' \
-             ORDER BY ts",
-            key.hour.format("%Y-%m-%dT%H:%M:%S"),
-            hour_end.format("%Y-%m-%dT%H:%M:%S"),
-        );
-        let fill_json = db.query_json(&fill_sql).await?;
+        let fill_query = Query::select(&[
+            "ts",
+            "coin",
+            "category",
+            "buy_side",
+            "quantity",
+            "trade_count",
+        ])
+        .from(&db.table_name("hyperliquid_fill_1m_aggregate"))
+        .time_range("ts", key.hour, hour_end)
+        .order_by("ts")
+        .via(ReadPath::PgWire);
 
         // Fetch per-minute market snapshots for [H, H+1h).
-        // market_data stores minute-level snapshots so we scope to the exact
+        // asset_ctx stores minute-level snapshots so we scope to the exact
         // hour to avoid fetching millions of rows for the whole day.
-        let market_table = db.table_name("market_data");
-        let market_sql = format!(
-            "SELECT ts, ticker, category, value \
-             FROM {market_table} \
-             WHERE ts >= '{}Z' AND ts < '{}Z' \
-             AND source = 'HYPERLIQUID_S3'",
-            key.hour.format("%Y-%m-%dT%H:%M:%S"),
-            hour_end.format("%Y-%m-%dT%H:%M:%S"),
-        );
-        let market_json = db.query_json(&market_sql).await?;
-
-        let fetch_ms = t_fetch.elapsed().as_millis();
-
-        // Build market snapshot map: coin → category → value
-        let market_map = parse_market_data(&market_json)?;
-
-        // Accumulate fill stats per (minute_ms, coin).
-        let fill_rows = fill_json["dataset"]
-            .as_array()
-            .map(Vec::as_slice)
-            .unwrap_or(&[]);
-
+        let market_query = Query::select(&[
+            "ts",
+            "coin",
+            "oracle_px",
+            "mark_px",
+            "mid_px",
+            "open_interest",
+            "funding",
+            "day_ntl_vlm",
+        ])
+        .from(&db.table_name("asset_ctx"))
+        .time_range("ts", key.hour, hour_end)
+        .via(ReadPath::PgWire);
+        let market_rows: Vec<AssetCtxSnapshot> = db.query(&market_query).await?;
+
+        // Build market snapshot map: (minute, coin) → snapshot
+        let market_map = snapshots_by_minute(market_rows);
+
+        // Accumulate fill stats per (minute_ms, coin) as cursor batches
+        // arrive, so the hour's fills are never held in memory at once.
         let mut accum: HashMap<(i64, String), FillAccum> = HashMap::new();
-
-        for row in fill_rows {
-            let ts_str = row[0].as_str().unwrap_or("");
-            let minute_ms = questdb_ts_to_ms(ts_str)?;
-            let coin = row[1].as_str().unwrap_or("").to_string();
-            let category = row[2].as_str().unwrap_or("");
-            let buy_side = row[3].as_bool().unwrap_or(false);
-            let quantity = row[4].as_f64().unwrap_or(0.0);
-            let trade_count = row[5].as_i64().unwrap_or(0);
-
-            let e = accum
-                .entry((minute_ms, coin))
-                .or_insert_with(FillAccum::default);
-
-            // Count only buy side — buys/sells are paired so this avoids
-            // double-counting the same notional volume.
-            if buy_side {
-                e.trade_volume += quantity;
-                e.trade_count += trade_count;
-            }
-
-            if LIQUIDATION_LONG.contains(&category) {
-                e.liq_long_volume += quantity;
-                e.liq_long_count += trade_count;
-            } else if LIQUIDATION_SHORT.contains(&category) {
-                e.liq_short_volume += quantity;
-                e.liq_short_count += trade_count;
+        let mut fills = db.stream::<FillRow>(&fill_query);
+
+        while let Some(batch) = fills.try_next().await? {
+            for row in batch {
+                let category = row.category.as_str();
+                let quantity = row.quantity.unwrap_or(0.0);
+                let trade_count = row.trade_count.unwrap_or(0);
+
+                let e = accum
+                    .entry((row.ts.timestamp_millis(), row.coin))
+                    .or_default();
+
+                // Count only buy side — buys/sells are paired so this avoids
+                // double-counting the same notional volume.
+                if row.buy_side {
+                    e.trade_volume += quantity;
+                    e.trade_count += trade_count;
+                }
+
+                if LIQUIDATION_LONG.contains(&category) {
+                    e.liq_long_volume += quantity;
+                    e.liq_long_count += trade_count;
+                } else if LIQUIDATION_SHORT.contains(&category) {
+                    e.liq_short_volume += quantity;
+                    e.liq_short_count += trade_count;
+                }
             }
         }
 
+        let fetch_ms = t_fetch.elapsed().as_millis();
+
         // Build output rows.
         let mut rows: Vec<MarketStateRow> = Vec::with_capacity(accum.len());
 
         for ((minute_ms, coin), a) in accum {
             let snap = market_map.get(&(minute_ms, coin.clone()));
-            let price_oracle = snap_get(snap, "oracle_px");
-            let price_mark = snap_get(snap, "mark_px");
-            let price_mid = snap
-                .and_then(|m| m.get("mid_px"))
-                .copied()
-                .unwrap_or_else(|| {
-                    if price_oracle > 0.0 && price_mark > 0.0 {
-                        (price_oracle + price_mark) / 2.0
-                    } else {
-                        0.0
-                    }
-                });
+            let get = |f: fn(&AssetCtxSnapshot) -> Option<f64>| snap.and_then(f).unwrap_or(0.0);
+            let price_oracle = get(|s| s.oracle_px);
+            let price_mark = get(|s| s.mark_px);
+            let price_mid = snap.and_then(|s| s.mid_px).unwrap_or_else(|| {
+                if price_oracle > 0.0 && price_mark > 0.0 {
+                    (price_oracle + price_mark) / 2.0
+                } else {
+                    0.0
+                }
+            });
 
             rows.push(MarketStateRow {
                 minute_ms,
@@ -176,9 +175,9 @@ impl PartitionedSource for MarketState1mSource {
                 price_oracle,
                 price_mark,
                 price_mid,
-                open_interest: snap_get(snap, "open_interest"),
-                funding_rate: snap_get(snap, "funding"),
-                volume_24h_usd: snap_get(snap, "day_ntl_vlm"),
+                open_interest: get(|s| s.open_interest),
+                funding_rate: get(|s| s.funding),
+                volume_24h_usd: get(|s| s.day_ntl_vlm),
                 trade_volume: a.trade_volume,
                 trade_count: a.trade_count,
                 liquidation_long_volume: a.liq_long_volume,
@@ -195,10 +194,7 @@ impl PartitionedSource for MarketState1mSource {
         });
 
         let t_insert = std::time::Instant::now();
-        let ms1m_table = db.table_name("market_state_1m");
-        let row_count = tokio::task::block_in_place(|| {
-            db.with_sender(|s| insert_market_state_1m(s, &ms1m_table, &rows))
-        })? as u64;
+        let row_count = sink.write(RowBatch::MarketState1m(&rows)).await? as u64;
         let insert_ms = t_insert.elapsed().as_millis();
 
         Ok(PartitionStats { rows: row_count, fetch_ms, insert_ms })
@@ -209,6 +205,30 @@ impl PartitionedSource for MarketState1mSource {
 // Helpers
 // ---------------------------------------------------------------------------
 
+/// Row of `hyperliquid_fill_1m_aggregate` as read back for aggregation.
+#[derive(Debug, Deserialize)]
+struct FillRow {
+    ts: DateTime<Utc>,
+    coin: String,
+    category: String,
+    buy_side: bool,
+    quantity: Option<f64>,
+    trade_count: Option<i64>,
+}
+
+/// Row of `asset_ctx` as read back for aggregation.
+#[derive(Debug, Deserialize)]
+struct AssetCtxSnapshot {
+    ts: DateTime<Utc>,
+    coin: String,
+    oracle_px: Option<f64>,
+    mark_px: Option<f64>,
+    mid_px: Option<f64>,
+    open_interest: Option<f64>,
+    funding: Option<f64>,
+    day_ntl_vlm: Option<f64>,
+}
+
 /// Per-(minute, coin) fill accumulator.
 #[derive(Default)]
 struct FillAccum {
@@ -220,41 +240,11 @@ struct FillAccum {
     liq_short_count: i64,
 }
 
-/// Build `(minute_ms, coin) → category → value` map from a `market_data`
-/// query response (columns: ts, ticker, category, value).
-///
-/// market_data has per-minute snapshots so we key by both the minute
-/// timestamp and the coin to allow exact-minute lookups.
-fn parse_market_data(
-    json: &serde_json::Value,
-) -> AnySignalResult<HashMap<(i64, String), HashMap<String, f64>>> {
-    let mut map: HashMap<(i64, String), HashMap<String, f64>> = HashMap::new();
-    let rows = json["dataset"].as_array().map(Vec::as_slice).unwrap_or(&[]);
-    for row in rows {
-        let ts_str = row[0].as_str().unwrap_or("");
-        let minute_ms = questdb_ts_to_ms(ts_str)?;
-        let coin = row[1].as_str().unwrap_or("").to_string();
-        let category = row[2].as_str().unwrap_or("").to_string();
-        let value = row[3].as_f64().unwrap_or(0.0);
-        map.entry((minute_ms, coin)).or_default().insert(category, value);
-    }
-    Ok(map)
-}
-
-/// Parse a QuestDB timestamp string (`"2025-06-01T12:00:00.000000Z"`) to Unix ms.
-fn questdb_ts_to_ms(ts: &str) -> AnySignalResult<i64> {
-    chrono::DateTime::parse_from_rfc3339(ts)
-        .map(|dt| dt.timestamp_millis())
-        .map_err(|e| {
-            AnySignalError::Adapter(AdapterError::FetchError(format!(
-                "invalid QuestDB timestamp '{ts}': {e}"
-            )))
-        })
-}
-
-/// Look up a value from an optional snapshot map, defaulting to `0.0`.
-fn snap_get(snap: Option<&HashMap<String, f64>>, key: &str) -> f64 {
-    snap.and_then(|m| m.get(key)).copied().unwrap_or(0.0)
+/// Key `asset_ctx` rows by `(minute_ms, coin)` for exact-minute lookups.
+fn snapshots_by_minute(rows: Vec<AssetCtxSnapshot>) -> HashMap<(i64, String), AssetCtxSnapshot> {
+    rows.into_iter()
+        .map(|row| ((row.ts.timestamp_millis(), row.coin.clone()), row))
+        .collect()
 }
 
 // ---------------------------------------------------------------------------
@@ -264,17 +254,48 @@ fn snap_get(snap: Option<&HashMap<String, f64>>, key: &str) -> f64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::database::query::decode_rows;
 
     #[test]
-    fn questdb_ts_to_ms_parses_correctly() {
-        let ms = questdb_ts_to_ms("2025-06-01T12:00:00.000000Z").unwrap();
+    fn snapshots_keyed_by_minute_and_coin() {
+        let json = serde_json::json!({
+            "query": "SELECT ts, coin, oracle_px, mark_px, mid_px, open_interest, funding, day_ntl_vlm FROM asset_ctx",
+            "columns": [
+                {"name": "ts", "type": "TIMESTAMP"},
+                {"name": "coin", "type": "SYMBOL"},
+                {"name": "oracle_px", "type": "DOUBLE"},
+                {"name": "mark_px", "type": "DOUBLE"},
+                {"name": "mid_px", "type": "DOUBLE"},
+                {"name": "open_interest", "type": "DOUBLE"},
+                {"name": "funding", "type": "DOUBLE"},
+                {"name": "day_ntl_vlm", "type": "DOUBLE"}
+            ],
+            "dataset": [
+                ["2025-06-01T12:00:00.000000Z", "BTC", 104000.5, 104010.0, null, 1.5, null, 2.5e9],
+                ["2025-06-01T12:00:00.000000Z", "ETH", 2500.0, 2501.0, 2500.5, 3.0, 0.0001, 1e9]
+            ],
+            "count": 2
+        });
+        let rows = decode_rows::<AssetCtxSnapshot>(json).unwrap();
+        let map = snapshots_by_minute(rows);
         // 2025-06-01T12:00:00 UTC
-        assert_eq!(ms, 1748779200000);
+        let btc = &map[&(1748779200000, "BTC".to_string())];
+        assert_eq!(btc.oracle_px, Some(104000.5));
+        assert_eq!(btc.mid_px, None);
+        assert_eq!(btc.funding, None);
+        assert_eq!(
+            map[&(1748779200000, "ETH".to_string())].mid_px,
+            Some(2500.5)
+        );
     }
 
     #[test]
-    fn questdb_ts_to_ms_rejects_invalid() {
-        assert!(questdb_ts_to_ms("not-a-timestamp").is_err());
+    fn fill_row_rejects_invalid_timestamp() {
+        let json = serde_json::json!({
+            "columns": [{"name": "ts", "type": "TIMESTAMP"}],
+            "dataset": [["not-a-timestamp"]],
+        });
+        assert!(decode_rows::<FillRow>(json).is_err());
     }
 
     #[tokio::test]
@@ -284,15 +305,22 @@ mod tests {
         use crate::config::Config;
         #[allow(unused_imports)]
         let config = Config::from_env();
-        let db = crate::database::QuestDbClient::new(&config).expect("db");
+        let db = std::sync::Arc::new(crate::database::QuestDbClient::new(&config).expect("db"));
+        let sink = crate::sink::QuestDbSink::new(db.clone());
         let source = MarketState1mSource::new();
         let hour = chrono::NaiveDate::from_ymd_opt(2025, 6, 1)
             .unwrap()
             .and_hms_opt(12, 0, 0)
             .unwrap();
         let key = MarketState1mHourKey { hour };
-        let stats = source.ingest_partition(&db, &key).await.expect("ingest");
-        eprintln!("rows={} fetch_ms={} insert_ms={}", stats.rows, stats.fetch_ms, stats.insert_ms);
+        let stats = source
+            .ingest_partition(&db, &sink, &key)
+            .await
+            .expect("ingest");
+        eprintln!(
+            "rows={} fetch_ms={} insert_ms={}",
+            stats.rows, stats.fetch_ms, stats.insert_ms
+        );
         assert!(stats.rows > 0);
     }
 }
@@ -10,13 +10,11 @@ pub mod tracker;
 use crate::adapter::error::AdapterError;
 use crate::database::QuestDbClient;
 use crate::error::{AnySignalError, AnySignalResult};
+use crate::sink::{PartitionFilter, Sink};
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicU64, Ordering};
 use tracker::BackfillTracker;
 
-/// Maximum number of partition keys processed concurrently within one backfill job.
-const BACKFILL_CONCURRENCY: usize = 8;
-
 // ---------------------------------------------------------------------------
 // Traits
 // ---------------------------------------------------------------------------
@@ -29,42 +27,44 @@ pub trait PartitionKey: std::fmt::Display + Send + Sync {}
 /// [`run_backfill`] uses this to emit a single structured log line per partition
 /// so individual implementations do not need their own `tracing::info!` calls.
 pub struct PartitionStats {
-    /// Number of rows written to QuestDB.
+    /// Number of rows written to the sink.
     pub rows: u64,
     /// Wall-clock time spent fetching / parsing upstream data (ms).
     pub fetch_ms: u128,
-    /// Wall-clock time spent writing to QuestDB (ms).
+    /// Wall-clock time spent writing to the sink (ms).
     pub insert_ms: u128,
 }
 
 /// A data source that can check for and ingest one partition at a time.
 ///
-/// `partition_exists` only needs the DB handle; the adapter state (S3 client
-/// etc.) lives on `self` and is used by `ingest_partition`.
+/// `partition` only describes the rows a key writes, which [`run_backfill`]
+/// looks up in the sink; the adapter state (S3 client etc.) lives on `self`
+/// and is used by `ingest_partition`.
 #[async_trait::async_trait]
 pub trait PartitionedSource: Send + Sync {
     type Key: PartitionKey;
 
     /// Maximum number of partitions processed concurrently within one backfill
-    /// job.  Defaults to [`BACKFILL_CONCURRENCY`] (8), which is appropriate
-    /// for S3-fetching sources.  Override with a lower value for sources that
-    /// issue multiple QuestDB queries per partition to avoid overwhelming the
-    /// database.
-    fn concurrency() -> usize {
-        BACKFILL_CONCURRENCY
+    /// job.  `None` uses the configured `BACKFILL_CONCURRENCY` (default 8),
+    /// which is appropriate for S3-fetching sources.  Return a lower value for
+    /// sources that issue multiple QuestDB queries per partition to avoid
+    /// overwhelming the database; it is still capped by the configured value.
+    fn concurrency() -> Option<usize> {
+        None
     }
 
-    /// Return `true` if this partition is already present in QuestDB.
-    /// Should return `Ok(false)` on any DB error so the backfill proceeds.
-    async fn partition_exists(db: &QuestDbClient, key: &Self::Key) -> AnySignalResult<bool>;
+    /// The rows `key` writes; the partition is present when the sink holds
+    /// any of them (see [`Sink::is_partition_present`]).
+    fn partition(key: &Self::Key) -> PartitionFilter;
 
-    /// Fetch the partition from its upstream source and write it to QuestDB.
+    /// Fetch the partition from its upstream source and write it to `sink`.
     /// Returns [`PartitionStats`] with row count and fetch/insert timings.
     /// Implementations should **not** emit their own log lines — [`run_backfill`]
     /// handles that using the returned stats.
     async fn ingest_partition(
         &self,
         db: &QuestDbClient,
+        sink: &dyn Sink,
         key: &Self::Key,
     ) -> AnySignalResult<PartitionStats>;
 }
@@ -82,9 +82,15 @@ pub struct BackfillStats {
     pub elapsed_ms: u64,
 }
 
+/// Outcome of the Phase 1 existence check for one key: the key to ingest
+/// (`None` when already present) and its log label, or the check error.
+type CheckResult<K> = Result<(Option<K>, String), String>;
+
 /// Drive a concurrent backfill over `keys`, honouring dedup and collecting results.
 ///
-/// Up to [`BACKFILL_CONCURRENCY`] keys are processed simultaneously.
+/// Up to `max_concurrency` keys (the configured `BACKFILL_CONCURRENCY`, or
+/// the source's lower [`PartitionedSource::concurrency`]) are processed
+/// simultaneously.
 /// Returns `Err(msg)` if an AWS credential error is encountered (recorded after
 /// all in-flight work drains).  Otherwise accumulates per-key outcomes into
 /// [`BackfillStats`].
@@ -104,8 +110,10 @@ pub struct BackfillStats {
 pub async fn run_backfill<S>(
     source: &S,
     db: &QuestDbClient,
+    sink: &dyn Sink,
     keys: Vec<S::Key>,
     force: bool,
+    max_concurrency: usize,
     tracker: Option<(&BackfillTracker, &str)>,
 ) -> Result<BackfillStats, String>
 where
@@ -133,12 +141,14 @@ where
     // Existence checks run concurrently so we know the full pending set before
     // any ingestion begins.  Results are encoded as:
     //   Ok((Some(key), label)) → missing, queue for ingestion
-    //   Ok((None,      label)) → already in QuestDB → keys_skipped
+    //   Ok((None,      label)) → already in the sink → keys_skipped
     //   Err(msg)               → existence check failed → keys_err
     // -----------------------------------------------------------------------
-    let concurrency = S::concurrency();
+    let concurrency = S::concurrency()
+        .map_or(max_concurrency, |c| c.min(max_concurrency))
+        .max(1);
 
-    let check_results: Vec<Result<(Option<S::Key>, String), String>> = if force {
+    let check_results: Vec<CheckResult<S::Key>> = if force {
         keys.into_iter()
             .map(|k| { let l = k.to_string(); Ok((Some(k), l)) })
             .collect()
@@ -146,7 +156,7 @@ where
         futures::stream::iter(keys)
             .map(|key| async move {
                 let label = key.to_string();
-                match S::partition_exists(db, &key).await {
+                match sink.is_partition_present(&S::partition(&key)).await {
                     Ok(true)  => Ok((None, label)),
                     Ok(false) => Ok((Some(key), label)),
                     Err(e)    => Err(format!("{label}: existence check failed: {e}")),
@@ -165,7 +175,7 @@ where
         let mut ke = keys_err.lock().unwrap_or_else(|p| p.into_inner());
         for result in check_results {
             match result {
-                Ok((None, label)) => sk.push(label), // already exists in QuestDB
+                Ok((None, label)) => sk.push(label), // already exists in the sink
                 Ok((Some(key), label)) => {
                     // Dedup: if another concurrent job already owns this key, skip it.
                     let claimed = match (tracker, guard_id) {
@@ -220,7 +230,7 @@ where
                     }
 
                     // Ingest partition.
-                    match source.ingest_partition(db, &key).await {
+                    match source.ingest_partition(db, sink, &key).await {
                         Ok(ps) => {
                             tracing::info!(
                                 key       = %label,
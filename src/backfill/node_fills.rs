@@ -1,7 +1,8 @@
 use super::{PartitionKey, PartitionedSource, PartitionStats};
 use crate::adapter::hyperliquid_s3::node_fills::NodeFills;
-use crate::database::{insert_hyperliquid_fills, QuestDbClient};
+use crate::database::QuestDbClient;
 use crate::error::AnySignalResult;
+use crate::sink::{PartitionFilter, RowBatch, Sink};
 use chrono::{NaiveDateTime, Timelike};
 
 // ---------------------------------------------------------------------------
@@ -35,7 +36,7 @@ impl PartitionKey for NodeFillsLegacyHourKey {}
 /// the same value used by [`NodeFillsSource`] (node_fills_by_block).
 ///
 /// The two archives are non-overlapping except for 2025-07-27 hour 8 which
-/// appears in both.  The `partition_exists` check prevents double-ingestion
+/// appears in both.  The partition presence check prevents double-ingestion
 /// regardless of which source is backfilled first.
 ///
 /// [`NodeFillsSource`]: crate::backfill::node_fills_by_block::NodeFillsSource
@@ -55,26 +56,16 @@ impl NodeFillsLegacySource {
 impl PartitionedSource for NodeFillsLegacySource {
     type Key = NodeFillsLegacyHourKey;
 
-    async fn partition_exists(
-        db: &QuestDbClient,
-        key: &NodeFillsLegacyHourKey,
-    ) -> AnySignalResult<bool> {
-        let hour_end = key.hour + chrono::Duration::hours(1);
-        let table = db.table_name("hyperliquid_fill");
-        let sql = format!(
-            "SELECT count() FROM {table} \
-             WHERE source = 'HYPERLIQUID_NODE' \
-             AND ts >= '{}Z' \
-             AND ts < '{}Z'",
-            key.hour.format("%Y-%m-%dT%H:%M:%S"),
-            hour_end.format("%Y-%m-%dT%H:%M:%S"),
-        );
-        Ok(db.count(&sql).await? > 0)
+    fn partition(key: &NodeFillsLegacyHourKey) -> PartitionFilter {
+        let hour = key.hour.and_utc();
+        PartitionFilter::new("hyperliquid_fill", hour, hour + chrono::Duration::hours(1))
+            .eq("source", "HYPERLIQUID_NODE")
     }
 
     async fn ingest_partition(
         &self,
-        db: &QuestDbClient,
+        _db: &QuestDbClient,
+        sink: &dyn Sink,
         key: &NodeFillsLegacyHourKey,
     ) -> AnySignalResult<PartitionStats> {
         let date = key.hour.date();
@@ -89,12 +80,7 @@ impl PartitionedSource for NodeFillsLegacySource {
         fills.sort_unstable_by_key(|f| f.time_ms);
 
         let t_insert = std::time::Instant::now();
-        // Use block_in_place so the blocking mutex + synchronous HTTP flush
-        // don't starve the Tokio thread pool.
-        let table = db.table_name("hyperliquid_fill");
-        let rows = tokio::task::block_in_place(|| {
-            db.with_sender(|s| insert_hyperliquid_fills(s, &table, &fills))
-        })? as u64;
+        let rows = sink.write(RowBatch::Fills(&fills)).await? as u64;
         let insert_ms = t_insert.elapsed().as_millis();
 
         Ok(PartitionStats { rows, fetch_ms, insert_ms })
@@ -1,7 +1,8 @@
 use super::{PartitionKey, PartitionedSource, PartitionStats};
 use crate::adapter::hyperliquid_s3::node_fills_by_block::{NodeFillsByBlock, ParsedFill};
-use crate::database::{insert_hyperliquid_fill_1m_aggregate, Fill1mAggregate, QuestDbClient};
+use crate::database::{Fill1mAggregate, QuestDbClient};
 use crate::error::AnySignalResult;
+use crate::sink::{PartitionFilter, RowBatch, Sink};
 use chrono::{NaiveDateTime, Timelike};
 use std::collections::HashMap;
 
@@ -89,25 +90,19 @@ impl NodeFills1mAggregateSource {
 impl PartitionedSource for NodeFills1mAggregateSource {
     type Key = NodeFills1mAggregateHourKey;
 
-    async fn partition_exists(
-        db: &QuestDbClient,
-        key: &NodeFills1mAggregateHourKey,
-    ) -> AnySignalResult<bool> {
-        let hour_end = key.hour + chrono::Duration::hours(1);
-        let table = db.table_name("hyperliquid_fill_1m_aggregate");
-        let sql = format!(
-            "SELECT count() FROM {table} \
-             WHERE ts >= '{}Z' \
-             AND ts < '{}Z'",
-            key.hour.format("%Y-%m-%dT%H:%M:%S"),
-            hour_end.format("%Y-%m-%dT%H:%M:%S"),
-        );
-        Ok(db.count(&sql).await? > 0)
+    fn partition(key: &NodeFills1mAggregateHourKey) -> PartitionFilter {
+        let hour = key.hour.and_utc();
+        PartitionFilter::new(
+            "hyperliquid_fill_1m_aggregate",
+            hour,
+            hour + chrono::Duration::hours(1),
+        )
     }
 
     async fn ingest_partition(
         &self,
-        db: &QuestDbClient,
+        _db: &QuestDbClient,
+        sink: &dyn Sink,
         key: &NodeFills1mAggregateHourKey,
     ) -> AnySignalResult<PartitionStats> {
         // S3 files are keyed by *block processing time*, not fill timestamp.
@@ -183,12 +178,7 @@ impl PartitionedSource for NodeFills1mAggregateSource {
         aggregated.sort_unstable_by_key(|a| a.minute_ms);
 
         let t_insert = std::time::Instant::now();
-        // Use block_in_place so the blocking mutex + synchronous HTTP flush
-        // don't starve the Tokio thread pool.
-        let table = db.table_name("hyperliquid_fill_1m_aggregate");
-        let rows = tokio::task::block_in_place(|| {
-            db.with_sender(|s| insert_hyperliquid_fill_1m_aggregate(s, &table, &aggregated))
-        })? as u64;
+        let rows = sink.write(RowBatch::Fill1mAggregates(&aggregated)).await? as u64;
         let insert_ms = t_insert.elapsed().as_millis();
 
         // Log the raw fill count here since it's specific to this source
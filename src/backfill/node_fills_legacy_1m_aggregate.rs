@@ -1,8 +1,9 @@
 use super::{PartitionKey, PartitionedSource, PartitionStats};
 use crate::adapter::hyperliquid_s3::node_fills::NodeFills;
 use crate::backfill::node_fills_1m_aggregate::aggregate_fills;
-use crate::database::{insert_hyperliquid_fill_1m_aggregate, QuestDbClient};
+use crate::database::QuestDbClient;
 use crate::error::AnySignalResult;
+use crate::sink::{PartitionFilter, RowBatch, Sink};
 use chrono::{NaiveDateTime, Timelike};
 
 // ---------------------------------------------------------------------------
@@ -55,25 +56,19 @@ impl NodeFillsLegacy1mAggregateSource {
 impl PartitionedSource for NodeFillsLegacy1mAggregateSource {
     type Key = NodeFillsLegacy1mAggregateHourKey;
 
-    async fn partition_exists(
-        db: &QuestDbClient,
-        key: &NodeFillsLegacy1mAggregateHourKey,
-    ) -> AnySignalResult<bool> {
-        let hour_end = key.hour + chrono::Duration::hours(1);
-        let table = db.table_name("hyperliquid_fill_1m_aggregate");
-        let sql = format!(
-            "SELECT count() FROM {table} \
-             WHERE ts >= '{}Z' \
-             AND ts < '{}Z'",
-            key.hour.format("%Y-%m-%dT%H:%M:%S"),
-            hour_end.format("%Y-%m-%dT%H:%M:%S"),
-        );
-        Ok(db.count(&sql).await? > 0)
+    fn partition(key: &NodeFillsLegacy1mAggregateHourKey) -> PartitionFilter {
+        let hour = key.hour.and_utc();
+        PartitionFilter::new(
+            "hyperliquid_fill_1m_aggregate",
+            hour,
+            hour + chrono::Duration::hours(1),
+        )
     }
 
     async fn ingest_partition(
         &self,
-        db: &QuestDbClient,
+        _db: &QuestDbClient,
+        sink: &dyn Sink,
         key: &NodeFillsLegacy1mAggregateHourKey,
     ) -> AnySignalResult<PartitionStats> {
         let date = key.hour.date();
@@ -88,10 +83,7 @@ impl PartitionedSource for NodeFillsLegacy1mAggregateSource {
         aggregated.sort_unstable_by_key(|a| a.minute_ms);
 
         let t_insert = std::time::Instant::now();
-        let table = db.table_name("hyperliquid_fill_1m_aggregate");
-        let rows = tokio::task::block_in_place(|| {
-            db.with_sender(|s| insert_hyperliquid_fill_1m_aggregate(s, &table, &aggregated))
-        })? as u64;
+        let rows = sink.write(RowBatch::Fill1mAggregates(&aggregated)).await? as u64;
         let insert_ms = t_insert.elapsed().as_millis();
 
         tracing::debug!(key = %key, raw_fills, "aggregated from raw fills");
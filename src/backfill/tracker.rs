@@ -127,3 +127,9 @@ impl BackfillTracker {
             .collect()
     }
 }
+
+impl Default for BackfillTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::database::calendar::is_tombstone;
+use crate::database::query::{Query, ReadPath};
+use crate::database::signal_data::signal_query;
+use crate::database::{select_signal_db, QuestDbClient};
+use crate::engine::{MarketKey, MarketState};
+use crate::error::{AnySignalError, AnySignalResult};
+use crate::model::signal::{Signal, SignalInfo};
+use crate::model::strategy::{BookMetrics, Feeds, FillFlow, StrategyEvent};
+
+/// Venue the rows of the minute tables belong to.
+pub const VENUE: &str = "HlPerp";
+
+/// Bucket length of the minute tables. Their rows are stamped with the
+/// bucket start and replayed at its end, when the live engine would seal it.
+const MINUTE_US: i64 = 60_000_000;
+
+// ---------------------------------------------------------------------------
+// Rows
+// ---------------------------------------------------------------------------
+
+/// One `market_state_1m` row.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MarketRow {
+    pub ts: DateTime<Utc>,
+    pub coin: String,
+    pub price_oracle: Option<f64>,
+    pub price_mark: Option<f64>,
+    pub price_mid: Option<f64>,
+    pub open_interest: Option<f64>,
+    pub funding_rate: Option<f64>,
+    pub volume_24h_usd: Option<f64>,
+    pub trade_volume: Option<f64>,
+    pub trade_count: Option<i64>,
+    pub liquidation_long_volume: Option<f64>,
+    pub liquidation_short_volume: Option<f64>,
+    pub liquidation_long_count: Option<i64>,
+    pub liquidation_short_count: Option<i64>,
+    pub predicted_funding_rate: Option<f64>,
+}
+
+impl MarketRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "ts",
+        "coin",
+        "price_oracle",
+        "price_mark",
+        "price_mid",
+        "open_interest",
+        "funding_rate",
+        "volume_24h_usd",
+        "trade_volume",
+        "trade_count",
+        "liquidation_long_volume",
+        "liquidation_short_volume",
+        "liquidation_long_count",
+        "liquidation_short_count",
+        "predicted_funding_rate",
+    ];
+
+    fn into_state(self) -> MarketState {
+        let count = |c: Option<i64>| c.unwrap_or_default().max(0) as u64;
+        MarketState {
+            price_mid: self.price_mid,
+            price_oracle: self.price_oracle,
+            price_mark: self.price_mark,
+            open_interest: self.open_interest,
+            funding_rate: self.funding_rate,
+            trading_volume_24h_usd: self.volume_24h_usd.unwrap_or_default(),
+            predicted_funding_rate: self.predicted_funding_rate,
+            trading_volume: self.trade_volume.unwrap_or_default(),
+            trade_count: count(self.trade_count),
+            short_liquidation_volume: self.liquidation_short_volume.unwrap_or_default(),
+            short_liquidation_count: count(self.liquidation_short_count),
+            lng_liquidation_volume: self.liquidation_long_volume.unwrap_or_default(),
+            lng_liquidation_count: count(self.liquidation_long_count),
+            last_updated: Some(self.ts),
+            ..MarketState::new(&MarketKey::new(VENUE, &self.coin))
+        }
+    }
+}
+
+/// One `hyperliquid_fill_1m_aggregate` row.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FillRow {
+    pub ts: DateTime<Utc>,
+    pub coin: String,
+    pub category: String,
+    pub buy_side: bool,
+    pub quantity: Option<f64>,
+    pub trade_count: Option<i64>,
+}
+
+impl FillRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "ts",
+        "coin",
+        "category",
+        "buy_side",
+        "quantity",
+        "trade_count",
+    ];
+
+    fn into_flow(self) -> FillFlow {
+        FillFlow {
+            coin: self.coin,
+            category: self.category,
+            buy_side: self.buy_side,
+            quantity: self.quantity.unwrap_or_default(),
+            trade_count: self.trade_count.unwrap_or_default().max(0) as u64,
+        }
+    }
+}
+
+/// One side of one coin's `l2_orderbook_1m` levels over a minute,
+/// aggregated by QuestDB.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BookSideRow {
+    pub ts: DateTime<Utc>,
+    pub ticker: String,
+    /// `bid` or `ask`.
+    pub side: String,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    /// Quote value resting over the levels kept.
+    pub depth: Option<f64>,
+}
+
+impl BookSideRow {
+    /// Best price of the side: the highest bid or the lowest ask.
+    fn best(&self) -> Option<f64> {
+        match self.side.as_str() {
+            "bid" => self.high,
+            "ask" => self.low,
+            _ => None,
+        }
+        .filter(|p| p.is_finite() && *p > 0.0)
+    }
+}
+
+/// Top of book and depth of `coin` from its `bid` and `ask` sides of one
+/// minute. `None` unless both sides have a usable price.
+pub fn book_metrics(coin: &str, bid: &BookSideRow, ask: &BookSideRow) -> Option<BookMetrics> {
+    let (best_bid, best_ask) = (bid.best()?, ask.best()?);
+    let depth = |s: &BookSideRow| s.depth.filter(|d| d.is_finite()).unwrap_or_default();
+    let (bid_depth, ask_depth) = (depth(bid), depth(ask));
+    let mid = (best_bid + best_ask) / 2.0;
+    let total = bid_depth + ask_depth;
+    Some(BookMetrics {
+        coin: coin.to_string(),
+        best_bid,
+        best_ask,
+        spread_bps: (best_ask - best_bid) / mid * 10_000.0,
+        bid_depth,
+        ask_depth,
+        imbalance: if total > 0.0 {
+            (bid_depth - ask_depth) / total
+        } else {
+            0.0
+        },
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Chunks
+// ---------------------------------------------------------------------------
+
+/// Every row replayed over one time range.
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub markets: Vec<MarketRow>,
+    pub fills: Vec<FillRow>,
+    pub books: Vec<BookSideRow>,
+    pub signals: Vec<(SignalInfo, Signal)>,
+}
+
+impl Chunk {
+    /// Rows of `coins` (every coin when empty) stamped within `[from, to)`,
+    /// and rows of `signals`. Fills and books are only read when `feeds`
+    /// asks for them. Tables that do not exist yet contribute none.
+    pub async fn read(
+        db: &QuestDbClient,
+        coins: &[String],
+        feeds: Feeds,
+        signals: &[SignalInfo],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> AnySignalResult<Self> {
+        let minute_query = |table: &str, columns: &[&str], coin: &str| {
+            let query = Query::select(columns).from(&db.table_name(table));
+            let query = if coins.is_empty() {
+                query
+            } else {
+                query.in_list(coin, coins)
+            };
+            query.time_range("ts", from, to).via(ReadPath::PgWire)
+        };
+        let mut chunk = Chunk {
+            markets: optional(
+                db.query(
+                    &minute_query("market_state_1m", MarketRow::COLUMNS, "coin").order_by("ts"),
+                )
+                .await,
+            )?,
+            ..Chunk::default()
+        };
+        if feeds.fills {
+            let query = minute_query("hyperliquid_fill_1m_aggregate", FillRow::COLUMNS, "coin")
+                .order_by("ts");
+            chunk.fills = optional(db.query(&query).await)?;
+        }
+        if feeds.books {
+            // two rows per coin and minute instead of every level
+            let query = minute_query("l2_orderbook_1m", &["ts", "ticker", "side"], "ticker")
+                .select_expr("max(price) high")
+                .select_expr("min(price) low")
+                .select_expr("sum(price * quantity) depth")
+                .group_by("ts")
+                .group_by("ticker")
+                .group_by("side")
+                .order_by("ts");
+            chunk.books = optional(db.query(&query).await)?;
+        }
+        for info in signals {
+            let query = signal_query(db, info)
+                .time_range("timestamp", from, to)
+                .via(ReadPath::PgWire);
+            let rows = optional(select_signal_db(db, info.data_type, &query).await)?;
+            chunk.signals.extend(
+                rows.into_iter()
+                    .filter(|s| !is_tombstone(s))
+                    .map(|s| (info.clone(), s)),
+            );
+        }
+        Ok(chunk)
+    }
+
+    /// The chunk as events in replay order: by time, and at equal times
+    /// market state before fills, fills before books and books before
+    /// signals, so a strategy reacting to a signal sees the latest prices.
+    pub fn into_events(self) -> Vec<StrategyEvent> {
+        let bucket_end = |ts: DateTime<Utc>| ts.timestamp_micros() + MINUTE_US;
+        let mut markets: BTreeMap<i64, Vec<MarketState>> = BTreeMap::new();
+        for row in self.markets {
+            markets
+                .entry(bucket_end(row.ts))
+                .or_default()
+                .push(row.into_state());
+        }
+        let mut fills: BTreeMap<i64, Vec<FillFlow>> = BTreeMap::new();
+        for row in self.fills {
+            fills
+                .entry(bucket_end(row.ts))
+                .or_default()
+                .push(row.into_flow());
+        }
+        let mut sides: BTreeMap<(i64, String), [Option<BookSideRow>; 2]> = BTreeMap::new();
+        for row in self.books {
+            let i = match row.side.as_str() {
+                "bid" => 0,
+                "ask" => 1,
+                _ => continue,
+            };
+            let key = (bucket_end(row.ts), row.ticker.clone());
+            sides.entry(key).or_default()[i] = Some(row);
+        }
+        let mut books: BTreeMap<i64, Vec<BookMetrics>> = BTreeMap::new();
+        for ((t, coin), [bid, ask]) in sides {
+            let (Some(bid), Some(ask)) = (bid, ask) else {
+                continue;
+            };
+            if let Some(metrics) = book_metrics(&coin, &bid, &ask) {
+                books.entry(t).or_default().push(metrics);
+            }
+        }
+
+        let at = |t: i64| DateTime::from_timestamp_micros(t).unwrap_or_default();
+        let mut events: Vec<(i64, u8, StrategyEvent)> = Vec::new();
+        for (t, mut states) in markets {
+            states.sort_by(|a, b| a.coin.cmp(&b.coin));
+            let event = StrategyEvent::Market {
+                time: at(t),
+                states,
+            };
+            events.push((t, 0, event));
+        }
+        for (t, mut fills) in fills {
+            fills.sort_by(|a, b| {
+                (&a.coin, &a.category, a.buy_side).cmp(&(&b.coin, &b.category, b.buy_side))
+            });
+            events.push((t, 1, StrategyEvent::Fills { time: at(t), fills }));
+        }
+        for (t, books) in books {
+            events.push((t, 2, StrategyEvent::Book { time: at(t), books }));
+        }
+        for (info, signal) in self.signals {
+            let t = signal.timestamp_us;
+            events.push((t, 3, StrategyEvent::Signal { info, signal }));
+        }
+        // stable: signals keep the order they were read in
+        events.sort_by_key(|(t, rank, _)| (*t, *rank));
+        events.into_iter().map(|(_, _, event)| event).collect()
+    }
+}
+
+/// Rows of a query whose table may not exist yet.
+fn optional<T>(rows: AnySignalResult<Vec<T>>) -> AnySignalResult<Vec<T>> {
+    match rows {
+        Err(AnySignalError::QuestQuery(e)) if e.is_missing_table() => Ok(Vec::new()),
+        rows => rows,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::coinmarketcap::fear_and_greed;
+
+    fn minute(m: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp_micros(m * MINUTE_US).unwrap()
+    }
+
+    fn side(ts: DateTime<Utc>, side: &str, high: f64, low: f64, depth: f64) -> BookSideRow {
+        BookSideRow {
+            ts,
+            ticker: "BTC".to_string(),
+            side: side.to_string(),
+            high: Some(high),
+            low: Some(low),
+            depth: Some(depth),
+        }
+    }
+
+    #[test]
+    fn computes_book_metrics() {
+        let t = minute(0);
+        let bid = side(t, "bid", 99.5, 99.0, 297.5);
+        let ask = side(t, "ask", 101.0, 100.5, 100.5);
+        let book = book_metrics("BTC", &bid, &ask).unwrap();
+        assert_eq!((book.best_bid, book.best_ask), (99.5, 100.5));
+        assert_eq!(book.spread_bps, 100.0);
+        assert_eq!((book.bid_depth, book.ask_depth), (297.5, 100.5));
+        assert!((book.imbalance - 197.0 / 398.0).abs() < 1e-12);
+        let empty = BookSideRow {
+            high: None,
+            low: None,
+            ..ask.clone()
+        };
+        assert_eq!(book_metrics("BTC", &bid, &empty), None);
+    }
+
+    #[test]
+    fn orders_events_by_time_then_kind() {
+        let market = |ts, coin: &str| MarketRow {
+            ts,
+            coin: coin.to_string(),
+            price_oracle: None,
+            price_mark: None,
+            price_mid: Some(100.0),
+            open_interest: None,
+            funding_rate: Some(0.0001),
+            volume_24h_usd: None,
+            trade_volume: None,
+            trade_count: None,
+            liquidation_long_volume: None,
+            liquidation_short_volume: None,
+            liquidation_long_count: None,
+            liquidation_short_count: None,
+            predicted_funding_rate: None,
+        };
+        let mut signal = Signal::dummy_scalar(20.0);
+        signal.timestamp_us = minute(1).timestamp_micros();
+        let chunk = Chunk {
+            markets: vec![
+                market(minute(1), "ETH"),
+                market(minute(0), "BTC"),
+                market(minute(0), "ETH"),
+            ],
+            fills: vec![FillRow {
+                ts: minute(0),
+                coin: "BTC".to_string(),
+                category: "Open Long".to_string(),
+                buy_side: true,
+                quantity: Some(1.5),
+                trade_count: Some(3),
+            }],
+            books: vec![
+                side(minute(0), "bid", 99.0, 98.0, 99.0),
+                side(minute(0), "ask", 102.0, 101.0, 101.0),
+                // a coin with one side only has no metrics
+                BookSideRow {
+                    ticker: "ETH".to_string(),
+                    ..side(minute(0), "bid", 9.0, 8.0, 9.0)
+                },
+            ],
+            signals: vec![(fear_and_greed::signal_info(), signal)],
+        };
+        let events = chunk.into_events();
+        let kinds: Vec<(i64, &str)> = events
+            .iter()
+            .map(|e| {
+                let kind = match e {
+                    StrategyEvent::Market { .. } => "market",
+                    StrategyEvent::Fills { .. } => "fills",
+                    StrategyEvent::Book { .. } => "book",
+                    StrategyEvent::Signal { .. } => "signal",
+                };
+                (e.time().timestamp_micros() / MINUTE_US, kind)
+            })
+            .collect();
+        // minute buckets replay at their end, after a signal stamped then
+        assert_eq!(
+            kinds,
+            [
+                (1, "market"),
+                (1, "fills"),
+                (1, "book"),
+                (1, "signal"),
+                (2, "market")
+            ]
+        );
+        let StrategyEvent::Market { states, .. } = &events[0] else {
+            panic!("expected market state first");
+        };
+        let coins: Vec<&str> = states.iter().map(|s| s.coin.as_str()).collect();
+        assert_eq!(coins, ["BTC", "ETH"]);
+        assert_eq!(states[0].venue, VENUE);
+        assert_eq!(states[0].last_updated, Some(minute(0)));
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+
+use super::simulator::Backtest;
+
+/// Backtests allowed to run at once.
+pub const MAX_RUNNING: usize = 2;
+/// Backtests remembered; the oldest finished ones are forgotten first.
+const MAX_JOBS: usize = 50;
+
+/// Lifecycle of a backtest job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Running,
+    Done,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobStatus::Running => "running",
+            JobStatus::Done => "done",
+            JobStatus::Failed => "failed",
+        }
+    }
+}
+
+/// One backtest started through the API.
+#[derive(Debug, Clone)]
+pub struct BacktestJob {
+    pub id: u64,
+    pub strategy: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub status: JobStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// History replayed up to here.
+    pub reached: DateTime<Utc>,
+    pub events: u64,
+    pub error: Option<String>,
+    pub result: Option<Arc<Backtest>>,
+}
+
+impl BacktestJob {
+    /// Share of the range replayed, from 0 to 1.
+    pub fn progress(&self) -> f64 {
+        let total = (self.to - self.from).num_seconds();
+        if total <= 0 {
+            return 1.0;
+        }
+        ((self.reached - self.from).num_seconds() as f64 / total as f64).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    jobs: BTreeMap<u64, BacktestJob>,
+    last_id: u64,
+}
+
+/// Backtest jobs of this process, kept in memory only.
+/// Cheaply cloneable — shared between the API and the tasks running them.
+#[derive(Clone, Default)]
+pub struct BacktestJobs(Arc<Mutex<Inner>>);
+
+impl BacktestJobs {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.0.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Register a running backtest of `strategy` over `[from, to)`. Fails
+    /// while [`MAX_RUNNING`] others are running.
+    pub fn start(
+        &self,
+        strategy: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<u64, String> {
+        let mut inner = self.lock();
+        let running = inner
+            .jobs
+            .values()
+            .filter(|j| j.status == JobStatus::Running)
+            .count();
+        if running >= MAX_RUNNING {
+            return Err(format!(
+                "{running} backtests are already running, retry once one finished"
+            ));
+        }
+        while inner.jobs.len() >= MAX_JOBS {
+            let Some(oldest) = inner
+                .jobs
+                .values()
+                .find(|j| j.status != JobStatus::Running)
+                .map(|j| j.id)
+            else {
+                break;
+            };
+            inner.jobs.remove(&oldest);
+        }
+        inner.last_id += 1;
+        let id = inner.last_id;
+        inner.jobs.insert(
+            id,
+            BacktestJob {
+                id,
+                strategy: strategy.to_string(),
+                from,
+                to,
+                status: JobStatus::Running,
+                started_at: Utc::now(),
+                finished_at: None,
+                reached: from,
+                events: 0,
+                error: None,
+                result: None,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Record that job `id` replayed history up to `reached`.
+    pub fn progress(&self, id: u64, reached: DateTime<Utc>, events: u64) {
+        if let Some(job) = self.lock().jobs.get_mut(&id) {
+            job.reached = reached;
+            job.events = events;
+        }
+    }
+
+    /// Record the outcome of job `id`.
+    pub fn finish(&self, id: u64, result: Result<Backtest, String>) {
+        let mut inner = self.lock();
+        let Some(job) = inner.jobs.get_mut(&id) else {
+            return;
+        };
+        job.finished_at = Some(Utc::now());
+        match result {
+            Ok(backtest) => {
+                job.status = JobStatus::Done;
+                job.reached = job.to;
+                job.events = backtest.events;
+                job.result = Some(Arc::new(backtest));
+            }
+            Err(e) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(e);
+            }
+        }
+    }
+
+    /// Run job `id` as `task` and record its outcome. The task runs in its
+    /// own Tokio task watched by another, so a panic fails the job instead
+    /// of leaving it running and holding one of the [`MAX_RUNNING`] slots.
+    pub fn spawn<F>(&self, id: u64, task: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Future<Output = Result<Backtest, String>> + Send + 'static,
+    {
+        let jobs = self.clone();
+        let handle = tokio::spawn(task);
+        tokio::spawn(async move {
+            let result = match handle.await {
+                Ok(result) => result,
+                Err(e) => {
+                    tracing::error!(id, error = %e, "backtest task panicked");
+                    Err(format!("backtest task failed: {e}"))
+                }
+            };
+            jobs.finish(id, result);
+        })
+    }
+
+    pub fn get(&self, id: u64) -> Option<BacktestJob> {
+        self.lock().jobs.get(&id).cloned()
+    }
+
+    /// Every remembered job, newest first.
+    pub fn list(&self) -> Vec<BacktestJob> {
+        self.lock().jobs.values().rev().cloned().collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_running_jobs_and_forgets_old_ones() {
+        let jobs = BacktestJobs::default();
+        let from = DateTime::UNIX_EPOCH;
+        let to = DateTime::from_timestamp(100, 0).unwrap();
+        let a = jobs.start("a", from, to).unwrap();
+        let b = jobs.start("b", from, to).unwrap();
+        assert!(jobs.start("c", from, to).is_err());
+
+        jobs.progress(a, DateTime::from_timestamp(25, 0).unwrap(), 7);
+        assert_eq!(jobs.get(a).unwrap().progress(), 0.25);
+        jobs.finish(a, Err("boom".to_string()));
+        assert_eq!(jobs.get(a).unwrap().status, JobStatus::Failed);
+
+        for _ in 0..MAX_JOBS {
+            let id = jobs.start("x", from, to).unwrap();
+            jobs.finish(id, Err(String::new()));
+        }
+        let list = jobs.list();
+        assert_eq!(list.len(), MAX_JOBS);
+        // the running job is kept, the oldest finished one is not
+        assert!(jobs.get(b).is_some());
+        assert!(jobs.get(a).is_none());
+        assert!(list[0].id > list[1].id);
+    }
+
+    #[tokio::test]
+    async fn a_panicking_job_fails_and_frees_its_slot() {
+        let jobs = BacktestJobs::default();
+        let from = DateTime::UNIX_EPOCH;
+        let to = DateTime::from_timestamp(100, 0).unwrap();
+        let a = jobs.start("a", from, to).unwrap();
+        let _b = jobs.start("b", from, to).unwrap();
+
+        jobs.spawn(a, async { panic!("strategy bug") })
+            .await
+            .unwrap();
+        let job = jobs.get(a).unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert!(job.error.unwrap().contains("panicked"));
+        assert!(job.finished_at.is_some());
+        assert!(jobs.start("c", from, to).is_ok());
+    }
+}
@@ -0,0 +1,9 @@
+/// history read from QuestDB as strategy events
+pub mod feed;
+/// backtests started through the API
+pub mod jobs;
+/// strategy replay with simulated fills, funding and statistics
+pub mod simulator;
+
+pub use jobs::BacktestJobs;
+pub use simulator::{run_backtest, Backtest};
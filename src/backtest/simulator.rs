@@ -0,0 +1,627 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Duration, Utc};
+
+use super::feed::Chunk;
+use crate::analysis::series::BarInterval;
+use crate::database::registry::SignalRegistry;
+use crate::database::QuestDbClient;
+use crate::engine::funding::hourly_rate;
+use crate::engine::{MarketKey, MarketState};
+use crate::error::AnySignalResult;
+use crate::model::instance::{InstanceStatus, PaperInstance, Side};
+use crate::model::strategy::{Action, LegOrder, Strategy, StrategyEvent, StrategyInfo};
+use crate::strategy::broker;
+
+/// Span of history read per round trip to QuestDB for chosen coins.
+const CHUNK: Duration = Duration::days(1);
+/// Span read per round trip when every coin is replayed.
+const ALL_COINS_CHUNK: Duration = Duration::hours(1);
+
+const HOUR_US: i64 = 3_600_000_000;
+const YEAR_US: f64 = 365.25 * 86_400_000_000.0;
+
+// ---------------------------------------------------------------------------
+// Settings
+// ---------------------------------------------------------------------------
+
+/// Trading costs charged on every simulated fill.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Costs {
+    /// Fee on traded notional, in basis points.
+    pub fee_bps: f64,
+    /// Distance from the mid a market order fills at, in basis points.
+    pub slippage_bps: f64,
+}
+
+impl Costs {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, bps) in [
+            ("fee_bps", self.fee_bps),
+            ("slippage_bps", self.slippage_bps),
+        ] {
+            if !(bps.is_finite() && bps >= 0.0) {
+                return Err(format!("{name} must be zero or positive"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Price an order buying (or selling) at `mid` fills at.
+    pub fn fill_price(&self, buy: bool, mid: f64) -> f64 {
+        let sign = if buy { 1.0 } else { -1.0 };
+        mid * (1.0 + sign * self.slippage_bps / 10_000.0)
+    }
+
+    /// Fee on trading `size` at `price`.
+    pub fn fee(&self, size: f64, price: f64) -> f64 {
+        size.abs() * price * self.fee_bps / 10_000.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub costs: Costs,
+    /// Pay and receive hourly funding on open legs.
+    pub funding: bool,
+    pub initial_capital: f64,
+    /// Spacing of the equity curve.
+    pub equity_interval: BarInterval,
+}
+
+// ---------------------------------------------------------------------------
+// Results
+// ---------------------------------------------------------------------------
+
+/// One simulated fill of a leg.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub time: DateTime<Utc>,
+    pub instance_id: u64,
+    pub market: MarketKey,
+    /// Side of the leg traded, opened or closed.
+    pub side: Side,
+    /// Whether the trade opened the leg rather than closed it.
+    pub opening: bool,
+    pub size: f64,
+    pub price: f64,
+    pub fee: f64,
+}
+
+/// An instance spawned during the backtest with what it paid on top of its
+/// legs' price pnl.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceResult {
+    pub instance: PaperInstance,
+    pub fees: f64,
+    /// Funding received, negative when paid.
+    pub funding: f64,
+}
+
+impl InstanceResult {
+    /// Legs' pnl net of fees and funding.
+    pub fn pnl(&self) -> f64 {
+        self.instance.pnl() - self.fees + self.funding
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquityPoint {
+    pub time: DateTime<Utc>,
+    pub equity: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub initial_capital: f64,
+    pub final_equity: f64,
+    pub total_return: f64,
+    /// Largest fall from a peak of the equity curve, as a fraction of it.
+    pub max_drawdown: f64,
+    /// Annualised Sharpe ratio of the equity curve's returns, at a zero
+    /// risk-free rate. `None` without enough variation to compute it.
+    pub sharpe: Option<f64>,
+    pub trades: usize,
+    pub instances: usize,
+    pub closed: usize,
+    /// Share of closed instances with a positive net pnl.
+    pub win_rate: Option<f64>,
+    pub fees: f64,
+    pub funding: f64,
+    /// Actions that could not be carried out, e.g. an order without price.
+    pub rejected: usize,
+}
+
+/// Outcome of replaying one strategy over a time range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backtest {
+    pub strategy: StrategyInfo,
+    pub settings: Settings,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub events: u64,
+    pub trades: Vec<Trade>,
+    /// Instances by id; those still open are valued at their last marks.
+    pub instances: Vec<InstanceResult>,
+    pub equity: Vec<EquityPoint>,
+    pub summary: Summary,
+}
+
+// ---------------------------------------------------------------------------
+// Simulator
+// ---------------------------------------------------------------------------
+
+/// Drives a strategy with replayed events and fills its actions on its own
+/// book. Orders fill at the latest `price_mid` moved by the slippage, and
+/// open legs settle funding for every hour boundary the replay crosses, at
+/// their market's last known rate, gaps in the data included.
+pub struct Simulator {
+    strategy: Box<dyn Strategy>,
+    settings: Settings,
+    instances: BTreeMap<u64, InstanceResult>,
+    /// Latest `price_mid` per market.
+    marks: HashMap<MarketKey, f64>,
+    /// Latest `funding_rate` per market.
+    rates: HashMap<MarketKey, f64>,
+    /// Hour (since the epoch) of the last market state.
+    funding_hour: Option<i64>,
+    trades: Vec<Trade>,
+    equity: Vec<EquityPoint>,
+    last_time: Option<DateTime<Utc>>,
+    events: u64,
+    rejected: usize,
+}
+
+impl Simulator {
+    pub fn new(strategy: Box<dyn Strategy>, settings: Settings) -> Self {
+        Self {
+            strategy,
+            settings,
+            instances: BTreeMap::new(),
+            marks: HashMap::new(),
+            rates: HashMap::new(),
+            funding_hour: None,
+            trades: Vec::new(),
+            equity: Vec::new(),
+            last_time: None,
+            events: 0,
+            rejected: 0,
+        }
+    }
+
+    pub fn events(&self) -> u64 {
+        self.events
+    }
+
+    /// Capital plus the net pnl of every instance, open ones at their marks.
+    pub fn equity(&self) -> f64 {
+        self.settings.initial_capital
+            + self
+                .instances
+                .values()
+                .map(InstanceResult::pnl)
+                .sum::<f64>()
+    }
+
+    /// Hand `event` to the strategy and carry out its actions. Events must
+    /// arrive in time order.
+    pub fn on_event(&mut self, event: &StrategyEvent) {
+        let time = event.time();
+        self.events += 1;
+        self.last_time = Some(time);
+        if let StrategyEvent::Market { states, .. } = event {
+            self.on_market(time, states);
+        }
+        let open: Vec<PaperInstance> = self
+            .instances
+            .values()
+            .filter(|i| i.instance.is_open())
+            .map(|i| i.instance.clone())
+            .collect();
+        for action in self.strategy.on_event(event, &open) {
+            match action {
+                Action::Open { legs, reason } => self.open(time, &legs, reason),
+                Action::Close {
+                    instance_id,
+                    reason,
+                } => self.close(time, instance_id, reason),
+            }
+        }
+        if matches!(event, StrategyEvent::Market { .. }) {
+            self.record_equity(time);
+        }
+    }
+
+    fn on_market(&mut self, time: DateTime<Utc>, states: &[MarketState]) {
+        // positions held through the hours that ended before this state
+        let hour = time.timestamp_micros().div_euclid(HOUR_US);
+        if let Some(last) = self.funding_hour.filter(|h| hour > *h) {
+            if self.settings.funding {
+                self.settle_funding(hour - last);
+            }
+        }
+        self.funding_hour = Some(hour);
+
+        for state in states {
+            let key = state.key();
+            if let Some(rate) = state.funding_rate.filter(|r| r.is_finite()) {
+                self.rates.insert(key.clone(), rate);
+            }
+            if let Some(price) = state.price_mid.filter(|p| p.is_finite() && *p > 0.0) {
+                self.marks.insert(key, price);
+            }
+        }
+        let marks = &self.marks;
+        for result in self.instances.values_mut() {
+            result.instance.mark(|m| marks.get(m).copied());
+        }
+    }
+
+    /// Settle `hours` of funding on every open leg at its market's last
+    /// known rate and the leg's mark.
+    fn settle_funding(&mut self, hours: i64) {
+        let rates = &self.rates;
+        for result in self.instances.values_mut().filter(|i| i.instance.is_open()) {
+            for leg in &result.instance.legs {
+                if let Some(rate) = rates.get(&leg.market) {
+                    // longs pay positive funding
+                    let rate = hourly_rate(&leg.market.venue, *rate);
+                    result.funding -=
+                        leg.side.sign() * leg.size * leg.mark_price * rate * hours as f64;
+                }
+            }
+        }
+    }
+
+    fn open(&mut self, time: DateTime<Utc>, orders: &[LegOrder], reason: String) {
+        let mut legs = match broker::fill(orders, |m| self.marks.get(m).copied()) {
+            Ok(legs) => legs,
+            Err(e) => {
+                tracing::debug!(error = e.as_str(), "backtest order not filled");
+                self.rejected += 1;
+                return;
+            }
+        };
+        let id = self.instances.len() as u64 + 1;
+        let costs = self.settings.costs;
+        let mut fees = 0.0;
+        for leg in &mut legs {
+            // the mark stays at the mid: slippage is an immediate loss
+            leg.entry_price = costs.fill_price(leg.side == Side::Long, leg.entry_price);
+            let fee = costs.fee(leg.size, leg.entry_price);
+            fees += fee;
+            self.trades.push(Trade {
+                time,
+                instance_id: id,
+                market: leg.market.clone(),
+                side: leg.side,
+                opening: true,
+                size: leg.size,
+                price: leg.entry_price,
+                fee,
+            });
+        }
+        let instance = PaperInstance::open(id, self.strategy.info().id, legs, time, reason);
+        self.instances.insert(
+            id,
+            InstanceResult {
+                instance,
+                fees,
+                funding: 0.0,
+            },
+        );
+    }
+
+    fn close(&mut self, time: DateTime<Utc>, id: u64, reason: String) {
+        let Some(result) = self.instances.get_mut(&id).filter(|i| i.instance.is_open()) else {
+            self.rejected += 1;
+            return;
+        };
+        let costs = self.settings.costs;
+        for leg in &mut result.instance.legs {
+            leg.mark_price = costs.fill_price(leg.side == Side::Short, leg.mark_price);
+            let fee = costs.fee(leg.size, leg.mark_price);
+            result.fees += fee;
+            self.trades.push(Trade {
+                time,
+                instance_id: id,
+                market: leg.market.clone(),
+                side: leg.side,
+                opening: false,
+                size: leg.size,
+                price: leg.mark_price,
+                fee,
+            });
+        }
+        result.instance.close(InstanceStatus::Closed, time, reason);
+    }
+
+    /// Set the equity of the bar `time` falls in, appending a point when it
+    /// starts a new bar.
+    fn record_equity(&mut self, time: DateTime<Utc>) {
+        let point = EquityPoint {
+            time,
+            equity: self.equity(),
+        };
+        let interval = &self.settings.equity_interval;
+        let bar = interval.bar(time.timestamp_micros());
+        match self.equity.last_mut() {
+            Some(last) if interval.bar(last.time.timestamp_micros()) == bar => *last = point,
+            _ => self.equity.push(point),
+        }
+    }
+
+    /// The backtest of `[from, to)` replayed so far.
+    pub fn finish(mut self, from: DateTime<Utc>, to: DateTime<Utc>) -> Backtest {
+        if let Some(time) = self.last_time {
+            self.record_equity(time);
+        }
+        let instances: Vec<InstanceResult> = self.instances.into_values().collect();
+        let summary = summarize(
+            &self.settings,
+            &self.equity,
+            &instances,
+            self.trades.len(),
+            self.rejected,
+        );
+        Backtest {
+            strategy: self.strategy.info(),
+            settings: self.settings,
+            from,
+            to,
+            events: self.events,
+            trades: self.trades,
+            instances,
+            equity: self.equity,
+            summary,
+        }
+    }
+}
+
+/// Summary statistics of a finished replay.
+pub fn summarize(
+    settings: &Settings,
+    equity: &[EquityPoint],
+    instances: &[InstanceResult],
+    trades: usize,
+    rejected: usize,
+) -> Summary {
+    let initial = settings.initial_capital;
+    let final_equity = equity.last().map_or(initial, |p| p.equity);
+
+    let mut peak = initial;
+    let mut max_drawdown: f64 = 0.0;
+    for point in equity {
+        peak = peak.max(point.equity);
+        if peak > 0.0 {
+            max_drawdown = max_drawdown.max((peak - point.equity) / peak);
+        }
+    }
+
+    let returns: Vec<f64> = std::iter::once(initial)
+        .chain(equity.iter().map(|p| p.equity))
+        .collect::<Vec<_>>()
+        .windows(2)
+        .filter(|w| w[0] > 0.0)
+        .map(|w| w[1] / w[0] - 1.0)
+        .collect();
+    let sharpe = (returns.len() >= 2)
+        .then(|| {
+            let n = returns.len() as f64;
+            let mean = returns.iter().sum::<f64>() / n;
+            let var = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            let periods = YEAR_US / settings.equity_interval.micros as f64;
+            (var > 0.0).then(|| mean / var.sqrt() * periods.sqrt())
+        })
+        .flatten();
+
+    let closed: Vec<&InstanceResult> = instances.iter().filter(|i| !i.instance.is_open()).collect();
+    let wins = closed.iter().filter(|i| i.pnl() > 0.0).count();
+    Summary {
+        initial_capital: initial,
+        final_equity,
+        total_return: final_equity / initial - 1.0,
+        max_drawdown,
+        sharpe,
+        trades,
+        instances: instances.len(),
+        closed: closed.len(),
+        win_rate: (!closed.is_empty()).then(|| wins as f64 / closed.len() as f64),
+        fees: instances.iter().map(|i| i.fees).sum(),
+        funding: instances.iter().map(|i| i.funding).sum(),
+        rejected,
+    }
+}
+
+/// What to replay.
+#[derive(Debug, Clone)]
+pub struct Spec {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Coins to replay market data of; every coin when empty.
+    pub coins: Vec<String>,
+    pub settings: Settings,
+}
+
+/// Replay `[spec.from, spec.to)` through `strategy` a day at a time (an
+/// hour at a time for every coin), reporting the time reached and events
+/// replayed after each. Every signal the strategy subscribes to must be
+/// registered.
+pub async fn run_backtest(
+    db: &QuestDbClient,
+    signals: &SignalRegistry,
+    strategy: Box<dyn Strategy>,
+    spec: Spec,
+    mut progress: impl FnMut(DateTime<Utc>, u64),
+) -> AnySignalResult<Backtest> {
+    let subscribed = strategy
+        .signals()
+        .iter()
+        .map(|s| {
+            signals.find(s).ok_or_else(|| {
+                eyre::eyre!("signal {}/{} is not registered", s.source, s.signal_type).into()
+            })
+        })
+        .collect::<AnySignalResult<Vec<_>>>()?;
+    let feeds = strategy.feeds();
+    let step = if spec.coins.is_empty() {
+        ALL_COINS_CHUNK
+    } else {
+        CHUNK
+    };
+    let mut simulator = Simulator::new(strategy, spec.settings);
+    let mut from = spec.from;
+    while from < spec.to {
+        let to = (from + step).min(spec.to);
+        let chunk = Chunk::read(db, &spec.coins, feeds, &subscribed, from, to).await?;
+        for event in chunk.into_events() {
+            simulator.on_event(&event);
+        }
+        progress(to, simulator.events());
+        from = to;
+    }
+    Ok(simulator.finish(spec.from, spec.to))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Opens one BTC long on the first market event, closes it on the third.
+    struct OneTrade(usize);
+
+    impl Strategy for OneTrade {
+        fn info(&self) -> StrategyInfo {
+            StrategyInfo {
+                id: 9,
+                name: "one_trade".to_string(),
+                description: String::new(),
+                creator: String::new(),
+            }
+        }
+
+        fn on_event(&mut self, event: &StrategyEvent, open: &[PaperInstance]) -> Vec<Action> {
+            if !matches!(event, StrategyEvent::Market { .. }) {
+                return Vec::new();
+            }
+            self.0 += 1;
+            match (self.0, open.first()) {
+                (1, None) => vec![Action::Open {
+                    legs: vec![LegOrder {
+                        market: MarketKey::new("HlPerp", "BTC"),
+                        side: Side::Long,
+                        notional: 1_000.0,
+                    }],
+                    reason: "enter".to_string(),
+                }],
+                (3, Some(i)) => vec![Action::Close {
+                    instance_id: i.id,
+                    reason: "exit".to_string(),
+                }],
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    fn market(minutes: i64, price: f64, funding_rate: f64) -> StrategyEvent {
+        let mut state = MarketState::new(&MarketKey::new("HlPerp", "BTC"));
+        state.price_mid = Some(price);
+        state.funding_rate = Some(funding_rate);
+        StrategyEvent::Market {
+            time: DateTime::from_timestamp(minutes * 60, 0).unwrap(),
+            states: vec![state],
+        }
+    }
+
+    fn settings() -> Settings {
+        Settings {
+            costs: Costs {
+                fee_bps: 10.0,
+                slippage_bps: 100.0,
+            },
+            funding: true,
+            initial_capital: 10_000.0,
+            equity_interval: "1h".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn charges_fees_slippage_and_funding() {
+        let mut sim = Simulator::new(Box::new(OneTrade(0)), settings());
+        // enter at 101 (mid 100 + 1%), settle funding at 01:00 and 02:00, then exit
+        sim.on_event(&market(30, 100.0, 0.001));
+        sim.on_event(&market(60, 110.0, 0.001));
+        sim.on_event(&market(120, 120.0, 0.001));
+        let backtest = sim.finish(
+            DateTime::UNIX_EPOCH,
+            DateTime::from_timestamp(7_200, 0).unwrap(),
+        );
+
+        let size = 10.0;
+        let [entry, exit] = &backtest.trades[..] else {
+            panic!("expected two trades, got {:?}", backtest.trades);
+        };
+        assert_eq!(
+            (entry.price, entry.size, entry.opening),
+            (101.0, size, true)
+        );
+        assert!((exit.price - 118.8).abs() < 1e-9);
+        assert!(!exit.opening);
+
+        let result = &backtest.instances[0];
+        assert!((result.fees - (1.01 + 1.188)).abs() < 1e-9);
+        // the long pays 0.1% of its value held into each hour: 1000, then 1100
+        assert!((result.funding + 2.1).abs() < 1e-9);
+        let pnl = size * (118.8 - 101.0) - result.fees + result.funding;
+        assert!((result.pnl() - pnl).abs() < 1e-9);
+
+        let summary = &backtest.summary;
+        assert_eq!(
+            (summary.trades, summary.instances, summary.closed),
+            (2, 1, 1)
+        );
+        assert_eq!(summary.win_rate, Some(1.0));
+        assert!((summary.final_equity - (10_000.0 + pnl)).abs() < 1e-9);
+        // one point per hour bar
+        assert_eq!(backtest.equity.len(), 3);
+    }
+
+    #[test]
+    fn settles_every_hour_of_a_gap_at_the_last_rate() {
+        let mut sim = Simulator::new(Box::new(OneTrade(0)), settings());
+        sim.on_event(&market(30, 100.0, 0.001));
+        // no BTC state at all for three hours, then one without a rate
+        let mut state = MarketState::new(&MarketKey::new("HlPerp", "BTC"));
+        state.price_mid = Some(100.0);
+        let time = DateTime::from_timestamp(210 * 60, 0).unwrap();
+        sim.on_event(&StrategyEvent::Market {
+            time,
+            states: vec![state],
+        });
+        let backtest = sim.finish(DateTime::UNIX_EPOCH, time);
+        assert!((backtest.instances[0].funding + 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn summarizes_drawdown_and_sharpe() {
+        let point = |h: i64, equity| EquityPoint {
+            time: DateTime::from_timestamp(h * 3_600, 0).unwrap(),
+            equity,
+        };
+        let equity = [point(0, 110.0), point(1, 88.0), point(2, 99.0)];
+        let mut settings = settings();
+        settings.initial_capital = 100.0;
+        let summary = summarize(&settings, &equity, &[], 0, 0);
+        assert!((summary.max_drawdown - 0.2).abs() < 1e-12);
+        assert!((summary.total_return + 0.01).abs() < 1e-12);
+        assert!(summary.sharpe.is_some());
+        assert_eq!(summary.win_rate, None);
+
+        let flat = [point(0, 100.0), point(1, 100.0)];
+        assert_eq!(summarize(&settings, &flat, &[], 0, 0).sharpe, None);
+    }
+}
@@ -0,0 +1,129 @@
+use serde::Deserialize;
+
+/// Contents of `config.toml`. Every key is optional: unset keys fall through
+/// to the built-in defaults, and env vars override whatever is set here.
+/// Unknown keys are rejected so a typo fails at startup instead of being
+/// silently ignored.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileConfig {
+    /// Runners to start, e.g. `["api", "realtime"]`.
+    pub runners: Option<Vec<String>>,
+    pub dev: Option<bool>,
+    pub postmortem_log: Option<String>,
+    pub api: ApiSection,
+    pub questdb: QuestDbSection,
+    pub sink: SinkSection,
+    pub retention: RetentionSection,
+    pub backfill: BackfillSection,
+    pub realtime: RealtimeSection,
+    pub coinmarketcap: CoinMarketCapSection,
+    pub newsapi: NewsApiSection,
+    pub polygonio: PolygonIoSection,
+    pub microstrategy: MicroStrategySection,
+    pub youtube: YouTubeSection,
+    pub strategy: StrategySection,
+}
+
+/// `[api]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiSection {
+    pub port: Option<u16>,
+    pub base_url: Option<String>,
+}
+
+/// `[questdb]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuestDbSection {
+    pub addr: Option<String>,
+    pub pg_addr: Option<String>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub spool_dir: Option<String>,
+    pub spool_max_mb: Option<u64>,
+    pub sender_pool_size: Option<usize>,
+}
+
+/// `[sink]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SinkSection {
+    /// `questdb`, `parquet` or `postgres`.
+    pub kind: Option<String>,
+    pub parquet_dir: Option<String>,
+    pub postgres_url: Option<String>,
+}
+
+/// `[retention]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionSection {
+    pub policy_path: Option<String>,
+    pub interval_secs: Option<u64>,
+}
+
+/// `[backfill]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackfillSection {
+    pub concurrency: Option<usize>,
+}
+
+/// `[realtime]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RealtimeSection {
+    pub allowed_lateness_secs: Option<u64>,
+    pub venues: Option<Vec<String>>,
+}
+
+/// `[coinmarketcap]` — reloaded without a restart.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CoinMarketCapSection {
+    pub poll_interval_secs: Option<u64>,
+}
+
+/// `[newsapi]` — reloaded without a restart.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NewsApiSection {
+    pub poll_interval_secs: Option<u64>,
+    /// Searched as `keyword OR keyword …`.
+    pub keywords: Option<Vec<String>>,
+}
+
+/// `[polygonio]` — reloaded without a restart.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolygonIoSection {
+    /// Polygon subscription channels, e.g. `AM.MSFT`.
+    pub tickers: Option<Vec<String>>,
+}
+
+/// `[microstrategy]` — reloaded without a restart.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MicroStrategySection {
+    pub poll_interval_secs: Option<u64>,
+}
+
+/// `[youtube]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct YouTubeSection {
+    /// `https://www.youtube.com/watch?v=…` URLs, one caption signal each.
+    pub video_urls: Option<Vec<String>>,
+    pub poll_interval_secs: Option<u64>,
+}
+
+/// `[strategy]`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StrategySection {
+    /// Built-in strategies to paper-trade, e.g. `["funding_carry"]`.
+    pub strategies: Option<Vec<String>>,
+    pub signal_poll_interval_secs: Option<u64>,
+}
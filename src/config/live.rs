@@ -0,0 +1,104 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use super::{Config, RELOADABLE};
+use crate::error::AnySignalResult;
+
+/// The running configuration, shared between `main`, the runners and the
+/// API. Cheap to clone.
+///
+/// [`Self::watch`] re-reads `config_path` whenever its modification time
+/// changes. Fields in [`RELOADABLE`] (tickers, keywords, poll intervals)
+/// take effect on the runners' next iteration. Any other change is logged
+/// and waits for a restart, and a file that fails to parse or validate is
+/// ignored so the last good config stays active.
+#[derive(Clone)]
+pub struct LiveConfig(Arc<Mutex<Config>>);
+
+impl LiveConfig {
+    pub fn new(config: Config) -> Self {
+        Self(Arc::new(Mutex::new(config)))
+    }
+
+    /// A snapshot of the current configuration.
+    pub fn get(&self) -> Config {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Config> {
+        self.0.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Apply `next` and return the reloadable fields that changed. Changes
+    /// to any other field are logged and dropped.
+    pub fn apply(&self, next: &Config) -> Vec<String> {
+        let mut current = self.lock();
+        let changed = current.changed_fields(next);
+        let (applied, pending): (Vec<String>, Vec<String>) = changed
+            .into_iter()
+            .partition(|f| RELOADABLE.contains(&f.as_str()));
+        if !pending.is_empty() {
+            tracing::warn!(fields = ?pending, "config changes need a restart to take effect");
+        }
+        current.apply_reloadable(next);
+        applied
+    }
+
+    /// Re-read the config file and apply it.
+    pub fn reload(&self) -> AnySignalResult<Vec<String>> {
+        let path = self.lock().config_path.clone();
+        let next = Config::load_from(&path)?;
+        Ok(self.apply(&next))
+    }
+
+    /// Poll the config file every `interval` and reload it when it changes.
+    pub async fn watch(self, interval: Duration) {
+        let path = self.lock().config_path.clone();
+        let modified = || -> Option<SystemTime> { std::fs::metadata(&path).ok()?.modified().ok() };
+        let mut last = modified();
+        loop {
+            tokio::time::sleep(interval).await;
+            let now = modified();
+            if now == last {
+                continue;
+            }
+            last = now;
+            match self.reload() {
+                Ok(applied) if applied.is_empty() => {}
+                Ok(applied) => {
+                    tracing::info!(fields = ?applied, path = path.as_str(), "config reloaded")
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, path = path.as_str(), "config reload failed, keeping current config")
+                }
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_only_takes_reloadable_fields() {
+        let live = LiveConfig::new(Config::from_env());
+        let mut next = live.get();
+        next.polygonio_tickers = vec!["AM.NVDA".to_string()];
+        next.newsapi_poll_interval_secs = 60;
+        next.api_port = 4000;
+
+        let mut applied = live.apply(&next);
+        applied.sort();
+        assert_eq!(applied, ["newsapi_poll_interval_secs", "polygonio_tickers"]);
+
+        let current = live.get();
+        assert_eq!(current.polygonio_tickers, ["AM.NVDA"]);
+        assert_eq!(current.newsapi_poll_interval_secs, 60);
+        assert_eq!(current.api_port, Config::from_env().api_port);
+    }
+}
@@ -0,0 +1,583 @@
+/// `config.toml` sections
+pub mod file;
+/// hot reload of the settings that can change without a restart
+pub mod live;
+
+use crate::adapter::AdapterError;
+use crate::error::AnySignalResult;
+use crate::strategy::builtin::{self, STRATEGIES};
+use file::FileConfig;
+use std::env;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Runners `RUNNERS` may name.
+pub const RUNNERS: &[&str] = &[
+    "api",
+    "coinmarketcap",
+    "microstrategy",
+    "newsapi",
+    "polygonio",
+    "realtime",
+    "retention",
+    "strategy",
+    "youtube",
+];
+
+/// Fields [`live::LiveConfig`] applies when `config.toml` changes; every
+/// other change is logged and waits for a restart.
+pub const RELOADABLE: &[&str] = &[
+    "coinmarketcap_poll_interval_secs",
+    "newsapi_poll_interval_secs",
+    "newsapi_keywords",
+    "polygonio_tickers",
+    "microstrategy_poll_interval_secs",
+];
+
+/// Runtime configuration, layered: env vars override `config.toml`, which
+/// overrides the defaults. Each field documents its env var and default;
+/// `config.example.toml` lists the matching file keys.
+///
+/// API keys are read from `API_KEY_<ID>` (uppercase), e.g. `API_KEY_NEWSAPI`.
+/// Active runners are read from `RUNNERS` as a comma-separated list,
+/// e.g. `RUNNERS=api,coinmarketcap,newsapi`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Config {
+    runners: Vec<String>,
+    pub questdb_addr: String,
+    /// `host:port` of QuestDB's PostgreSQL wire endpoint, used for large
+    /// streamed reads.
+    /// Set via `QUESTDB_PG_ADDR` (default: `questdb_addr` host on port 8812).
+    pub questdb_pg_addr: String,
+    pub questdb_user: Option<String>,
+    pub questdb_password: Option<String>,
+    /// Directory ILP buffers are spooled to when QuestDB rejects them;
+    /// empty disables the spool.
+    /// Set via `QUESTDB_SPOOL_DIR` (default: `/data/spool`).
+    pub questdb_spool_dir: String,
+    /// Cap on the spool size in MiB; past it, failed writes error as before.
+    /// Set via `QUESTDB_SPOOL_MAX_MB` (default: 1024).
+    pub questdb_spool_max_mb: u64,
+    /// ILP senders shared by concurrent writers; keep it at or above the
+    /// backfill partition concurrency so each partition flushes on its own.
+    /// Set via `QUESTDB_SENDER_POOL_SIZE` (default: 8, `BACKFILL_CONCURRENCY`).
+    pub questdb_sender_pool_size: usize,
+    pub api_base_url: String,
+    /// Path to the postmortem log file. Written on panic; survives restarts.
+    /// Set via `POSTMORTEM_LOG` (default: `/data/postmortem.log`).
+    pub postmortem_log_path: String,
+    /// When `true`, all QuestDB table names are suffixed with `_dev` to
+    /// prevent test data from polluting production tables.
+    /// Set via `DEV_MODE=true`.
+    pub dev: bool,
+    /// How long the realtime engine keeps a minute bucket open for late
+    /// trades after the minute ends.
+    /// Set via `REALTIME_ALLOWED_LATENESS_SECS` (default: 5).
+    pub realtime_allowed_lateness_secs: u64,
+    /// Venues the realtime runner opens a bridge for, using
+    /// `guilder-abstraction` venue names.
+    /// Set via `REALTIME_VENUES` as a comma-separated list (default: `HlPerp`).
+    pub realtime_venues: Vec<String>,
+    /// Storage backend backfills and the realtime scheduler write to:
+    /// `questdb`, `parquet` or `postgres`.
+    /// Set via `SINK` (default: `questdb`).
+    pub sink: String,
+    /// Root directory of the `parquet` sink.
+    /// Set via `SINK_PARQUET_DIR` (default: `/data/parquet`).
+    pub sink_parquet_dir: String,
+    /// Connection string of the `postgres` sink (PostgreSQL or TimescaleDB).
+    /// Set via `SINK_POSTGRES_URL`. Not logged: it usually embeds a password.
+    #[serde(skip_serializing)]
+    pub sink_postgres_url: Option<String>,
+    /// JSON file holding the retention policies edited via `PUT /retention`.
+    /// Set via `RETENTION_POLICY_PATH` (default: `/data/retention.json`).
+    pub retention_policy_path: String,
+    /// How often the `retention` runner applies the policies.
+    /// Set via `RETENTION_INTERVAL_SECS` (default: 3600).
+    pub retention_interval_secs: u64,
+    /// TOML file layered under the env vars; watched for hot reload.
+    /// Set via `CONFIG_PATH` (default: `config.toml`).
+    pub config_path: String,
+    /// Port the `api` runner listens on.
+    /// Set via `API_PORT` (default: 3000).
+    pub api_port: u16,
+    /// Partitions processed concurrently by one backfill job, unless the
+    /// source sets a lower limit.
+    /// Set via `BACKFILL_CONCURRENCY` (default: 8).
+    pub backfill_concurrency: usize,
+    /// Fear and Greed and Bitcoin dominance poll interval. Reloadable.
+    /// Set via `COINMARKETCAP_POLL_INTERVAL_SECS` (default: 10).
+    pub coinmarketcap_poll_interval_secs: u64,
+    /// NewsAPI poll interval. Reloadable.
+    /// Set via `NEWSAPI_POLL_INTERVAL_SECS` (default: 10).
+    pub newsapi_poll_interval_secs: u64,
+    /// Keywords NewsAPI searches for, joined with `OR`. Reloadable.
+    /// Set via `NEWSAPI_KEYWORDS` as a comma-separated list (default: `bitcoin`).
+    pub newsapi_keywords: Vec<String>,
+    /// Polygon channels the `polygonio` runner subscribes to. Reloadable.
+    /// Set via `POLYGONIO_TICKERS` as a comma-separated list
+    /// (default: `AM.LPL,AM.MSFT,AM.TSLA`).
+    pub polygonio_tickers: Vec<String>,
+    /// MicroStrategy purchase history poll interval. Reloadable.
+    /// Set via `MICROSTRATEGY_POLL_INTERVAL_SECS` (default: 3600).
+    pub microstrategy_poll_interval_secs: u64,
+    /// Videos the `youtube` runner polls captions of, one signal each.
+    /// Set via `YOUTUBE_VIDEO_URLS` as a comma-separated list (default: none).
+    pub youtube_video_urls: Vec<String>,
+    /// YouTube caption poll interval.
+    /// Set via `YOUTUBE_POLL_INTERVAL_SECS` (default: 60).
+    pub youtube_poll_interval_secs: u64,
+    /// Built-in strategies the `strategy` runner paper-trades.
+    /// Set via `STRATEGIES` as a comma-separated list (default: none).
+    pub strategies: Vec<String>,
+    /// How often the `strategy` runner reads new rows of subscribed signals.
+    /// Set via `STRATEGY_SIGNAL_POLL_INTERVAL_SECS` (default: 60).
+    pub strategy_signal_poll_interval_secs: u64,
+}
+
+/// Resolves one setting at a time: env var, then file value, then default.
+/// Unparseable env values are collected rather than silently replaced.
+#[derive(Default)]
+struct Layers {
+    errors: Vec<String>,
+}
+
+impl Layers {
+    fn env<T>(&mut self, key: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let raw = env::var(key).ok().filter(|v| !v.trim().is_empty())?;
+        match raw.trim().parse() {
+            Ok(v) => Some(v),
+            Err(e) => {
+                self.errors.push(format!("{key}={raw:?}: {e}"));
+                None
+            }
+        }
+    }
+
+    fn get<T>(&mut self, key: &str, file: Option<T>, default: T) -> T
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.env(key).or(file).unwrap_or(default)
+    }
+
+    /// Comma-separated in env, an array in the file.
+    fn list(&mut self, key: &str, file: Option<Vec<String>>, default: &[&str]) -> Vec<String> {
+        let env = self.env::<String>(key).map(|v| {
+            v.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        });
+        env.or(file)
+            .unwrap_or_else(|| default.iter().map(|s| s.to_string()).collect())
+    }
+}
+
+impl Config {
+    /// Env vars and defaults only, ignoring `config.toml` and invalid values.
+    /// Used by tests; the service starts with [`Self::load`].
+    pub fn from_env() -> Self {
+        Self::build(FileConfig::default(), String::new()).0
+    }
+
+    /// Load the layered configuration from `CONFIG_PATH` (default
+    /// `config.toml`; a missing file is skipped) and validate it.
+    pub fn load() -> AnySignalResult<Self> {
+        let path = env::var("CONFIG_PATH")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "config.toml".to_string());
+        Self::load_from(&path)
+    }
+
+    /// [`Self::load`] with an explicit file path. Every problem found is
+    /// reported at once, one per line.
+    pub fn load_from(path: &str) -> AnySignalResult<Self> {
+        let file = match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str::<FileConfig>(&text).map_err(|e| {
+                AdapterError::ConfigurationError(format!("invalid config file {path}: {e}"))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => FileConfig::default(),
+            Err(e) => {
+                return Err(AdapterError::ConfigurationError(format!(
+                    "cannot read config file {path}: {e}"
+                ))
+                .into())
+            }
+        };
+        let (config, mut errors) = Self::build(file, path.to_string());
+        errors.extend(config.validate());
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(AdapterError::ConfigurationError(format!(
+                "invalid configuration:\n  - {}",
+                errors.join("\n  - ")
+            ))
+            .into())
+        }
+    }
+
+    fn build(file: FileConfig, config_path: String) -> (Self, Vec<String>) {
+        let mut l = Layers::default();
+        let runners = l
+            .list("RUNNERS", file.runners, &[])
+            .into_iter()
+            .map(|s| s.to_lowercase())
+            .collect();
+
+        let questdb_addr = l.get(
+            "QUESTDB_ADDR",
+            file.questdb.addr,
+            "localhost:9000".to_string(),
+        );
+        let questdb_pg_addr = l
+            .env("QUESTDB_PG_ADDR")
+            .or(file.questdb.pg_addr)
+            .unwrap_or_else(|| {
+                let host = questdb_addr
+                    .rsplit_once(':')
+                    .map_or(questdb_addr.as_str(), |(h, _)| h);
+                format!("{host}:8812")
+            });
+        let questdb_user = l.env("QUESTDB_USER").or(file.questdb.user);
+        let questdb_password = l.env("QUESTDB_PASSWORD").or(file.questdb.password);
+        // an explicitly empty QUESTDB_SPOOL_DIR disables the spool
+        let questdb_spool_dir = env::var("QUESTDB_SPOOL_DIR")
+            .ok()
+            .or(file.questdb.spool_dir)
+            .unwrap_or_else(|| "/data/spool".to_string());
+        let questdb_spool_max_mb = l.get("QUESTDB_SPOOL_MAX_MB", file.questdb.spool_max_mb, 1024);
+        let backfill_concurrency = l.get("BACKFILL_CONCURRENCY", file.backfill.concurrency, 8);
+        let questdb_sender_pool_size = l.get(
+            "QUESTDB_SENDER_POOL_SIZE",
+            file.questdb.sender_pool_size,
+            backfill_concurrency,
+        );
+        let api_port = l.get("API_PORT", file.api.port, 3000);
+        let api_base_url = l.get(
+            "API_BASE_URL",
+            file.api.base_url,
+            "http://localhost:3000".to_string(),
+        );
+        let postmortem_log_path = l.get(
+            "POSTMORTEM_LOG",
+            file.postmortem_log,
+            "/data/postmortem.log".to_string(),
+        );
+        let dev = match l.env::<String>("DEV_MODE") {
+            Some(v) => v == "true" || v == "1",
+            None => file.dev.unwrap_or(false),
+        };
+        let realtime_allowed_lateness_secs = l.get(
+            "REALTIME_ALLOWED_LATENESS_SECS",
+            file.realtime.allowed_lateness_secs,
+            5,
+        );
+        let realtime_venues = l.list("REALTIME_VENUES", file.realtime.venues, &["HlPerp"]);
+        let sink = l
+            .get("SINK", file.sink.kind, "questdb".to_string())
+            .trim()
+            .to_lowercase();
+        let sink_parquet_dir = l.get(
+            "SINK_PARQUET_DIR",
+            file.sink.parquet_dir,
+            "/data/parquet".to_string(),
+        );
+        let sink_postgres_url = l.env("SINK_POSTGRES_URL").or(file.sink.postgres_url);
+        let retention_policy_path = l.get(
+            "RETENTION_POLICY_PATH",
+            file.retention.policy_path,
+            "/data/retention.json".to_string(),
+        );
+        let retention_interval_secs = l.get(
+            "RETENTION_INTERVAL_SECS",
+            file.retention.interval_secs,
+            3600,
+        );
+        let coinmarketcap_poll_interval_secs = l.get(
+            "COINMARKETCAP_POLL_INTERVAL_SECS",
+            file.coinmarketcap.poll_interval_secs,
+            10,
+        );
+        let newsapi_poll_interval_secs = l.get(
+            "NEWSAPI_POLL_INTERVAL_SECS",
+            file.newsapi.poll_interval_secs,
+            10,
+        );
+        let newsapi_keywords = l.list("NEWSAPI_KEYWORDS", file.newsapi.keywords, &["bitcoin"]);
+        let polygonio_tickers = l.list(
+            "POLYGONIO_TICKERS",
+            file.polygonio.tickers,
+            &["AM.LPL", "AM.MSFT", "AM.TSLA"],
+        );
+        let microstrategy_poll_interval_secs = l.get(
+            "MICROSTRATEGY_POLL_INTERVAL_SECS",
+            file.microstrategy.poll_interval_secs,
+            3600,
+        );
+        let youtube_video_urls = l.list("YOUTUBE_VIDEO_URLS", file.youtube.video_urls, &[]);
+        let youtube_poll_interval_secs = l.get(
+            "YOUTUBE_POLL_INTERVAL_SECS",
+            file.youtube.poll_interval_secs,
+            60,
+        );
+        let strategies = l.list("STRATEGIES", file.strategy.strategies, &[]);
+        let strategy_signal_poll_interval_secs = l.get(
+            "STRATEGY_SIGNAL_POLL_INTERVAL_SECS",
+            file.strategy.signal_poll_interval_secs,
+            60,
+        );
+        let config = Self {
+            runners,
+            questdb_addr,
+            questdb_pg_addr,
+            questdb_user,
+            questdb_password,
+            questdb_spool_dir,
+            questdb_spool_max_mb,
+            questdb_sender_pool_size,
+            api_base_url,
+            postmortem_log_path,
+            dev,
+            realtime_allowed_lateness_secs,
+            realtime_venues,
+            sink,
+            sink_parquet_dir,
+            sink_postgres_url,
+            retention_policy_path,
+            retention_interval_secs,
+            config_path,
+            api_port,
+            backfill_concurrency,
+            coinmarketcap_poll_interval_secs,
+            newsapi_poll_interval_secs,
+            newsapi_keywords,
+            polygonio_tickers,
+            microstrategy_poll_interval_secs,
+            youtube_video_urls,
+            youtube_poll_interval_secs,
+            strategies,
+            strategy_signal_poll_interval_secs,
+        };
+        (config, l.errors)
+    }
+
+    /// Cross-field checks, one message per problem.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for runner in &self.runners {
+            if !RUNNERS.contains(&runner.as_str()) {
+                errors.push(format!(
+                    "unknown runner {runner:?} (available: {})",
+                    RUNNERS.join(", ")
+                ));
+            }
+        }
+        match self.sink.as_str() {
+            "questdb" | "parquet" => {}
+            "postgres" if self.sink_postgres_url.is_none() => {
+                errors.push("sink \"postgres\" needs SINK_POSTGRES_URL".to_string())
+            }
+            "postgres" => {}
+            other => errors.push(format!(
+                "unknown sink {other:?} (available: questdb, parquet, postgres)"
+            )),
+        }
+        let positive = [
+            ("API_PORT", self.api_port as u64),
+            ("BACKFILL_CONCURRENCY", self.backfill_concurrency as u64),
+            (
+                "QUESTDB_SENDER_POOL_SIZE",
+                self.questdb_sender_pool_size as u64,
+            ),
+            ("RETENTION_INTERVAL_SECS", self.retention_interval_secs),
+            (
+                "COINMARKETCAP_POLL_INTERVAL_SECS",
+                self.coinmarketcap_poll_interval_secs,
+            ),
+            (
+                "NEWSAPI_POLL_INTERVAL_SECS",
+                self.newsapi_poll_interval_secs,
+            ),
+            (
+                "MICROSTRATEGY_POLL_INTERVAL_SECS",
+                self.microstrategy_poll_interval_secs,
+            ),
+            (
+                "YOUTUBE_POLL_INTERVAL_SECS",
+                self.youtube_poll_interval_secs,
+            ),
+            (
+                "STRATEGY_SIGNAL_POLL_INTERVAL_SECS",
+                self.strategy_signal_poll_interval_secs,
+            ),
+        ];
+        for (key, value) in positive {
+            if value == 0 {
+                errors.push(format!("{key} must be greater than 0"));
+            }
+        }
+        if self.newsapi_keywords.is_empty() {
+            errors.push("NEWSAPI_KEYWORDS must name at least one keyword".to_string());
+        }
+        if self.polygonio_tickers.is_empty() {
+            errors.push("POLYGONIO_TICKERS must name at least one ticker".to_string());
+        }
+        if let Some(t) = self
+            .polygonio_tickers
+            .iter()
+            .find(|t| t.contains(|c: char| c.is_whitespace() || c == ','))
+        {
+            errors.push(format!("invalid Polygon ticker {t:?}"));
+        }
+        if self.has_runner("youtube") && self.youtube_video_urls.is_empty() {
+            errors.push("runner \"youtube\" needs YOUTUBE_VIDEO_URLS".to_string());
+        }
+        for name in &self.strategies {
+            match builtin::build(name) {
+                None => errors.push(format!(
+                    "unknown strategy {name:?} (available: {})",
+                    STRATEGIES.join(", ")
+                )),
+                Some(s) if !s.feeds().live() => errors.push(format!(
+                    "strategy {name:?} needs fill or book events, which only backtests replay"
+                )),
+                Some(_) => {}
+            }
+        }
+        if self.has_runner("strategy") {
+            if self.strategies.is_empty() {
+                errors.push("runner \"strategy\" needs STRATEGIES".to_string());
+            }
+            // paper trading follows the engine of the same process
+            if !self.has_runner("realtime") {
+                errors.push("runner \"strategy\" needs runner \"realtime\"".to_string());
+            }
+        }
+        errors
+    }
+
+    /// Names of the fields that differ from `other`.
+    pub fn changed_fields(&self, other: &Config) -> Vec<String> {
+        let (Ok(serde_json::Value::Object(a)), Ok(serde_json::Value::Object(b))) =
+            (serde_json::to_value(self), serde_json::to_value(other))
+        else {
+            return Vec::new();
+        };
+        let mut changed: Vec<String> = a
+            .iter()
+            .filter(|(k, v)| b.get(*k) != Some(*v))
+            .map(|(k, _)| k.clone())
+            .collect();
+        if self.sink_postgres_url != other.sink_postgres_url {
+            changed.push("sink_postgres_url".to_string());
+        }
+        changed
+    }
+
+    /// Copy the [`RELOADABLE`] fields from `other`.
+    pub fn apply_reloadable(&mut self, other: &Config) {
+        self.coinmarketcap_poll_interval_secs = other.coinmarketcap_poll_interval_secs;
+        self.newsapi_poll_interval_secs = other.newsapi_poll_interval_secs;
+        self.newsapi_keywords = other.newsapi_keywords.clone();
+        self.polygonio_tickers = other.polygonio_tickers.clone();
+        self.microstrategy_poll_interval_secs = other.microstrategy_poll_interval_secs;
+    }
+
+    /// Returns the QuestDB table name for the given base name.
+    ///
+    /// In dev mode (`DEV_MODE=true`) the suffix `_dev` is appended so that
+    /// test data never touches production tables.
+    pub fn table_name(&self, base: &str) -> String {
+        if self.dev {
+            format!("{}_dev", base)
+        } else {
+            base.to_string()
+        }
+    }
+
+    pub fn has_runner(&self, name: &str) -> bool {
+        self.runners.iter().any(|r| r == name)
+    }
+
+    /// Returns the API key for the given service id.
+    /// Reads `API_KEY_<ID_UPPERCASE>` from the environment.
+    pub fn get_api_key(&self, id: &str) -> AnySignalResult<String> {
+        let var_name = format!("API_KEY_{}", id.to_uppercase());
+        env::var(&var_name).map_err(|_| {
+            AdapterError::ConfigurationError(format!("missing env var: {var_name}")).into()
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(name: &str, text: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("anysignal-{name}-{}.toml", std::process::id()));
+        std::fs::write(&path, text).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn file_values_fill_in_under_defaults() {
+        let path = write_config(
+            "layers",
+            r#"
+            [newsapi]
+            keywords = ["ethereum", "solana"]
+            poll_interval_secs = 30
+
+            [polygonio]
+            tickers = ["AM.NVDA"]
+            "#,
+        );
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(config.newsapi_keywords, ["ethereum", "solana"]);
+        assert_eq!(config.newsapi_poll_interval_secs, 30);
+        assert_eq!(config.polygonio_tickers, ["AM.NVDA"]);
+        // untouched sections keep their defaults
+        assert_eq!(config.coinmarketcap_poll_interval_secs, 10);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unknown_keys_and_bad_values_are_reported() {
+        let path = write_config("typo", "[newsapi]\nkeyword = [\"bitcoin\"]\n");
+        let err = Config::load_from(&path).unwrap_err().to_string();
+        assert!(err.contains("unknown field `keyword`"), "{err}");
+
+        let path2 = write_config(
+            "invalid",
+            "runners = [\"api\", \"nope\", \"youtube\", \"strategy\"]\n[newsapi]\nkeywords = []\n\
+             [strategy]\nstrategies = [\"hodl\"]\n",
+        );
+        let err = Config::load_from(&path2).unwrap_err().to_string();
+        assert!(err.contains("unknown runner \"nope\""), "{err}");
+        assert!(err.contains("NEWSAPI_KEYWORDS"), "{err}");
+        assert!(err.contains("YOUTUBE_VIDEO_URLS"), "{err}");
+        assert!(err.contains("unknown strategy \"hodl\""), "{err}");
+        assert!(err.contains("needs runner \"realtime\""), "{err}");
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(path2).unwrap();
+    }
+
+    #[test]
+    fn missing_file_is_skipped() {
+        let config = Config::load_from("/nonexistent/anysignal.toml").unwrap();
+        assert_eq!(config.config_path, "/nonexistent/anysignal.toml");
+    }
+}
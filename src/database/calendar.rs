@@ -0,0 +1,571 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Deserialize;
+
+use super::registry::SignalRegistry;
+use super::signal_data::signal_query;
+use super::{insert_keyed_signal_rows, select_signal_db, QuestDbClient};
+use crate::error::AnySignalResult;
+use crate::model::signal::{FieldValue, Signal, SignalData, SignalDataType, SignalInfo};
+
+/// `signal_type` of the calendar signal.
+pub const SIGNAL_TYPE: &str = "calendar_event";
+/// `source` of the calendar signal.
+pub const SOURCE: &str = "Manual";
+
+const ACTIVE: &str = "active";
+const DELETED: &str = "deleted";
+
+/// What a user enters for an event, normalised by [`EventSpec::new`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventSpec {
+    pub time: DateTime<Utc>,
+    /// Uppercase, e.g. `NVDA`; `None` for market-wide events.
+    pub ticker: Option<String>,
+    /// Lowercase snake case, e.g. `conference` or `product_launch`.
+    pub event_type: String,
+    pub title: String,
+    /// Trimmed, sorted and deduplicated.
+    pub tags: Vec<String>,
+}
+
+impl EventSpec {
+    pub fn new(
+        time: DateTime<Utc>,
+        ticker: Option<&str>,
+        event_type: &str,
+        title: &str,
+        tags: &[String],
+    ) -> Result<Self, String> {
+        let ticker = ticker
+            .map(|t| t.trim().to_uppercase())
+            .filter(|t| !t.is_empty());
+        if let Some(t) = ticker
+            .as_deref()
+            .filter(|t| t.contains(|c: char| c.is_whitespace() || c == ','))
+        {
+            return Err(format!("invalid ticker {t:?}"));
+        }
+        let event_type = event_type.trim().to_lowercase().replace([' ', '-'], "_");
+        if event_type.is_empty()
+            || !event_type
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(format!(
+                "invalid event type {event_type:?}: use letters, digits and '_'"
+            ));
+        }
+        let title = title.trim().to_string();
+        if title.is_empty() {
+            return Err("an event needs a title".to_string());
+        }
+        let mut tags: Vec<String> = tags
+            .iter()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect();
+        if let Some(t) = tags.iter().find(|t| t.contains(',')) {
+            return Err(format!("invalid tag {t:?}: tags cannot contain ','"));
+        }
+        tags.sort();
+        tags.dedup();
+        Ok(Self {
+            time,
+            ticker,
+            event_type,
+            title,
+            tags,
+        })
+    }
+
+    fn signal(&self, info_id: i64, event_id: i64, status: &str) -> Signal {
+        let text = |s: &str| FieldValue::Text(s.to_string());
+        // every field is always written, so a rewrite at the same timestamp
+        // replaces all of them
+        let fields = [
+            ("event_id", FieldValue::Number(event_id as f64)),
+            ("event_type", text(&self.event_type)),
+            ("ticker", text(self.ticker.as_deref().unwrap_or_default())),
+            ("title", text(&self.title)),
+            ("tags", text(&self.tags.join(","))),
+            ("status", text(status)),
+        ];
+        Signal {
+            info_id,
+            timestamp_us: self.time.timestamp_micros(),
+            data: SignalData::Structured(fields.map(|(k, v)| (k.to_string(), v)).into()),
+        }
+    }
+
+    /// The spec an active event record was written from.
+    fn from_signal(signal: &Signal) -> Option<Self> {
+        let SignalData::Structured(fields) = &signal.data else {
+            return None;
+        };
+        let text = |name: &str| match fields.get(name) {
+            Some(FieldValue::Text(t)) => Some(t.as_str()),
+            _ => None,
+        };
+        if text("status") != Some(ACTIVE) {
+            return None;
+        }
+        Some(Self {
+            time: DateTime::from_timestamp_micros(signal.timestamp_us)?,
+            ticker: text("ticker").filter(|t| !t.is_empty()).map(str::to_string),
+            event_type: text("event_type")?.to_string(),
+            title: text("title")?.to_string(),
+            tags: text("tags")?
+                .split(',')
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect(),
+        })
+    }
+}
+
+/// A calendar event. Events are records of one [`SignalData::Structured`]
+/// signal, [`signal_info`], keyed by their `event_id` field, so they can be
+/// joined against price data in `signal_structured`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub id: i64,
+    /// Id of the calendar signal in the registry.
+    pub signal_id: i64,
+    pub spec: EventSpec,
+}
+
+/// The signal every event is written under.
+pub fn signal_info() -> SignalInfo {
+    SignalInfo {
+        id: SignalInfo::UNREGISTERED,
+        signal_type: SIGNAL_TYPE.to_string(),
+        data_type: SignalDataType::Structured,
+        source: SOURCE.to_string(),
+        description: "Calendar events".to_string(),
+        is_atomic: true,
+        parameters: Default::default(),
+    }
+}
+
+/// The `event_id` field of an event record, active or not.
+fn event_id(signal: &Signal) -> Option<i64> {
+    match &signal.data {
+        SignalData::Structured(fields) => match fields.get("event_id") {
+            Some(FieldValue::Number(id)) => Some(*id as i64),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether `signal` is the tombstone a moved or deleted event leaves behind.
+pub fn is_tombstone(signal: &Signal) -> bool {
+    match &signal.data {
+        SignalData::Structured(fields) => {
+            fields.get("status") == Some(&FieldValue::Text(DELETED.to_string()))
+        }
+        _ => false,
+    }
+}
+
+/// Filters of [`EventCalendar::list`]; unset ones match everything.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// Inclusive.
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive.
+    pub to: Option<DateTime<Utc>>,
+    pub ticker: Option<String>,
+    pub event_type: Option<String>,
+    pub tag: Option<String>,
+}
+
+impl EventFilter {
+    fn matches(&self, spec: &EventSpec) -> bool {
+        self.from.is_none_or(|from| spec.time >= from)
+            && self.to.is_none_or(|to| spec.time < to)
+            && self
+                .ticker
+                .as_ref()
+                .is_none_or(|t| spec.ticker.as_deref() == Some(t.to_uppercase().as_str()))
+            && self
+                .event_type
+                .as_ref()
+                .is_none_or(|t| spec.event_type == t.to_lowercase())
+            && self.tag.as_ref().is_none_or(|t| spec.tags.contains(t))
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    events: BTreeMap<i64, Event>,
+    /// Highest event id ever assigned, deleted events included.
+    last_id: i64,
+    loaded: bool,
+}
+
+/// Manually curated events, stored as records of the calendar signal in
+/// `signal_structured`, keyed by event id.
+///
+/// Rows are rewritten in place while an event keeps its time. Moving or
+/// deleting one rewrites its old rows with `status = deleted`, since QuestDB
+/// cannot delete rows; queries joining events should filter on
+/// `status = 'active'`. Loaded from QuestDB on first use and cached.
+/// Cheaply cloneable — shared by the API handlers.
+#[derive(Clone)]
+pub struct EventCalendar {
+    inner: Arc<Mutex<Inner>>,
+    signals: SignalRegistry,
+    /// Serialises changes, which span several awaits.
+    writes: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl EventCalendar {
+    pub fn new(signals: SignalRegistry) -> Self {
+        Self {
+            inner: Arc::default(),
+            signals,
+            writes: Arc::default(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Read the latest active rows of every event, unless already done.
+    async fn ensure_loaded(&self, db: &QuestDbClient) -> AnySignalResult<()> {
+        if self.lock().loaded {
+            return Ok(());
+        }
+        self.signals.ensure_loaded(db).await?;
+        let rows = match self.signals.find(&signal_info()) {
+            Some(info) => {
+                let query = signal_query(db, &info);
+                select_signal_db(db, SignalDataType::Structured, &query).await?
+            }
+            None => Vec::new(),
+        };
+
+        let mut inner = self.lock();
+        if inner.loaded {
+            return Ok(());
+        }
+        inner.last_id = rows.iter().filter_map(event_id).max().unwrap_or(0);
+        for signal in &rows {
+            // an event's deleted rows never share its current timestamp: both
+            // would be the same dedup key
+            let (Some(id), Some(spec)) = (event_id(signal), EventSpec::from_signal(signal)) else {
+                continue;
+            };
+            let event = Event {
+                id,
+                signal_id: signal.info_id,
+                spec,
+            };
+            inner.events.insert(id, event);
+        }
+        inner.loaded = true;
+        tracing::info!(events = inner.events.len(), "event calendar loaded");
+        Ok(())
+    }
+
+    /// Events matching `filter`, by time then id.
+    pub async fn list(
+        &self,
+        db: &QuestDbClient,
+        filter: &EventFilter,
+    ) -> AnySignalResult<Vec<Event>> {
+        self.ensure_loaded(db).await?;
+        let mut events: Vec<Event> = self
+            .lock()
+            .events
+            .values()
+            .filter(|e| filter.matches(&e.spec))
+            .cloned()
+            .collect();
+        events.sort_by_key(|e| (e.spec.time, e.id));
+        Ok(events)
+    }
+
+    pub async fn get(&self, db: &QuestDbClient, id: i64) -> AnySignalResult<Option<Event>> {
+        self.ensure_loaded(db).await?;
+        Ok(self.lock().events.get(&id).cloned())
+    }
+
+    /// Add `specs` as new events, written in one batch. Returns them with
+    /// their ids, in the given order.
+    pub async fn create(
+        &self,
+        db: &QuestDbClient,
+        specs: Vec<EventSpec>,
+    ) -> AnySignalResult<Vec<Event>> {
+        self.ensure_loaded(db).await?;
+        let _writes = self.writes.lock().await;
+        let info = self.signals.register(db, &signal_info()).await?;
+        let events: Vec<Event> = {
+            let mut inner = self.lock();
+            let first_id = inner.last_id + 1;
+            // ids are not reused even if the write fails: part of it may
+            // have landed
+            inner.last_id += specs.len() as i64;
+            (first_id..)
+                .zip(specs)
+                .map(|(id, spec)| Event {
+                    id,
+                    signal_id: info.id,
+                    spec,
+                })
+                .collect()
+        };
+        let rows: Vec<(i64, Signal)> = events
+            .iter()
+            .map(|e| (e.id, e.spec.signal(e.signal_id, e.id, ACTIVE)))
+            .collect();
+        self.write(db, &rows).await?;
+        let mut inner = self.lock();
+        for event in &events {
+            inner.events.insert(event.id, event.clone());
+        }
+        Ok(events)
+    }
+
+    /// Replace event `id`; `None` if there is no such event.
+    pub async fn update(
+        &self,
+        db: &QuestDbClient,
+        id: i64,
+        spec: EventSpec,
+    ) -> AnySignalResult<Option<Event>> {
+        self.ensure_loaded(db).await?;
+        let _writes = self.writes.lock().await;
+        let Some(old) = self.get(db, id).await? else {
+            return Ok(None);
+        };
+        let mut rows = Vec::new();
+        if old.spec.time != spec.time {
+            rows.push((id, old.spec.signal(old.signal_id, id, DELETED)));
+        }
+        rows.push((id, spec.signal(old.signal_id, id, ACTIVE)));
+        self.write(db, &rows).await?;
+        let event = Event {
+            id,
+            signal_id: old.signal_id,
+            spec,
+        };
+        self.lock().events.insert(id, event.clone());
+        Ok(Some(event))
+    }
+
+    /// Delete event `id`, returning it; `None` if there is no such event.
+    pub async fn delete(&self, db: &QuestDbClient, id: i64) -> AnySignalResult<Option<Event>> {
+        self.ensure_loaded(db).await?;
+        let _writes = self.writes.lock().await;
+        let Some(event) = self.get(db, id).await? else {
+            return Ok(None);
+        };
+        let row = (id, event.spec.signal(event.signal_id, id, DELETED));
+        self.write(db, &[row]).await?;
+        self.lock().events.remove(&id);
+        Ok(Some(event))
+    }
+
+    /// Write event records, each keyed by its event id.
+    async fn write(&self, db: &QuestDbClient, rows: &[(i64, Signal)]) -> AnySignalResult<()> {
+        let table = db.table_name(SignalDataType::Structured.table());
+        db.write_rows(&table, rows, insert_keyed_signal_rows)
+            .await?;
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CSV import
+// ---------------------------------------------------------------------------
+
+/// One row of an event CSV: `time,ticker,event_type,title,tags`, with tags
+/// separated by `;`.
+#[derive(Debug, Deserialize)]
+struct CsvEvent {
+    time: String,
+    #[serde(default)]
+    ticker: Option<String>,
+    event_type: String,
+    title: String,
+    #[serde(default)]
+    tags: Option<String>,
+}
+
+/// `YYYY-MM-DD`, `YYYY-MM-DDTHH:MM:SS` (both UTC) or RFC 3339.
+pub fn parse_event_time(s: &str) -> Result<DateTime<Utc>, String> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
+        return Ok(dt.and_utc());
+    }
+    if let Some(dt) = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+    {
+        return Ok(dt.and_utc());
+    }
+    Err(format!(
+        "invalid time {s:?}: expected YYYY-MM-DD, YYYY-MM-DDTHH:MM:SS or RFC 3339"
+    ))
+}
+
+/// Parse an event CSV with a header row. Every invalid row is reported at
+/// once, one per line, by its line number.
+pub fn parse_csv(text: &str) -> Result<Vec<EventSpec>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let mut specs = Vec::new();
+    let mut errors = Vec::new();
+    for (i, record) in reader.deserialize::<CsvEvent>().enumerate() {
+        // line 1 is the header
+        let line = i + 2;
+        let spec = record.map_err(|e| e.to_string()).and_then(|r| {
+            let tags: Vec<String> = r
+                .tags
+                .as_deref()
+                .unwrap_or_default()
+                .split(';')
+                .map(str::to_string)
+                .collect();
+            EventSpec::new(
+                parse_event_time(&r.time)?,
+                r.ticker.as_deref(),
+                &r.event_type,
+                &r.title,
+                &tags,
+            )
+        });
+        match spec {
+            Ok(spec) => specs.push(spec),
+            Err(e) => errors.push(format!("line {line}: {e}")),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors.join("\n"));
+    }
+    if specs.is_empty() {
+        return Err("no events in CSV".to_string());
+    }
+    Ok(specs)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs_are_normalised_and_round_trip_through_signals() {
+        let time = parse_event_time("2025-02-19").unwrap();
+        let tags = [" quantum ".to_string(), "".to_string(), "azure".to_string()];
+        let spec = EventSpec::new(
+            time,
+            Some(" msft "),
+            "Product Launch",
+            " Majorana 1 ",
+            &tags,
+        )
+        .unwrap();
+        assert_eq!(spec.ticker.as_deref(), Some("MSFT"));
+        assert_eq!(spec.event_type, "product_launch");
+        assert_eq!(spec.title, "Majorana 1");
+        assert_eq!(spec.tags, ["azure", "quantum"]);
+
+        let signal = spec.signal(9, 3, ACTIVE);
+        assert_eq!(signal.timestamp_us, time.timestamp_micros());
+        assert_eq!(EventSpec::from_signal(&signal), Some(spec.clone()));
+        assert_eq!(event_id(&signal), Some(3));
+        let deleted = spec.signal(9, 3, DELETED);
+        assert_eq!(EventSpec::from_signal(&deleted), None);
+        assert_eq!(event_id(&deleted), Some(3));
+        assert!(is_tombstone(&deleted));
+        assert!(!is_tombstone(&signal));
+
+        let no_ticker = EventSpec::new(time, Some(""), "fomc", "FOMC", &[]).unwrap();
+        assert_eq!(
+            EventSpec::from_signal(&no_ticker.signal(9, 1, ACTIVE)),
+            Some(no_ticker)
+        );
+
+        assert!(EventSpec::new(time, None, "", "x", &[]).is_err());
+        assert!(EventSpec::new(time, None, "a/b", "x", &[]).is_err());
+        assert!(EventSpec::new(time, None, "gtc", " ", &[]).is_err());
+        assert!(EventSpec::new(time, Some("A B"), "gtc", "x", &[]).is_err());
+    }
+
+    #[test]
+    fn parses_csv_and_reports_every_bad_row() {
+        let csv = "time,ticker,event_type,title,tags\n\
+                   2024-06-10,AAPL,conference,WWDC,ai;software\n\
+                   2024-09-25T17:00:00Z,META,conference,\"Meta Connect, 2024\",\n\
+                   2025-01-23T18:00:00,,product_launch,Xbox Developer Direct,\n";
+        let specs = parse_csv(csv).unwrap();
+        assert_eq!(specs.len(), 3);
+        assert_eq!(specs[0].tags, ["ai", "software"]);
+        assert_eq!(specs[1].title, "Meta Connect, 2024");
+        assert_eq!(specs[1].time.to_rfc3339(), "2024-09-25T17:00:00+00:00");
+        assert_eq!(specs[2].ticker, None);
+
+        let bad = "time,ticker,event_type,title,tags\n\
+                   10-JUN,AAPL,conference,WWDC,\n\
+                   2024-06-10,AAPL,conference,WWDC,\n\
+                   2024-06-10,AAPL,,WWDC,\n";
+        let err = parse_csv(bad).unwrap_err();
+        assert!(err.contains("line 2: invalid time"), "{err}");
+        assert!(err.contains("line 4: invalid event type"), "{err}");
+        assert!(!err.contains("line 3"), "{err}");
+
+        assert!(parse_csv("time,ticker,event_type,title,tags\n").is_err());
+
+        let conferences =
+            parse_csv(include_str!("../../strategy/1_conference_events.csv")).unwrap();
+        assert_eq!(conferences.len(), 22);
+        assert_eq!(conferences[4].title, "Apple Event \"Let Loose\"");
+    }
+
+    #[test]
+    fn filters_match_ticker_type_tag_and_range() {
+        let spec = EventSpec::new(
+            parse_event_time("2024-06-10").unwrap(),
+            Some("AAPL"),
+            "conference",
+            "WWDC",
+            &["ai".to_string()],
+        )
+        .unwrap();
+        let filter = |f: EventFilter| f.matches(&spec);
+        assert!(filter(EventFilter::default()));
+        assert!(filter(EventFilter {
+            ticker: Some("aapl".to_string()),
+            event_type: Some("Conference".to_string()),
+            tag: Some("ai".to_string()),
+            ..Default::default()
+        }));
+        assert!(!filter(EventFilter {
+            ticker: Some("MSFT".to_string()),
+            ..Default::default()
+        }));
+        assert!(!filter(EventFilter {
+            to: Some(spec.time),
+            ..Default::default()
+        }));
+        assert!(filter(EventFilter {
+            from: Some(spec.time),
+            ..Default::default()
+        }));
+    }
+}
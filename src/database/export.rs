@@ -0,0 +1,341 @@
+use std::sync::Arc;
+
+use arrow_array::RecordBatch;
+use arrow_ipc::writer::StreamWriter;
+use arrow_schema::SchemaRef;
+use bytes::Bytes;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde_json::Map;
+
+use super::query::{Query, ReadPath};
+use super::schema::{Column, ColumnType, TableSchema};
+use super::QuestDbClient;
+use crate::error::AnySignalResult;
+use crate::sink::parquet::{arrow_schema, record_batch};
+use crate::sink::{Record, Value};
+
+/// One row as decoded by the PG wire read path.
+type JsonRow = Map<String, serde_json::Value>;
+
+/// File format of an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Parquet,
+    Csv,
+    /// Arrow IPC streaming format.
+    Arrow,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Arrow => "arrow",
+        }
+    }
+}
+
+/// Column holding the coin for `table`, if it has one: `coin` on the fill
+/// and market-state tables, `ticker` on `market_data` and `l2_orderbook`.
+pub fn coin_column(table: &TableSchema) -> Option<&'static str> {
+    let columns = table.columns();
+    ["coin", "ticker"]
+        .into_iter()
+        .find(|name| columns.iter().any(|c| c.name == *name))
+}
+
+/// Every schema column of `table` in `[from, to)`, oldest first, optionally
+/// restricted to `coins`. Always read over PG wire so the rows arrive in
+/// cursor-sized batches.
+pub fn export_query(
+    db: &QuestDbClient,
+    table: &TableSchema,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+    coins: Option<&[String]>,
+) -> AnySignalResult<Query> {
+    let ts = table
+        .designated_timestamp()
+        .ok_or_else(|| eyre::eyre!("table '{}' has no designated timestamp", table.name))?;
+    let columns: Vec<&str> = table.columns().iter().map(|c| c.name).collect();
+    let mut query = Query::select(&columns)
+        .from(&db.table_name(table.name))
+        .time_range(ts, from, to);
+    if let Some(coins) = coins {
+        let column = coin_column(table)
+            .ok_or_else(|| eyre::eyre!("table '{}' has no coin column", table.name))?;
+        query = query.in_list(column, coins.iter().cloned());
+    }
+    Ok(query.order_by(ts).via(ReadPath::PgWire))
+}
+
+/// Stream `query` (built by [`export_query`] for `table`) encoded as `format`.
+///
+/// Each PG wire batch is converted to one Arrow batch and encoded as soon as
+/// it arrives — a CSV chunk, an IPC message, or a Parquet row group — so
+/// memory stays bounded by the fetch size regardless of the range. An error
+/// mid-way ends the stream after the bytes already sent; clients see a
+/// truncated file.
+pub fn export(
+    db: &QuestDbClient,
+    table: &TableSchema,
+    query: &Query,
+    format: ExportFormat,
+) -> AnySignalResult<BoxStream<'static, AnySignalResult<Bytes>>> {
+    let columns = table.columns();
+    let schema = arrow_schema(&columns);
+    let encoder = Encoder::new(format, schema.clone())?;
+    let rows = db.stream::<JsonRow>(query);
+
+    struct State {
+        rows: BoxStream<'static, AnySignalResult<Vec<JsonRow>>>,
+        encoder: Encoder,
+        columns: Vec<Column>,
+        schema: SchemaRef,
+    }
+
+    let state = State {
+        rows,
+        encoder,
+        columns,
+        schema,
+    };
+    let stream = stream::unfold(Some(state), |state| async move {
+        let mut state = state?;
+        match state.rows.next().await {
+            Some(Ok(rows)) => {
+                let chunk = to_batch(state.schema.clone(), &state.columns, &rows)
+                    .and_then(|batch| state.encoder.write(&batch));
+                let next = chunk.is_ok().then_some(state);
+                Some((chunk, next))
+            }
+            Some(Err(e)) => Some((Err(e), None)),
+            None => Some((state.encoder.finish(), None)),
+        }
+    })
+    .filter(|chunk| {
+        let keep = !matches!(chunk, Ok(b) if b.is_empty());
+        async move { keep }
+    });
+    Ok(stream.boxed())
+}
+
+// ---------------------------------------------------------------------------
+// Row conversion
+// ---------------------------------------------------------------------------
+
+fn to_batch(
+    schema: SchemaRef,
+    columns: &[Column],
+    rows: &[JsonRow],
+) -> AnySignalResult<RecordBatch> {
+    let records = rows
+        .iter()
+        .map(|row| to_record(columns, row))
+        .collect::<AnySignalResult<Vec<_>>>()?;
+    let refs: Vec<&Record> = records.iter().collect();
+    record_batch(schema, columns, &refs).map_err(|e| eyre::eyre!("export: {e}").into())
+}
+
+fn to_record(columns: &[Column], row: &JsonRow) -> AnySignalResult<Record> {
+    columns
+        .iter()
+        .map(|c| {
+            let json = row.get(c.name).unwrap_or(&serde_json::Value::Null);
+            let value = to_value(c.ty, json).ok_or_else(|| {
+                eyre::eyre!("column '{}' ({}) cannot hold {json}", c.name, c.ty.name())
+            })?;
+            Ok((c.name, value))
+        })
+        .collect::<AnySignalResult<Vec<_>>>()
+        .map(Record)
+}
+
+fn to_value(ty: ColumnType, json: &serde_json::Value) -> Option<Value> {
+    if json.is_null() {
+        return Some(Value::Null);
+    }
+    let value = match ty {
+        ColumnType::Timestamp => Value::Timestamp(
+            DateTime::parse_from_rfc3339(json.as_str()?)
+                .ok()?
+                .with_timezone(&Utc),
+        ),
+        ColumnType::Symbol { .. } | ColumnType::String => Value::Str(json.as_str()?.to_string()),
+        ColumnType::Boolean => Value::Bool(json.as_bool()?),
+        ColumnType::Int | ColumnType::Long => Value::I64(json.as_i64()?),
+        ColumnType::Double => Value::F64(json.as_f64()?),
+    };
+    Some(value)
+}
+
+// ---------------------------------------------------------------------------
+// Encoders
+// ---------------------------------------------------------------------------
+
+/// Incremental file encoder writing into an in-memory buffer that is drained
+/// after every batch.
+enum Encoder {
+    Csv { header: bool },
+    Arrow(StreamWriter<Vec<u8>>),
+    Parquet(ArrowWriter<Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(format: ExportFormat, schema: SchemaRef) -> AnySignalResult<Self> {
+        let encoder = match format {
+            ExportFormat::Csv => Encoder::Csv { header: true },
+            ExportFormat::Arrow => Encoder::Arrow(
+                StreamWriter::try_new(Vec::new(), &schema)
+                    .map_err(|e| eyre::eyre!("arrow writer: {e}"))?,
+            ),
+            ExportFormat::Parquet => {
+                let props = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                Encoder::Parquet(
+                    ArrowWriter::try_new(Vec::new(), Arc::clone(&schema), Some(props))
+                        .map_err(|e| eyre::eyre!("parquet writer: {e}"))?,
+                )
+            }
+        };
+        Ok(encoder)
+    }
+
+    /// Encode `batch` and return the bytes ready to send.
+    fn write(&mut self, batch: &RecordBatch) -> AnySignalResult<Bytes> {
+        match self {
+            Encoder::Csv { header } => {
+                let mut writer = arrow_csv::WriterBuilder::new()
+                    .with_header(*header)
+                    .build(Vec::new());
+                writer
+                    .write(batch)
+                    .map_err(|e| eyre::eyre!("csv writer: {e}"))?;
+                *header = false;
+                Ok(writer.into_inner().into())
+            }
+            Encoder::Arrow(writer) => {
+                writer
+                    .write(batch)
+                    .map_err(|e| eyre::eyre!("arrow writer: {e}"))?;
+                Ok(std::mem::take(writer.get_mut()).into())
+            }
+            Encoder::Parquet(writer) => {
+                // one row group per batch, so nothing accumulates in memory
+                writer
+                    .write(batch)
+                    .and_then(|_| writer.flush())
+                    .map_err(|e| eyre::eyre!("parquet writer: {e}"))?;
+                Ok(std::mem::take(writer.inner_mut()).into())
+            }
+        }
+    }
+
+    /// Trailing bytes: the IPC end-of-stream marker or the Parquet footer.
+    fn finish(&mut self) -> AnySignalResult<Bytes> {
+        match self {
+            Encoder::Csv { .. } => Ok(Bytes::new()),
+            Encoder::Arrow(writer) => {
+                writer
+                    .finish()
+                    .map_err(|e| eyre::eyre!("arrow writer: {e}"))?;
+                Ok(std::mem::take(writer.get_mut()).into())
+            }
+            Encoder::Parquet(writer) => {
+                writer
+                    .finish()
+                    .map_err(|e| eyre::eyre!("parquet writer: {e}"))?;
+                Ok(std::mem::take(writer.inner_mut()).into())
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::schema;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use serde_json::json;
+
+    fn rows() -> Vec<JsonRow> {
+        [
+            json!({"ts": "2025-06-01T00:00:00.000000Z", "coin": "BTC", "category": "Buy",
+                   "buy_side": true, "quantity": 1.5, "trade_count": 3}),
+            json!({"ts": "2025-06-01T00:01:00.000000Z", "coin": "ETH", "category": "Sell",
+                   "buy_side": false, "quantity": null, "trade_count": 7}),
+        ]
+        .into_iter()
+        .map(|v| v.as_object().unwrap().clone())
+        .collect()
+    }
+
+    /// Encode `rows()` twice (two PG batches) and concatenate the chunks.
+    fn encode(format: ExportFormat) -> Vec<u8> {
+        let table = schema::table("hyperliquid_fill_1m_aggregate").unwrap();
+        let columns = table.columns();
+        let schema = arrow_schema(&columns);
+        let mut encoder = Encoder::new(format, schema.clone()).unwrap();
+        let mut out = Vec::new();
+        for _ in 0..2 {
+            let batch = to_batch(schema.clone(), &columns, &rows()).unwrap();
+            out.extend_from_slice(&encoder.write(&batch).unwrap());
+        }
+        out.extend_from_slice(&encoder.finish().unwrap());
+        out
+    }
+
+    #[test]
+    fn csv_writes_header_once() {
+        let csv = String::from_utf8(encode(ExportFormat::Csv)).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "ts,coin,category,buy_side,quantity,trade_count");
+        assert!(lines[2].contains("ETH,Sell,false,,7"), "{}", lines[2]);
+    }
+
+    #[test]
+    fn parquet_writes_one_row_group_per_batch() {
+        let file = Bytes::from(encode(ExportFormat::Parquet));
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 2);
+        let total: usize = reader.build().unwrap().map(|b| b.unwrap().num_rows()).sum();
+        assert_eq!(total, 4);
+    }
+
+    #[test]
+    fn arrow_stream_round_trips() {
+        let bytes = encode(ExportFormat::Arrow);
+        let reader =
+            arrow_ipc::reader::StreamReader::try_new(std::io::Cursor::new(bytes), None).unwrap();
+        let total: usize = reader.map(|b| b.unwrap().num_rows()).sum();
+        assert_eq!(total, 4);
+    }
+
+    #[test]
+    fn rejects_mistyped_cell() {
+        let table = schema::table("hyperliquid_fill_1m_aggregate").unwrap();
+        let mut row = rows().remove(0);
+        row.insert("trade_count".into(), json!("three"));
+        assert!(to_record(&table.columns(), &row).is_err());
+    }
+
+    #[test]
+    fn coin_column_per_table() {
+        let coin = |name| coin_column(schema::table(name).unwrap());
+        assert_eq!(coin("market_state_1m"), Some("coin"));
+        assert_eq!(coin("l2_orderbook"), Some("ticker"));
+        assert_eq!(coin("signal_scalar"), None);
+    }
+}
@@ -0,0 +1,440 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::query::{Query, SqlValue};
+use super::retention::list_partitions;
+use super::schema::{Step, TableSchema, TABLES};
+use super::QuestDbClient;
+use crate::error::AnySignalResult;
+
+/// Meta table recording every applied migration, one row per version.
+pub const META_TABLE: &str = "schema_migrations";
+
+/// A migration applied by [`run_migrations`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AppliedMigration {
+    /// Resolved table name (with the dev suffix, if any).
+    pub table: String,
+    pub version: u32,
+    pub description: String,
+}
+
+/// One row of `table_columns()`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LiveColumn {
+    #[serde(rename = "column")]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub designated: bool,
+    /// Part of the table's `DEDUP UPSERT KEYS`.
+    #[serde(rename = "upsertKey")]
+    pub upsert_key: bool,
+}
+
+/// Column present in both the expected and live schema with different types.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeMismatch {
+    pub column: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Expected vs live shape of one managed table.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaDiff {
+    /// Resolved table name.
+    pub table: String,
+    pub exists: bool,
+    /// Latest migration version defined for the table.
+    pub expected_version: u32,
+    /// Highest version recorded in [`META_TABLE`]; `0` when none.
+    pub applied_version: u32,
+    /// Expected columns the live table lacks.
+    pub missing_columns: Vec<String>,
+    /// Live columns the schema does not define (e.g. added ad hoc via ILP).
+    pub unexpected_columns: Vec<String>,
+    pub type_mismatches: Vec<TypeMismatch>,
+    pub expected_timestamp: Option<String>,
+    /// Live designated timestamp column; `None` when the table is missing or
+    /// has none.
+    pub actual_timestamp: Option<String>,
+    pub expected_dedup_keys: Vec<String>,
+    /// Live `DEDUP UPSERT KEYS`, in column order; empty when dedup is off.
+    pub actual_dedup_keys: Vec<String>,
+}
+
+impl SchemaDiff {
+    pub fn in_sync(&self) -> bool {
+        self.exists
+            && self.applied_version == self.expected_version
+            && self.missing_columns.is_empty()
+            && self.unexpected_columns.is_empty()
+            && self.type_mismatches.is_empty()
+            && self.actual_timestamp == self.expected_timestamp
+            && self.dedup_keys_match()
+    }
+
+    /// Same key set, regardless of order.
+    pub fn dedup_keys_match(&self) -> bool {
+        let expected: HashSet<&String> = self.expected_dedup_keys.iter().collect();
+        let actual: HashSet<&String> = self.actual_dedup_keys.iter().collect();
+        expected == actual
+    }
+}
+
+/// Compare `live` columns (empty when the table does not exist) against the
+/// expected `schema`.
+pub fn diff_table(
+    schema: &TableSchema,
+    table: &str,
+    exists: bool,
+    live: &[LiveColumn],
+    applied_version: u32,
+) -> SchemaDiff {
+    let expected = schema.columns();
+    let live_by_name: HashMap<&str, &LiveColumn> =
+        live.iter().map(|c| (c.name.as_str(), c)).collect();
+    let expected_names: HashSet<&str> = expected.iter().map(|c| c.name).collect();
+
+    let mut missing_columns = Vec::new();
+    let mut type_mismatches = Vec::new();
+    for col in &expected {
+        match live_by_name.get(col.name) {
+            None => missing_columns.push(col.name.to_string()),
+            Some(l) if !l.type_name.eq_ignore_ascii_case(col.ty.name()) => {
+                type_mismatches.push(TypeMismatch {
+                    column: col.name.to_string(),
+                    expected: col.ty.name().to_string(),
+                    actual: l.type_name.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    let unexpected_columns = live
+        .iter()
+        .filter(|c| !expected_names.contains(c.name.as_str()))
+        .map(|c| c.name.clone())
+        .collect();
+
+    SchemaDiff {
+        table: table.to_string(),
+        exists,
+        expected_version: schema.latest_version(),
+        applied_version,
+        missing_columns: if exists { missing_columns } else { Vec::new() },
+        unexpected_columns,
+        type_mismatches,
+        expected_timestamp: schema.designated_timestamp().map(String::from),
+        actual_timestamp: live.iter().find(|c| c.designated).map(|c| c.name.clone()),
+        expected_dedup_keys: schema.dedup_keys().iter().map(|k| k.to_string()).collect(),
+        actual_dedup_keys: live
+            .iter()
+            .filter(|c| c.upsert_key)
+            .map(|c| c.name.clone())
+            .collect(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// QuestDB access
+// ---------------------------------------------------------------------------
+
+async fn ensure_meta_table(db: &QuestDbClient) -> AnySignalResult<String> {
+    let meta = db.table_name(META_TABLE);
+    // Not WAL: versions must be readable immediately after they are recorded.
+    db.exec(&format!(
+        "CREATE TABLE IF NOT EXISTS {meta} (\
+         table_name SYMBOL, version INT, description STRING, applied_at TIMESTAMP\
+         ) TIMESTAMP(applied_at) PARTITION BY YEAR BYPASS WAL"
+    ))
+    .await?;
+    Ok(meta)
+}
+
+/// Highest applied version per resolved table name.
+async fn applied_versions(db: &QuestDbClient, meta: &str) -> AnySignalResult<HashMap<String, u32>> {
+    #[derive(Deserialize)]
+    struct Applied {
+        table_name: String,
+        version: i64,
+    }
+    let query = Query::select(&["table_name"])
+        .select_expr("max(version) version")
+        .from(meta)
+        .group_by("table_name");
+    let rows: Vec<Applied> = db.query(&query).await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|r| Some((r.table_name, u32::try_from(r.version).ok()?)))
+        .collect())
+}
+
+async fn existing_tables(db: &QuestDbClient) -> AnySignalResult<HashSet<String>> {
+    #[derive(Deserialize)]
+    struct Table {
+        table_name: String,
+    }
+    let query = Query::select(&["table_name"]).from_function("tables", &[]);
+    let rows: Vec<Table> = db.query(&query).await?;
+    Ok(rows.into_iter().map(|r| r.table_name).collect())
+}
+
+async fn live_columns(db: &QuestDbClient, table: &str) -> AnySignalResult<Vec<LiveColumn>> {
+    let query = Query::select(&["column", "type", "designated", "upsertKey"])
+        .from_function("table_columns", &[table.into()]);
+    db.query(&query).await
+}
+
+/// Apply every pending migration of every table in [`TABLES`], recording each
+/// in [`META_TABLE`]. Run once on startup, before any ILP writer can create a
+/// table with guessed column types.
+///
+/// Stops at the first failing statement; migrations applied before it stay
+/// recorded, so the next run resumes from there.
+pub async fn run_migrations(db: &QuestDbClient) -> AnySignalResult<Vec<AppliedMigration>> {
+    let meta = ensure_meta_table(db).await?;
+    let applied = applied_versions(db, &meta).await?;
+
+    let mut done = Vec::new();
+    for schema in TABLES {
+        let table = db.table_name(schema.name);
+        let current = applied.get(&table).copied().unwrap_or(0);
+        for migration in schema.pending(current) {
+            for sql in migration.statements(&table, |t| db.table_name(t)) {
+                match migration.step {
+                    Step::Backfill { source, .. } => {
+                        backfill(db, &sql, &db.table_name(source)).await?
+                    }
+                    _ => {
+                        db.exec(&sql).await?;
+                    }
+                }
+            }
+            db.exec(&format!(
+                "INSERT INTO {meta} (table_name, version, description, applied_at) \
+                 VALUES ({}, {}, {}, now())",
+                SqlValue::from(table.as_str()).render(),
+                migration.version,
+                SqlValue::from(migration.description).render(),
+            ))
+            .await?;
+            tracing::info!(
+                table = table.as_str(),
+                version = migration.version,
+                description = migration.description,
+                "applied schema migration"
+            );
+            done.push(AppliedMigration {
+                table: table.clone(),
+                version: migration.version,
+                description: migration.description.to_string(),
+            });
+        }
+    }
+    Ok(done)
+}
+
+/// Run a [`Step::Backfill`] statement once per non-empty partition of
+/// `source`, oldest first, so each `INSERT … SELECT` reads one partition.
+async fn backfill(db: &QuestDbClient, sql: &str, source: &str) -> AnySignalResult<()> {
+    let mut partitions = list_partitions(db, source).await?;
+    partitions.sort_by_key(|p| p.min_timestamp);
+    for partition in partitions {
+        let (Some(min), Some(max)) = (partition.min_timestamp, partition.max_timestamp) else {
+            continue;
+        };
+        // `to` is exclusive; QuestDB timestamps are microseconds
+        let to = max + chrono::Duration::microseconds(1);
+        db.exec(
+            &sql.replace("{from}", &SqlValue::from(min).render())
+                .replace("{to}", &SqlValue::from(to).render()),
+        )
+        .await?;
+        tracing::debug!(source, partition = partition.name, "backfilled partition");
+    }
+    Ok(())
+}
+
+/// Diff every managed table's live columns against its expected schema.
+pub async fn schema_report(db: &QuestDbClient) -> AnySignalResult<Vec<SchemaDiff>> {
+    let meta = db.table_name(META_TABLE);
+    let existing = existing_tables(db).await?;
+    let applied = if existing.contains(&meta) {
+        applied_versions(db, &meta).await?
+    } else {
+        HashMap::new()
+    };
+
+    let mut report = Vec::with_capacity(TABLES.len());
+    for schema in TABLES {
+        let table = db.table_name(schema.name);
+        let exists = existing.contains(&table);
+        let live = if exists {
+            live_columns(db, &table).await?
+        } else {
+            Vec::new()
+        };
+        let version = applied.get(&table).copied().unwrap_or(0);
+        report.push(diff_table(schema, &table, exists, &live, version));
+    }
+    Ok(report)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::query::decode_rows;
+    use crate::database::schema::table;
+
+    fn live(name: &str, type_name: &str, designated: bool) -> LiveColumn {
+        LiveColumn {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            designated,
+            upsert_key: false,
+        }
+    }
+
+    #[test]
+    fn diff_reports_missing_unexpected_and_mismatched_columns() {
+        let schema = table("hyperliquid_fill_1m_aggregate").unwrap();
+        // shape as guessed by ILP: `timestamp` instead of `ts`, LONG quantity
+        let cols = vec![
+            live("coin", "SYMBOL", false),
+            live("category", "SYMBOL", false),
+            live("buy_side", "BOOLEAN", false),
+            live("quantity", "LONG", false),
+            live("trade_count", "LONG", false),
+            live("timestamp", "TIMESTAMP", true),
+        ];
+        let diff = diff_table(schema, "hyperliquid_fill_1m_aggregate", true, &cols, 0);
+
+        assert_eq!(diff.missing_columns, vec!["ts"]);
+        assert_eq!(diff.unexpected_columns, vec!["timestamp"]);
+        assert_eq!(
+            diff.type_mismatches,
+            vec![TypeMismatch {
+                column: "quantity".into(),
+                expected: "DOUBLE".into(),
+                actual: "LONG".into(),
+            }]
+        );
+        assert_eq!(diff.actual_timestamp.as_deref(), Some("timestamp"));
+        assert!(!diff.in_sync());
+    }
+
+    #[test]
+    fn diff_in_sync_when_live_matches() {
+        let schema = table("signal_scalar").unwrap();
+        let mut cols = vec![
+            live("info_id", "LONG", false),
+            live("value", "DOUBLE", false),
+            live("timestamp", "TIMESTAMP", true),
+        ];
+        let diff = diff_table(schema, "signal_scalar", true, &cols, 2);
+        assert!(!diff.dedup_keys_match(), "dedup not enabled yet");
+
+        cols[0].upsert_key = true;
+        cols[2].upsert_key = true;
+        let diff = diff_table(schema, "signal_scalar", true, &cols, 2);
+        assert!(diff.in_sync(), "{diff:?}");
+    }
+
+    #[test]
+    fn diff_missing_table() {
+        let schema = table("market_data").unwrap();
+        let diff = diff_table(schema, "market_data", false, &[], 0);
+        assert!(!diff.exists);
+        assert!(diff.missing_columns.is_empty());
+        assert_eq!(diff.expected_version, 3);
+        assert!(!diff.in_sync());
+    }
+
+    #[test]
+    fn live_columns_decode_by_name() {
+        let json = serde_json::json!({
+            "query": "SELECT \"column\", type, designated, upsertKey FROM table_columns('t')",
+            "columns": [
+                {"name": "column", "type": "STRING"},
+                {"name": "type", "type": "STRING"},
+                {"name": "designated", "type": "BOOLEAN"},
+                {"name": "upsertKey", "type": "BOOLEAN"}
+            ],
+            "timestamp": -1,
+            "dataset": [["ts", "TIMESTAMP", true, true], ["coin", "SYMBOL", false, false]],
+            "count": 2
+        });
+        let cols: Vec<LiveColumn> = decode_rows(json).unwrap();
+        let mut ts = live("ts", "TIMESTAMP", true);
+        ts.upsert_key = true;
+        assert_eq!(cols, vec![ts, live("coin", "SYMBOL", false)]);
+    }
+
+    /// Writes the same aggregate row twice and expects exactly one row, i.e. a
+    /// forced re-backfill replaces instead of duplicating. Uses `_dev` tables.
+    #[tokio::test]
+    #[ignore = "requires a local QuestDB: docker compose up -d questdb"]
+    async fn reingest_replaces_rows() {
+        use crate::config::Config;
+        use crate::database::query::Query;
+        use crate::database::{insert_hyperliquid_fill_1m_aggregate, Fill1mAggregate};
+        use chrono::DateTime;
+
+        dotenvy::dotenv().ok();
+        let mut config = Config::from_env();
+        config.dev = true;
+        let db = QuestDbClient::new(&config).unwrap();
+        run_migrations(&db).await.unwrap();
+
+        let table = db.table_name("hyperliquid_fill_1m_aggregate");
+        let rows = [Fill1mAggregate {
+            minute_ms: 946_684_800_000, // 2000-01-01T00:00:00Z
+            coin: "DEDUP_TEST".to_string(),
+            category: "Buy".to_string(),
+            buy_side: true,
+            quantity: 1.5,
+            trade_count: 3,
+        }];
+        for _ in 0..2 {
+            db.write_rows(&table, &rows, insert_hyperliquid_fill_1m_aggregate)
+                .await
+                .unwrap();
+        }
+
+        // WAL tables apply writes in order: once a later row is visible,
+        // both duplicates have landed.
+        let sentinel = [Fill1mAggregate {
+            minute_ms: 946_684_800_000,
+            coin: "DEDUP_SENTINEL".to_string(),
+            category: "Buy".to_string(),
+            buy_side: true,
+            quantity: 1.5,
+            trade_count: 3,
+        }];
+        db.write_rows(&table, &sentinel, insert_hyperliquid_fill_1m_aggregate)
+            .await
+            .unwrap();
+        let minute = DateTime::from_timestamp_millis(946_684_800_000).unwrap();
+        let count_of = |coin: &str| {
+            Query::count()
+                .from(&table)
+                .eq("coin", coin)
+                .eq("ts", minute)
+        };
+        for _ in 0..20 {
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            if db.count(&count_of("DEDUP_SENTINEL")).await.unwrap() > 0 {
+                break;
+            }
+        }
+        let count = db.count(&count_of("DEDUP_TEST")).await.unwrap();
+        assert_eq!(count, 1);
+    }
+}
@@ -1,5 +1,27 @@
+/// manually curated events stored as structured signals
+pub mod calendar;
+/// streams a table's time range as Parquet, CSV or Arrow
+pub mod export;
+/// applies schema migrations and diffs live tables against them
+pub mod migration;
+/// streaming reads over the PostgreSQL wire protocol
+pub mod pg;
+/// pool of ILP senders with async checkout
+pub mod pool;
+/// typed `/exec` query builder and result decoding
+pub mod query;
+/// signal ids and descriptions persisted in `signal_info`
+pub mod registry;
 /// direct response of the response
 pub mod response;
+/// per-table retention policies: roll up, then drop old partitions
+pub mod retention;
+/// expected table layouts as versioned migrations
+pub mod schema;
+/// reads of registered signals, optionally resampled
+pub mod signal_data;
+/// write-ahead spool for ILP buffers QuestDB did not accept
+pub mod spool;
 /// representation of each table
 pub mod table;
 
@@ -8,136 +30,414 @@ use crate::adapter::hyperliquid_s3::market_data::L2Snapshot;
 use crate::adapter::hyperliquid_s3::node_fills_by_block::ParsedFill;
 use crate::config::Config;
 use crate::database::table::*;
-use crate::error::AnySignalResult;
-use crate::model::signal::{Signal, SignalData, SignalDataType, SignalInfo};
+use crate::error::{AnySignalError, AnySignalResult};
+use crate::model::instance::PaperInstance;
+use crate::model::signal::{FieldValue, Signal, SignalData, SignalDataType, SignalInfo};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use pg::{PgClient, PgConfig};
+use pool::{BufferedWrites, SenderPool};
+use query::{check_error, decode_rows, Query, ReadPath};
 use questdb::ingress::{Buffer, Sender, TimestampMicros};
 use questdb::Result as QuestResult;
-use response::QuestDbResponse;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use spool::Spool;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
 
-// batch insert using questdb ingress
+// batch insert using questdb ingress, into `table` (resolved with the dev
+// suffix) under the id of `signal_info`
 pub fn insert_signal_db(
-    sender: &mut Sender,
+    sender: &mut impl IlpFlush,
+    table: &str,
     signal_info: &SignalInfo,
     signals: &[Signal],
 ) -> QuestResult<()> {
-    // do a query on existing data with the timestamp
-
     // batch store data into the buffer
     let mut buffer = Buffer::new();
+    let written_us = chrono::Utc::now().timestamp_micros();
+    for signal in signals {
+        signal_line(&mut buffer, table, signal_info.id, signal, written_us)?;
+    }
+    if !buffer.is_empty() {
+        sender.flush(&mut buffer)?;
+    }
+    Ok(())
+}
+
+/// Write `signals` to `table`, the `signal_*` table of their data type, each
+/// under its own `info_id`. Returns the number of rows written.
+pub fn insert_signal_rows(
+    sender: &mut impl IlpFlush,
+    table: &str,
+    signals: &[Signal],
+) -> QuestResult<usize> {
+    let mut buffer = Buffer::new();
+    let written_us = chrono::Utc::now().timestamp_micros();
     for signal in signals {
-        let timestamp_us = signal.timestamp_us;
-        match &signal.data {
-            SignalData::Simple => buffer
-                .table("signal_simple")?
-                .column_i64("info_id", signal_info.id)?
-                .at(TimestampMicros::new(timestamp_us))?,
-            SignalData::Binary(value) => buffer
-                .table("signal_binary")?
-                .column_i64("info_id", signal_info.id)?
-                .column_bool("value", *value)?
-                .at(TimestampMicros::new(timestamp_us))?,
-            SignalData::Scalar(value) => buffer
-                .table("signal_scalar")?
-                .column_i64("info_id", signal_info.id)?
-                .column_f64("value", *value)?
-                .at(TimestampMicros::new(timestamp_us))?,
-            SignalData::Text(value) => buffer
-                .table("signal_text")?
-                .column_i64("info_id", signal_info.id)?
+        signal_line(&mut buffer, table, signal.info_id, signal, written_us)?;
+    }
+    if !buffer.is_empty() {
+        sender.flush(&mut buffer)?;
+    }
+    Ok(signals.len())
+}
+
+/// Write structured `signals` to `table` (`signal_structured`), each under
+/// its `info_id` and the key paired with it, so records of one signal that
+/// share a timestamp (e.g. calendar events, keyed by event id) do not
+/// replace each other. Other data types are skipped with a warning and not
+/// counted. Returns the number of signals written.
+pub fn insert_keyed_signal_rows(
+    sender: &mut impl IlpFlush,
+    table: &str,
+    signals: &[(i64, Signal)],
+) -> QuestResult<usize> {
+    let mut buffer = Buffer::new();
+    let written_us = chrono::Utc::now().timestamp_micros();
+    let mut count = 0;
+    for (key, signal) in signals {
+        let SignalData::Structured(fields) = &signal.data else {
+            tracing::warn!(
+                info_id = signal.info_id,
+                "skipping keyed signal that is not structured"
+            );
+            continue;
+        };
+        let at = TimestampMicros::new(signal.timestamp_us);
+        let info_id = signal.info_id;
+        structured_lines(
+            &mut buffer,
+            table,
+            info_id,
+            Some(*key),
+            at,
+            fields,
+            written_us,
+        )?;
+        count += 1;
+    }
+    if !buffer.is_empty() {
+        sender.flush(&mut buffer)?;
+    }
+    Ok(count)
+}
+
+/// The `signal_*` lines of one signal; the value columns follow the data
+/// variant. Vectors take one line per element and structured signals one per
+/// field, so empty ones write nothing. Structured fields are stamped with
+/// `written_us` so reads can tell them from fields of an earlier write; a NaN
+/// number is written with neither `value` nor `text`.
+fn signal_line(
+    buffer: &mut Buffer,
+    table: &str,
+    info_id: i64,
+    signal: &Signal,
+    written_us: i64,
+) -> QuestResult<()> {
+    let at = TimestampMicros::new(signal.timestamp_us);
+    match &signal.data {
+        SignalData::Vector(values) => {
+            for (idx, value) in values.iter().enumerate() {
+                buffer
+                    .table(table)?
+                    .column_i64("info_id", info_id)?
+                    .column_i64("idx", idx as i64)?
+                    .column_i64("len", values.len() as i64)?
+                    .column_f64("value", *value)?
+                    .at(at)?;
+            }
+            return Ok(());
+        }
+        SignalData::Structured(fields) => {
+            return structured_lines(buffer, table, info_id, None, at, fields, written_us);
+        }
+        _ => {}
+    }
+    buffer.table(table)?.column_i64("info_id", info_id)?;
+    match &signal.data {
+        SignalData::Binary(value) => {
+            buffer.column_bool("value", *value)?;
+        }
+        SignalData::Scalar(value) => {
+            buffer.column_f64("value", *value)?;
+        }
+        SignalData::Text(value) => {
+            buffer
                 .column_str("value", value)?
-                .at(TimestampMicros::new(timestamp_us))?,
+                .column_i64("hash", text_hash(value))?;
         }
+        _ => {}
     }
-    sender.flush(&mut buffer)?;
+    buffer.at(at)?;
     Ok(())
 }
 
-// select via query on http
+/// The `signal_structured` lines of one structured signal, one per field.
+/// `key` tells apart records of one signal that share a timestamp, see
+/// [`insert_keyed_signal_rows`].
+fn structured_lines(
+    buffer: &mut Buffer,
+    table: &str,
+    info_id: i64,
+    key: Option<i64>,
+    at: TimestampMicros,
+    fields: &BTreeMap<String, FieldValue>,
+    written_us: i64,
+) -> QuestResult<()> {
+    for (field, value) in fields {
+        buffer
+            .table(table)?
+            .symbol("field", field)?
+            .column_i64("info_id", info_id)?;
+        if let Some(key) = key {
+            buffer.column_i64("key", key)?;
+        }
+        buffer
+            .column_i64("len", fields.len() as i64)?
+            .column_i64("written", written_us)?;
+        match value {
+            FieldValue::Number(v) if v.is_nan() => {}
+            FieldValue::Number(v) => {
+                buffer.column_f64("value", *v)?;
+            }
+            FieldValue::Text(v) => {
+                buffer.column_str("text", v)?;
+            }
+        }
+        buffer.at(at)?;
+    }
+    Ok(())
+}
+
+/// 64-bit FNV-1a of `text`: part of `signal_text`'s upsert keys. Stable
+/// across builds, unlike `std`'s hasher.
+pub fn text_hash(text: &str) -> i64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in text.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash as i64
+}
+
+/// Write one `signal_info` row per registration, stamped now. `parameters`
+/// are stored as a JSON object. Returns the number of rows written.
+pub fn insert_signal_info(
+    sender: &mut impl IlpFlush,
+    table: &str,
+    infos: &[SignalInfo],
+) -> QuestResult<usize> {
+    let mut buffer = Buffer::new();
+    let now = TimestampMicros::new(chrono::Utc::now().timestamp_micros());
+    for info in infos {
+        let parameters = serde_json::to_string(&info.parameters).unwrap_or_default();
+        buffer
+            .table(table)?
+            .symbol("signal_type", &info.signal_type)?
+            .symbol("data_type", info.data_type.name())?
+            .symbol("source", &info.source)?
+            .column_i64("id", info.id)?
+            .column_str("description", &info.description)?
+            .column_bool("is_atomic", info.is_atomic)?
+            .column_str("parameters", &parameters)?
+            .at(now)?;
+    }
+    if !buffer.is_empty() {
+        sender.flush(&mut buffer)?;
+    }
+    Ok(infos.len())
+}
+
+/// Write one `strategy_instance` row per instance, stamped with its latest
+/// change (`closed_at`, else `opened_at`). `legs` are stored as a JSON array.
+/// Returns the number of rows written.
+pub fn insert_strategy_instance(
+    sender: &mut impl IlpFlush,
+    table: &str,
+    instances: &[PaperInstance],
+) -> QuestResult<usize> {
+    let mut buffer = Buffer::new();
+    for instance in instances {
+        let legs = serde_json::to_string(&instance.legs).unwrap_or_default();
+        let opened_at = TimestampMicros::new(instance.opened_at.timestamp_micros());
+        let at = instance.closed_at.unwrap_or(instance.opened_at);
+        let row = buffer
+            .table(table)?
+            .symbol("status", instance.status.name())?
+            .column_i64("instance_id", instance.id as i64)?
+            .column_i64("strategy_id", instance.strategy_id as i64)?
+            .column_str("legs", &legs)?
+            .column_f64("pnl", instance.pnl())?
+            .column_str("reason", &instance.reason)?
+            .column_ts("opened_at", opened_at)?;
+        if let Some(closed_at) = instance.closed_at {
+            row.column_ts(
+                "closed_at",
+                TimestampMicros::new(closed_at.timestamp_micros()),
+            )?;
+        }
+        row.at(TimestampMicros::new(at.timestamp_micros()))?;
+    }
+    if !buffer.is_empty() {
+        sender.flush(&mut buffer)?;
+    }
+    Ok(instances.len())
+}
+
+/// Run `query` against the `signal_*` table of `signal_data_type` (see
+/// [`signal_data::signal_query`]) and rebuild the [`Signal`]s.
 pub async fn select_signal_db(
+    db: &QuestDbClient,
     signal_data_type: SignalDataType,
-    query_string: impl AsRef<str>,
+    query: &Query,
 ) -> AnySignalResult<Vec<Signal>> {
-    let client = reqwest::Client::new();
-    let response = client
-        .get("http://localhost:9000/exec")
-        .query(&[("query", query_string.as_ref())])
-        .send()
-        .await?;
-    let text = response.text().await?;
-
-    // TODO select type based on the provided signal_data_type
     let signals = match signal_data_type {
-        SignalDataType::Simple => {
-            let result: QuestDbResponse<SignalSimpleRow> = serde_json::from_str(&text)?;
-            result
-                .dataset
-                .into_iter()
-                .map(|i| Signal {
-                    info_id: i.info_id,
-                    data: SignalData::Simple,
-                    timestamp_us: i.timestamp.timestamp_micros(),
-                })
-                .collect()
-        }
-        SignalDataType::Binary => {
-            let result: QuestDbResponse<SignalBooleanRow> = serde_json::from_str(&text)?;
-            result
-                .dataset
-                .into_iter()
-                .map(|i| Signal {
-                    info_id: i.info_id,
-                    data: SignalData::Binary(i.value),
-                    timestamp_us: i.timestamp.timestamp_micros(),
-                })
-                .collect()
-        }
-        SignalDataType::Scalar => {
-            let result: QuestDbResponse<SignalScalarRow> = serde_json::from_str(&text)?;
-            result
-                .dataset
-                .into_iter()
-                .map(|i| Signal {
-                    info_id: i.info_id,
-                    data: SignalData::Scalar(i.value),
-                    timestamp_us: i.timestamp.timestamp_micros(),
-                })
-                .collect()
-        }
-        SignalDataType::Text => {
-            let result: QuestDbResponse<SignalStringRow> = serde_json::from_str(&text)?;
-            result
-                .dataset
-                .into_iter()
-                .map(|i| Signal {
-                    info_id: i.info_id,
-                    data: SignalData::Text(i.value),
-                    timestamp_us: i.timestamp.timestamp_micros(),
-                })
-                .collect()
+        SignalDataType::Simple => db
+            .query::<SignalSimpleRow>(query)
+            .await?
+            .into_iter()
+            .map(|i| Signal {
+                info_id: i.info_id,
+                data: SignalData::Simple,
+                timestamp_us: i.timestamp.timestamp_micros(),
+            })
+            .collect(),
+        SignalDataType::Binary => db
+            .query::<SignalBooleanRow>(query)
+            .await?
+            .into_iter()
+            .map(|i| Signal {
+                info_id: i.info_id,
+                data: SignalData::Binary(i.value),
+                timestamp_us: i.timestamp.timestamp_micros(),
+            })
+            .collect(),
+        SignalDataType::Scalar => db
+            .query::<SignalScalarRow>(query)
+            .await?
+            .into_iter()
+            .map(|i| Signal {
+                info_id: i.info_id,
+                data: SignalData::Scalar(i.value),
+                timestamp_us: i.timestamp.timestamp_micros(),
+            })
+            .collect(),
+        SignalDataType::Text => db
+            .query::<SignalStringRow>(query)
+            .await?
+            .into_iter()
+            .map(|i| Signal {
+                info_id: i.info_id,
+                data: SignalData::Text(i.value),
+                timestamp_us: i.timestamp.timestamp_micros(),
+            })
+            .collect(),
+        SignalDataType::Vector => vector_signals(db.query::<SignalVectorRow>(query).await?),
+        SignalDataType::Structured => {
+            structured_signals(db.query::<SignalStructuredRow>(query).await?)
         }
     };
-    // TODO implement the reconstruction of signal from the questdb
     Ok(signals)
 }
 
+/// Fold element rows, ordered by timestamp then `idx`, back into vectors.
+/// Elements past `len` are left over from a longer earlier write; a vector
+/// missing elements (e.g. cut off by a row limit) is dropped.
+fn vector_signals(rows: Vec<SignalVectorRow>) -> Vec<Signal> {
+    let mut signals = Vec::new();
+    for group in rows.chunk_by(|a, b| a.info_id == b.info_id && a.timestamp == b.timestamp) {
+        let len = group[0].len.max(0) as usize;
+        let elements = &group[..len.min(group.len())];
+        if elements.len() != len || elements.iter().enumerate().any(|(i, r)| r.idx != i as i64) {
+            tracing::debug!(
+                info_id = group[0].info_id,
+                "skipping incomplete vector signal"
+            );
+            continue;
+        }
+        let values = elements
+            .iter()
+            .map(|r| r.value.unwrap_or(f64::NAN))
+            .collect();
+        signals.push(Signal {
+            info_id: group[0].info_id,
+            timestamp_us: group[0].timestamp.timestamp_micros(),
+            data: SignalData::Vector(values),
+        });
+    }
+    signals
+}
+
+/// Fold field rows, ordered by timestamp then key, back into structured
+/// signals. Only
+/// the fields of the latest write count: fields an earlier write had and the
+/// latest dropped are left over. A signal missing fields (e.g. cut off by a
+/// row limit) is dropped. A field with neither `value` nor `text` is NaN.
+/// Records under different keys at one timestamp are separate signals.
+fn structured_signals(rows: Vec<SignalStructuredRow>) -> Vec<Signal> {
+    let mut signals = Vec::new();
+    let same = |a: &SignalStructuredRow, b: &SignalStructuredRow| {
+        a.info_id == b.info_id && a.timestamp == b.timestamp && a.key == b.key
+    };
+    for group in rows.chunk_by(same) {
+        let written = group.iter().map(|r| r.written).max().flatten();
+        let latest: Vec<&SignalStructuredRow> =
+            group.iter().filter(|r| r.written == written).collect();
+        let fields: BTreeMap<String, FieldValue> = latest
+            .iter()
+            .map(|r| {
+                let value = match (r.value, &r.text) {
+                    (Some(v), _) => FieldValue::Number(v),
+                    (None, Some(t)) => FieldValue::Text(t.clone()),
+                    (None, None) => FieldValue::Number(f64::NAN),
+                };
+                (r.field.clone(), value)
+            })
+            .collect();
+        if fields.len() as i64 != latest[0].len {
+            tracing::debug!(
+                info_id = group[0].info_id,
+                "skipping incomplete structured signal"
+            );
+            continue;
+        }
+        signals.push(Signal {
+            info_id: group[0].info_id,
+            timestamp_us: group[0].timestamp.timestamp_micros(),
+            data: SignalData::Structured(fields),
+        });
+    }
+    signals
+}
+
+// signals not already present in `existing`
+fn unseen_signals(signals: &[Signal], existing: &HashSet<Signal>) -> Vec<Signal> {
+    signals
+        .iter()
+        .filter(|i| !existing.contains(i))
+        .cloned()
+        .collect()
+}
+
 // syntatic sugar
 // provide skip fn, so we insert only needed ones
 pub async fn insert_unique_signal_db(
-    sender: &mut Sender,
+    sender: &mut impl IlpFlush,
+    db: &QuestDbClient,
     signal_info: &SignalInfo,
     signals: &[Signal],
-    skip_query_string: impl AsRef<str>,
+    skip_query: &Query,
 ) -> AnySignalResult<usize> {
-    let signals_to_skip = select_signal_db(signal_info.data_type, skip_query_string).await?;
+    let signals_to_skip = select_signal_db(db, signal_info.data_type, skip_query).await?;
     let signals_to_skip: HashSet<Signal> = signals_to_skip.into_iter().collect();
 
-    let mut signals = signals.to_vec();
-    signals.retain(|i| signals_to_skip.contains(i));
+    let signals = unseen_signals(signals, &signals_to_skip);
     let len = signals.len();
     if !signals.is_empty() {
-        insert_signal_db(sender, signal_info, &signals)?;
+        let table = db.table_name(signal_info.data_type.table());
+        insert_signal_db(sender, &table, signal_info, &signals)?;
     }
     Ok(len)
 }
@@ -165,25 +465,94 @@ pub fn questdb_sender(config: &Config) -> QuestResult<Sender> {
     Sender::from_conf(conf)
 }
 
+/// Destination of a filled ILP [`Buffer`]. The `insert_*` writers flush
+/// through this so they can write to a bare [`Sender`] or a
+/// [`SpoolingSender`].
+pub trait IlpFlush {
+    /// Send the buffer and clear it. On error the buffer is left intact.
+    fn flush(&mut self, buffer: &mut Buffer) -> QuestResult<()>;
+}
+
+impl IlpFlush for Sender {
+    fn flush(&mut self, buffer: &mut Buffer) -> QuestResult<()> {
+        Sender::flush(self, buffer)
+    }
+}
+
+/// A [`Sender`] that spools buffers for one table instead of losing them.
+/// Used by [`pool::PooledSender::flush`].
+///
+/// A buffer goes to the [`Spool`] when QuestDB rejects it, or when earlier
+/// buffers are still spooled so replay keeps write order; that check reads
+/// the spool's in-memory count, so a flush only touches disk when it spools.
+/// If the spool is full the QuestDB error is returned as before.
+pub struct SpoolingSender<'a> {
+    sender: &'a mut Sender,
+    spool: Option<&'a Spool>,
+    table: &'a str,
+}
+
+impl IlpFlush for SpoolingSender<'_> {
+    fn flush(&mut self, buffer: &mut Buffer) -> QuestResult<()> {
+        let Some(spool) = self.spool else {
+            return self.sender.flush(buffer);
+        };
+        if !spool.is_empty() {
+            match spool.push(self.table, buffer) {
+                Ok(()) => {
+                    buffer.clear();
+                    return Ok(());
+                }
+                // out of order beats dropping the rows
+                Err(e) => tracing::warn!(
+                    table = self.table,
+                    error = e.as_str(),
+                    "spool rejected buffer, sending directly"
+                ),
+            }
+            return self.sender.flush(buffer);
+        }
+        match self.sender.flush(buffer) {
+            Ok(()) => Ok(()),
+            Err(flush_err) => match spool.push(self.table, buffer) {
+                Ok(()) => {
+                    tracing::warn!(table = self.table, error = %flush_err, bytes = buffer.len(), "ILP flush failed, buffer spooled");
+                    buffer.clear();
+                    Ok(())
+                }
+                Err(e) => {
+                    tracing::error!(
+                        table = self.table,
+                        error = e.as_str(),
+                        "ILP flush failed and spool rejected buffer"
+                    );
+                    Err(flush_err)
+                }
+            },
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // QuestDbClient — combined HTTP query + ILP sender handle
 // ---------------------------------------------------------------------------
 
-/// Bundles the QuestDB HTTP query address and an ILP [`Sender`] into a single
-/// handle that can be passed to [`PartitionedSource`] implementations.
+/// Bundles the QuestDB HTTP query address and a pool of ILP [`Sender`]s into
+/// a single handle that can be passed to [`PartitionedSource`] implementations.
 pub struct QuestDbClient {
     pub addr: String,
-    sender: std::sync::Mutex<Sender>,
+    pg: Arc<PgClient>,
+    pool: Arc<SenderPool>,
     dev: bool,
 }
 
 impl QuestDbClient {
     /// Build a client from the application [`Config`].
     pub fn new(config: &Config) -> QuestResult<Self> {
-        let sender = questdb_sender(config)?;
         Ok(Self {
             addr: config.questdb_addr.clone(),
-            sender: std::sync::Mutex::new(sender),
+            pg: Arc::new(PgClient::new(PgConfig::new(config))),
+            pool: SenderPool::new(config)?,
             dev: config.dev,
         })
     }
@@ -200,58 +569,131 @@ impl QuestDbClient {
         }
     }
 
-    /// Execute a closure with exclusive access to the ILP [`Sender`].
-    /// The closure must be synchronous — do not await inside it.
-    pub fn with_sender<F, T>(&self, f: F) -> QuestResult<T>
+    /// Write `rows` to `table` with one of the `insert_*` writers.
+    ///
+    /// Rows are encoded in chunks of [`WRITE_CHUNK_ROWS`] on the calling task,
+    /// and each chunk is flushed on a blocking thread through a sender checked
+    /// out of the pool, so concurrent writers neither share a connection nor
+    /// block Tokio workers. Buffers QuestDB rejects are spooled for replay.
+    pub async fn write_rows<R, F>(&self, table: &str, rows: &[R], encode: F) -> QuestResult<usize>
     where
-        F: FnOnce(&mut Sender) -> QuestResult<T>,
+        F: Fn(&mut BufferedWrites, &str, &[R]) -> QuestResult<usize>,
     {
-        let mut guard = self.sender.lock().unwrap_or_else(|p| p.into_inner());
-        f(&mut guard)
+        let mut sender = self.pool.get().await?;
+        let mut count = 0;
+        for chunk in rows.chunks(WRITE_CHUNK_ROWS) {
+            let mut writes = BufferedWrites::default();
+            count += encode(&mut writes, table, chunk)?;
+            for buffer in writes.0 {
+                sender.flush(table, buffer).await?;
+            }
+        }
+        Ok(count)
+    }
+
+    /// The write-ahead spool, unless disabled with an empty
+    /// `QUESTDB_SPOOL_DIR`.
+    pub fn spool(&self) -> Option<&Spool> {
+        self.pool.spool()
     }
 
     /// Run an arbitrary SQL query via the QuestDB HTTP `/exec` endpoint and
     /// return the raw JSON response body.
     pub async fn query_json(&self, sql: &str) -> AnySignalResult<serde_json::Value> {
-        let url = format!("http://{}/exec", self.addr);
-        let json: serde_json::Value = reqwest::Client::new()
-            .get(&url)
-            .query(&[("query", sql)])
-            .send()
-            .await?
-            .json()
-            .await?;
+        exec_json(&self.addr, sql).await
+    }
+
+    /// Run a SQL statement (DDL or query) via `/exec` and return the JSON
+    /// body, failing with [`AnySignalError::QuestQuery`] when QuestDB answers
+    /// with an `error` payload.
+    ///
+    /// [`AnySignalError::QuestQuery`]: crate::error::AnySignalError::QuestQuery
+    pub async fn exec(&self, sql: &str) -> AnySignalResult<serde_json::Value> {
+        let json = self.query_json(sql).await?;
+        check_error(&json, sql)?;
         Ok(json)
     }
 
-    /// Run a `SELECT count()` SQL query via the QuestDB HTTP `/exec` endpoint
-    /// and return the first cell as an `i64`.
+    /// Run `query` over its [`ReadPath`] and decode each result row into `T`
+    /// by column name.
     ///
-    /// Returns `Ok(0)` on any QuestDB-level error (e.g. table not yet created)
-    /// so callers can treat a missing table as "no data present".
-    pub async fn count(&self, sql: &str) -> AnySignalResult<i64> {
-        let url = format!("http://{}/exec", self.addr);
-        let json: serde_json::Value = reqwest::Client::new()
-            .get(&url)
-            .query(&[("query", sql)])
-            .send()
-            .await?
-            .json()
-            .await?;
-        if let Some(err) = json.get("error").and_then(|v| v.as_str()) {
-            tracing::warn!(sql, questdb_error = err, "QuestDB count query failed; treating as 0");
-            return Ok(0);
-        }
-        let count = json["dataset"][0][0].as_i64().unwrap_or(0);
-        tracing::debug!(sql, count, "QuestDB count");
+    /// PG wire queries run on one connection shared by every query this
+    /// client makes; use [`stream`](Self::stream) for results too large to
+    /// hold in memory.
+    pub async fn query<T>(&self, query: &Query) -> AnySignalResult<Vec<T>>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let sql = query.sql();
+        let rows = match query.read_path() {
+            ReadPath::Http => decode_rows(self.query_json(&sql).await?)?,
+            ReadPath::PgWire => self.pg.query(&sql).await?,
+        };
+        tracing::debug!(sql, rows = rows.len(), "QuestDB query");
+        Ok(rows)
+    }
+
+    /// Stream the rows of `query` in batches.
+    ///
+    /// Over [`ReadPath::PgWire`] rows arrive through a server-side cursor,
+    /// [`pg::FETCH_SIZE`] at a time, so memory stays bounded however large
+    /// the result; over [`ReadPath::Http`] the whole result is one batch.
+    pub fn stream<T>(&self, query: &Query) -> BoxStream<'static, AnySignalResult<Vec<T>>>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        match query.read_path() {
+            ReadPath::PgWire => pg::stream(self.pg.config().clone(), query.sql()).boxed(),
+            ReadPath::Http => {
+                let addr = self.addr.clone();
+                let sql = query.sql();
+                futures::stream::once(async move { decode_rows(exec_json(&addr, &sql).await?) })
+                    .boxed()
+            }
+        }
+    }
+
+    /// Run a [`Query::count`] query and return the count.
+    ///
+    /// A table that does not exist yet counts as `0` rows, so callers can
+    /// treat it as "no data present"; any other QuestDB error is returned.
+    pub async fn count(&self, query: &Query) -> AnySignalResult<i64> {
+        #[derive(Deserialize)]
+        struct Count {
+            count: i64,
+        }
+        let count = match self.query::<Count>(query).await {
+            Ok(rows) => rows.first().map(|r| r.count).unwrap_or(0),
+            Err(AnySignalError::QuestQuery(e)) if e.is_missing_table() => 0,
+            Err(e) => return Err(e),
+        };
+        tracing::debug!(sql = %query, count, "QuestDB count");
         Ok(count)
     }
 }
 
+/// `GET /exec` on the QuestDB HTTP endpoint at `addr`, returning the raw
+/// JSON body.
+async fn exec_json(addr: &str, sql: &str) -> AnySignalResult<serde_json::Value> {
+    let url = format!("http://{addr}/exec");
+    let json: serde_json::Value = reqwest::Client::new()
+        .get(&url)
+        .query(&[("query", sql)])
+        .send()
+        .await?
+        .json()
+        .await?;
+    Ok(json)
+}
+
 // ---------------------------------------------------------------------------
 // Hyperliquid asset_ctxs ingestion
 // ---------------------------------------------------------------------------
 
+/// Rows encoded per flush by [`QuestDbClient::write_rows`]; keeps one
+/// chunk's buffer in memory per writer instead of the whole batch.
+const WRITE_CHUNK_ROWS: usize = 8192;
+
 /// Flush the buffer when it exceeds this size (64 MiB), well below QuestDB's
 /// default 100 MiB cap.  A single day of asset_ctxs data is ~192 MiB
 /// uncompressed, so without chunking the flush always fails.
@@ -266,15 +708,20 @@ const BUFFER_FLUSH_THRESHOLD: usize = 64 * 1024 * 1024;
 ///   open_interest, funding, mark_px, oracle_px, prev_day_px, day_ntl_vlm,
 ///   mid_px (skipped when None), premium (skipped when None).
 ///
+/// Every line is tagged with the row's `market_type` symbol, plus `dex` for
+/// perps on builder-deployed DEXes.
+///
 /// The buffer is flushed automatically whenever it reaches
 /// [`BUFFER_FLUSH_THRESHOLD`], so callers never need to worry about the
 /// QuestDB maximum-buffer-size limit regardless of input size.
+/// Returns the number of `market_data` rows written.
 pub fn insert_asset_ctxs(
-    sender: &mut Sender,
+    sender: &mut impl IlpFlush,
     table: &str,
     rows: &[AssetCtxRow],
-) -> QuestResult<()> {
+) -> QuestResult<usize> {
     let mut buffer = Buffer::new();
+    let mut count: usize = 0;
 
     for row in rows {
         let ts_us = TimestampMicros::new(row.time.timestamp_micros());
@@ -289,33 +736,18 @@ pub fn insert_asset_ctxs(
         ];
 
         for (category, value) in metrics {
-            buffer
-                .table(table)?
-                .symbol("category", category)?
-                .symbol("ticker", &row.coin)?
-                .symbol("source", "HYPERLIQUID_S3")?
-                .column_f64("value", *value)?
-                .at(ts_us)?;
+            asset_ctx_line(&mut buffer, table, row, category, *value, ts_us)?;
         }
+        count += metrics.len();
 
         if let Some(v) = row.mid_px {
-            buffer
-                .table(table)?
-                .symbol("category", "mid_px")?
-                .symbol("ticker", &row.coin)?
-                .symbol("source", "HYPERLIQUID_S3")?
-                .column_f64("value", v)?
-                .at(ts_us)?;
+            asset_ctx_line(&mut buffer, table, row, "mid_px", v, ts_us)?;
+            count += 1;
         }
 
         if let Some(v) = row.premium {
-            buffer
-                .table(table)?
-                .symbol("category", "premium")?
-                .symbol("ticker", &row.coin)?
-                .symbol("source", "HYPERLIQUID_S3")?
-                .column_f64("value", v)?
-                .at(ts_us)?;
+            asset_ctx_line(&mut buffer, table, row, "premium", v, ts_us)?;
+            count += 1;
         }
 
         if buffer.len() >= BUFFER_FLUSH_THRESHOLD {
@@ -327,9 +759,81 @@ pub fn insert_asset_ctxs(
     if !buffer.is_empty() {
         sender.flush(&mut buffer)?;
     }
+    Ok(count)
+}
+
+/// One `market_data` line for a single asset_ctxs metric. `dex` is only
+/// written for builder-deployed perps.
+fn asset_ctx_line(
+    buffer: &mut Buffer,
+    table: &str,
+    row: &AssetCtxRow,
+    category: &str,
+    value: f64,
+    ts: TimestampMicros,
+) -> QuestResult<()> {
+    buffer
+        .table(table)?
+        .symbol("category", category)?
+        .symbol("ticker", &row.coin)?
+        .symbol("source", "HYPERLIQUID_S3")?
+        .symbol("market_type", row.market_type.as_str())?;
+    if !row.dex.is_empty() {
+        buffer.symbol("dex", &row.dex)?;
+    }
+    buffer.column_f64("value", value)?.at(ts)?;
     Ok(())
 }
 
+/// Write `asset_ctxs` rows to the wide `asset_ctx` table, one line per
+/// `(ts, coin)` with every field as its own column. Optional prices absent
+/// from the archive are left null. Returns the number of lines written.
+pub fn insert_asset_ctx_wide(
+    sender: &mut impl IlpFlush,
+    table: &str,
+    rows: &[AssetCtxRow],
+) -> QuestResult<usize> {
+    let mut buffer = Buffer::new();
+
+    for row in rows {
+        buffer
+            .table(table)?
+            .symbol("coin", &row.coin)?
+            .symbol("market_type", row.market_type.as_str())?;
+        if !row.dex.is_empty() {
+            buffer.symbol("dex", &row.dex)?;
+        }
+        buffer
+            .column_f64("funding", row.funding)?
+            .column_f64("open_interest", row.open_interest)?
+            .column_f64("prev_day_px", row.prev_day_px)?
+            .column_f64("day_ntl_vlm", row.day_ntl_vlm)?
+            .column_f64("oracle_px", row.oracle_px)?
+            .column_f64("mark_px", row.mark_px)?;
+        let optional = [
+            ("premium", row.premium),
+            ("mid_px", row.mid_px),
+            ("impact_bid_px", row.impact_bid_px),
+            ("impact_ask_px", row.impact_ask_px),
+        ];
+        for (column, value) in optional {
+            if let Some(v) = value {
+                buffer.column_f64(column, v)?;
+            }
+        }
+        buffer.at(TimestampMicros::new(row.time.timestamp_micros()))?;
+
+        if buffer.len() >= BUFFER_FLUSH_THRESHOLD {
+            sender.flush(&mut buffer)?;
+        }
+    }
+
+    if !buffer.is_empty() {
+        sender.flush(&mut buffer)?;
+    }
+    Ok(rows.len())
+}
+
 // ---------------------------------------------------------------------------
 // Hyperliquid L2 orderbook ingestion
 // ---------------------------------------------------------------------------
@@ -346,7 +850,7 @@ const L2_BUFFER_FLUSH_THRESHOLD: usize = 64 * 1024 * 1024;
 /// The buffer is flushed automatically at [`L2_BUFFER_FLUSH_THRESHOLD`].
 /// Returns the total number of rows written.
 pub fn insert_l2_snapshots(
-    sender: &mut Sender,
+    sender: &mut impl IlpFlush,
     table: &str,
     snapshots: &[L2Snapshot],
 ) -> QuestResult<usize> {
@@ -420,7 +924,7 @@ pub struct Fill1mAggregate {
 /// The buffer is flushed automatically at [`BUFFER_FLUSH_THRESHOLD`].
 /// Returns the total number of rows written.
 pub fn insert_hyperliquid_fills(
-    sender: &mut Sender,
+    sender: &mut impl IlpFlush,
     table: &str,
     fills: &[ParsedFill],
 ) -> QuestResult<usize> {
@@ -510,7 +1014,7 @@ pub struct MarketStateRow {
 /// The buffer is flushed automatically at [`BUFFER_FLUSH_THRESHOLD`].
 /// Returns the total number of rows written.
 pub fn insert_market_state_1m(
-    sender: &mut Sender,
+    sender: &mut impl IlpFlush,
     table: &str,
     rows: &[MarketStateRow],
 ) -> QuestResult<usize> {
@@ -568,7 +1072,14 @@ pub fn insert_market_state_1m(
 pub struct MarketStateRt1mRow {
     /// Left-closed minute bucket start, Unix milliseconds.
     pub minute_ms: i64,
+    /// Exchange venue, e.g. `HlPerp` or `BinPerp`.
+    pub venue: String,
     pub coin: String,
+    /// `perp` or `spot`.
+    pub market_type: String,
+    /// Perp DEX name; empty (written as NULL) for spot and the venue's
+    /// default DEX.
+    pub dex: String,
     /// Oracle price from the exchange asset context, when available.
     pub price_oracle: Option<f64>,
     /// Mark price from the exchange asset context.
@@ -596,7 +1107,7 @@ pub struct MarketStateRt1mRow {
 /// The buffer is flushed automatically at [`BUFFER_FLUSH_THRESHOLD`].
 /// Returns the total number of rows written.
 pub fn insert_market_state_rt_1m(
-    sender: &mut Sender,
+    sender: &mut impl IlpFlush,
     table: &str,
     rows: &[MarketStateRt1mRow],
 ) -> QuestResult<usize> {
@@ -608,7 +1119,14 @@ pub fn insert_market_state_rt_1m(
 
         let row_buf = buffer
             .table(table)?
+            .symbol("venue", &row.venue)?
             .symbol("coin", &row.coin)?
+            .symbol("market_type", &row.market_type)?;
+        // Left NULL for the default DEX.
+        if !row.dex.is_empty() {
+            row_buf.symbol("dex", &row.dex)?;
+        }
+        row_buf
             .column_f64("price_mark", row.price_mark)?
             .column_f64("price_mid", row.price_mid)?
             .column_f64("open_interest", row.open_interest)?
@@ -657,7 +1175,7 @@ pub fn insert_market_state_rt_1m(
 /// The buffer is flushed automatically at [`BUFFER_FLUSH_THRESHOLD`].
 /// Returns the total number of rows written.
 pub fn insert_hyperliquid_fill_1m_aggregate(
-    sender: &mut Sender,
+    sender: &mut impl IlpFlush,
     table: &str,
     rows: &[Fill1mAggregate],
 ) -> QuestResult<usize> {
@@ -690,6 +1208,139 @@ pub fn insert_hyperliquid_fill_1m_aggregate(
     Ok(count)
 }
 
+// ---------------------------------------------------------------------------
+// Predicted funding (all venues)
+// ---------------------------------------------------------------------------
+
+/// One row in `predicted_funding`: a venue's predicted next funding rate for a
+/// coin, snapshotted once per realtime minute bucket.
+#[derive(Debug, Clone)]
+pub struct PredictedFundingRow {
+    /// Left-closed minute bucket start, Unix milliseconds.
+    pub minute_ms: i64,
+    /// Venue the prediction is for, e.g. `HlPerp`, `BinPerp`, `BybitPerp`.
+    pub venue: String,
+    pub coin: String,
+    /// Predicted rate for the venue's own funding interval (not normalised).
+    pub funding_rate: f64,
+    /// When the predicted rate is next applied, Unix milliseconds.
+    pub next_funding_time_ms: i64,
+}
+
+/// Batch-insert [`PredictedFundingRow`]s into the `predicted_funding` table.
+///
+/// Returns the total number of rows written.
+pub fn insert_predicted_funding(
+    sender: &mut impl IlpFlush,
+    table: &str,
+    rows: &[PredictedFundingRow],
+) -> QuestResult<usize> {
+    let mut buffer = Buffer::new();
+    let mut count: usize = 0;
+
+    for row in rows {
+        let ts_us = TimestampMicros::new(row.minute_ms * 1_000);
+
+        buffer
+            .table(table)?
+            .symbol("venue", &row.venue)?
+            .symbol("coin", &row.coin)?
+            .column_f64("funding_rate", row.funding_rate)?
+            .column_ts(
+                "next_funding_time",
+                TimestampMicros::new(row.next_funding_time_ms * 1_000),
+            )?
+            .at(ts_us)?;
+
+        count += 1;
+
+        if buffer.len() >= BUFFER_FLUSH_THRESHOLD {
+            sender.flush(&mut buffer)?;
+        }
+    }
+
+    if !buffer.is_empty() {
+        sender.flush(&mut buffer)?;
+    }
+
+    Ok(count)
+}
+
+// ---------------------------------------------------------------------------
+// Funding spread (HL vs other venues)
+// ---------------------------------------------------------------------------
+
+/// One row in `funding_spread`: Hyperliquid vs one other venue for a coin in
+/// one minute bucket.
+///
+/// Rates are normalised to hourly before comparison since venues settle on
+/// different intervals. `long_venue` / `short_venue` / `carry_annualised`
+/// describe the best pair across *all* venues quoting the coin and are
+/// repeated on every row of that coin and minute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingSpreadRow {
+    /// Left-closed minute bucket start, Unix milliseconds.
+    pub minute_ms: i64,
+    pub coin: String,
+    /// The venue compared against Hyperliquid.
+    pub venue: String,
+    /// Hyperliquid predicted rate, normalised to one hour.
+    pub hl_rate_hourly: f64,
+    /// `venue` predicted rate, normalised to one hour.
+    pub venue_rate_hourly: f64,
+    /// `venue_rate_hourly - hl_rate_hourly`.
+    pub spread_hourly: f64,
+    /// `spread_hourly` annualised (× 8760).
+    pub spread_annualised: f64,
+    /// Venue with the lowest predicted funding — where to hold the long.
+    pub long_venue: String,
+    /// Venue with the highest predicted funding — where to hold the short.
+    pub short_venue: String,
+    /// Annualised funding collected by the long/short pair.
+    pub carry_annualised: f64,
+}
+
+/// Batch-insert [`FundingSpreadRow`]s into the `funding_spread` table.
+///
+/// Returns the total number of rows written.
+pub fn insert_funding_spread(
+    sender: &mut impl IlpFlush,
+    table: &str,
+    rows: &[FundingSpreadRow],
+) -> QuestResult<usize> {
+    let mut buffer = Buffer::new();
+    let mut count: usize = 0;
+
+    for row in rows {
+        let ts_us = TimestampMicros::new(row.minute_ms * 1_000);
+
+        buffer
+            .table(table)?
+            .symbol("coin", &row.coin)?
+            .symbol("venue", &row.venue)?
+            .symbol("long_venue", &row.long_venue)?
+            .symbol("short_venue", &row.short_venue)?
+            .column_f64("hl_rate_hourly", row.hl_rate_hourly)?
+            .column_f64("venue_rate_hourly", row.venue_rate_hourly)?
+            .column_f64("spread_hourly", row.spread_hourly)?
+            .column_f64("spread_annualised", row.spread_annualised)?
+            .column_f64("carry_annualised", row.carry_annualised)?
+            .at(ts_us)?;
+
+        count += 1;
+
+        if buffer.len() >= BUFFER_FLUSH_THRESHOLD {
+            sender.flush(&mut buffer)?;
+        }
+    }
+
+    if !buffer.is_empty() {
+        sender.flush(&mut buffer)?;
+    }
+
+    Ok(count)
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::Utc;
@@ -708,7 +1359,7 @@ mod tests {
         let time = Utc::now().timestamp_micros();
         let signal_1 = Signal {
             data: SignalData::Scalar(1.0),
-            timestamp_us: time.clone(),
+            timestamp_us: time,
             ..Default::default()
         };
         let mut signal_2 = signal_1.clone();
@@ -716,28 +1367,206 @@ mod tests {
 
         let data = [signal_1, signal_2].to_vec();
 
-        insert_signal_db(&mut sender, &signal_info, &data).unwrap();
+        let table = signal_info.data_type.table();
+        insert_signal_db(&mut sender, table, &signal_info, &data).unwrap();
     }
 
     #[tokio::test]
     async fn test_select_signal() {
         use super::*;
-        let query = "SELECT * FROM signal_scalar where info_id = 0;";
-        let signals = select_signal_db(SignalDataType::Scalar, query)
+        let db = QuestDbClient::new(&Config::from_env()).unwrap();
+        let query = signal_data::signal_query(&db, &SignalInfo::dummy());
+        let signals = select_signal_db(&db, SignalDataType::Scalar, &query)
             .await
             .unwrap();
         assert!(!signals.is_empty(), "is empty");
     }
 
+    #[test]
+    fn test_unseen_signals_skips_existing() {
+        use super::*;
+
+        let existing = Signal {
+            data: SignalData::Scalar(1.0),
+            timestamp_us: 1,
+            ..Default::default()
+        };
+        let new = Signal {
+            data: SignalData::Scalar(2.0),
+            timestamp_us: 2,
+            ..Default::default()
+        };
+        let skip: HashSet<Signal> = [existing.clone()].into_iter().collect();
+
+        assert_eq!(unseen_signals(&[existing, new.clone()], &skip), vec![new]);
+    }
+
+    #[test]
+    fn vector_signals_round_trip() {
+        use super::*;
+
+        let ts: chrono::DateTime<Utc> = "2025-06-01T00:00:00Z".parse().unwrap();
+        let vector = Signal {
+            info_id: 5,
+            timestamp_us: ts.timestamp_micros(),
+            data: SignalData::Vector(vec![1.5, -2.0]),
+        };
+        let mut buffer = Buffer::new();
+        signal_line(&mut buffer, "signal_vector", 5, &vector, 0).unwrap();
+        assert_eq!(
+            buffer.as_str(),
+            "signal_vector info_id=5i,idx=0i,len=2i,value=1.5 1748736000000000000\n\
+             signal_vector info_id=5i,idx=1i,len=2i,value=-2.0 1748736000000000000\n"
+        );
+
+        let element = |idx, len, value| SignalVectorRow {
+            info_id: 5,
+            idx,
+            len,
+            value: Some(value),
+            timestamp: ts,
+        };
+        // a stale third element from an earlier, longer write is ignored
+        let rows = vec![element(0, 2, 1.5), element(1, 2, -2.0), element(2, 3, 9.0)];
+        assert_eq!(vector_signals(rows), vec![vector]);
+        // a vector cut short is dropped
+        assert!(vector_signals(vec![element(0, 2, 1.5)]).is_empty());
+    }
+
+    #[test]
+    fn text_signals_carry_a_content_hash() {
+        use super::*;
+
+        let text = Signal {
+            info_id: 4,
+            timestamp_us: 1_748_736_000_000_000,
+            data: SignalData::Text("a".to_string()),
+        };
+        let mut buffer = Buffer::new();
+        signal_line(&mut buffer, "signal_text", 4, &text, 0).unwrap();
+        assert_eq!(
+            buffer.as_str(),
+            "signal_text info_id=4i,value=\"a\",hash=-5808556873153909620i 1748736000000000000\n"
+        );
+        assert_ne!(text_hash("a"), text_hash("b"));
+    }
+
+    /// `signal_structured` row of field `name` at 2025-06-01 for info id 6.
+    fn structured_row(
+        name: &str,
+        len: i64,
+        written: i64,
+        value: Option<f64>,
+        text: Option<&str>,
+    ) -> super::SignalStructuredRow {
+        super::SignalStructuredRow {
+            info_id: 6,
+            field: name.to_string(),
+            key: None,
+            len,
+            written: Some(written),
+            value,
+            text: text.map(str::to_string),
+            timestamp: "2025-06-01T00:00:00Z".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn structured_signals_round_trip() {
+        use super::*;
+
+        let ts: chrono::DateTime<Utc> = "2025-06-01T00:00:00Z".parse().unwrap();
+        let fields: BTreeMap<String, FieldValue> = [
+            ("price".to_string(), FieldValue::Number(0.42)),
+            ("spread".to_string(), FieldValue::Number(f64::NAN)),
+            ("symbol".to_string(), FieldValue::Text("TRUMP".to_string())),
+        ]
+        .into();
+        let structured = Signal {
+            info_id: 6,
+            timestamp_us: ts.timestamp_micros(),
+            data: SignalData::Structured(fields.clone()),
+        };
+        let mut buffer = Buffer::new();
+        signal_line(&mut buffer, "signal_structured", 6, &structured, 7).unwrap();
+        assert_eq!(
+            buffer.as_str(),
+            "signal_structured,field=price info_id=6i,len=3i,written=7i,value=0.42 1748736000000000000\n\
+             signal_structured,field=spread info_id=6i,len=3i,written=7i 1748736000000000000\n\
+             signal_structured,field=symbol info_id=6i,len=3i,written=7i,text=\"TRUMP\" 1748736000000000000\n"
+        );
+
+        // `volume` is left over from an earlier write with other fields
+        let rows = vec![
+            structured_row("price", 3, 7, Some(0.42), None),
+            structured_row("spread", 3, 7, None, None),
+            structured_row("symbol", 3, 7, None, Some("TRUMP")),
+            structured_row("volume", 2, 3, Some(1.0), None),
+        ];
+        let read = structured_signals(rows);
+        assert_eq!(read.len(), 1);
+        let SignalData::Structured(read_fields) = &read[0].data else {
+            panic!("not structured: {:?}", read[0].data);
+        };
+        assert_eq!(read_fields.len(), 3);
+        assert_eq!(read_fields["price"], fields["price"]);
+        assert_eq!(read_fields["symbol"], fields["symbol"]);
+        assert!(matches!(read_fields["spread"], FieldValue::Number(v) if v.is_nan()));
+    }
+
+    #[test]
+    fn structured_signal_cut_short_is_dropped() {
+        use super::*;
+
+        let rows = vec![structured_row("price", 3, 7, Some(0.42), None)];
+        assert!(structured_signals(rows).is_empty());
+    }
+
+    #[test]
+    fn keyed_structured_records_stay_apart() {
+        use super::*;
+
+        // records of one signal sharing a timestamp
+        let keyed = |key| SignalStructuredRow {
+            key: Some(key),
+            ..structured_row("title", 1, 7, None, Some("WWDC"))
+        };
+        assert_eq!(structured_signals(vec![keyed(1), keyed(2)]).len(), 2);
+
+        let record = Signal {
+            info_id: 6,
+            timestamp_us: 1_748_736_000_000_000,
+            data: SignalData::Structured(
+                [("title".to_string(), FieldValue::Text("WWDC".to_string()))].into(),
+            ),
+        };
+        let scalar = Signal {
+            data: SignalData::Scalar(1.0),
+            ..record.clone()
+        };
+        let mut writes = BufferedWrites::default();
+        let written = insert_keyed_signal_rows(
+            &mut writes,
+            "signal_structured",
+            &[(2, record), (3, scalar)],
+        )
+        .unwrap();
+        // the scalar is skipped and not counted
+        assert_eq!(written, 1);
+        let line = writes.0[0].as_str();
+        assert!(line.starts_with("signal_structured,field=title info_id=6i,key=2i,len=1i,"));
+        assert!(line.ends_with(",text=\"WWDC\" 1748736000000000000\n"));
+    }
+
     #[tokio::test]
     async fn test_insert_unique_signal() {
         use super::*;
 
         use questdb::ingress::Sender;
         let mut sender = Sender::from_conf("http::addr=localhost:9000;").unwrap();
-        let skip_query = "SELECT * FROM signal_scalar where info_id = 0;";
-
+        let db = QuestDbClient::new(&Config::from_env()).unwrap();
         let signal_info = SignalInfo::dummy();
+        let skip_query = signal_data::signal_query(&db, &signal_info);
 
         let signal_1 = Signal {
             data: SignalData::Scalar(1.0),
@@ -749,7 +1578,7 @@ mod tests {
 
         let data = [signal_1, signal_2].to_vec();
 
-        insert_unique_signal_db(&mut sender, &signal_info, &data, skip_query)
+        insert_unique_signal_db(&mut sender, &db, &signal_info, &data, &skip_query)
             .await
             .unwrap();
     }
@@ -0,0 +1,303 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use futures::Stream;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Number, Value};
+use tokio::sync::{mpsc, Mutex};
+use tokio_postgres::error::ErrorPosition;
+use tokio_postgres::types::Type;
+use tokio_postgres::{Client, NoTls, Row};
+
+use super::query::QueryError;
+use crate::config::Config;
+use crate::error::AnySignalResult;
+
+/// Rows fetched per cursor round trip when streaming over PG wire.
+pub const FETCH_SIZE: i32 = 10_000;
+
+/// Result batches buffered ahead of a slow consumer before the cursor stops
+/// fetching.
+const STREAM_BUFFER: usize = 2;
+
+// ---------------------------------------------------------------------------
+// Connection
+// ---------------------------------------------------------------------------
+
+/// Connection settings for QuestDB's PostgreSQL wire endpoint (port 8812).
+#[derive(Debug, Clone)]
+pub struct PgConfig {
+    pub addr: String,
+    pub user: String,
+    pub password: String,
+}
+
+impl PgConfig {
+    /// Uses `questdb_pg_addr` and the QuestDB credentials, falling back to
+    /// QuestDB's built-in `admin` / `quest` when auth is unset.
+    pub fn new(config: &Config) -> Self {
+        Self {
+            addr: config.questdb_pg_addr.clone(),
+            user: config
+                .questdb_user
+                .clone()
+                .unwrap_or_else(|| "admin".to_string()),
+            password: config
+                .questdb_password
+                .clone()
+                .unwrap_or_else(|| "quest".to_string()),
+        }
+    }
+
+    /// Driver settings for this endpoint, built field by field so credentials
+    /// need no quoting.
+    fn pg_config(&self) -> tokio_postgres::Config {
+        let (host, port) = self
+            .addr
+            .rsplit_once(':')
+            .and_then(|(host, port)| Some((host, port.parse().ok()?)))
+            .unwrap_or((self.addr.as_str(), 8812));
+        let mut config = tokio_postgres::Config::new();
+        config
+            .host(host)
+            .port(port)
+            .user(&self.user)
+            .password(&self.password)
+            .dbname("qdb");
+        config
+    }
+
+    /// Open a connection and drive it on a background task.
+    async fn connect(&self) -> Result<Client, tokio_postgres::Error> {
+        let (client, connection) = self.pg_config().connect(NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::warn!(error = %e, "QuestDB PG connection closed with error");
+            }
+        });
+        Ok(client)
+    }
+}
+
+/// A PG connection shared by one-shot queries, opened on first use and
+/// reopened once the server closes it.
+///
+/// Concurrent queries pipeline over the same connection; cursors (see
+/// [`stream`]) still take a connection of their own since they hold a
+/// transaction open.
+#[derive(Debug)]
+pub struct PgClient {
+    config: PgConfig,
+    client: Mutex<Option<Arc<Client>>>,
+}
+
+impl PgClient {
+    pub fn new(config: PgConfig) -> Self {
+        Self {
+            config,
+            client: Mutex::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &PgConfig {
+        &self.config
+    }
+
+    /// Run `sql` to completion on the shared connection and decode every row
+    /// into `T`.
+    pub async fn query<T: DeserializeOwned>(&self, sql: &str) -> AnySignalResult<Vec<T>> {
+        let rows = async { self.client().await?.query(sql, &[]).await }
+            .await
+            .map_err(|e| query_error(sql, e))?;
+        decode_pg_rows(sql, &rows)
+    }
+
+    async fn client(&self) -> Result<Arc<Client>, tokio_postgres::Error> {
+        let mut slot = self.client.lock().await;
+        if let Some(client) = slot.as_ref().filter(|c| !c.is_closed()) {
+            return Ok(client.clone());
+        }
+        let client = Arc::new(self.config.connect().await?);
+        *slot = Some(client.clone());
+        Ok(client)
+    }
+}
+
+/// Map a driver error to the same [`QueryError`] the HTTP path produces.
+fn query_error(sql: &str, e: tokio_postgres::Error) -> QueryError {
+    let db = e.as_db_error();
+    QueryError {
+        query: sql.to_string(),
+        message: db
+            .map(|d| d.message().to_string())
+            .unwrap_or_else(|| e.to_string()),
+        position: db.and_then(|d| match d.position() {
+            Some(ErrorPosition::Original(p)) => Some(i64::from(*p)),
+            _ => None,
+        }),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Streaming reader
+// ---------------------------------------------------------------------------
+
+/// Stream the rows of `sql` in batches of [`FETCH_SIZE`] through a server-side
+/// cursor, decoding each row into `T` by column name.
+///
+/// The cursor runs on its own connection in a background task and stays at
+/// most [`STREAM_BUFFER`] batches ahead of the consumer; dropping the stream
+/// closes it.
+pub fn stream<T>(pg: PgConfig, sql: String) -> impl Stream<Item = AnySignalResult<Vec<T>>>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+    tokio::spawn(async move {
+        if let Err(e) = run_cursor(&pg, &sql, &tx).await {
+            let _ = tx.send(Err(query_error(&sql, e).into())).await;
+        }
+    });
+    futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|batch| (batch, rx))
+    })
+}
+
+async fn run_cursor<T: DeserializeOwned>(
+    pg: &PgConfig,
+    sql: &str,
+    tx: &mpsc::Sender<AnySignalResult<Vec<T>>>,
+) -> Result<(), tokio_postgres::Error> {
+    let mut client = pg.connect().await?;
+    let txn = client.transaction().await?;
+    let portal = txn.bind(sql, &[]).await?;
+    let mut total = 0usize;
+    loop {
+        let rows = txn.query_portal(&portal, FETCH_SIZE).await?;
+        let fetched = rows.len();
+        total += fetched;
+        if fetched > 0 && tx.send(decode_pg_rows(sql, &rows)).await.is_err() {
+            // consumer dropped the stream
+            break;
+        }
+        if fetched < FETCH_SIZE as usize {
+            break;
+        }
+    }
+    tracing::debug!(sql, rows = total, "QuestDB PG query");
+    txn.commit().await
+}
+
+// ---------------------------------------------------------------------------
+// Row decoding
+// ---------------------------------------------------------------------------
+
+/// Decode PG rows into `T` via the same JSON shapes `/exec` returns, so one
+/// row struct works for both read paths. A column that fails to decode is
+/// reported as a [`QueryError`] against `sql`.
+pub fn decode_pg_rows<T: DeserializeOwned>(sql: &str, rows: &[Row]) -> AnySignalResult<Vec<T>> {
+    rows.iter()
+        .map(|row| {
+            let object = row_to_object(sql, row)?;
+            serde_json::from_value(Value::Object(object)).map_err(Into::into)
+        })
+        .collect()
+}
+
+fn row_to_object(sql: &str, row: &Row) -> Result<Map<String, Value>, QueryError> {
+    let mut object = Map::with_capacity(row.len());
+    for (i, column) in row.columns().iter().enumerate() {
+        let value = cell(row, i, column.type_()).map_err(|e| QueryError {
+            query: sql.to_string(),
+            message: format!("column '{}' ({}): {e}", column.name(), column.type_()),
+            position: None,
+        })?;
+        object.insert(column.name().to_string(), value);
+    }
+    Ok(object)
+}
+
+fn cell(row: &Row, i: usize, ty: &Type) -> Result<Value, tokio_postgres::Error> {
+    let value = match *ty {
+        Type::BOOL => row.try_get::<_, Option<bool>>(i)?.map(Value::Bool),
+        Type::INT2 => row.try_get::<_, Option<i16>>(i)?.map(Value::from),
+        Type::INT4 => row.try_get::<_, Option<i32>>(i)?.map(Value::from),
+        Type::INT8 => row.try_get::<_, Option<i64>>(i)?.map(Value::from),
+        Type::FLOAT4 => row
+            .try_get::<_, Option<f32>>(i)?
+            .and_then(|v| Number::from_f64(f64::from(v)))
+            .map(Value::Number),
+        Type::FLOAT8 => row
+            .try_get::<_, Option<f64>>(i)?
+            .and_then(Number::from_f64)
+            .map(Value::Number),
+        Type::TIMESTAMP => row
+            .try_get::<_, Option<NaiveDateTime>>(i)?
+            .map(|t| Value::String(format_ts(t.and_utc()))),
+        Type::TIMESTAMPTZ => row
+            .try_get::<_, Option<DateTime<Utc>>>(i)?
+            .map(|t| Value::String(format_ts(t))),
+        Type::DATE => row
+            .try_get::<_, Option<NaiveDate>>(i)?
+            .map(|d| Value::String(d.to_string())),
+        // symbols, varchar, char and anything else QuestDB sends as text
+        _ => row.try_get::<_, Option<String>>(i)?.map(Value::String),
+    };
+    Ok(value.unwrap_or(Value::Null))
+}
+
+/// Timestamps in the `/exec` format (`2025-06-01T12:00:00.000000Z`).
+fn format_ts(t: DateTime<Utc>) -> String {
+    t.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::query::Query;
+    use crate::database::QuestDbClient;
+    use futures::StreamExt;
+
+    #[test]
+    fn pg_config_splits_host_and_port() {
+        let pg = PgConfig {
+            addr: "questdb:8812".to_string(),
+            user: "admin".to_string(),
+            password: "p@ss word='x'".to_string(),
+        };
+        let config = pg.pg_config();
+        assert_eq!(
+            config.get_hosts(),
+            &[tokio_postgres::config::Host::Tcp("questdb".to_string())]
+        );
+        assert_eq!(config.get_ports(), &[8812]);
+        assert_eq!(config.get_user(), Some("admin"));
+        assert_eq!(config.get_password(), Some("p@ss word='x'".as_bytes()));
+        assert_eq!(config.get_dbname(), Some("qdb"));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local QuestDB: docker compose up -d questdb"]
+    async fn streams_in_fetch_size_batches() {
+        #[derive(serde::Deserialize)]
+        struct Row {
+            x: i64,
+            ts: DateTime<Utc>,
+        }
+
+        dotenvy::dotenv().ok();
+        let db = QuestDbClient::new(&Config::from_env()).unwrap();
+        let query = Query::select(&["x"])
+            .select_expr("timestamp_sequence(0, 1000000) ts")
+            .from_function("long_sequence", &[(i64::from(FETCH_SIZE) * 2 + 1).into()]);
+        let batches: Vec<Vec<Row>> = db.stream(&query).map(|b| b.unwrap()).collect().await;
+        assert_eq!(batches.len(), 3);
+        let last = batches.last().unwrap().last().unwrap();
+        assert_eq!(last.x, i64::from(FETCH_SIZE) * 2 + 1);
+        assert_eq!(last.ts.timestamp(), i64::from(FETCH_SIZE) * 2);
+    }
+}
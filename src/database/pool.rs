@@ -0,0 +1,188 @@
+use std::sync::{Arc, Mutex};
+
+use questdb::ingress::{Buffer, Sender};
+use questdb::{Error as QuestError, ErrorCode, Result as QuestResult};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use super::spool::Spool;
+use super::{questdb_sender, IlpFlush, SpoolingSender};
+use crate::config::Config;
+
+/// A fixed-size pool of ILP [`Sender`]s shared by one [`super::QuestDbClient`].
+///
+/// Checkout is async: when every sender is in use, [`Self::get`] waits on a
+/// semaphore instead of blocking a Tokio worker. Senders are created lazily
+/// up to the pool size and returned on drop, so concurrent backfill
+/// partitions each flush over their own HTTP connection.
+pub struct SenderPool {
+    config: Config,
+    idle: Mutex<Vec<Sender>>,
+    permits: Arc<Semaphore>,
+    size: usize,
+    spool: Option<Spool>,
+}
+
+/// Pool size and current checkouts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolStatus {
+    pub size: usize,
+    pub in_use: usize,
+}
+
+impl SenderPool {
+    /// Build a pool of `QUESTDB_SENDER_POOL_SIZE` senders. One sender is
+    /// created up front so a bad `QUESTDB_ADDR` fails here, not on first write.
+    pub fn new(config: &Config) -> QuestResult<Arc<Self>> {
+        let size = config.questdb_sender_pool_size.max(1);
+        let first = questdb_sender(config)?;
+        Ok(Arc::new(Self {
+            config: config.clone(),
+            idle: Mutex::new(vec![first]),
+            permits: Arc::new(Semaphore::new(size)),
+            size,
+            spool: Spool::from_config(config),
+        }))
+    }
+
+    /// Check out a sender, waiting while all of them are in use.
+    pub async fn get(self: &Arc<Self>) -> QuestResult<PooledSender> {
+        let permit = Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .map_err(|e| QuestError::new(ErrorCode::InvalidApiCall, e.to_string()))?;
+        let sender = match self.lock_idle().pop() {
+            Some(sender) => sender,
+            None => questdb_sender(&self.config)?,
+        };
+        Ok(PooledSender {
+            sender: Some(sender),
+            pool: Arc::clone(self),
+            _permit: permit,
+        })
+    }
+
+    pub fn spool(&self) -> Option<&Spool> {
+        self.spool.as_ref()
+    }
+
+    pub fn status(&self) -> PoolStatus {
+        PoolStatus {
+            size: self.size,
+            in_use: self.size - self.permits.available_permits(),
+        }
+    }
+
+    /// The idle list is only held to push or pop, never across a flush.
+    fn lock_idle(&self) -> std::sync::MutexGuard<'_, Vec<Sender>> {
+        self.idle.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+/// A sender checked out of a [`SenderPool`]; returned to it on drop.
+pub struct PooledSender {
+    sender: Option<Sender>,
+    pool: Arc<SenderPool>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl PooledSender {
+    /// Flush `buffer` for `table` on Tokio's blocking thread pool, spooling it
+    /// when QuestDB rejects it (see [`SpoolingSender`]).
+    pub async fn flush(&mut self, table: &str, mut buffer: Buffer) -> QuestResult<()> {
+        let mut sender = match self.sender.take() {
+            Some(sender) => sender,
+            None => questdb_sender(&self.pool.config)?,
+        };
+        let spool = self.pool.spool.clone();
+        let table = table.to_string();
+        let (sender, result) = tokio::task::spawn_blocking(move || {
+            let result = SpoolingSender {
+                sender: &mut sender,
+                spool: spool.as_ref(),
+                table: &table,
+            }
+            .flush(&mut buffer);
+            (sender, result)
+        })
+        .await
+        .map_err(|e| QuestError::new(ErrorCode::SocketError, format!("ILP flush task: {e}")))?;
+        // a sender that must close is dropped; the pool opens a new one
+        if !sender.must_close() {
+            self.sender = Some(sender);
+        }
+        result
+    }
+}
+
+impl Drop for PooledSender {
+    fn drop(&mut self) {
+        if let Some(sender) = self.sender.take() {
+            self.pool.lock_idle().push(sender);
+        }
+    }
+}
+
+/// Collects the buffers an `insert_*` writer flushes, so they can be sent
+/// afterwards through a [`PooledSender`] without blocking the caller.
+#[derive(Default)]
+pub struct BufferedWrites(pub Vec<Buffer>);
+
+impl IlpFlush for BufferedWrites {
+    fn flush(&mut self, buffer: &mut Buffer) -> QuestResult<()> {
+        let full = std::mem::replace(buffer, Buffer::new());
+        self.0.push(full);
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{insert_predicted_funding, PredictedFundingRow};
+
+    fn config(size: usize) -> Config {
+        let mut config = Config::from_env();
+        config.questdb_addr = "localhost:9000".to_string();
+        config.questdb_sender_pool_size = size;
+        config.questdb_spool_dir = String::new();
+        config
+    }
+
+    #[tokio::test]
+    async fn checkout_waits_for_a_free_sender() {
+        let pool = SenderPool::new(&config(2)).unwrap();
+        let a = pool.get().await.unwrap();
+        let _b = pool.get().await.unwrap();
+        assert_eq!(pool.status(), PoolStatus { size: 2, in_use: 2 });
+
+        let waiting = tokio::time::timeout(std::time::Duration::from_millis(50), pool.get()).await;
+        assert!(waiting.is_err(), "third checkout should wait");
+
+        drop(a);
+        let _c = pool.get().await.unwrap();
+        assert_eq!(pool.lock_idle().len(), 0);
+        assert_eq!(pool.status().in_use, 2);
+    }
+
+    #[test]
+    fn buffered_writes_collect_each_flush() {
+        let rows = [PredictedFundingRow {
+            minute_ms: 1_748_779_200_000,
+            venue: "HlPerp".to_string(),
+            coin: "BTC".to_string(),
+            funding_rate: 0.0001,
+            next_funding_time_ms: 1_748_782_800_000,
+        }];
+        let mut writes = BufferedWrites::default();
+        let n = insert_predicted_funding(&mut writes, "predicted_funding", &rows).unwrap();
+        assert_eq!(n, 1);
+        assert_eq!(writes.0.len(), 1);
+        assert!(writes.0[0]
+            .as_str()
+            .starts_with("predicted_funding,venue=HlPerp,coin=BTC "));
+    }
+}
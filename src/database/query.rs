@@ -0,0 +1,465 @@
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use super::response::Column;
+use crate::error::{AnySignalError, AnySignalResult, ThisError};
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+/// An `error` payload returned by QuestDB's `/exec` endpoint.
+#[derive(Debug, Clone, PartialEq, ThisError)]
+#[error("{message} (position {position:?}) in query: {query}")]
+pub struct QueryError {
+    pub query: String,
+    pub message: String,
+    /// Character offset of the error in `query`, when QuestDB reports one.
+    pub position: Option<i64>,
+}
+
+impl QueryError {
+    /// The query referenced a table that has not been created yet.
+    pub fn is_missing_table(&self) -> bool {
+        self.message.contains("table does not exist")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Values + identifiers
+// ---------------------------------------------------------------------------
+
+/// A literal bound into a [`Query`], rendered with proper escaping.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Timestamp(DateTime<Utc>),
+}
+
+impl SqlValue {
+    /// SQL literal, quoted and escaped where needed.
+    pub fn render(&self) -> String {
+        match self {
+            SqlValue::Str(s) => format!("'{}'", s.replace('\'', "''")),
+            SqlValue::Int(v) => v.to_string(),
+            SqlValue::Float(v) => v.to_string(),
+            SqlValue::Bool(v) => v.to_string(),
+            SqlValue::Timestamp(t) => format!("'{}'", t.format("%Y-%m-%dT%H:%M:%S%.6fZ")),
+        }
+    }
+}
+
+impl From<&str> for SqlValue {
+    fn from(v: &str) -> Self {
+        SqlValue::Str(v.to_string())
+    }
+}
+
+impl From<String> for SqlValue {
+    fn from(v: String) -> Self {
+        SqlValue::Str(v)
+    }
+}
+
+impl From<&String> for SqlValue {
+    fn from(v: &String) -> Self {
+        SqlValue::Str(v.clone())
+    }
+}
+
+impl From<i64> for SqlValue {
+    fn from(v: i64) -> Self {
+        SqlValue::Int(v)
+    }
+}
+
+impl From<f64> for SqlValue {
+    fn from(v: f64) -> Self {
+        SqlValue::Float(v)
+    }
+}
+
+impl From<bool> for SqlValue {
+    fn from(v: bool) -> Self {
+        SqlValue::Bool(v)
+    }
+}
+
+impl From<DateTime<Utc>> for SqlValue {
+    fn from(v: DateTime<Utc>) -> Self {
+        SqlValue::Timestamp(v)
+    }
+}
+
+/// Interpreted as UTC.
+impl From<NaiveDateTime> for SqlValue {
+    fn from(v: NaiveDateTime) -> Self {
+        SqlValue::Timestamp(v.and_utc())
+    }
+}
+
+/// Midnight UTC.
+impl From<NaiveDate> for SqlValue {
+    fn from(v: NaiveDate) -> Self {
+        SqlValue::Timestamp(v.and_time(chrono::NaiveTime::MIN).and_utc())
+    }
+}
+
+/// Keywords that appear as column names in QuestDB metadata functions
+/// (`table_columns()` has a `column` column).
+const RESERVED: &[&str] = &[
+    "column", "from", "group", "order", "select", "table", "where",
+];
+
+/// Quote an identifier unless it is a plain `[A-Za-z_][A-Za-z0-9_]*` name
+/// that is not a reserved keyword.
+pub fn ident(name: &str) -> String {
+    let plain = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !RESERVED.contains(&name.to_ascii_lowercase().as_str());
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Query builder
+// ---------------------------------------------------------------------------
+
+/// Which QuestDB endpoint a [`Query`] is read through.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReadPath {
+    /// HTTP `/exec` (port 9000): the whole result set as one JSON document.
+    /// Cheap for small lookups such as counts and metadata.
+    #[default]
+    Http,
+    /// PostgreSQL wire (port 8812): rows streamed through a server-side
+    /// cursor. Use for large analytical reads.
+    PgWire,
+}
+
+/// Builder for the `SELECT … FROM … WHERE … ORDER BY … LIMIT` queries our
+/// readers run against QuestDB. Column and table names are quoted when
+/// needed, and values are rendered as escaped literals.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    select: Vec<String>,
+    from: String,
+    filters: Vec<String>,
+    sample_by: Option<String>,
+    group_by: Vec<String>,
+    order_by: Vec<String>,
+    limit: Option<u64>,
+    read_path: ReadPath,
+}
+
+impl Query {
+    /// `SELECT <columns>`.
+    pub fn select(columns: &[&str]) -> Self {
+        Self {
+            select: columns.iter().map(|c| ident(c)).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// `SELECT count()`, decoded by [`QuestDbClient::count`].
+    ///
+    /// [`QuestDbClient::count`]: super::QuestDbClient::count
+    pub fn count() -> Self {
+        Self::default().select_expr("count()")
+    }
+
+    /// Add a raw select expression such as `sum(quantity) volume`.
+    /// Not escaped — never pass user input.
+    pub fn select_expr(mut self, expr: &str) -> Self {
+        self.select.push(expr.to_string());
+        self
+    }
+
+    /// Read this query through `path` instead of HTTP `/exec`.
+    pub fn via(mut self, path: ReadPath) -> Self {
+        self.read_path = path;
+        self
+    }
+
+    pub fn read_path(&self) -> ReadPath {
+        self.read_path
+    }
+
+    pub fn from(mut self, table: &str) -> Self {
+        self.from = ident(table);
+        self
+    }
+
+    /// `FROM <function>(<args>)` for table functions such as
+    /// `table_partitions('market_data')`.
+    pub fn from_function(mut self, function: &str, args: &[SqlValue]) -> Self {
+        let args = args
+            .iter()
+            .map(SqlValue::render)
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.from = format!("{}({args})", ident(function));
+        self
+    }
+
+    /// `<column> >= from AND <column> < to`.
+    pub fn time_range(
+        mut self,
+        column: &str,
+        from: impl Into<SqlValue>,
+        to: impl Into<SqlValue>,
+    ) -> Self {
+        let column = ident(column);
+        self.filters
+            .push(format!("{column} >= {}", from.into().render()));
+        self.filters
+            .push(format!("{column} < {}", to.into().render()));
+        self
+    }
+
+    /// `<column> = value`.
+    pub fn eq(mut self, column: &str, value: impl Into<SqlValue>) -> Self {
+        self.filters
+            .push(format!("{} = {}", ident(column), value.into().render()));
+        self
+    }
+
+    /// `<column> IN (values…)`. An empty list matches nothing.
+    pub fn in_list<V: Into<SqlValue>>(
+        mut self,
+        column: &str,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let values: Vec<String> = values.into_iter().map(|v| v.into().render()).collect();
+        if values.is_empty() {
+            self.filters.push("false".to_string());
+        } else {
+            self.filters
+                .push(format!("{} IN ({})", ident(column), values.join(", ")));
+        }
+        self
+    }
+
+    /// `SAMPLE BY <interval>`, e.g. `1h`, bucketing on the designated
+    /// timestamp. Not escaped — validate the interval first.
+    pub fn sample_by(mut self, interval: &str) -> Self {
+        self.sample_by = Some(interval.to_string());
+        self
+    }
+
+    pub fn group_by(mut self, column: &str) -> Self {
+        self.group_by.push(ident(column));
+        self
+    }
+
+    pub fn order_by(mut self, column: &str) -> Self {
+        self.order_by.push(ident(column));
+        self
+    }
+
+    pub fn order_by_desc(mut self, column: &str) -> Self {
+        self.order_by.push(format!("{} DESC", ident(column)));
+        self
+    }
+
+    pub fn limit(mut self, n: u64) -> Self {
+        self.limit = Some(n);
+        self
+    }
+
+    pub fn sql(&self) -> String {
+        let select = if self.select.is_empty() {
+            "*".to_string()
+        } else {
+            self.select.join(", ")
+        };
+        let mut sql = format!("SELECT {select} FROM {}", self.from);
+        if !self.filters.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&self.filters.join(" AND "));
+        }
+        if let Some(interval) = &self.sample_by {
+            sql.push_str(&format!(" SAMPLE BY {interval}"));
+        }
+        if !self.group_by.is_empty() {
+            sql.push_str(" GROUP BY ");
+            sql.push_str(&self.group_by.join(", "));
+        }
+        if !self.order_by.is_empty() {
+            sql.push_str(" ORDER BY ");
+            sql.push_str(&self.order_by.join(", "));
+        }
+        if let Some(n) = self.limit {
+            sql.push_str(&format!(" LIMIT {n}"));
+        }
+        sql
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.sql())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Result decoding
+// ---------------------------------------------------------------------------
+
+/// `/exec` body covering both the success and error shapes.
+#[derive(Debug, Deserialize)]
+struct ExecResponse {
+    #[serde(default)]
+    query: String,
+    #[serde(default)]
+    columns: Vec<Column>,
+    #[serde(default)]
+    dataset: Vec<Vec<serde_json::Value>>,
+    error: Option<String>,
+    position: Option<i64>,
+}
+
+/// Turn an `/exec` `error` payload into [`AnySignalError::QuestQuery`].
+pub fn check_error(json: &serde_json::Value, sql: &str) -> AnySignalResult<()> {
+    match json.get("error").and_then(|v| v.as_str()) {
+        Some(message) => Err(AnySignalError::QuestQuery(QueryError {
+            query: json
+                .get("query")
+                .and_then(|v| v.as_str())
+                .unwrap_or(sql)
+                .to_string(),
+            message: message.to_string(),
+            position: json.get("position").and_then(|v| v.as_i64()),
+        })),
+        None => Ok(()),
+    }
+}
+
+/// Decode an `/exec` response into `T`s, matching dataset cells to struct
+/// fields by **column name** rather than position. Use `#[serde(rename)]` or
+/// SQL aliases when a column name is not a valid field name (e.g. `count()`
+/// → `count`).
+pub fn decode_rows<T: DeserializeOwned>(json: serde_json::Value) -> AnySignalResult<Vec<T>> {
+    let resp: ExecResponse = serde_json::from_value(json)?;
+    if let Some(message) = resp.error {
+        return Err(AnySignalError::QuestQuery(QueryError {
+            query: resp.query,
+            message,
+            position: resp.position,
+        }));
+    }
+    let names: Vec<String> = resp.columns.into_iter().map(|c| c.name).collect();
+    resp.dataset
+        .into_iter()
+        .map(|row| {
+            let object: serde_json::Map<String, serde_json::Value> =
+                names.iter().cloned().zip(row).collect();
+            serde_json::from_value(serde_json::Value::Object(object)).map_err(Into::into)
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_filtered_time_range_query() {
+        let from = NaiveDate::from_ymd_opt(2025, 6, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let q = Query::select(&["ts", "coin", "quantity"])
+            .from("hyperliquid_fill_1m_aggregate")
+            .time_range("ts", from, from + chrono::Duration::hours(1))
+            .eq("source", "HYPERLIQUID_S3")
+            .in_list("coin", ["BTC", "ETH"])
+            .order_by("ts")
+            .limit(10);
+        assert_eq!(
+            q.sql(),
+            "SELECT ts, coin, quantity FROM hyperliquid_fill_1m_aggregate \
+             WHERE ts >= '2025-06-01T12:00:00.000000Z' AND ts < '2025-06-01T13:00:00.000000Z' \
+             AND source = 'HYPERLIQUID_S3' AND coin IN ('BTC', 'ETH') ORDER BY ts LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn escapes_literals_and_identifiers() {
+        let q = Query::count()
+            .from("my table")
+            .eq("ticker", "x' OR '1'='1")
+            .in_list::<&str>("coin", []);
+        assert_eq!(
+            q.sql(),
+            "SELECT count() FROM \"my table\" WHERE ticker = 'x'' OR ''1''=''1' AND false"
+        );
+        assert_eq!(
+            Query::select(&["column"])
+                .from_function("table_columns", &["market_data".into()])
+                .sql(),
+            "SELECT \"column\" FROM table_columns('market_data')"
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Row {
+        coin: String,
+        ts: DateTime<Utc>,
+        quantity: f64,
+    }
+
+    #[test]
+    fn decodes_rows_by_column_name() {
+        // columns deliberately in a different order than `Row`'s fields
+        let json = serde_json::json!({
+            "query": "SELECT ts, quantity, coin FROM t",
+            "columns": [
+                {"name": "ts", "type": "TIMESTAMP"},
+                {"name": "quantity", "type": "DOUBLE"},
+                {"name": "coin", "type": "SYMBOL"}
+            ],
+            "timestamp": 0,
+            "dataset": [["2025-06-01T12:00:00.000000Z", 1.5, "BTC"]],
+            "count": 1
+        });
+        let rows: Vec<Row> = decode_rows(json).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].coin, "BTC");
+        assert_eq!(rows[0].quantity, 1.5);
+        assert_eq!(rows[0].ts.timestamp_millis(), 1748779200000);
+    }
+
+    #[test]
+    fn error_payload_becomes_query_error() {
+        let json = serde_json::json!({
+            "query": "SELECT * FROM nope",
+            "error": "table does not exist [table=nope]",
+            "position": 14
+        });
+        let err = decode_rows::<Row>(json).unwrap_err();
+        match err {
+            AnySignalError::QuestQuery(e) => {
+                assert!(e.is_missing_table());
+                assert_eq!(e.position, Some(14));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+
+use super::query::Query;
+use super::{insert_signal_info, QuestDbClient};
+use crate::error::{AnySignalError, AnySignalResult};
+use crate::model::signal::{SignalDataType, SignalInfo};
+
+/// Table every registration is recorded in.
+pub const TABLE: &str = "signal_info";
+
+/// One `signal_info` row as read back from QuestDB.
+#[derive(Debug, Deserialize)]
+struct SignalInfoRow {
+    id: i64,
+    signal_type: String,
+    data_type: String,
+    source: String,
+    description: Option<String>,
+    is_atomic: bool,
+    parameters: Option<String>,
+}
+
+impl SignalInfoRow {
+    fn into_info(self) -> Result<SignalInfo, String> {
+        let data_type = SignalDataType::from_name(&self.data_type)
+            .ok_or_else(|| format!("signal {}: unknown data type {:?}", self.id, self.data_type))?;
+        let parameters = match self.parameters.as_deref() {
+            None | Some("") => BTreeMap::new(),
+            Some(json) => serde_json::from_str(json)
+                .map_err(|e| format!("signal {}: bad parameters: {e}", self.id))?,
+        };
+        Ok(SignalInfo {
+            id: self.id,
+            signal_type: self.signal_type,
+            data_type,
+            source: self.source,
+            description: self.description.unwrap_or_default(),
+            is_atomic: self.is_atomic,
+            parameters,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    signals: BTreeMap<i64, SignalInfo>,
+    /// Set once [`TABLE`] has been read; ids are only allocated after that.
+    loaded: bool,
+}
+
+/// Every known signal by id, loaded from [`TABLE`] at startup.
+///
+/// Adapters describe their signals with [`SignalInfo::UNREGISTERED`] and
+/// [`Self::register`] them: a signal with the same type, source and
+/// parameters keeps its id across restarts, a new one gets the next free id
+/// (or its id from before the registry, see [`LEGACY_IDS`]).
+/// A registry that could not be loaded (see [`Default`]) retries the load on
+/// the next registration rather than allocate ids that may already exist.
+/// Cheaply cloneable — shared between `main`, the runners and the API.
+#[derive(Clone, Default)]
+pub struct SignalRegistry(Arc<Mutex<Inner>>);
+
+impl SignalRegistry {
+    /// Read the latest row of every id. A missing table is an empty registry.
+    pub async fn load(db: &QuestDbClient) -> AnySignalResult<Self> {
+        let registry = Self::default();
+        registry.reload(db).await?;
+        Ok(registry)
+    }
+
+    /// Merge [`TABLE`] into the registry. Entries assigned meanwhile by a
+    /// registration still in flight are kept.
+    async fn reload(&self, db: &QuestDbClient) -> AnySignalResult<()> {
+        let query = Query::select(&[
+            "id",
+            "signal_type",
+            "data_type",
+            "source",
+            "description",
+            "is_atomic",
+            "parameters",
+        ])
+        .from(&db.table_name(TABLE))
+        .order_by("ts");
+        let rows: Vec<SignalInfoRow> = match db.query(&query).await {
+            Ok(rows) => rows,
+            Err(AnySignalError::QuestQuery(e)) if e.is_missing_table() => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        let mut inner = self.lock();
+        for row in rows {
+            match row.into_info() {
+                Ok(info) => {
+                    inner.signals.insert(info.id, info);
+                }
+                Err(e) => tracing::warn!(error = e.as_str(), "signal registry: skipping row"),
+            }
+        }
+        inner.loaded = true;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.0.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.lock().loaded
+    }
+
+    /// Load [`TABLE`] unless that already succeeded.
+    pub async fn ensure_loaded(&self, db: &QuestDbClient) -> AnySignalResult<()> {
+        if self.is_loaded() {
+            return Ok(());
+        }
+        self.reload(db).await
+    }
+
+    /// Every registered signal, by id.
+    pub fn list(&self) -> Vec<SignalInfo> {
+        self.lock().signals.values().cloned().collect()
+    }
+
+    pub fn get(&self, id: i64) -> Option<SignalInfo> {
+        self.lock().signals.get(&id).cloned()
+    }
+
+    /// The registered entry for the same signal as `spec`, ignoring its id.
+    pub fn find(&self, spec: &SignalInfo) -> Option<SignalInfo> {
+        self.lock()
+            .signals
+            .values()
+            .find(|i| i.same_signal(spec))
+            .cloned()
+    }
+
+    /// Register `spec` and return it with its id. Only new signals, or ones
+    /// whose description, data type or atomic flag changed, are written.
+    pub async fn register(
+        &self,
+        db: &QuestDbClient,
+        spec: &SignalInfo,
+    ) -> AnySignalResult<SignalInfo> {
+        self.ensure_loaded(db).await?;
+        let (info, previous) = match self.assign(spec) {
+            Assigned::Unchanged(info) => return Ok(info),
+            Assigned::Changed { info, previous } => (info, previous),
+        };
+        let table = db.table_name(TABLE);
+        if let Err(e) = db
+            .write_rows(&table, std::slice::from_ref(&info), insert_signal_info)
+            .await
+        {
+            // roll back so a retry allocates again instead of trusting an
+            // entry that was never stored
+            let signals = &mut self.lock().signals;
+            match previous {
+                Some(previous) => signals.insert(info.id, previous),
+                None => signals.remove(&info.id),
+            };
+            return Err(e.into());
+        }
+        tracing::info!(
+            id = info.id,
+            signal_type = info.signal_type.as_str(),
+            source = info.source.as_str(),
+            "signal registered"
+        );
+        Ok(info)
+    }
+
+    /// Resolve `spec` against the registry under one lock, so concurrent
+    /// registrations of the same signal share an id.
+    fn assign(&self, spec: &SignalInfo) -> Assigned {
+        let signals = &mut self.lock().signals;
+        if let Some(existing) = signals.values().find(|i| i.same_signal(spec)) {
+            let info = SignalInfo {
+                id: existing.id,
+                ..spec.clone()
+            };
+            if info == *existing {
+                return Assigned::Unchanged(info);
+            }
+            let previous = signals.insert(info.id, info.clone());
+            return Assigned::Changed { info, previous };
+        }
+        let legacy = LEGACY_IDS
+            .iter()
+            .find(|(signal_type, source, _)| {
+                spec.signal_type == *signal_type
+                    && spec.source == *source
+                    && spec.parameters.is_empty()
+            })
+            .map(|(_, _, id)| *id)
+            .filter(|id| !signals.contains_key(id));
+        let id = legacy.unwrap_or_else(|| {
+            let last = signals.keys().next_back().copied();
+            last.unwrap_or(SignalInfo::UNREGISTERED).max(LEGACY_MAX) + 1
+        });
+        let info = SignalInfo { id, ..spec.clone() };
+        signals.insert(id, info.clone());
+        Assigned::Changed {
+            info,
+            previous: None,
+        }
+    }
+}
+
+/// Ids adapters hardcoded before the registry existed, by signal type and
+/// source; their history in the signal tables is stored under them. The
+/// first registration of one of these signals takes its legacy id, and new
+/// ids are allocated above [`LEGACY_MAX`]. YouTube captions shared id 4 with
+/// NewsAPI, so their earlier rows cannot be told apart and stay with `news`.
+const LEGACY_IDS: &[(&str, &str, i64)] = &[
+    ("fear_and_greed", "CoinMarketCap", 0),
+    ("btc_dominance", "CoinMarketCap", 2),
+    ("news", "NewsAPI", 4),
+];
+
+/// Highest id in [`LEGACY_IDS`].
+const LEGACY_MAX: i64 = 4;
+
+/// Outcome of [`SignalRegistry::assign`].
+enum Assigned {
+    Unchanged(SignalInfo),
+    /// New or updated entry that still has to be written; `previous` is what
+    /// to restore if the write fails.
+    Changed {
+        info: SignalInfo,
+        previous: Option<SignalInfo>,
+    },
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(signal_type: &str, url: Option<&str>) -> SignalInfo {
+        SignalInfo {
+            signal_type: signal_type.to_string(),
+            parameters: url
+                .map(|u| [("url".to_string(), u.to_string())].into())
+                .unwrap_or_default(),
+            ..SignalInfo::dummy()
+        }
+    }
+
+    fn assigned(a: Assigned) -> (SignalInfo, bool) {
+        match a {
+            Assigned::Unchanged(info) => (info, false),
+            Assigned::Changed { info, .. } => (info, true),
+        }
+    }
+
+    #[test]
+    fn assign_allocates_above_legacy_ids_and_reuses_ids_by_key() {
+        let registry = SignalRegistry::default();
+        let (a, new_a) = assigned(registry.assign(&spec("fear_and_greed", None)));
+        let (b, _) = assigned(registry.assign(&spec("captions", Some("https://y/1"))));
+        let (c, _) = assigned(registry.assign(&spec("captions", Some("https://y/2"))));
+        assert!(new_a);
+        assert_eq!((a.id, b.id, c.id), (5, 6, 7));
+
+        // same type, source and parameters: same id, nothing to write
+        let (again, changed) = assigned(registry.assign(&spec("captions", Some("https://y/1"))));
+        assert_eq!(again.id, 6);
+        assert!(!changed);
+
+        // a new description keeps the id but is written
+        let mut described = spec("fear_and_greed", None);
+        described.description = "CMC Fear and Greed".to_string();
+        let (updated, changed) = assigned(registry.assign(&described));
+        assert_eq!(updated.id, 5);
+        assert!(changed);
+        assert_eq!(registry.get(5).unwrap().description, "CMC Fear and Greed");
+        assert_eq!(registry.list().len(), 3);
+    }
+
+    #[test]
+    fn legacy_signals_keep_their_hardcoded_ids() {
+        let legacy = |signal_type: &str, source: &str| SignalInfo {
+            source: source.to_string(),
+            ..spec(signal_type, None)
+        };
+        let registry = SignalRegistry::default();
+        let (news, _) = assigned(registry.assign(&legacy("news", "NewsAPI")));
+        let (fear, _) = assigned(registry.assign(&legacy("fear_and_greed", "CoinMarketCap")));
+        let (other, _) = assigned(registry.assign(&legacy("btc_holding", "Microstrategy")));
+        assert_eq!((news.id, fear.id, other.id), (4, 0, 5));
+
+        // a legacy id already taken by another signal is not reused
+        let registry = SignalRegistry::default();
+        registry.lock().signals.insert(2, spec("captions", None));
+        let (dominance, _) = assigned(registry.assign(&legacy("btc_dominance", "CoinMarketCap")));
+        assert_eq!(dominance.id, 5);
+    }
+
+    #[test]
+    fn rows_round_trip_parameters_and_data_type() {
+        let row = SignalInfoRow {
+            id: 7,
+            signal_type: "youtube_closed_caption".to_string(),
+            data_type: "text".to_string(),
+            source: "YouTube".to_string(),
+            description: None,
+            is_atomic: true,
+            parameters: Some(r#"{"url":"https://y/1"}"#.to_string()),
+        };
+        let info = row.into_info().unwrap();
+        assert_eq!(info.data_type, SignalDataType::Text);
+        assert_eq!(info.parameters["url"], "https://y/1");
+
+        let bad = SignalInfoRow {
+            id: 8,
+            signal_type: "x".to_string(),
+            data_type: "matrix".to_string(),
+            source: "x".to_string(),
+            description: None,
+            is_atomic: false,
+            parameters: None,
+        };
+        assert!(bad.into_info().is_err());
+    }
+}
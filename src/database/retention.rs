@@ -0,0 +1,558 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::query::{Query, SqlValue};
+use super::schema;
+use super::QuestDbClient;
+use crate::error::AnySignalResult;
+
+/// Table every dropped partition is recorded in.
+pub const LOG_TABLE: &str = "retention_log";
+
+// ---------------------------------------------------------------------------
+// Rollups
+// ---------------------------------------------------------------------------
+
+/// Downsampling of one raw table into a coarser one, run on a partition just
+/// before it is dropped.
+#[derive(Debug, Clone, Copy)]
+pub struct Rollup {
+    /// Raw table the rollup reads.
+    pub source: &'static str,
+    /// Rollup table, also the name policies refer to it by.
+    pub target: &'static str,
+    /// `INSERT … SELECT` over `[{from}, {to})` of `{source}` into `{target}`.
+    /// Targets dedup on their keys, so re-running a rollup is idempotent.
+    sql: &'static str,
+}
+
+/// Every rollup a policy can request.
+pub const ROLLUPS: &[Rollup] = &[
+    Rollup {
+        source: "l2_orderbook",
+        target: "l2_orderbook_1m",
+        sql: "INSERT INTO {target} (ts, ticker, side, level, price, quantity, samples) \
+              SELECT timestamp_floor('m', ts) bucket, ticker, side, level, \
+              last(price), avg(quantity), count() \
+              FROM {source} WHERE ts >= {from} AND ts < {to} ORDER BY bucket",
+    },
+    Rollup {
+        source: "l2_orderbook",
+        target: "l2_orderbook_1h",
+        sql: "INSERT INTO {target} (ts, ticker, side, level, price, quantity, samples) \
+              SELECT timestamp_floor('h', ts) bucket, ticker, side, level, \
+              last(price), avg(quantity), count() \
+              FROM {source} WHERE ts >= {from} AND ts < {to} ORDER BY bucket",
+    },
+    Rollup {
+        source: "hyperliquid_fill",
+        target: "hyperliquid_fill_1m_aggregate",
+        // same buckets as `aggregate_fills`, so this agrees with the S3 backfill
+        sql: "INSERT INTO {target} (ts, coin, category, buy_side, quantity, trade_count) \
+              SELECT timestamp_floor('m', ts) bucket, coin, category, side = 'buy', \
+              sum(quantity), count() \
+              FROM {source} WHERE ts >= {from} AND ts < {to} ORDER BY bucket",
+    },
+    Rollup {
+        source: "hyperliquid_fill",
+        target: "hyperliquid_fill_1h_aggregate",
+        sql: "INSERT INTO {target} (ts, coin, category, buy_side, quantity, trade_count) \
+              SELECT timestamp_floor('h', ts) bucket, coin, category, side = 'buy', \
+              sum(quantity), count() \
+              FROM {source} WHERE ts >= {from} AND ts < {to} ORDER BY bucket",
+    },
+];
+
+impl Rollup {
+    pub fn find(target: &str) -> Option<&'static Rollup> {
+        ROLLUPS.iter().find(|r| r.target == target)
+    }
+
+    /// Statement rolling up `[from, to)`; table names are resolved with the
+    /// dev suffix.
+    pub fn sql(&self, db: &QuestDbClient, from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+        self.sql
+            .replace("{source}", &db.table_name(self.source))
+            .replace("{target}", &db.table_name(self.target))
+            .replace("{from}", &SqlValue::from(from).render())
+            .replace("{to}", &SqlValue::from(to).render())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Policies
+// ---------------------------------------------------------------------------
+
+/// How long to keep one raw table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Base table name.
+    pub table: String,
+    /// Partitions whose newest row is older than this many days are dropped.
+    pub keep_days: u32,
+    /// Rollup targets (see [`ROLLUPS`]) filled from a partition before it is
+    /// dropped.
+    #[serde(default)]
+    pub rollups: Vec<String>,
+}
+
+/// Everything `GET`/`PUT /retention` reads and replaces.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionSettings {
+    /// Only report what would be dropped; roll up and drop nothing.
+    pub dry_run: bool,
+    pub policies: Vec<RetentionPolicy>,
+}
+
+impl Default for RetentionSettings {
+    /// Raw L2 for 7 days and fills for 30, each rolled up to 1m and 1h.
+    /// Starts in dry-run so nothing is dropped until it is switched off.
+    fn default() -> Self {
+        Self {
+            dry_run: true,
+            policies: vec![
+                RetentionPolicy {
+                    table: "l2_orderbook".to_string(),
+                    keep_days: 7,
+                    rollups: vec!["l2_orderbook_1m".to_string(), "l2_orderbook_1h".to_string()],
+                },
+                RetentionPolicy {
+                    table: "hyperliquid_fill".to_string(),
+                    keep_days: 30,
+                    rollups: vec![
+                        "hyperliquid_fill_1m_aggregate".to_string(),
+                        "hyperliquid_fill_1h_aggregate".to_string(),
+                    ],
+                },
+            ],
+        }
+    }
+}
+
+impl RetentionSettings {
+    /// Reject unknown tables, duplicate policies, `keep_days = 0` and rollups
+    /// that do not read the policy's table.
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, policy) in self.policies.iter().enumerate() {
+            let table = &policy.table;
+            if schema::table(table).is_none() {
+                return Err(format!("unknown table '{table}'"));
+            }
+            if self.policies[..i].iter().any(|p| p.table == *table) {
+                return Err(format!("duplicate policy for '{table}'"));
+            }
+            if policy.keep_days == 0 {
+                return Err(format!("'{table}': keep_days must be at least 1"));
+            }
+            for target in &policy.rollups {
+                match Rollup::find(target) {
+                    Some(r) if r.source == table => {}
+                    Some(r) => {
+                        return Err(format!("'{table}': rollup '{target}' reads '{}'", r.source))
+                    }
+                    None => return Err(format!("'{table}': unknown rollup '{target}'")),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Runs
+// ---------------------------------------------------------------------------
+
+/// One row of `table_partitions()`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PartitionInfo {
+    pub name: String,
+    #[serde(rename = "minTimestamp")]
+    pub min_timestamp: Option<DateTime<Utc>>,
+    #[serde(rename = "maxTimestamp")]
+    pub max_timestamp: Option<DateTime<Utc>>,
+    #[serde(rename = "numRows")]
+    pub num_rows: i64,
+    /// The partition currently written to; QuestDB refuses to drop it.
+    pub active: bool,
+}
+
+/// Partitions of a table whose newest row is before `cutoff`, oldest first.
+/// Empty and active partitions are never dropped.
+pub fn expired(partitions: &[PartitionInfo], cutoff: DateTime<Utc>) -> Vec<&PartitionInfo> {
+    let mut out: Vec<&PartitionInfo> = partitions
+        .iter()
+        .filter(|p| !p.active && p.max_timestamp.is_some_and(|max| max < cutoff))
+        .collect();
+    out.sort_by_key(|p| p.min_timestamp);
+    out
+}
+
+/// A partition dropped (or, in dry-run, due to be dropped).
+#[derive(Debug, Clone, Serialize)]
+pub struct PartitionAction {
+    /// Resolved table name.
+    pub table: String,
+    pub partition: String,
+    pub min_timestamp: Option<DateTime<Utc>>,
+    pub max_timestamp: Option<DateTime<Utc>>,
+    pub num_rows: i64,
+    /// Rollups filled from the partition before the drop.
+    pub rollups: Vec<String>,
+    /// `false` in dry-run or when a rollup or the drop failed.
+    pub dropped: bool,
+}
+
+/// Outcome of one pass over every policy.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionRun {
+    pub started_at: DateTime<Utc>,
+    pub dry_run: bool,
+    pub actions: Vec<PartitionAction>,
+    /// Per-table or per-partition failures; other partitions still run.
+    pub errors: Vec<String>,
+}
+
+/// Every partition of `table`, as `table_partitions()` lists them.
+pub async fn list_partitions(
+    db: &QuestDbClient,
+    table: &str,
+) -> AnySignalResult<Vec<PartitionInfo>> {
+    let query = Query::select(&["name", "minTimestamp", "maxTimestamp", "numRows", "active"])
+        .from_function("table_partitions", &[table.into()]);
+    db.query(&query).await
+}
+
+/// Roll up then drop one partition.
+async fn drop_partition(
+    db: &QuestDbClient,
+    table: &str,
+    partition: &PartitionInfo,
+    rollups: &[&Rollup],
+) -> AnySignalResult<()> {
+    if let (Some(min), Some(max)) = (partition.min_timestamp, partition.max_timestamp) {
+        // `to` is exclusive; QuestDB timestamps are microseconds
+        let to = max + chrono::Duration::microseconds(1);
+        for rollup in rollups {
+            db.exec(&rollup.sql(db, min, to)).await?;
+        }
+    }
+    db.exec(&format!(
+        "ALTER TABLE {table} DROP PARTITION LIST {}",
+        SqlValue::from(partition.name.as_str()).render()
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Record a dropped partition in [`LOG_TABLE`].
+async fn log_drop(
+    db: &QuestDbClient,
+    table: &str,
+    partition: &PartitionInfo,
+    rollups: &[&Rollup],
+) -> AnySignalResult<()> {
+    let ts =
+        |t: Option<DateTime<Utc>>| t.map_or("NULL".to_string(), |t| SqlValue::from(t).render());
+    let targets: Vec<&str> = rollups.iter().map(|r| r.target).collect();
+    db.exec(&format!(
+        "INSERT INTO {} (ts, table_name, partition_name, min_ts, max_ts, num_rows, rollups) \
+         VALUES (now(), {}, {}, {}, {}, {}, {})",
+        db.table_name(LOG_TABLE),
+        SqlValue::from(table).render(),
+        SqlValue::from(partition.name.as_str()).render(),
+        ts(partition.min_timestamp),
+        ts(partition.max_timestamp),
+        partition.num_rows,
+        SqlValue::from(targets.join(",")).render(),
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Apply `settings` once. With `dry_run` (from the argument, or the settings)
+/// partitions are only listed.
+pub async fn run_once(
+    db: &QuestDbClient,
+    settings: &RetentionSettings,
+    dry_run: bool,
+) -> RetentionRun {
+    let started_at = Utc::now();
+    let dry_run = dry_run || settings.dry_run;
+    let mut actions = Vec::new();
+    let mut errors = Vec::new();
+
+    for policy in &settings.policies {
+        let table = db.table_name(&policy.table);
+        let partitions = match list_partitions(db, &table).await {
+            Ok(p) => p,
+            Err(e) => {
+                errors.push(format!("{table}: {e}"));
+                continue;
+            }
+        };
+        let cutoff = started_at - chrono::Duration::days(i64::from(policy.keep_days));
+        let rollups: Vec<&Rollup> = policy
+            .rollups
+            .iter()
+            .filter_map(|r| Rollup::find(r))
+            .collect();
+
+        for partition in expired(&partitions, cutoff) {
+            let dropped = if dry_run {
+                false
+            } else {
+                match drop_partition(db, &table, partition, &rollups).await {
+                    Ok(()) => {
+                        tracing::info!(
+                            table = table.as_str(),
+                            partition = partition.name.as_str(),
+                            rows = partition.num_rows,
+                            "retention: dropped partition"
+                        );
+                        // the partition is gone either way; only the log
+                        // row is missing
+                        if let Err(e) = log_drop(db, &table, partition, &rollups).await {
+                            errors.push(format!(
+                                "{table}/{}: dropped, but not logged: {e}",
+                                partition.name
+                            ));
+                        }
+                        true
+                    }
+                    Err(e) => {
+                        errors.push(format!("{table}/{}: {e}", partition.name));
+                        false
+                    }
+                }
+            };
+            actions.push(PartitionAction {
+                table: table.clone(),
+                partition: partition.name.clone(),
+                min_timestamp: partition.min_timestamp,
+                max_timestamp: partition.max_timestamp,
+                num_rows: partition.num_rows,
+                rollups: policy.rollups.clone(),
+                dropped,
+            });
+        }
+    }
+
+    RetentionRun {
+        started_at,
+        dry_run,
+        actions,
+        errors,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Manager
+// ---------------------------------------------------------------------------
+
+struct Inner {
+    path: PathBuf,
+    settings: RetentionSettings,
+    last_run: Option<RetentionRun>,
+}
+
+/// Current retention settings, persisted as JSON, plus the last scheduled run.
+///
+/// Cheaply cloneable — shared between the `retention` runner and the API.
+#[derive(Clone)]
+pub struct RetentionManager(Arc<Mutex<Inner>>);
+
+impl RetentionManager {
+    /// Load settings from `path`, falling back to the defaults when the file
+    /// is missing or invalid.
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let settings = match std::fs::read_to_string(&path) {
+            Ok(text) => match serde_json::from_str::<RetentionSettings>(&text) {
+                Ok(s) if s.validate().is_ok() => s,
+                Ok(s) => {
+                    tracing::warn!(path = %path.display(), error = ?s.validate().err(), "retention: invalid settings, using defaults");
+                    RetentionSettings::default()
+                }
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "retention: unreadable settings, using defaults");
+                    RetentionSettings::default()
+                }
+            },
+            Err(_) => RetentionSettings::default(),
+        };
+        Self(Arc::new(Mutex::new(Inner {
+            path,
+            settings,
+            last_run: None,
+        })))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        match self.0.lock() {
+            Ok(g) => g,
+            Err(p) => p.into_inner(),
+        }
+    }
+
+    pub fn settings(&self) -> RetentionSettings {
+        self.lock().settings.clone()
+    }
+
+    pub fn last_run(&self) -> Option<RetentionRun> {
+        self.lock().last_run.clone()
+    }
+
+    /// Validate, persist and apply `settings`. Takes effect on the next run.
+    pub fn update(&self, settings: RetentionSettings) -> Result<(), String> {
+        settings.validate()?;
+        let mut inner = self.lock();
+        let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        if let Some(dir) = inner.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        }
+        std::fs::write(&inner.path, json).map_err(|e| format!("{}: {e}", inner.path.display()))?;
+        inner.settings = settings;
+        Ok(())
+    }
+
+    /// Apply the current settings every `interval`, starting immediately.
+    pub async fn run_loop(self, db: QuestDbClient, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let run = run_once(&db, &self.settings(), false).await;
+            for e in &run.errors {
+                tracing::warn!(error = e.as_str(), "retention run error");
+            }
+            tracing::info!(
+                dry_run = run.dry_run,
+                partitions = run.actions.len(),
+                dropped = run.actions.iter().filter(|a| a.dropped).count(),
+                "retention run finished"
+            );
+            self.lock().last_run = Some(run);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partition(name: &str, min: &str, max: &str, active: bool) -> PartitionInfo {
+        PartitionInfo {
+            name: name.to_string(),
+            min_timestamp: Some(min.parse().unwrap()),
+            max_timestamp: Some(max.parse().unwrap()),
+            num_rows: 10,
+            active,
+        }
+    }
+
+    #[test]
+    fn expired_skips_recent_and_active_partitions() {
+        let partitions = [
+            partition(
+                "2025-06-02T00",
+                "2025-06-02T00:00:00Z",
+                "2025-06-02T00:59:59Z",
+                false,
+            ),
+            partition(
+                "2025-06-01T23",
+                "2025-06-01T23:00:00Z",
+                "2025-06-01T23:59:59Z",
+                false,
+            ),
+            partition(
+                "2025-06-03T00",
+                "2025-06-03T00:00:00Z",
+                "2025-06-03T00:10:00Z",
+                true,
+            ),
+            PartitionInfo {
+                min_timestamp: None,
+                max_timestamp: None,
+                ..partition(
+                    "2025-05-01T00",
+                    "2025-05-01T00:00:00Z",
+                    "2025-05-01T00:00:00Z",
+                    false,
+                )
+            },
+        ];
+        let cutoff = "2025-06-02T00:30:00Z".parse().unwrap();
+        let names: Vec<&str> = expired(&partitions, cutoff)
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, ["2025-06-01T23"]);
+
+        let cutoff = "2025-06-04T00:00:00Z".parse().unwrap();
+        assert_eq!(expired(&partitions, cutoff).len(), 2);
+    }
+
+    #[test]
+    fn default_settings_are_valid_and_dry_run() {
+        let settings = RetentionSettings::default();
+        assert!(settings.dry_run);
+        settings.validate().unwrap();
+        for rollup in ROLLUPS {
+            assert!(schema::table(rollup.source).is_some(), "{}", rollup.source);
+            assert!(schema::table(rollup.target).is_some(), "{}", rollup.target);
+        }
+    }
+
+    #[test]
+    fn validate_rejects_bad_policies() {
+        let mut settings = RetentionSettings::default();
+        settings.policies[0].rollups = vec!["hyperliquid_fill_1h_aggregate".to_string()];
+        assert!(settings
+            .validate()
+            .unwrap_err()
+            .contains("reads 'hyperliquid_fill'"));
+
+        let mut settings = RetentionSettings::default();
+        settings.policies[1].keep_days = 0;
+        assert!(settings.validate().is_err());
+
+        let mut settings = RetentionSettings::default();
+        settings.policies.push(settings.policies[0].clone());
+        assert!(settings.validate().unwrap_err().contains("duplicate"));
+
+        let mut settings = RetentionSettings::default();
+        settings.policies[0].table = "nope".to_string();
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn manager_persists_updates() {
+        let path = std::env::temp_dir().join(format!(
+            "anysignal-retention-{}.json",
+            Utc::now().timestamp_micros()
+        ));
+        let manager = RetentionManager::load(&path);
+        assert_eq!(manager.settings(), RetentionSettings::default());
+
+        let mut settings = manager.settings();
+        settings.dry_run = false;
+        settings.policies.truncate(1);
+        manager.update(settings.clone()).unwrap();
+        assert_eq!(RetentionManager::load(&path).settings(), settings);
+
+        settings.policies[0].keep_days = 0;
+        assert!(manager.update(settings).is_err());
+        assert!(!manager.settings().dry_run);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,1036 @@
+/// QuestDB column type, with the options we set at creation time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Timestamp,
+    /// `capacity` is a sizing hint for the symbol table (distinct values).
+    Symbol {
+        capacity: u32,
+    },
+    Boolean,
+    Int,
+    Long,
+    Double,
+    String,
+}
+
+impl ColumnType {
+    /// Type name as reported by `table_columns()`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColumnType::Timestamp => "TIMESTAMP",
+            ColumnType::Symbol { .. } => "SYMBOL",
+            ColumnType::Boolean => "BOOLEAN",
+            ColumnType::Int => "INT",
+            ColumnType::Long => "LONG",
+            ColumnType::Double => "DOUBLE",
+            ColumnType::String => "STRING",
+        }
+    }
+
+    /// Type clause for `CREATE TABLE` / `ADD COLUMN`.
+    pub fn ddl(&self) -> String {
+        match self {
+            ColumnType::Symbol { capacity } => format!("SYMBOL CAPACITY {capacity}"),
+            other => other.name().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    pub name: &'static str,
+    pub ty: ColumnType,
+}
+
+const fn col(name: &'static str, ty: ColumnType) -> Column {
+    Column { name, ty }
+}
+
+const fn ts(name: &'static str) -> Column {
+    col(name, ColumnType::Timestamp)
+}
+
+const fn sym(name: &'static str, capacity: u32) -> Column {
+    col(name, ColumnType::Symbol { capacity })
+}
+
+const fn double(name: &'static str) -> Column {
+    col(name, ColumnType::Double)
+}
+
+const fn long(name: &'static str) -> Column {
+    col(name, ColumnType::Long)
+}
+
+const fn boolean(name: &'static str) -> Column {
+    col(name, ColumnType::Boolean)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionBy {
+    Hour,
+    Day,
+    Month,
+}
+
+impl PartitionBy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PartitionBy::Hour => "HOUR",
+            PartitionBy::Day => "DAY",
+            PartitionBy::Month => "MONTH",
+        }
+    }
+}
+
+/// One schema change.
+#[derive(Debug, Clone, Copy)]
+pub enum Step {
+    /// `CREATE TABLE IF NOT EXISTS` as a WAL table. `timestamp` names the
+    /// designated timestamp column, which must be one of `columns`.
+    Create {
+        columns: &'static [Column],
+        timestamp: &'static str,
+        partition_by: PartitionBy,
+    },
+    /// `ALTER TABLE … ADD COLUMN IF NOT EXISTS` per column.
+    AddColumns(&'static [Column]),
+    /// `ALTER TABLE … DEDUP ENABLE UPSERT KEYS(…)`: rows whose keys match an
+    /// existing row replace it, so re-ingesting a partition is idempotent.
+    /// Keys must include the designated timestamp; replaces earlier keys.
+    Dedup(&'static [&'static str]),
+    /// Data migration, typically `INSERT INTO {table} SELECT … FROM {source}`,
+    /// run once per partition of `source` with `{from}` (inclusive) and `{to}`
+    /// (exclusive) bounding it, so no single statement scans the whole table.
+    /// `{table}` is the migrated table and `{<name>}` any other managed table,
+    /// both resolved with the dev suffix. `source` must come earlier in
+    /// [`TABLES`] so it exists by the time this runs, and the migrated table
+    /// should have dedup keys so a rerun after a partial failure is harmless.
+    Backfill {
+        source: &'static str,
+        sql: &'static str,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// 1-based, strictly increasing within a table.
+    pub version: u32,
+    pub description: &'static str,
+    pub step: Step,
+}
+
+impl Migration {
+    /// Statements applying this migration to `table` (the resolved, possibly
+    /// `_dev`-suffixed, name). `resolve` maps other base table names for
+    /// [`Step::Backfill`], whose `{from}` and `{to}` are left for the caller
+    /// to bind per partition.
+    pub fn statements(&self, table: &str, resolve: impl Fn(&str) -> String) -> Vec<String> {
+        match self.step {
+            Step::Create {
+                columns,
+                timestamp,
+                partition_by,
+            } => {
+                let cols = columns
+                    .iter()
+                    .map(|c| format!("{} {}", c.name, c.ty.ddl()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                vec![format!(
+                    "CREATE TABLE IF NOT EXISTS {table} ({cols}) TIMESTAMP({timestamp}) PARTITION BY {} WAL",
+                    partition_by.name()
+                )]
+            }
+            Step::AddColumns(columns) => columns
+                .iter()
+                .map(|c| {
+                    format!(
+                        "ALTER TABLE {table} ADD COLUMN IF NOT EXISTS {} {}",
+                        c.name,
+                        c.ty.ddl()
+                    )
+                })
+                .collect(),
+            Step::Dedup(keys) => vec![format!(
+                "ALTER TABLE {table} DEDUP ENABLE UPSERT KEYS({})",
+                keys.join(", ")
+            )],
+            Step::Backfill { sql, .. } => {
+                let sql = TABLES.iter().fold(sql.replace("{table}", table), |sql, t| {
+                    sql.replace(&format!("{{{}}}", t.name), &resolve(t.name))
+                });
+                vec![sql]
+            }
+        }
+    }
+}
+
+/// A table owned by the migration subsystem.
+#[derive(Debug, Clone, Copy)]
+pub struct TableSchema {
+    /// Base name; [`QuestDbClient::table_name`] adds the dev suffix.
+    ///
+    /// [`QuestDbClient::table_name`]: super::QuestDbClient::table_name
+    pub name: &'static str,
+    pub migrations: &'static [Migration],
+}
+
+impl TableSchema {
+    pub fn latest_version(&self) -> u32 {
+        self.migrations.last().map(|m| m.version).unwrap_or(0)
+    }
+
+    /// Migrations newer than `applied`.
+    pub fn pending(&self, applied: u32) -> impl Iterator<Item = &Migration> {
+        self.migrations.iter().filter(move |m| m.version > applied)
+    }
+
+    /// Columns after every migration has been applied, in creation order.
+    pub fn columns(&self) -> Vec<Column> {
+        let mut out: Vec<Column> = Vec::new();
+        for m in self.migrations {
+            let added = match m.step {
+                Step::Create { columns, .. } | Step::AddColumns(columns) => columns,
+                Step::Dedup(_) | Step::Backfill { .. } => continue,
+            };
+            for c in added {
+                if !out.iter().any(|o| o.name == c.name) {
+                    out.push(*c);
+                }
+            }
+        }
+        out
+    }
+
+    /// Natural key set by the latest [`Step::Dedup`]; empty when none.
+    pub fn dedup_keys(&self) -> &'static [&'static str] {
+        self.migrations
+            .iter()
+            .rev()
+            .find_map(|m| match m.step {
+                Step::Dedup(keys) => Some(keys),
+                _ => None,
+            })
+            .unwrap_or(&[])
+    }
+
+    pub fn designated_timestamp(&self) -> Option<&'static str> {
+        self.migrations.iter().find_map(|m| match m.step {
+            Step::Create { timestamp, .. } => Some(timestamp),
+            _ => None,
+        })
+    }
+}
+
+/// Look up a managed table by base name.
+pub fn table(name: &str) -> Option<&'static TableSchema> {
+    TABLES.iter().find(|t| t.name == name)
+}
+
+// ---------------------------------------------------------------------------
+// Table definitions
+// ---------------------------------------------------------------------------
+
+/// Symbol capacity for coin / ticker columns (perps + spot + builder DEXes).
+const COINS: u32 = 2048;
+/// Symbol capacity for small closed sets (source, side, venue, …).
+const SMALL: u32 = 256;
+
+/// Every table owned by the migration subsystem.
+///
+/// A table's expected shape is whatever its migrations produce when applied
+/// in order. Migrations are append-only: never edit a released step, add a
+/// new version instead.
+pub const TABLES: &[TableSchema] = &[
+    TableSchema {
+        name: "market_data",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create market_data",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("category", SMALL),
+                        sym("ticker", COINS),
+                        sym("source", SMALL),
+                        double("value"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Day,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "add market_type and dex",
+                step: Step::AddColumns(&[sym("market_type", SMALL), sym("dex", SMALL)]),
+            },
+            Migration {
+                version: 3,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "category", "ticker", "source"]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "asset_ctx",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create asset_ctx",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("coin", COINS),
+                        sym("market_type", SMALL),
+                        sym("dex", SMALL),
+                        double("funding"),
+                        double("open_interest"),
+                        double("prev_day_px"),
+                        double("day_ntl_vlm"),
+                        double("premium"),
+                        double("oracle_px"),
+                        double("mark_px"),
+                        double("mid_px"),
+                        double("impact_bid_px"),
+                        double("impact_ask_px"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Day,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "coin"]),
+            },
+            Migration {
+                version: 3,
+                description: "pivot market_data into asset_ctx",
+                // Impact prices were never written to market_data and stay null.
+                step: Step::Backfill {
+                    source: "market_data",
+                    sql:
+                        "INSERT INTO {table} (ts, coin, market_type, dex, funding, open_interest, \
+                         prev_day_px, day_ntl_vlm, premium, oracle_px, mark_px, mid_px) \
+                         SELECT ts, ticker, first(market_type), first(dex), \
+                         max(CASE WHEN category = 'funding' THEN value END), \
+                         max(CASE WHEN category = 'open_interest' THEN value END), \
+                         max(CASE WHEN category = 'prev_day_px' THEN value END), \
+                         max(CASE WHEN category = 'day_ntl_vlm' THEN value END), \
+                         max(CASE WHEN category = 'premium' THEN value END), \
+                         max(CASE WHEN category = 'oracle_px' THEN value END), \
+                         max(CASE WHEN category = 'mark_px' THEN value END), \
+                         max(CASE WHEN category = 'mid_px' THEN value END) \
+                         FROM {market_data} WHERE source = 'HYPERLIQUID_S3' \
+                         AND ts >= {from} AND ts < {to} \
+                         GROUP BY ts, ticker ORDER BY ts",
+                },
+            },
+        ],
+    },
+    TableSchema {
+        name: "l2_orderbook",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create l2_orderbook",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("source", SMALL),
+                        sym("ticker", COINS),
+                        sym("side", SMALL),
+                        col("level", ColumnType::Int),
+                        double("price"),
+                        double("quantity"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Hour,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "ticker", "side", "level"]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "l2_orderbook_1m",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create l2_orderbook_1m",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("ticker", COINS),
+                        sym("side", SMALL),
+                        col("level", ColumnType::Int),
+                        double("price"),
+                        double("quantity"),
+                        long("samples"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Day,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "ticker", "side", "level"]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "l2_orderbook_1h",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create l2_orderbook_1h",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("ticker", COINS),
+                        sym("side", SMALL),
+                        col("level", ColumnType::Int),
+                        double("price"),
+                        double("quantity"),
+                        long("samples"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Month,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "ticker", "side", "level"]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "hyperliquid_fill",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create hyperliquid_fill",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("coin", COINS),
+                        sym("wallet", 1 << 20),
+                        sym("side", SMALL),
+                        sym("category", SMALL),
+                        sym("source", SMALL),
+                        boolean("is_taker"),
+                        double("price"),
+                        double("quantity"),
+                        double("position_before"),
+                        double("realized_pnl"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Hour,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&[
+                    "ts",
+                    "coin",
+                    "wallet",
+                    "side",
+                    "price",
+                    "quantity",
+                    "position_before",
+                ]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "hyperliquid_fill_1m_aggregate",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create hyperliquid_fill_1m_aggregate",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("coin", COINS),
+                        sym("category", SMALL),
+                        boolean("buy_side"),
+                        double("quantity"),
+                        long("trade_count"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Day,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "coin", "category", "buy_side"]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "hyperliquid_fill_1h_aggregate",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create hyperliquid_fill_1h_aggregate",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("coin", COINS),
+                        sym("category", SMALL),
+                        boolean("buy_side"),
+                        double("quantity"),
+                        long("trade_count"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Month,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "coin", "category", "buy_side"]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "market_state_1m",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create market_state_1m",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("coin", COINS),
+                        double("price_oracle"),
+                        double("price_mark"),
+                        double("price_mid"),
+                        double("open_interest"),
+                        double("funding_rate"),
+                        double("volume_24h_usd"),
+                        double("trade_volume"),
+                        long("trade_count"),
+                        double("liquidation_long_volume"),
+                        double("liquidation_short_volume"),
+                        long("liquidation_long_count"),
+                        long("liquidation_short_count"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Day,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "add predicted_funding_rate",
+                step: Step::AddColumns(&[double("predicted_funding_rate")]),
+            },
+            Migration {
+                version: 3,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "coin"]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "market_state_rt_1m",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create market_state_rt_1m",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("coin", COINS),
+                        double("price_oracle"),
+                        double("price_mark"),
+                        double("price_mid"),
+                        double("open_interest"),
+                        double("funding_rate"),
+                        double("volume_24h_usd"),
+                        double("predicted_funding_rate"),
+                        double("trade_volume"),
+                        long("trade_count"),
+                        double("liquidation_long_volume"),
+                        double("liquidation_short_volume"),
+                        long("liquidation_long_count"),
+                        long("liquidation_short_count"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Day,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "add venue",
+                step: Step::AddColumns(&[sym("venue", SMALL)]),
+            },
+            Migration {
+                version: 3,
+                description: "add market_type and dex",
+                step: Step::AddColumns(&[sym("market_type", SMALL), sym("dex", SMALL)]),
+            },
+            Migration {
+                version: 4,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "venue", "coin"]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "predicted_funding",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create predicted_funding",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("venue", SMALL),
+                        sym("coin", COINS),
+                        double("funding_rate"),
+                        ts("next_funding_time"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Day,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "venue", "coin"]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "funding_spread",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create funding_spread",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("coin", COINS),
+                        sym("venue", SMALL),
+                        sym("long_venue", SMALL),
+                        sym("short_venue", SMALL),
+                        double("hl_rate_hourly"),
+                        double("venue_rate_hourly"),
+                        double("spread_hourly"),
+                        double("spread_annualised"),
+                        double("carry_annualised"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Day,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "coin", "venue"]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "retention_log",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create retention_log",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        sym("table_name", SMALL),
+                        col("partition_name", ColumnType::String),
+                        ts("min_ts"),
+                        ts("max_ts"),
+                        long("num_rows"),
+                        col("rollups", ColumnType::String),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Month,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "table_name", "partition_name"]),
+            },
+        ],
+    },
+    // Signal tables keep ILP's default `timestamp` column name, which
+    // `select_signal_db` and existing deployments rely on.
+    TableSchema {
+        name: "signal_simple",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create signal_simple",
+                step: Step::Create {
+                    columns: &[long("info_id"), ts("timestamp")],
+                    timestamp: "timestamp",
+                    partition_by: PartitionBy::Month,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["timestamp", "info_id"]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "signal_binary",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create signal_binary",
+                step: Step::Create {
+                    columns: &[long("info_id"), boolean("value"), ts("timestamp")],
+                    timestamp: "timestamp",
+                    partition_by: PartitionBy::Month,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["timestamp", "info_id"]),
+            },
+        ],
+    },
+    TableSchema {
+        name: "signal_scalar",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create signal_scalar",
+                step: Step::Create {
+                    columns: &[long("info_id"), double("value"), ts("timestamp")],
+                    timestamp: "timestamp",
+                    partition_by: PartitionBy::Month,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["timestamp", "info_id"]),
+            },
+        ],
+    },
+    // `hash` is the FNV-1a hash of `value`, so distinct texts sharing a
+    // timestamp (two articles of the same second) are both kept. Rows
+    // written before it existed have no hash.
+    TableSchema {
+        name: "signal_text",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create signal_text",
+                step: Step::Create {
+                    columns: &[
+                        long("info_id"),
+                        col("value", ColumnType::String),
+                        ts("timestamp"),
+                    ],
+                    timestamp: "timestamp",
+                    partition_by: PartitionBy::Month,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["timestamp", "info_id"]),
+            },
+            Migration {
+                version: 3,
+                description: "add hash",
+                step: Step::AddColumns(&[long("hash")]),
+            },
+            Migration {
+                version: 4,
+                description: "dedup on text content",
+                step: Step::Dedup(&["timestamp", "info_id", "hash"]),
+            },
+        ],
+    },
+    // One row per element; `len` is the vector length at write time, so a
+    // shorter rewrite at the same timestamp hides the stale tail.
+    TableSchema {
+        name: "signal_vector",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create signal_vector",
+                step: Step::Create {
+                    columns: &[
+                        long("info_id"),
+                        long("idx"),
+                        long("len"),
+                        double("value"),
+                        ts("timestamp"),
+                    ],
+                    timestamp: "timestamp",
+                    partition_by: PartitionBy::Month,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["timestamp", "info_id", "idx"]),
+            },
+        ],
+    },
+    // One row per field, holding either `value` or `text` (neither for NaN);
+    // `len` is the field count at write time and `written` the write's time,
+    // so fields left over from an earlier write can be told apart. `key`
+    // separates records of one signal sharing a timestamp (calendar events).
+    TableSchema {
+        name: "signal_structured",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create signal_structured",
+                step: Step::Create {
+                    columns: &[
+                        long("info_id"),
+                        sym("field", SMALL),
+                        long("len"),
+                        double("value"),
+                        col("text", ColumnType::String),
+                        ts("timestamp"),
+                    ],
+                    timestamp: "timestamp",
+                    partition_by: PartitionBy::Month,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["timestamp", "info_id", "field"]),
+            },
+            Migration {
+                version: 3,
+                description: "add written",
+                step: Step::AddColumns(&[long("written")]),
+            },
+            Migration {
+                version: 4,
+                description: "add key",
+                step: Step::AddColumns(&[long("key")]),
+            },
+            Migration {
+                version: 5,
+                description: "dedup on key",
+                step: Step::Dedup(&["timestamp", "info_id", "key", "field"]),
+            },
+        ],
+    },
+    // One row per registration; the latest row per `id` is current.
+    TableSchema {
+        name: "signal_info",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create signal_info",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        long("id"),
+                        sym("signal_type", SMALL),
+                        sym("data_type", SMALL),
+                        sym("source", SMALL),
+                        col("description", ColumnType::String),
+                        boolean("is_atomic"),
+                        col("parameters", ColumnType::String),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Month,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "id"]),
+            },
+        ],
+    },
+    // One row per status change of a paper-traded instance; the latest row
+    // per `instance_id` is current.
+    TableSchema {
+        name: "strategy_instance",
+        migrations: &[
+            Migration {
+                version: 1,
+                description: "create strategy_instance",
+                step: Step::Create {
+                    columns: &[
+                        ts("ts"),
+                        long("instance_id"),
+                        long("strategy_id"),
+                        sym("status", SMALL),
+                        col("legs", ColumnType::String),
+                        double("pnl"),
+                        col("reason", ColumnType::String),
+                        ts("opened_at"),
+                        ts("closed_at"),
+                    ],
+                    timestamp: "ts",
+                    partition_by: PartitionBy::Month,
+                },
+            },
+            Migration {
+                version: 2,
+                description: "dedup upsert keys",
+                step: Step::Dedup(&["ts", "instance_id"]),
+            },
+        ],
+    },
+];
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrations_are_well_formed() {
+        for t in TABLES {
+            // versions are 1..=n in order
+            for (i, m) in t.migrations.iter().enumerate() {
+                assert_eq!(m.version as usize, i + 1, "{}: bad version order", t.name);
+            }
+            // exactly one Create, and it comes first
+            assert!(
+                matches!(
+                    t.migrations.first().map(|m| m.step),
+                    Some(Step::Create { .. })
+                ),
+                "{}: first migration must create the table",
+                t.name
+            );
+            let creates = t
+                .migrations
+                .iter()
+                .filter(|m| matches!(m.step, Step::Create { .. }))
+                .count();
+            assert_eq!(creates, 1, "{}", t.name);
+            // designated timestamp is a TIMESTAMP column
+            let ts_col = t.designated_timestamp().unwrap();
+            assert!(t
+                .columns()
+                .iter()
+                .any(|c| c.name == ts_col && c.ty == ColumnType::Timestamp));
+        }
+    }
+
+    #[test]
+    fn every_table_has_valid_dedup_keys() {
+        for t in TABLES {
+            let keys = t.dedup_keys();
+            assert!(!keys.is_empty(), "{}: no dedup keys", t.name);
+            assert!(
+                keys.contains(&t.designated_timestamp().unwrap()),
+                "{}: dedup keys must include the designated timestamp",
+                t.name
+            );
+            let columns = t.columns();
+            for k in keys {
+                assert!(
+                    columns.iter().any(|c| c.name == *k),
+                    "{}: unknown dedup key {k}",
+                    t.name
+                );
+            }
+        }
+        assert_eq!(
+            table("hyperliquid_fill_1m_aggregate").unwrap().dedup_keys(),
+            &["ts", "coin", "category", "buy_side"]
+        );
+        assert_eq!(
+            table("l2_orderbook").unwrap().dedup_keys(),
+            &["ts", "ticker", "side", "level"]
+        );
+        assert_eq!(
+            table("signal_text").unwrap().dedup_keys(),
+            &["timestamp", "info_id", "hash"]
+        );
+        assert_eq!(
+            table("signal_structured").unwrap().dedup_keys(),
+            &["timestamp", "info_id", "key", "field"]
+        );
+    }
+
+    #[test]
+    fn columns_fold_over_migrations() {
+        let rt = table("market_state_rt_1m").unwrap();
+        let names: Vec<&str> = rt.columns().iter().map(|c| c.name).collect();
+        assert_eq!(rt.latest_version(), 4);
+        assert!(names.contains(&"venue"));
+        assert_eq!(names.last(), Some(&"dex"));
+        assert_eq!(rt.pending(1).count(), 3);
+    }
+
+    #[test]
+    fn ddl_statements() {
+        let md = table("market_data").unwrap();
+        assert_eq!(
+            md.migrations[0].statements("market_data_dev", |t| format!("{t}_dev")),
+            vec!["CREATE TABLE IF NOT EXISTS market_data_dev (ts TIMESTAMP, \
+                  category SYMBOL CAPACITY 256, ticker SYMBOL CAPACITY 2048, \
+                  source SYMBOL CAPACITY 256, value DOUBLE) \
+                  TIMESTAMP(ts) PARTITION BY DAY WAL"
+                .to_string()]
+        );
+        assert_eq!(
+            md.migrations[1].statements("market_data", str::to_string),
+            vec![
+                "ALTER TABLE market_data ADD COLUMN IF NOT EXISTS market_type SYMBOL CAPACITY 256",
+                "ALTER TABLE market_data ADD COLUMN IF NOT EXISTS dex SYMBOL CAPACITY 256",
+            ]
+        );
+        assert_eq!(
+            md.migrations[2].statements("market_data", str::to_string),
+            vec!["ALTER TABLE market_data DEDUP ENABLE UPSERT KEYS(ts, category, ticker, source)"]
+        );
+    }
+
+    #[test]
+    fn backfill_resolves_table_names() {
+        let ctx = table("asset_ctx").unwrap();
+        let sql = ctx.migrations[2].statements("asset_ctx_dev", |t| format!("{t}_dev"));
+        assert_eq!(sql.len(), 1);
+        assert!(
+            sql[0].starts_with("INSERT INTO asset_ctx_dev (ts, coin,"),
+            "{}",
+            sql[0]
+        );
+        assert!(sql[0].contains("FROM market_data_dev WHERE"), "{}", sql[0]);
+        assert!(
+            sql[0].contains("AND ts >= {from} AND ts < {to}"),
+            "{}",
+            sql[0]
+        );
+        let unbound = sql[0].replace("{from}", "").replace("{to}", "");
+        assert!(!unbound.contains('{'), "{}", sql[0]);
+        assert!(matches!(
+            ctx.migrations[2].step,
+            Step::Backfill {
+                source: "market_data",
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,267 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::query::Query;
+use super::{select_signal_db, QuestDbClient};
+use crate::error::AnySignalResult;
+use crate::model::signal::{Signal, SignalDataType, SignalInfo};
+
+/// Rows returned when the caller sets no limit.
+pub const DEFAULT_LIMIT: u64 = 10_000;
+/// Upper bound on rows per read; `/exec` holds the whole result in memory.
+pub const MAX_LIMIT: u64 = 100_000;
+
+/// Aggregate applied to each interval of a [`Resample`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    /// Last value in the interval.
+    Last,
+    /// Mean of the values in the interval.
+    Mean,
+    /// First, highest, lowest and last value in the interval.
+    Ohlc,
+}
+
+/// `SAMPLE BY` resampling of a scalar signal, written `<aggregate>:<interval>`
+/// such as `mean:1h` or `ohlc:1d`. Intervals are a positive count of `s`,
+/// `m`, `h` or `d`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resample {
+    pub aggregate: Aggregate,
+    pub interval: String,
+}
+
+impl FromStr for Resample {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (aggregate, interval) = s
+            .split_once(':')
+            .ok_or_else(|| format!("resample '{s}': expected '<last|mean|ohlc>:<interval>'"))?;
+        let aggregate = match aggregate {
+            "last" => Aggregate::Last,
+            "mean" => Aggregate::Mean,
+            "ohlc" => Aggregate::Ohlc,
+            other => return Err(format!("resample '{s}': unknown aggregate '{other}'")),
+        };
+        if interval_micros(interval).is_none() {
+            return Err(format!(
+                "resample '{s}': interval must be a positive count of s, m, h or d, e.g. 1h"
+            ));
+        }
+        Ok(Self {
+            aggregate,
+            interval: interval.to_string(),
+        })
+    }
+}
+
+/// Length of a `SAMPLE BY` interval (a positive count of `s`, `m`, `h` or
+/// `d`, e.g. `15m`) in microseconds, or `None` when it is not one.
+pub fn interval_micros(interval: &str) -> Option<i64> {
+    let unit_us: i64 = match interval.chars().last()? {
+        's' => 1_000_000,
+        'm' => 60_000_000,
+        'h' => 3_600_000_000,
+        'd' => 86_400_000_000,
+        _ => return None,
+    };
+    let count = &interval[..interval.len() - 1];
+    if !count.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let count = count.parse::<u32>().ok().filter(|n| *n > 0)?;
+    unit_us.checked_mul(i64::from(count))
+}
+
+/// One resampled interval, stamped with its start.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ScalarPoint {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+}
+
+/// One `ohlc` interval, stamped with its start.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OhlcPoint {
+    pub timestamp: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+/// The result of [`read_signal`], shaped by the resample asked for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignalPoints {
+    Raw(Vec<Signal>),
+    Scalar(Vec<ScalarPoint>),
+    Ohlc(Vec<OhlcPoint>),
+}
+
+/// Every row of `info`, oldest first, from the `signal_*` table for its data
+/// type (with the dev suffix). Narrow it with [`Query::time_range`] and
+/// [`Query::limit`]; vector and structured signals span one row per element
+/// or field, and the limit counts those rows.
+pub fn signal_query(db: &QuestDbClient, info: &SignalInfo) -> Query {
+    let columns: &[&str] = match info.data_type {
+        SignalDataType::Simple => &["info_id", "timestamp"],
+        SignalDataType::Vector => &["info_id", "idx", "len", "value", "timestamp"],
+        SignalDataType::Structured => &[
+            "info_id",
+            "field",
+            "key",
+            "len",
+            "written",
+            "value",
+            "text",
+            "timestamp",
+        ],
+        _ => &["info_id", "value", "timestamp"],
+    };
+    let query = Query::select(columns)
+        .from(&db.table_name(info.data_type.table()))
+        .eq("info_id", info.id)
+        .order_by("timestamp");
+    match info.data_type {
+        SignalDataType::Vector => query.order_by("idx"),
+        SignalDataType::Structured => query.order_by("key"),
+        _ => query,
+    }
+}
+
+/// `resample` of `info` over `[from, to)`, one row per interval.
+pub fn resample_query(
+    db: &QuestDbClient,
+    info: &SignalInfo,
+    resample: &Resample,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Query {
+    let query = Query::select(&["timestamp"]);
+    let query = match resample.aggregate {
+        Aggregate::Last => query.select_expr("last(value) value"),
+        Aggregate::Mean => query.select_expr("avg(value) value"),
+        Aggregate::Ohlc => query
+            .select_expr("first(value) open")
+            .select_expr("max(value) high")
+            .select_expr("min(value) low")
+            .select_expr("last(value) close"),
+    };
+    query
+        .from(&db.table_name(info.data_type.table()))
+        .eq("info_id", info.id)
+        .time_range("timestamp", from, to)
+        .sample_by(&resample.interval)
+}
+
+/// Read `info` over `[from, to)`, at most `limit` rows, resampled when asked.
+/// Only scalar signals can be resampled.
+pub async fn read_signal(
+    db: &QuestDbClient,
+    info: &SignalInfo,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    limit: u64,
+    resample: Option<&Resample>,
+) -> AnySignalResult<SignalPoints> {
+    let limit = limit.min(MAX_LIMIT);
+    let Some(resample) = resample else {
+        let query = signal_query(db, info)
+            .time_range("timestamp", from, to)
+            .limit(limit);
+        return Ok(SignalPoints::Raw(
+            select_signal_db(db, info.data_type, &query).await?,
+        ));
+    };
+    if info.data_type != SignalDataType::Scalar {
+        return Err(eyre::eyre!(
+            "signal {} is {}; only scalar signals can be resampled",
+            info.id,
+            info.data_type.name()
+        )
+        .into());
+    }
+    let query = resample_query(db, info, resample, from, to).limit(limit);
+    Ok(match resample.aggregate {
+        Aggregate::Ohlc => SignalPoints::Ohlc(db.query(&query).await?),
+        Aggregate::Last | Aggregate::Mean => SignalPoints::Scalar(db.query(&query).await?),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn parses_resample() {
+        assert_eq!(
+            "ohlc:1h".parse::<Resample>(),
+            Ok(Resample {
+                aggregate: Aggregate::Ohlc,
+                interval: "1h".to_string()
+            })
+        );
+        assert_eq!(
+            "mean:15m".parse::<Resample>().unwrap().aggregate,
+            Aggregate::Mean
+        );
+        for bad in [
+            "1h",
+            "median:1h",
+            "last:0h",
+            "last:1w",
+            "last:h",
+            "last:-1h",
+            "last:1h; DROP",
+            "last:1é",
+        ] {
+            assert!(bad.parse::<Resample>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn measures_intervals() {
+        assert_eq!(interval_micros("15m"), Some(900_000_000));
+        assert_eq!(interval_micros("1d"), Some(86_400_000_000));
+        for bad in ["", "d", "0h", "1w", "+1h", "1.5h"] {
+            assert_eq!(interval_micros(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn builds_signal_queries_for_the_data_type_table() {
+        let mut config = Config::from_env();
+        config.questdb_addr = "localhost:9000".to_string();
+        config.questdb_spool_dir = String::new();
+        config.dev = true;
+        let db = QuestDbClient::new(&config).unwrap();
+        let info = SignalInfo {
+            id: 3,
+            ..SignalInfo::dummy()
+        };
+        let from = "2025-06-01T00:00:00Z".parse().unwrap();
+        let to = "2025-06-02T00:00:00Z".parse().unwrap();
+
+        assert_eq!(
+            signal_query(&db, &info).limit(5).sql(),
+            "SELECT info_id, value, timestamp FROM signal_scalar_dev WHERE info_id = 3 \
+             ORDER BY timestamp LIMIT 5"
+        );
+        let resample = "ohlc:1h".parse().unwrap();
+        assert_eq!(
+            resample_query(&db, &info, &resample, from, to).sql(),
+            "SELECT timestamp, first(value) open, max(value) high, min(value) low, \
+             last(value) close FROM signal_scalar_dev WHERE info_id = 3 \
+             AND timestamp >= '2025-06-01T00:00:00.000000Z' \
+             AND timestamp < '2025-06-02T00:00:00.000000Z' SAMPLE BY 1h"
+        );
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use questdb::ingress::Buffer;
+use serde::Serialize;
+
+use crate::config::Config;
+
+/// Disambiguates spool files written within the same microsecond.
+static SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Longest wait between replay attempts while QuestDB stays unreachable.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How often an empty spool is checked for new files.
+const IDLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Totals of every spool directory opened by this process. Each client's
+/// sender pool opens its own [`Spool`], so they share one count per
+/// directory.
+static OPEN: LazyLock<Mutex<HashMap<PathBuf, Arc<Mutex<Totals>>>>> =
+    LazyLock::new(Default::default);
+
+/// Write-ahead spool for ILP buffers QuestDB did not accept.
+///
+/// Each failed flush becomes one file `<micros>-<seq>-<table>.ilp` holding
+/// the raw ILP text, so files sort in write order. [`run_replay`] posts them
+/// to QuestDB's `/write` endpoint oldest first and deletes each once
+/// accepted. While anything is spooled, new buffers are appended behind it
+/// instead of being sent, so rows land in the order they were written.
+///
+/// The file count and byte total are read from disk once, when the
+/// directory is first opened, then kept in memory: the flush path checks
+/// them without listing the directory.
+#[derive(Debug, Clone)]
+pub struct Spool {
+    dir: PathBuf,
+    max_bytes: u64,
+    totals: Arc<Mutex<Totals>>,
+}
+
+/// Pending files and their bytes.
+#[derive(Debug, Default, PartialEq)]
+struct Totals {
+    files: usize,
+    bytes: u64,
+}
+
+impl Totals {
+    fn of(files: &[SpoolFile]) -> Self {
+        Self {
+            files: files.len(),
+            bytes: files.iter().map(|f| f.bytes).sum(),
+        }
+    }
+}
+
+/// One spooled buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpoolFile {
+    pub path: PathBuf,
+    pub written_at: Option<DateTime<Utc>>,
+    pub table: String,
+    pub bytes: u64,
+}
+
+/// Spool contents, reported by `GET /database`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SpoolStatus {
+    pub files: usize,
+    pub bytes: u64,
+    pub max_bytes: u64,
+    /// When the oldest pending buffer was spooled.
+    pub oldest: Option<DateTime<Utc>>,
+    /// Pending buffers per table.
+    pub tables: BTreeMap<String, usize>,
+}
+
+impl Spool {
+    pub fn new(dir: impl AsRef<Path>, max_bytes: u64) -> Self {
+        let dir = dir.as_ref().to_path_buf();
+        let totals = OPEN
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .entry(dir.clone())
+            .or_insert_with(|| Arc::new(Mutex::new(Totals::of(&list(&dir)))))
+            .clone();
+        Self {
+            dir,
+            max_bytes,
+            totals,
+        }
+    }
+
+    /// `None` when `QUESTDB_SPOOL_DIR` is empty.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        (!config.questdb_spool_dir.is_empty()).then(|| {
+            Self::new(
+                &config.questdb_spool_dir,
+                config.questdb_spool_max_mb.saturating_mul(1024 * 1024),
+            )
+        })
+    }
+
+    /// Pending files, oldest first. A missing directory is an empty spool.
+    pub fn files(&self) -> Vec<SpoolFile> {
+        list(&self.dir)
+    }
+
+    /// From the in-memory count; does not touch the directory.
+    pub fn is_empty(&self) -> bool {
+        self.totals().files == 0
+    }
+
+    /// Held while files are added or the count is re-read, so the two never
+    /// interleave.
+    fn totals(&self) -> MutexGuard<'_, Totals> {
+        self.totals.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Drop `file`, replayed or set aside, from the count.
+    fn forget(&self, file: &SpoolFile) {
+        let mut totals = self.totals();
+        totals.files = totals.files.saturating_sub(1);
+        totals.bytes = totals.bytes.saturating_sub(file.bytes);
+    }
+
+    pub fn status(&self) -> SpoolStatus {
+        let files = self.files();
+        let mut tables: BTreeMap<String, usize> = BTreeMap::new();
+        for f in &files {
+            *tables.entry(f.table.clone()).or_default() += 1;
+        }
+        SpoolStatus {
+            files: files.len(),
+            bytes: files.iter().map(|f| f.bytes).sum(),
+            max_bytes: self.max_bytes,
+            oldest: files.first().and_then(|f| f.written_at),
+            tables,
+        }
+    }
+
+    /// Append `buffer` for `table`. Fails when the spool would exceed its cap
+    /// or the file cannot be written; the buffer is left untouched.
+    pub fn push(&self, table: &str, buffer: &Buffer) -> Result<(), String> {
+        let body = buffer.as_str();
+        let mut totals = self.totals();
+        let used = totals.bytes;
+        if used + body.len() as u64 > self.max_bytes {
+            return Err(format!(
+                "spool full ({used} of {} bytes used)",
+                self.max_bytes
+            ));
+        }
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("create {}: {e}", self.dir.display()))?;
+        let name = format!(
+            "{:020}-{:08}-{table}",
+            Utc::now().timestamp_micros(),
+            SEQ.fetch_add(1, Ordering::Relaxed)
+        );
+        // write then rename, so replay never sees a partial file
+        let tmp = self.dir.join(format!("{name}.tmp"));
+        let path = self.dir.join(format!("{name}.ilp"));
+        std::fs::write(&tmp, body).map_err(|e| format!("write {}: {e}", tmp.display()))?;
+        std::fs::rename(&tmp, &path).map_err(|e| format!("rename {}: {e}", tmp.display()))?;
+        totals.files += 1;
+        totals.bytes += body.len() as u64;
+        Ok(())
+    }
+
+    /// Post every pending file to `/write` in order, deleting each once
+    /// accepted. Stops at the first failure so order is kept; a buffer
+    /// QuestDB rejects as malformed is renamed to `.rejected` and skipped.
+    /// The in-memory count is re-read from the listing first, picking up
+    /// files removed by hand. Returns the number of files replayed.
+    pub async fn replay(&self, config: &Config) -> Result<usize, String> {
+        let files = {
+            let mut totals = self.totals();
+            let files = self.files();
+            *totals = Totals::of(&files);
+            files
+        };
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/write", config.questdb_addr);
+        let mut replayed = 0;
+        for file in files {
+            let body = std::fs::read(&file.path)
+                .map_err(|e| format!("read {}: {e}", file.path.display()))?;
+            let mut request = client.post(&url).body(body);
+            if let (Some(user), Some(pass)) = (&config.questdb_user, &config.questdb_password) {
+                request = request.basic_auth(user, Some(pass));
+            }
+            let response = request.send().await.map_err(|e| e.to_string())?;
+            if response.status() == reqwest::StatusCode::BAD_REQUEST {
+                // malformed lines never succeed; set aside so the queue moves
+                let text = response.text().await.unwrap_or_default();
+                tracing::error!(
+                    table = file.table.as_str(),
+                    error = text.as_str(),
+                    "QuestDB rejected spooled buffer, moved to .rejected"
+                );
+                let rejected = file.path.with_extension("rejected");
+                std::fs::rename(&file.path, &rejected)
+                    .map_err(|e| format!("rename {}: {e}", file.path.display()))?;
+                self.forget(&file);
+                continue;
+            }
+            if !response.status().is_success() {
+                let status = response.status();
+                let text = response.text().await.unwrap_or_default();
+                return Err(format!("{}: HTTP {status}: {text}", file.table));
+            }
+            std::fs::remove_file(&file.path)
+                .map_err(|e| format!("remove {}: {e}", file.path.display()))?;
+            self.forget(&file);
+            replayed += 1;
+        }
+        Ok(replayed)
+    }
+}
+
+/// Spool files in `dir`, oldest first.
+fn list(dir: &Path) -> Vec<SpoolFile> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<SpoolFile> = entries
+        .filter_map(Result::ok)
+        .filter_map(|e| {
+            let name = e.file_name().into_string().ok()?;
+            let (written_at, table) = parse_name(&name)?;
+            Some(SpoolFile {
+                path: e.path(),
+                written_at,
+                table,
+                bytes: e.metadata().ok()?.len(),
+            })
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+/// `(written_at, table)` from `<micros>-<seq>-<table>.ilp`.
+fn parse_name(name: &str) -> Option<(Option<DateTime<Utc>>, String)> {
+    let stem = name.strip_suffix(".ilp")?;
+    let (micros, rest) = stem.split_once('-')?;
+    let (_seq, table) = rest.split_once('-')?;
+    let micros: i64 = micros.parse().ok()?;
+    Some((DateTime::from_timestamp_micros(micros), table.to_string()))
+}
+
+/// Replay the spool forever: every few seconds while it is empty, with
+/// exponential backoff (capped at a minute) while QuestDB rejects it.
+pub async fn run_replay(spool: Spool, config: Config) {
+    let mut backoff = Duration::from_secs(1);
+    loop {
+        if spool.is_empty() {
+            backoff = Duration::from_secs(1);
+            tokio::time::sleep(IDLE_INTERVAL).await;
+            continue;
+        }
+        match spool.replay(&config).await {
+            Ok(n) => {
+                tracing::info!(files = n, "replayed spooled ILP buffers");
+                backoff = Duration::from_secs(1);
+            }
+            Err(e) => {
+                tracing::warn!(error = e.as_str(), retry_in = ?backoff, "spool replay failed");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use questdb::ingress::TimestampMicros;
+
+    fn buffer(table: &str) -> Buffer {
+        let mut buffer = Buffer::new();
+        buffer
+            .table(table)
+            .unwrap()
+            .symbol("coin", "BTC")
+            .unwrap()
+            .column_f64("value", 1.0)
+            .unwrap()
+            .at(TimestampMicros::new(1_748_779_200_000_000))
+            .unwrap();
+        buffer
+    }
+
+    #[test]
+    fn push_keeps_order_and_respects_cap() {
+        let dir =
+            std::env::temp_dir().join(format!("anysignal-spool-{}", Utc::now().timestamp_micros()));
+        let one = buffer("market_data").as_str().len() as u64;
+        let spool = Spool::new(&dir, one * 2);
+        assert!(spool.is_empty());
+
+        spool.push("market_data", &buffer("market_data")).unwrap();
+        spool
+            .push("l2_orderbook", &buffer("l2_orderbook"))
+            .unwrap_err();
+        spool.push("asset_ctx", &buffer("market_data")).unwrap();
+        assert!(spool
+            .push("asset_ctx", &buffer("market_data"))
+            .unwrap_err()
+            .contains("full"));
+
+        let tables: Vec<String> = spool.files().into_iter().map(|f| f.table).collect();
+        assert_eq!(tables, ["market_data", "asset_ctx"]);
+        let status = spool.status();
+        assert_eq!(status.files, 2);
+        assert_eq!(status.bytes, one * 2);
+        assert!(status.oldest.is_some());
+
+        let text = std::fs::read_to_string(&spool.files()[0].path).unwrap();
+        assert_eq!(text, buffer("market_data").as_str());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn totals_are_shared_and_reread_on_replay() {
+        let dir = std::env::temp_dir().join(format!(
+            "anysignal-spool-totals-{}",
+            Utc::now().timestamp_micros()
+        ));
+        // files already on disk are counted when the directory is opened
+        std::fs::create_dir_all(&dir).unwrap();
+        let one = buffer("market_data").as_str().to_string();
+        std::fs::write(
+            dir.join("00001748779200000000-00000000-market_data.ilp"),
+            &one,
+        )
+        .unwrap();
+        let spool = Spool::new(&dir, u64::MAX);
+        assert_eq!(spool.status().files, 1);
+        assert!(!spool.is_empty());
+
+        // a second spool on the directory, as another client opens, shares
+        // the count
+        let other = Spool::new(&dir, u64::MAX);
+        other.push("market_data", &buffer("market_data")).unwrap();
+        assert_eq!(
+            *spool.totals(),
+            Totals {
+                files: 2,
+                bytes: one.len() as u64 * 2
+            }
+        );
+
+        // removed behind its back: the flush path still sees the count, and
+        // replay re-reads it before sending anything
+        for file in spool.files() {
+            std::fs::remove_file(file.path).unwrap();
+        }
+        assert!(!spool.is_empty());
+        let mut config = Config::from_env();
+        // nothing listens on port 1
+        config.questdb_addr = "127.0.0.1:1".to_string();
+        assert_eq!(spool.replay(&config).await, Ok(0));
+        assert!(spool.is_empty());
+        assert!(other.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parse_name_splits_table() {
+        let (at, table) = parse_name("00001748779200000000-00000003-l2_orderbook_dev.ilp").unwrap();
+        assert_eq!(table, "l2_orderbook_dev");
+        assert_eq!(at.unwrap().timestamp(), 1_748_779_200);
+        assert!(parse_name("x.tmp").is_none());
+    }
+}
@@ -29,3 +29,28 @@ pub struct SignalStringRow {
     pub value: String,            // STRING type
     pub timestamp: DateTime<Utc>, // QuestDB's TIMESTAMP
 }
+
+/// One element of a `signal_vector` row group.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignalVectorRow {
+    pub info_id: i64,
+    pub idx: i64,
+    pub len: i64,
+    pub value: Option<f64>, // NULL for a NaN element
+    pub timestamp: DateTime<Utc>,
+}
+
+/// One field of a `signal_structured` row group; `value` or `text` is set,
+/// neither for a NaN number.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignalStructuredRow {
+    pub info_id: i64,
+    pub field: String,
+    pub key: Option<i64>, // NULL unless records share timestamps
+    pub len: i64,
+    pub written: Option<i64>, // NULL for rows from before the column
+
+    pub value: Option<f64>,
+    pub text: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
@@ -9,16 +9,19 @@ use super::event::Event;
 #[derive(Default)]
 pub struct EventCounters {
     pub updates_processed: AtomicU64,
-    pub window_reset: AtomicU64,
+    pub bucket_sealed: AtomicU64,
     pub snapshot: AtomicU64,
+    /// Trades / liquidations dropped because their bucket was already sealed.
+    pub late_dropped: AtomicU64,
 }
 
 /// Plain serialisable snapshot of [`EventCounters`] values.
 #[derive(serde::Serialize)]
 pub struct EventCountSnapshot {
     pub updates_processed: u64,
-    pub window_reset: u64,
+    pub bucket_sealed: u64,
     pub snapshot: u64,
+    pub late_dropped: u64,
 }
 
 impl EventCounters {
@@ -28,11 +31,17 @@ impl EventCounters {
         self.updates_processed.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Increment the `late_dropped` counter (called per late [`super::Update`]).
+    #[inline]
+    pub fn increment_late_dropped(&self) {
+        self.late_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Increment the counter for the given [`Event`] variant.
     #[inline]
     pub fn increment(&self, event: &Event) {
         let c = match event {
-            Event::WindowReset { .. } => &self.window_reset,
+            Event::BucketSealed { .. } => &self.bucket_sealed,
             Event::Snapshot { .. } => &self.snapshot,
         };
         c.fetch_add(1, Ordering::Relaxed);
@@ -42,8 +51,9 @@ impl EventCounters {
     pub fn snapshot(&self) -> EventCountSnapshot {
         EventCountSnapshot {
             updates_processed: self.updates_processed.load(Ordering::Relaxed),
-            window_reset: self.window_reset.load(Ordering::Relaxed),
+            bucket_sealed: self.bucket_sealed.load(Ordering::Relaxed),
             snapshot: self.snapshot.load(Ordering::Relaxed),
+            late_dropped: self.late_dropped.load(Ordering::Relaxed),
         }
     }
 }
@@ -6,9 +6,15 @@ use super::market_state::MarketState;
 /// Events emitted by the [`super::MarketEngine`] via an unbounded MPSC channel.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Event {
-    /// Emitted when the rolling window is reset. The scheduler uses this as
-    /// the flush signal before zeroing per-window counters.
-    WindowReset { timestamp: DateTime<Utc> },
+    /// Emitted once per event-time bucket when it is sealed, i.e. when the
+    /// watermark has passed the bucket end plus the allowed lateness.
+    /// `bucket_start` is the left-closed start of the sealed bucket and
+    /// `states` holds every tracked market with the bucket's counters.
+    BucketSealed {
+        bucket_start: DateTime<Utc>,
+        states: Vec<MarketState>,
+        timestamp: DateTime<Utc>,
+    },
 
     /// Periodic full snapshot of all tracked coin states.
     Snapshot {
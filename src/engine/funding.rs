@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+use crate::database::{FundingSpreadRow, PredictedFundingRow};
+
+/// Reference venue every other venue's funding is compared against.
+pub const REFERENCE_VENUE: &str = "HlPerp";
+
+/// Hours in a (non-leap) year, used to annualise hourly rates.
+const HOURS_PER_YEAR: f64 = 8_760.0;
+
+/// Latest predicted funding for one `(venue, coin)`, as reported by
+/// `guilder-abstraction`'s `get_predicted_fundings`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PredictedFundingQuote {
+    pub venue: String,
+    pub coin: String,
+    /// Rate for the venue's own funding interval.
+    pub rate: f64,
+    /// When the rate is next applied, Unix milliseconds.
+    pub next_funding_time_ms: i64,
+}
+
+/// Funding settlement interval of `venue` in hours.
+///
+/// Hyperliquid settles hourly; Binance and Bybit perps settle every 8 hours
+/// for the bulk of their listings, which is also the fallback for unknown
+/// venues.
+pub fn funding_interval_hours(venue: &str) -> f64 {
+    match venue {
+        "HlPerp" => 1.0,
+        _ => 8.0,
+    }
+}
+
+/// Normalise a venue's per-interval rate to a one-hour rate.
+pub fn hourly_rate(venue: &str, rate: f64) -> f64 {
+    rate / funding_interval_hours(venue)
+}
+
+/// Snapshot `quotes` into `predicted_funding` rows for `minute_ms`.
+pub fn predicted_funding_rows(
+    minute_ms: i64,
+    quotes: &[PredictedFundingQuote],
+) -> Vec<PredictedFundingRow> {
+    quotes
+        .iter()
+        .map(|q| PredictedFundingRow {
+            minute_ms,
+            venue: q.venue.clone(),
+            coin: q.coin.clone(),
+            funding_rate: q.rate,
+            next_funding_time_ms: q.next_funding_time_ms,
+        })
+        .collect()
+}
+
+/// Compute `funding_spread` rows for `minute_ms`.
+///
+/// Emits one row per coin and non-reference venue. Coins without a
+/// [`REFERENCE_VENUE`] quote are skipped. Rows are ordered by coin, then venue.
+pub fn spread_rows(minute_ms: i64, quotes: &[PredictedFundingQuote]) -> Vec<FundingSpreadRow> {
+    let mut by_coin: std::collections::BTreeMap<&str, Vec<(&str, f64)>> = Default::default();
+    for q in quotes {
+        by_coin
+            .entry(q.coin.as_str())
+            .or_default()
+            .push((q.venue.as_str(), hourly_rate(&q.venue, q.rate)));
+    }
+
+    let mut rows = Vec::new();
+    for (coin, mut venues) in by_coin {
+        venues.sort_by(|a, b| a.0.cmp(b.0));
+        let Some(&(_, hl_rate)) = venues.iter().find(|(v, _)| *v == REFERENCE_VENUE) else {
+            continue;
+        };
+
+        // Longs pay positive funding: go long where it is lowest, short where
+        // it is highest. Ties resolve to the alphabetically first venue.
+        let (long_venue, long_rate) = venues
+            .iter()
+            .copied()
+            .reduce(|best, cur| if cur.1 < best.1 { cur } else { best })
+            .unwrap_or((REFERENCE_VENUE, hl_rate));
+        let (short_venue, short_rate) = venues
+            .iter()
+            .copied()
+            .reduce(|best, cur| if cur.1 > best.1 { cur } else { best })
+            .unwrap_or((REFERENCE_VENUE, hl_rate));
+        let carry_annualised = (short_rate - long_rate) * HOURS_PER_YEAR;
+
+        for &(venue, venue_rate) in venues.iter().filter(|(v, _)| *v != REFERENCE_VENUE) {
+            let spread_hourly = venue_rate - hl_rate;
+            rows.push(FundingSpreadRow {
+                minute_ms,
+                coin: coin.to_string(),
+                venue: venue.to_string(),
+                hl_rate_hourly: hl_rate,
+                venue_rate_hourly: venue_rate,
+                spread_hourly,
+                spread_annualised: spread_hourly * HOURS_PER_YEAR,
+                long_venue: long_venue.to_string(),
+                short_venue: short_venue.to_string(),
+                carry_annualised,
+            });
+        }
+    }
+    rows
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(venue: &str, coin: &str, rate: f64) -> PredictedFundingQuote {
+        PredictedFundingQuote {
+            venue: venue.to_string(),
+            coin: coin.to_string(),
+            rate,
+            next_funding_time_ms: 0,
+        }
+    }
+
+    #[test]
+    fn spread_normalises_intervals_and_picks_best_pair() {
+        let quotes = [
+            quote("HlPerp", "BTC", 0.0001),
+            quote("BinPerp", "BTC", 0.0016),    // 0.0002 / h
+            quote("BybitPerp", "BTC", -0.0008), // -0.0001 / h
+        ];
+        let rows = spread_rows(60_000, &quotes);
+        assert_eq!(rows.len(), 2);
+
+        let bin = &rows[0];
+        assert_eq!(bin.venue, "BinPerp");
+        assert!((bin.venue_rate_hourly - 0.0002).abs() < 1e-12);
+        assert!((bin.spread_hourly - 0.0001).abs() < 1e-12);
+        assert!((bin.spread_annualised - 0.876).abs() < 1e-9);
+
+        for row in &rows {
+            assert_eq!(row.long_venue, "BybitPerp");
+            assert_eq!(row.short_venue, "BinPerp");
+            assert!((row.carry_annualised - 0.0003 * 8_760.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn coins_without_reference_quote_are_skipped() {
+        let quotes = [
+            quote("BinPerp", "DOGE", 0.0001),
+            quote("BybitPerp", "DOGE", 0.0002),
+        ];
+        assert!(spread_rows(0, &quotes).is_empty());
+    }
+
+    #[test]
+    fn reference_only_coin_has_no_rows() {
+        assert!(spread_rows(0, &[quote("HlPerp", "HYPE", 0.0001)]).is_empty());
+    }
+}
@@ -1,16 +1,75 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-/// Aggregated market state for a single coin.
+/// Identifies one market across exchanges: a coin on a specific venue.
+///
+/// Venue names follow `guilder-abstraction`'s naming (e.g. `HlPerp`,
+/// `BinPerp`, `BybitPerp`) so they line up with `PredictedFunding::venue`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MarketKey {
+    pub venue: String,
+    pub coin: String,
+}
+
+impl MarketKey {
+    pub fn new(venue: impl Into<String>, coin: impl Into<String>) -> Self {
+        Self {
+            venue: venue.into(),
+            coin: coin.into(),
+        }
+    }
+}
+
+/// Kind of instrument a market trades.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MarketType {
+    #[default]
+    Perp,
+    Spot,
+}
+
+impl MarketType {
+    /// Value written to the `market_type` column.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketType::Perp => "perp",
+            MarketType::Spot => "spot",
+        }
+    }
+}
+
+/// A tradable market as announced to a bridge: the name the exchange streams
+/// use, and where its updates land in the engine.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MarketListing {
+    /// Exchange symbol used for stream subscriptions, e.g. `"@107"`.
+    pub symbol: String,
+    /// Engine key (venue + readable coin, e.g. `HlSpot` / `"HYPE/USDC"`).
+    pub key: MarketKey,
+    pub market_type: MarketType,
+    /// Perp DEX name; empty for spot and the venue's default DEX.
+    pub dex: String,
+    /// Whether the venue's WS client streams asset contexts for this market.
+    /// When `false`, only fills are streamed and snapshots arrive via REST.
+    pub stream_context: bool,
+}
+
+/// Aggregated market state for a single coin on a single venue.
 ///
 /// Snapshot fields (`price_*`, `open_interest`, `funding_rate`,
 /// `trading_volume_24h_usd`) persist across window resets.
 /// Rolling-window counters (`trading_volume`, `trade_count`,
-/// liquidation fields) are zeroed by [`MarketState::reset_window`]
-/// at the end of each 1-minute bucket.
+/// liquidation fields) are tracked per event-time bucket by the engine
+/// (see [`WindowCounters`]) and only populated on the copies emitted when a
+/// bucket is sealed; on the live state they stay zero.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MarketState {
+    /// Exchange venue the state belongs to (see [`MarketKey`]).
+    pub venue: String,
     pub coin: String,
+    pub market_type: MarketType,
+    /// Perp DEX name; empty for spot and the venue's default DEX.
+    pub dex: String,
 
     // --- Snapshots (carried forward, never reset) ---
     pub price_mid: Option<f64>,
@@ -37,20 +96,47 @@ pub struct MarketState {
 }
 
 impl MarketState {
-    pub fn new(coin: impl Into<String>) -> Self {
+    pub fn new(key: &MarketKey) -> Self {
         Self {
-            coin: coin.into(),
+            venue: key.venue.clone(),
+            coin: key.coin.clone(),
             ..Default::default()
         }
     }
 
+    pub fn key(&self) -> MarketKey {
+        MarketKey::new(&self.venue, &self.coin)
+    }
+
     /// Zero all rolling window counters. Snapshot fields are unchanged.
     pub fn reset_window(&mut self) {
-        self.trading_volume = 0.0;
-        self.trade_count = 0;
-        self.short_liquidation_volume = 0.0;
-        self.short_liquidation_count = 0;
-        self.lng_liquidation_volume = 0.0;
-        self.lng_liquidation_count = 0;
+        self.apply_window(&WindowCounters::default());
+    }
+
+    /// Overwrite the rolling window counters with those of a sealed bucket.
+    /// Snapshot fields are unchanged.
+    pub fn apply_window(&mut self, window: &WindowCounters) {
+        self.trading_volume = window.trading_volume;
+        self.trade_count = window.trade_count;
+        self.short_liquidation_volume = window.short_liquidation_volume;
+        self.short_liquidation_count = window.short_liquidation_count;
+        self.lng_liquidation_volume = window.lng_liquidation_volume;
+        self.lng_liquidation_count = window.lng_liquidation_count;
     }
 }
+
+/// Rolling-window counters for one coin within one event-time bucket.
+///
+/// Trades and liquidations are accumulated into the bucket that contains
+/// their exchange timestamp, not the bucket that was open when they arrived.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WindowCounters {
+    /// Sum of buy-side fill quantities in the bucket.
+    pub trading_volume: f64,
+    /// Count of buy-side fills in the bucket.
+    pub trade_count: u64,
+    pub short_liquidation_volume: f64,
+    pub short_liquidation_count: u64,
+    pub lng_liquidation_volume: f64,
+    pub lng_liquidation_count: u64,
+}
@@ -1,25 +1,30 @@
 pub mod counter;
 pub mod event;
+pub mod funding;
 pub mod market_state;
 pub mod scheduler;
 
 pub use counter::{EventCounters, EventCountSnapshot};
 pub use event::{Event, LiqSide};
-pub use market_state::MarketState;
+pub use funding::PredictedFundingQuote;
+pub use market_state::{MarketKey, MarketListing, MarketState, MarketType, WindowCounters};
 pub use scheduler::MarketStateScheduler;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 // ---------------------------------------------------------------------------
 // Updates fed INTO the MarketEngine
 // ---------------------------------------------------------------------------
 
 /// Raw market data update consumed by [`MarketEngine::process`].
+///
+/// Every market update carries the `venue` it came from; state is keyed by
+/// `(venue, coin)` so several exchange bridges can feed one engine.
 #[derive(Debug, Clone)]
 pub enum Update {
     /// Consolidated asset context snapshot from the data source.
@@ -28,7 +33,11 @@ pub enum Update {
     /// `open_interest`, `funding_rate`, `trading_volume_24h_usd`).
     /// `price_mid` falls back to `(oracle + mark) / 2` when `None`.
     AssetContext {
+        venue: String,
         coin: String,
+        market_type: MarketType,
+        /// Perp DEX name; empty for spot and the venue's default DEX.
+        dex: String,
         oracle_px: f64,
         mark_px: f64,
         /// Best-bid / best-ask midpoint from the exchange, if available.
@@ -38,29 +47,43 @@ pub enum Update {
         volume_24h_usd: f64,
     },
 
-    /// A completed buy-side trade (increments rolling window counters).
+    /// A completed buy-side trade (increments the counters of the bucket
+    /// containing `time_ms`).
     Trade {
+        venue: String,
         coin: String,
         /// Fill quantity in base units.
         volume: f64,
         /// Number of fills represented by this update (typically 1).
         count: u64,
+        /// Exchange timestamp of the fill in Unix milliseconds.
+        time_ms: i64,
     },
 
-    /// A liquidation event (increments rolling window liquidation counters).
+    /// A liquidation event (increments the liquidation counters of the bucket
+    /// containing `time_ms`).
     Liquidation {
+        venue: String,
         coin: String,
         side: LiqSide,
         volume: f64,
+        /// Exchange timestamp of the liquidation in Unix milliseconds.
+        time_ms: i64,
     },
 
-    /// Predicted next funding rate, polled once per minute from the exchange.
-    /// Overwrites the previous value; not reset on window boundaries.
-    PredictedFundingRate { coin: String, rate: f64 },
+    /// Predicted next funding rates for every venue and coin returned by one
+    /// poll. Replaces all previously held quotes of the venues present in the
+    /// batch; not reset on window boundaries. Tracked markets also get their
+    /// `predicted_funding_rate` set.
+    PredictedFundings { quotes: Vec<PredictedFundingQuote> },
 
-    /// Sent by the bridge after it has finished seeding all coins. The
-    /// scheduler skips flushes until this is received.
-    EngineReady,
+    /// Several updates delivered together (e.g. one REST poll of many markets),
+    /// processed in order.
+    Batch(Vec<Update>),
+
+    /// Sent by a bridge after it has finished seeding all coins of `venue`.
+    /// The scheduler skips flushes until the first one is received.
+    EngineReady { venue: String },
 }
 
 impl Update {
@@ -70,8 +93,18 @@ impl Update {
             Update::AssetContext { coin, .. } => Some(coin),
             Update::Trade { coin, .. } => Some(coin),
             Update::Liquidation { coin, .. } => Some(coin),
-            Update::PredictedFundingRate { coin, .. } => Some(coin),
-            Update::EngineReady => None,
+            Update::PredictedFundings { .. } | Update::Batch(_) => None,
+            Update::EngineReady { .. } => None,
+        }
+    }
+
+    pub fn venue(&self) -> Option<&str> {
+        match self {
+            Update::AssetContext { venue, .. }
+            | Update::Trade { venue, .. }
+            | Update::Liquidation { venue, .. }
+            | Update::EngineReady { venue } => Some(venue),
+            Update::PredictedFundings { .. } | Update::Batch(_) => None,
         }
     }
 }
@@ -81,28 +114,71 @@ impl Update {
 // ---------------------------------------------------------------------------
 
 pub struct MarketEngineConfig {
-    /// How often rolling window counters are reset (typically 60 seconds).
+    /// Width of one event-time bucket (typically 60 seconds).
     pub window_duration: std::time::Duration,
+    /// How long after a bucket's end the engine keeps it open for late
+    /// trades / liquidations before sealing it.
+    pub allowed_lateness: std::time::Duration,
+}
+
+impl Default for MarketEngineConfig {
+    fn default() -> Self {
+        Self {
+            window_duration: std::time::Duration::from_secs(60),
+            allowed_lateness: std::time::Duration::from_secs(5),
+        }
+    }
+}
+
+/// All coin states for one sealed event-time bucket.
+#[derive(Debug, Clone)]
+pub struct SealedBucket {
+    /// Left-closed bucket start, Unix milliseconds.
+    pub bucket_ms: i64,
+    /// One state per tracked coin, with the bucket's counters applied.
+    pub states: Vec<MarketState>,
+    /// Predicted funding quotes across all venues held at seal time, sorted
+    /// by `(venue, coin)`.
+    pub predicted_fundings: Vec<PredictedFundingQuote>,
 }
 
 // ---------------------------------------------------------------------------
 // MarketEngine
 // ---------------------------------------------------------------------------
 
+/// In-memory market state keyed by [`MarketKey`] (`(venue, coin)`).
+///
+/// Snapshot fields live on [`MarketState`]; trade and liquidation counters are
+/// kept per event-time bucket (keyed by the bucket start in Unix ms) until the
+/// bucket is sealed by [`MarketEngine::seal_until`].
 pub struct MarketEngine {
-    states: HashMap<String, MarketState>,
+    states: HashMap<MarketKey, MarketState>,
+    /// Open buckets: bucket start (ms) → market → counters.
+    buckets: BTreeMap<i64, HashMap<MarketKey, WindowCounters>>,
+    /// Latest predicted funding per market, including venues without a bridge.
+    predicted_fundings: BTreeMap<MarketKey, PredictedFundingQuote>,
+    /// Exclusive end of the last sealed bucket. `None` until
+    /// [`Self::open_from`] is called; nothing is sealed before that.
+    sealed_until_ms: Option<i64>,
+    window_ms: i64,
+    allowed_lateness_ms: i64,
     tx: UnboundedSender<Event>,
     counters: Arc<EventCounters>,
 }
 
 impl MarketEngine {
     /// Create a new engine and return the event receiver and shared counters.
-    pub fn new(_cfg: MarketEngineConfig) -> (Self, UnboundedReceiver<Event>, Arc<EventCounters>) {
+    pub fn new(cfg: MarketEngineConfig) -> (Self, UnboundedReceiver<Event>, Arc<EventCounters>) {
         let (tx, rx) = mpsc::unbounded_channel();
         let counters = Arc::new(EventCounters::default());
         (
             Self {
                 states: HashMap::new(),
+                buckets: BTreeMap::new(),
+                predicted_fundings: BTreeMap::new(),
+                sealed_until_ms: None,
+                window_ms: (cfg.window_duration.as_millis() as i64).max(1),
+                allowed_lateness_ms: cfg.allowed_lateness.as_millis() as i64,
                 tx,
                 counters: counters.clone(),
             },
@@ -111,13 +187,37 @@ impl MarketEngine {
         )
     }
 
+    /// Left-closed start of the bucket containing `time_ms`.
+    pub fn bucket_of(&self, time_ms: i64) -> i64 {
+        time_ms.div_euclid(self.window_ms) * self.window_ms
+    }
+
+    /// Bucket width in milliseconds.
+    pub fn window_ms(&self) -> i64 {
+        self.window_ms
+    }
+
+    /// Configured allowed lateness in milliseconds.
+    pub fn allowed_lateness_ms(&self) -> i64 {
+        self.allowed_lateness_ms
+    }
+
     /// Process a single market data update, mutating state in-place.
     pub fn process(&mut self, update: Update) {
+        if let Update::Batch(updates) = update {
+            for update in updates {
+                self.process(update);
+            }
+            return;
+        }
         self.counters.increment_update();
 
         match &update {
             Update::AssetContext {
+                venue,
                 coin,
+                market_type,
+                dex,
                 oracle_px,
                 mark_px,
                 mid_px,
@@ -125,7 +225,9 @@ impl MarketEngine {
                 funding_rate,
                 volume_24h_usd,
             } => {
-                let state = self.get_or_create(coin);
+                let state = self.get_or_create(MarketKey::new(venue, coin));
+                state.market_type = *market_type;
+                state.dex.clone_from(dex);
                 state.price_oracle = Some(*oracle_px);
                 state.price_mark = Some(*mark_px);
                 state.price_mid = Some(mid_px.unwrap_or((oracle_px + mark_px) / 2.0));
@@ -135,35 +237,61 @@ impl MarketEngine {
                 state.last_updated = Some(Utc::now());
             }
 
-            Update::Trade { coin, volume, count } => {
-                let state = self.get_or_create(coin);
-                state.trading_volume += volume;
-                state.trade_count += count;
-                state.last_updated = Some(Utc::now());
+            Update::Trade {
+                venue,
+                coin,
+                volume,
+                count,
+                time_ms,
+            } => {
+                let key = MarketKey::new(venue, coin);
+                self.get_or_create(key.clone()).last_updated = Some(Utc::now());
+                if let Some(window) = self.open_window(key, *time_ms) {
+                    window.trading_volume += volume;
+                    window.trade_count += count;
+                }
             }
 
-            Update::Liquidation { coin, side, volume } => {
-                let state = self.get_or_create(coin);
-                match side {
-                    LiqSide::Short => {
-                        state.short_liquidation_volume += volume;
-                        state.short_liquidation_count += 1;
-                    }
-                    LiqSide::Long => {
-                        state.lng_liquidation_volume += volume;
-                        state.lng_liquidation_count += 1;
+            Update::Liquidation {
+                venue,
+                coin,
+                side,
+                volume,
+                time_ms,
+            } => {
+                let key = MarketKey::new(venue, coin);
+                self.get_or_create(key.clone()).last_updated = Some(Utc::now());
+                if let Some(window) = self.open_window(key, *time_ms) {
+                    match side {
+                        LiqSide::Short => {
+                            window.short_liquidation_volume += volume;
+                            window.short_liquidation_count += 1;
+                        }
+                        LiqSide::Long => {
+                            window.lng_liquidation_volume += volume;
+                            window.lng_liquidation_count += 1;
+                        }
                     }
                 }
-                state.last_updated = Some(Utc::now());
             }
 
-            Update::PredictedFundingRate { coin, rate } => {
-                let state = self.get_or_create(coin);
-                state.predicted_funding_rate = Some(*rate);
-                state.last_updated = Some(Utc::now());
+            Update::PredictedFundings { quotes } => {
+                let venues: HashSet<&str> = quotes.iter().map(|q| q.venue.as_str()).collect();
+                self.predicted_fundings
+                    .retain(|key, _| !venues.contains(key.venue.as_str()));
+
+                for quote in quotes {
+                    let key = MarketKey::new(&quote.venue, &quote.coin);
+                    if let Some(state) = self.states.get_mut(&key) {
+                        state.predicted_funding_rate = Some(quote.rate);
+                        state.last_updated = Some(Utc::now());
+                    }
+                    self.predicted_fundings.insert(key, quote.clone());
+                }
             }
 
-            Update::EngineReady => {}
+            // Batches are unpacked above.
+            Update::Batch(_) | Update::EngineReady { .. } => {}
         }
     }
 
@@ -175,9 +303,9 @@ impl MarketEngine {
         });
     }
 
-    /// Read-only view of a coin's current state.
-    pub fn get(&self, coin: &str) -> Option<&MarketState> {
-        self.states.get(coin)
+    /// Read-only view of a market's current state.
+    pub fn get(&self, key: &MarketKey) -> Option<&MarketState> {
+        self.states.get(key)
     }
 
     /// Iterator over all tracked coin states.
@@ -185,27 +313,103 @@ impl MarketEngine {
         self.states.values()
     }
 
-    pub fn symbols(&self) -> Vec<String> {
+    pub fn keys(&self) -> Vec<MarketKey> {
         self.states.keys().cloned().collect()
     }
 
-    /// Zero all per-coin rolling window counters and emit [`Event::WindowReset`].
-    pub fn reset_all_windows(&mut self) {
-        for state in self.states.values_mut() {
-            state.reset_window();
+    /// Start sealing buckets from the one containing `now_ms`.
+    ///
+    /// Buckets that started before it are discarded: they were accumulated
+    /// while the engine was still being seeded and would be partial.
+    pub fn open_from(&mut self, now_ms: i64) {
+        let start = self.bucket_of(now_ms);
+        self.buckets = self.buckets.split_off(&start);
+        self.sealed_until_ms = Some(start);
+    }
+
+    /// Seal every bucket that ends at or before `watermark_ms`.
+    ///
+    /// Returns one [`SealedBucket`] per elapsed bucket in ascending order, each
+    /// holding a state for every tracked market (markets without trades in
+    /// that bucket get zero counters). Emits [`Event::BucketSealed`] per bucket.
+    /// No-op until [`Self::open_from`] has been called.
+    pub fn seal_until(&mut self, watermark_ms: i64) -> Vec<SealedBucket> {
+        let Some(mut next) = self.sealed_until_ms else {
+            return Vec::new();
+        };
+
+        let mut sealed = Vec::new();
+        while next + self.window_ms <= watermark_ms {
+            let windows = self.buckets.remove(&next).unwrap_or_default();
+            let states: Vec<MarketState> = self
+                .states
+                .iter()
+                .map(|(key, s)| {
+                    let mut state = s.clone();
+                    state.apply_window(windows.get(key).unwrap_or(&WindowCounters::default()));
+                    state
+                })
+                .collect();
+
+            self.emit(Event::BucketSealed {
+                bucket_start: DateTime::from_timestamp_millis(next).unwrap_or_default(),
+                states: states.clone(),
+                timestamp: Utc::now(),
+            });
+            sealed.push(SealedBucket {
+                bucket_ms: next,
+                states,
+                predicted_fundings: self.predicted_fundings.values().cloned().collect(),
+            });
+            next += self.window_ms;
         }
-        info!(coins = self.states.len(), "market window reset");
-        self.emit(Event::WindowReset { timestamp: Utc::now() });
+
+        self.sealed_until_ms = Some(next);
+        if !sealed.is_empty() {
+            info!(
+                buckets = sealed.len(),
+                markets = self.states.len(),
+                "market buckets sealed"
+            );
+        }
+        sealed
     }
 
     // -----------------------------------------------------------------------
     // Internal helpers
     // -----------------------------------------------------------------------
 
-    fn get_or_create(&mut self, coin: &str) -> &mut MarketState {
-        self.states
-            .entry(coin.to_string())
-            .or_insert_with(|| MarketState::new(coin))
+    /// Counters for `key` in the bucket containing `time_ms`, or `None` when
+    /// that bucket has already been sealed (the update is counted as late).
+    fn open_window(&mut self, key: MarketKey, time_ms: i64) -> Option<&mut WindowCounters> {
+        let bucket = self.bucket_of(time_ms);
+        if self.sealed_until_ms.is_some_and(|sealed| bucket < sealed) {
+            self.counters.increment_late_dropped();
+            debug!(
+                venue = key.venue.as_str(),
+                coin = key.coin.as_str(),
+                time_ms,
+                "update arrived after its bucket was sealed, dropping"
+            );
+            return None;
+        }
+        Some(
+            self.buckets
+                .entry(bucket)
+                .or_default()
+                .entry(key)
+                .or_default(),
+        )
+    }
+
+    /// New states pick up any predicted funding quote that arrived before the
+    /// market was seeded (pollers and bridges start concurrently).
+    fn get_or_create(&mut self, key: MarketKey) -> &mut MarketState {
+        self.states.entry(key).or_insert_with_key(|key| {
+            let mut state = MarketState::new(key);
+            state.predicted_funding_rate = self.predicted_fundings.get(key).map(|q| q.rate);
+            state
+        })
     }
 
     fn emit(&self, event: Event) {
@@ -215,3 +419,164 @@ impl MarketEngine {
         }
     }
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: i64 = 60_000;
+
+    fn engine() -> MarketEngine {
+        MarketEngine::new(MarketEngineConfig::default()).0
+    }
+
+    fn trade(coin: &str, volume: f64, time_ms: i64) -> Update {
+        Update::Trade {
+            venue: "HlPerp".to_string(),
+            coin: coin.to_string(),
+            volume,
+            count: 1,
+            time_ms,
+        }
+    }
+
+    fn seed(engine: &mut MarketEngine, coin: &str) {
+        seed_venue(engine, "HlPerp", coin);
+    }
+
+    fn seed_venue(engine: &mut MarketEngine, venue: &str, coin: &str) {
+        engine.process(Update::AssetContext {
+            venue: venue.to_string(),
+            coin: coin.to_string(),
+            market_type: MarketType::Perp,
+            dex: String::new(),
+            oracle_px: 100.0,
+            mark_px: 100.0,
+            mid_px: None,
+            open_interest: 1.0,
+            funding_rate: 0.0,
+            volume_24h_usd: 0.0,
+        });
+    }
+
+    #[test]
+    fn trades_are_bucketed_by_event_time() {
+        let mut e = engine();
+        seed(&mut e, "BTC");
+        e.open_from(10 * MINUTE);
+
+        // Arrives after the 10:00 tick but belongs to minute 10.
+        e.process(trade("BTC", 1.0, 10 * MINUTE + 59_999));
+        e.process(trade("BTC", 2.0, 11 * MINUTE));
+
+        let sealed = e.seal_until(12 * MINUTE);
+        assert_eq!(sealed.len(), 2);
+        assert_eq!(sealed[0].bucket_ms, 10 * MINUTE);
+        assert!((sealed[0].states[0].trading_volume - 1.0).abs() < 1e-9);
+        assert_eq!(sealed[1].bucket_ms, 11 * MINUTE);
+        assert!((sealed[1].states[0].trading_volume - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bucket_stays_open_until_watermark_passes_its_end() {
+        let mut e = engine();
+        seed(&mut e, "ETH");
+        e.open_from(10 * MINUTE);
+
+        assert!(e.seal_until(11 * MINUTE - 1).is_empty());
+        // Late fill for minute 10 still lands while the bucket is open.
+        e.process(trade("ETH", 3.0, 10 * MINUTE + 30_000));
+        let sealed = e.seal_until(11 * MINUTE);
+        assert_eq!(sealed.len(), 1);
+        assert_eq!(sealed[0].states[0].trade_count, 1);
+    }
+
+    #[test]
+    fn updates_for_sealed_buckets_are_dropped() {
+        let (mut e, _rx, counters) = MarketEngine::new(MarketEngineConfig::default());
+        seed(&mut e, "SOL");
+        e.open_from(10 * MINUTE);
+        e.seal_until(11 * MINUTE);
+
+        e.process(trade("SOL", 5.0, 10 * MINUTE + 1));
+        assert_eq!(counters.snapshot().late_dropped, 1);
+
+        let sealed = e.seal_until(12 * MINUTE);
+        assert_eq!(sealed.len(), 1);
+        assert_eq!(sealed[0].states[0].trade_count, 0);
+    }
+
+    #[test]
+    fn seeding_buckets_before_open_are_discarded() {
+        let mut e = engine();
+        seed(&mut e, "BTC");
+        e.process(trade("BTC", 1.0, 9 * MINUTE));
+        assert!(e.seal_until(20 * MINUTE).is_empty());
+
+        e.open_from(10 * MINUTE + 5_000);
+        let sealed = e.seal_until(11 * MINUTE);
+        assert_eq!(sealed.len(), 1);
+        assert_eq!(sealed[0].bucket_ms, 10 * MINUTE);
+        assert_eq!(sealed[0].states[0].trade_count, 0);
+    }
+
+    #[test]
+    fn same_coin_on_different_venues_is_tracked_separately() {
+        let mut e = engine();
+        seed_venue(&mut e, "HlPerp", "BTC");
+        seed_venue(&mut e, "BinPerp", "BTC");
+        e.open_from(10 * MINUTE);
+
+        e.process(Update::Trade {
+            venue: "BinPerp".to_string(),
+            coin: "BTC".to_string(),
+            volume: 4.0,
+            count: 1,
+            time_ms: 10 * MINUTE,
+        });
+
+        let sealed = e.seal_until(11 * MINUTE);
+        let mut states = sealed[0].states.clone();
+        states.sort_by_key(|s| s.key());
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[0].venue, "BinPerp");
+        assert_eq!(states[0].trade_count, 1);
+        assert_eq!(states[1].venue, "HlPerp");
+        assert_eq!(states[1].trade_count, 0);
+        assert!(e.get(&MarketKey::new("BinPerp", "BTC")).is_some());
+    }
+
+    #[test]
+    fn predicted_fundings_replace_per_venue() {
+        let mut e = engine();
+        seed(&mut e, "BTC");
+        e.open_from(0);
+
+        let quote = |venue: &str, coin: &str, rate: f64| PredictedFundingQuote {
+            venue: venue.to_string(),
+            coin: coin.to_string(),
+            rate,
+            next_funding_time_ms: 0,
+        };
+        e.process(Update::PredictedFundings {
+            quotes: vec![quote("HlPerp", "BTC", 0.1), quote("BinPerp", "ETH", 0.2)],
+        });
+        // A later batch for HlPerp only drops HlPerp coins it no longer lists.
+        e.process(Update::PredictedFundings {
+            quotes: vec![quote("HlPerp", "SOL", 0.3)],
+        });
+
+        let sealed = e.seal_until(MINUTE);
+        let held: Vec<_> = sealed[0]
+            .predicted_fundings
+            .iter()
+            .map(|q| (q.venue.as_str(), q.coin.as_str()))
+            .collect();
+        assert_eq!(held, [("BinPerp", "ETH"), ("HlPerp", "SOL")]);
+        assert_eq!(sealed[0].states[0].predicted_funding_rate, Some(0.1));
+    }
+}
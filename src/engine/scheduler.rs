@@ -6,16 +6,22 @@ use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tokio::time::MissedTickBehavior;
 use tracing::info;
 
-use crate::database::{insert_market_state_rt_1m, MarketStateRt1mRow, QuestDbClient};
+use crate::database::MarketStateRt1mRow;
 use crate::error::AnySignalResult;
+use crate::sink::{RowBatch, Sink};
 
 use super::counter::EventCounters;
 use super::event::Event;
+use super::funding;
 use super::{MarketEngine, MarketEngineConfig, MarketState, Update};
 
-/// Drives the [`MarketEngine`]: receives [`Update`]s from any data source,
-/// flushes a completed `market_state_1m` row per coin to QuestDB every minute,
-/// then resets the rolling window counters.
+/// Drives the [`MarketEngine`]: receives [`Update`]s from any number of data
+/// sources (one bridge per venue) and,
+/// once per minute, seals every event-time bucket whose end has passed the
+/// watermark (`now - allowed_lateness`), writing one `market_state_rt_1m` row
+/// per `(venue, coin)` per sealed bucket to the configured [`Sink`]. Each sealed bucket also
+/// snapshots every venue's predicted funding into `predicted_funding` and the
+/// derived Hyperliquid-vs-other-venue spreads into `funding_spread`.
 ///
 /// The scheduler is exchange-agnostic — it only knows about [`Update`] and
 /// [`MarketState`]. All exchange-specific adapters (WebSocket bridges, REST
@@ -23,12 +29,20 @@ use super::{MarketEngine, MarketEngineConfig, MarketState, Update};
 ///
 /// # Timing
 /// On [`Self::run`], the scheduler sleeps until the next wall-clock minute
-/// boundary before starting the 60-second interval, keeping bucket timestamps
-/// well-aligned regardless of when the process starts.
+/// boundary plus the allowed lateness before starting the 60-second interval,
+/// so each tick lands just after the previous bucket's lateness window closes.
+/// Trades are bucketed by their exchange timestamp, so a fill delivered a few
+/// seconds after the minute boundary still counts toward the minute it
+/// happened in; fills arriving after their bucket was sealed are dropped and
+/// counted in [`EventCounters`].
 ///
 /// # Readiness
 /// Flushes are skipped until [`Self::mark_ready`] is called. This prevents
-/// writing partial rows before the bridge has finished seeding the engine.
+/// writing partial rows before the bridge has finished seeding the engine;
+/// the first bucket written is the first full minute after readiness. With
+/// several bridges, the first [`Update::EngineReady`] enables flushing; a venue
+/// that finishes seeding later only appears from the bucket after its
+/// snapshots arrive (rows without a mark price are skipped).
 ///
 /// # Feeding updates
 /// Call [`Self::update_sender`] to obtain a cloneable [`UnboundedSender<Update>`]
@@ -36,30 +50,29 @@ use super::{MarketEngine, MarketEngineConfig, MarketState, Update};
 /// internally so the channel stays open until the scheduler is dropped.
 pub struct MarketStateScheduler {
     engine: MarketEngine,
-    db: Arc<QuestDbClient>,
+    sink: Arc<dyn Sink>,
     update_rx: UnboundedReceiver<Update>,
     /// Kept alive so the channel is not closed until the scheduler is dropped.
     _update_tx: UnboundedSender<Update>,
     #[allow(dead_code)]
     counters: Arc<EventCounters>,
-    /// Set by [`Self::mark_ready`] once the bridge has finished initialising.
+    /// Set by [`Self::mark_ready`] once the first bridge has finished
+    /// initialising.
     ready: bool,
 }
 
 impl MarketStateScheduler {
     /// Build a scheduler. Returns the scheduler and an event receiver for
     /// optional downstream consumers.
-    pub fn new(db: Arc<QuestDbClient>) -> (Self, UnboundedReceiver<Event>) {
+    pub fn new(sink: Arc<dyn Sink>, cfg: MarketEngineConfig) -> (Self, UnboundedReceiver<Event>) {
         use tokio::sync::mpsc;
 
         let (update_tx, update_rx) = mpsc::unbounded_channel::<Update>();
-        let (engine, event_rx, counters) = MarketEngine::new(MarketEngineConfig {
-            window_duration: Duration::from_secs(60),
-        });
+        let (engine, event_rx, counters) = MarketEngine::new(cfg);
 
         let scheduler = Self {
             engine,
-            db,
+            sink,
             update_rx,
             _update_tx: update_tx,
             counters,
@@ -77,15 +90,21 @@ impl MarketStateScheduler {
 
     /// Signal that the engine is fully seeded. Flushes will be skipped until
     /// this is called.
+    ///
+    /// The bucket containing the current instant is partial (the engine was
+    /// still seeding during part of it), so sealing starts from the next one.
     pub fn mark_ready(&mut self) {
+        let now_ms = Utc::now().timestamp_millis();
+        let next = self.engine.bucket_of(now_ms) + self.engine.window_ms();
+        self.engine.open_from(next);
         self.ready = true;
     }
 
     /// Run the scheduler loop.
     ///
-    /// - Sleeps until the next minute boundary (wall-clock alignment).
-    /// - Then ticks every 60 s: flushes the current window to QuestDB and
-    ///   resets rolling counters (skipped until [`Self::mark_ready`] is called).
+    /// - Sleeps until the next minute boundary plus the allowed lateness.
+    /// - Then ticks every 60 s: seals elapsed buckets and writes them to
+    ///   the sink (skipped until [`Self::mark_ready`] is called).
     /// - Incoming [`Update`]s are processed immediately as they arrive.
     ///
     /// Runs until the update channel is closed (all senders dropped).
@@ -93,8 +112,12 @@ impl MarketStateScheduler {
         // Align to the next wall-clock minute boundary.
         let now = Utc::now();
         let ms_into_minute = now.timestamp_millis() % 60_000;
-        let ms_until_next = (60_000 - ms_into_minute) as u64;
-        info!(ms_until_next, "market scheduler aligning to next minute boundary");
+        let lateness_ms = self.engine.allowed_lateness_ms() % 60_000;
+        let ms_until_next = ((60_000 - ms_into_minute + lateness_ms) % 60_000) as u64;
+        info!(
+            ms_until_next,
+            lateness_ms, "market scheduler aligning to next minute boundary"
+        );
         tokio::time::sleep(Duration::from_millis(ms_until_next)).await;
 
         let mut interval = tokio::time::interval(Duration::from_secs(60));
@@ -104,9 +127,13 @@ impl MarketStateScheduler {
             tokio::select! {
                 maybe_update = self.update_rx.recv() => {
                     match maybe_update {
-                        Some(Update::EngineReady) => {
-                            info!("market engine ready, flushes enabled");
-                            self.mark_ready();
+                        Some(Update::EngineReady { venue }) => {
+                            if self.ready {
+                                info!(venue, "venue seeded, joining from the next bucket");
+                            } else {
+                                info!(venue, "market engine ready, flushes enabled");
+                                self.mark_ready();
+                            }
                         }
                         Some(update) => self.engine.process(update),
                         None => {
@@ -116,7 +143,7 @@ impl MarketStateScheduler {
                     }
                 }
                 _ = interval.tick() => {
-                    if let Err(e) = self.flush_window() {
+                    if let Err(e) = self.flush_window().await {
                         tracing::error!(error = ?e, "market_state_1m flush failed");
                     }
                 }
@@ -130,35 +157,69 @@ impl MarketStateScheduler {
     // Internal helpers
     // -----------------------------------------------------------------------
 
-    /// Snapshot the current window, write rows to QuestDB, then reset counters.
-    /// No-op until [`Self::mark_ready`] has been called.
-    fn flush_window(&mut self) -> AnySignalResult<()> {
+    /// Seal every bucket older than the watermark and write its rows to
+    /// the sink. No-op until [`Self::mark_ready`] has been called. A failed
+    /// write is logged and the remaining batches and buckets still written.
+    async fn flush_window(&mut self) -> AnySignalResult<()> {
         if !self.ready {
             info!("market engine not yet ready, skipping flush");
             return Ok(());
         }
 
-        let now = Utc::now();
-        // Bucket is the minute that just completed: floor(now) - 1 min.
-        let bucket_ms = (now.timestamp_millis() / 60_000 - 1) * 60_000;
-
-        let rows: Vec<MarketStateRt1mRow> = self
-            .engine
-            .all()
-            .filter_map(|s| state_to_rt_row(s, bucket_ms))
-            .collect();
-
-        let count = rows.len();
-        if !rows.is_empty() {
-            let table = self.db.table_name("market_state_rt_1m");
-            self.db.with_sender(|s| insert_market_state_rt_1m(s, &table, &rows))?;
-        }
+        let watermark_ms = Utc::now().timestamp_millis() - self.engine.allowed_lateness_ms();
 
-        self.engine.reset_all_windows();
+        for bucket in self.engine.seal_until(watermark_ms) {
+            let rows: Vec<MarketStateRt1mRow> = bucket
+                .states
+                .iter()
+                .filter_map(|s| state_to_rt_row(s, bucket.bucket_ms))
+                .collect();
+
+            let count = rows.len();
+            if !rows.is_empty() {
+                self.write(RowBatch::MarketStateRt1m(&rows), bucket.bucket_ms)
+                    .await;
+            }
+            info!(
+                bucket_ms = bucket.bucket_ms,
+                coins = count,
+                "market_state_rt_1m flushed"
+            );
+
+            let fundings =
+                funding::predicted_funding_rows(bucket.bucket_ms, &bucket.predicted_fundings);
+            let spreads = funding::spread_rows(bucket.bucket_ms, &bucket.predicted_fundings);
+            if !fundings.is_empty() {
+                self.write(RowBatch::PredictedFunding(&fundings), bucket.bucket_ms)
+                    .await;
+            }
+            if !spreads.is_empty() {
+                self.write(RowBatch::FundingSpread(&spreads), bucket.bucket_ms)
+                    .await;
+            }
+            tracing::debug!(
+                bucket_ms = bucket.bucket_ms,
+                quotes = fundings.len(),
+                spreads = spreads.len(),
+                "predicted_funding / funding_spread flushed"
+            );
+        }
 
-        info!(bucket_ms, coins = count, "market_state_rt_1m flushed");
         Ok(())
     }
+
+    /// Write one batch of a sealed bucket. The bucket is gone from the
+    /// engine, so a failure only drops this batch.
+    async fn write(&self, batch: RowBatch<'_>, bucket_ms: i64) {
+        if let Err(e) = self.sink.write(batch).await {
+            tracing::error!(
+                error = ?e,
+                table = batch.table(),
+                bucket_ms,
+                "sealed bucket write failed, batch dropped"
+            );
+        }
+    }
 }
 
 /// Convert a [`MarketState`] snapshot into a [`MarketStateRt1mRow`] for the given
@@ -170,7 +231,10 @@ fn state_to_rt_row(state: &MarketState, bucket_ms: i64) -> Option<MarketStateRt1
 
     Some(MarketStateRt1mRow {
         minute_ms: bucket_ms,
+        venue: state.venue.clone(),
         coin: state.coin.clone(),
+        market_type: state.market_type.as_str().to_string(),
+        dex: state.dex.clone(),
         price_oracle: state.price_oracle,
         price_mark,
         price_mid,
@@ -1,4 +1,4 @@
-use crate::{adapter::AdapterError, api::rest::ApiError};
+use crate::{adapter::AdapterError, api::rest::ApiError, database::query::QueryError};
 pub use eyre::Error as EyreError;
 pub use questdb::Error as QuestError;
 pub use reqwest::Error as ReqwestError;
@@ -16,6 +16,8 @@ pub enum AnySignalError {
     Adapter(AdapterError),
     #[error("quest error: {0}")]
     Quest(QuestError),
+    #[error("questdb query error: {0}")]
+    QuestQuery(QueryError),
 }
 
 pub type AnySignalResult<T> = std::result::Result<T, AnySignalError>;
@@ -56,6 +58,12 @@ impl From<QuestError> for AnySignalError {
     }
 }
 
+impl From<QueryError> for AnySignalError {
+    fn from(error: QueryError) -> Self {
+        AnySignalError::QuestQuery(error)
+    }
+}
+
 impl From<ReqwestError> for AnySignalError {
     fn from(error: ReqwestError) -> Self {
         AnySignalError::Api(error.into())
@@ -2,13 +2,17 @@
 
 /// third party api
 pub mod adapter;
+/// studies over stored signals and prices
+pub mod analysis;
 /// realtime market state engine
 pub mod engine;
 /// api server
 pub mod api;
 /// partitioned backfill sources
 pub mod backfill;
-/// toml configuration
+/// strategy replay over stored history
+pub mod backtest;
+/// layered configuration: env vars over `config.toml` over defaults
 pub mod config;
 /// database
 pub mod database;
@@ -20,3 +24,9 @@ pub mod extension;
 pub mod metadata;
 /// project model
 pub mod model;
+/// periodic REST pollers
+pub mod poller;
+/// pluggable storage backends
+pub mod sink;
+/// paper-traded strategies and their instances
+pub mod strategy;
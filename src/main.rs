@@ -1,18 +1,103 @@
+use anysignal::adapter;
+use anysignal::adapter::coinmarketcap::bitcoin_dominance::BitcoinDominanceSignalSource;
 use anysignal::adapter::coinmarketcap::fear_and_greed::FearAndGreedSignalSource;
-use anysignal::adapter::coinmarketcap::prelude::PollingSignalSource;
-use anysignal::adapter::hyperliquid_ws::GuilderBridge;
-use anysignal::adapter::newsapi::run_news_fetcher;
+use anysignal::adapter::guilder;
+use anysignal::adapter::microstrategy::BtcPurchaseSignalSource;
+use anysignal::adapter::newsapi::NewsApiSignalSource;
+use anysignal::adapter::polling::{run_signal_source, PollingSignalSource};
 use anysignal::adapter::polygonio::run_polygonio_stock;
+use anysignal::adapter::youtube::{LiveClosedCaptionSource, YouTubeClosedCaptionRequirement};
 use anysignal::api::host_rest_api_server;
+use anysignal::config::live::LiveConfig;
 use anysignal::config::Config;
+use anysignal::database::migration::run_migrations;
+use anysignal::database::registry::SignalRegistry;
+use anysignal::database::retention::RetentionManager;
+use anysignal::database::spool::{self, Spool};
 use anysignal::database::QuestDbClient;
-use anysignal::engine::MarketStateScheduler;
+use anysignal::engine::{Event, MarketEngineConfig, MarketStateScheduler};
 use anysignal::error::{AnySignalError, AnySignalResult};
+use anysignal::model::strategy::Strategy;
+use anysignal::poller::PollerRegistry;
+use anysignal::sink;
+use anysignal::strategy::{builtin, runtime, StrategyRegistry, StrategyRuntime};
 use std::sync::Arc;
 use futures::future::join_all;
 use futures::TryFutureExt;
+use std::time::Duration;
+use tokio::sync::mpsc::error::TrySendError;
 use tokio::task::JoinHandle;
 
+/// How often `config.toml` is checked for changes.
+const CONFIG_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Spawn the polling runtime for one HTTP signal source. It only returns if
+/// no QuestDB client can be built; poll failures are retried.
+fn spawn_signal_source<S: PollingSignalSource + 'static>(
+    source: S,
+    config: &Config,
+    signals: &SignalRegistry,
+    pollers: &PollerRegistry,
+) -> JoinHandle<AnySignalResult<()>> {
+    let config = config.clone();
+    let signals = signals.clone();
+    let pollers = pollers.clone();
+    tokio::spawn(async move {
+        let db = Arc::new(QuestDbClient::new(&config).map_err(AnySignalError::from)?);
+        run_signal_source(source, db, signals, pollers).await;
+        Ok(())
+    })
+}
+
+/// Load the signal registry and register the adapters' signals. Without
+/// QuestDB the registry stays empty and unloaded; signal sources register on
+/// their first poll, which retries the load rather than allocate ids that
+/// could collide with stored signals.
+async fn load_signal_registry(config: &Config) -> SignalRegistry {
+    let db = match QuestDbClient::new(config) {
+        Ok(db) => db,
+        Err(e) => {
+            tracing::error!(error = %e, "signal registry unavailable: no QuestDB client");
+            return SignalRegistry::default();
+        }
+    };
+    let registry = match SignalRegistry::load(&db).await {
+        Ok(registry) => registry,
+        Err(e) => {
+            tracing::error!(error = %e, "signal registry unavailable");
+            return SignalRegistry::default();
+        }
+    };
+    for spec in adapter::signal_infos() {
+        if let Err(e) = registry.register(&db, &spec).await {
+            tracing::error!(error = %e, signal_type = spec.signal_type.as_str(), "signal registration failed");
+        }
+    }
+    tracing::info!(signals = registry.list().len(), "signal registry loaded");
+    registry
+}
+
+/// Load the instances of `strategies` stored in QuestDB. Without QuestDB the
+/// registry starts unloaded and opens no instances; the `strategy` runner
+/// retries the load before it trades.
+async fn load_strategy_registry(
+    config: &Config,
+    strategies: &[Box<dyn Strategy>],
+) -> StrategyRegistry {
+    let infos: Vec<_> = strategies.iter().map(|s| s.info()).collect();
+    let loaded = match QuestDbClient::new(config) {
+        Ok(db) => StrategyRegistry::load(&db, &infos).await,
+        Err(e) => Err(AnySignalError::from(e)),
+    };
+    match loaded {
+        Ok(registry) => registry,
+        Err(e) => {
+            tracing::error!(error = %e, "strategy instances unavailable");
+            StrategyRegistry::new(&infos)
+        }
+    }
+}
+
 /// load environment and manages runner at this level
 #[tokio::main]
 async fn main() -> AnySignalResult<()> {
@@ -21,8 +106,16 @@ async fn main() -> AnySignalResult<()> {
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
-    // set up config to load API tokens
-    let config: Config = Config::from_env();
+    // layered config: env vars over config.toml over defaults
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("{e}");
+            return Err(e);
+        }
+    };
+    let live_config = LiveConfig::new(config.clone());
+    tokio::spawn(live_config.clone().watch(CONFIG_RELOAD_INTERVAL));
 
     // Install a panic hook that appends a JSON entry to the postmortem log
     // file before the process exits.  The file persists across container
@@ -60,64 +153,201 @@ async fn main() -> AnySignalResult<()> {
     let config_json = serde_json::to_string_pretty(&config).unwrap_or_default();
     tracing::info!("config loaded:\n{config_json}");
 
+    // create / migrate QuestDB tables before any ILP writer can create them
+    // with guessed column types; not fatal so the API stays up without a DB
+    match QuestDbClient::new(&config) {
+        Ok(db) => match run_migrations(&db).await {
+            Ok(applied) => tracing::info!(applied = applied.len(), "schema migrations up to date"),
+            Err(e) => tracing::error!(error = %e, "schema migrations failed"),
+        },
+        Err(e) => tracing::error!(error = %e, "schema migrations skipped: no QuestDB client"),
+    }
+
+    // replay ILP buffers spooled while QuestDB was unreachable, in write order
+    if let Some(spool) = Spool::from_config(&config) {
+        let pending = spool.status();
+        if pending.files > 0 {
+            tracing::warn!(
+                files = pending.files,
+                bytes = pending.bytes,
+                "spooled ILP buffers pending replay"
+            );
+        }
+        tokio::spawn(spool::run_replay(spool, config.clone()));
+    }
+
+    // signal ids, shared between the signal runners and the API
+    let signals = load_signal_registry(&config).await;
+
+    // health of REST pollers, shared between the realtime runner and the API
+    let pollers = PollerRegistry::default();
+
+    // retention policies, shared between the retention runner and the API
+    let retention = RetentionManager::load(&config.retention_policy_path);
+
+    // paper-traded strategies and their instances, shared between the
+    // strategy runner and the API
+    let strategy_list: Vec<Box<dyn Strategy>> = if config.has_runner("strategy") {
+        config
+            .strategies
+            .iter()
+            .filter_map(|name| builtin::build(name))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let strategies = load_strategy_registry(&config, &strategy_list).await;
+
+    // engine events, forwarded by the realtime runner to the strategy runner
+    let (strategy_event_tx, strategy_event_rx) =
+        tokio::sync::mpsc::channel::<Event>(runtime::EVENT_CAPACITY);
+
     // each runner returns signals::error::Result<()>
     let mut runners: Vec<JoinHandle<AnySignalResult<()>>> = Vec::new();
 
     if config.has_runner("api") {
         tracing::info!("Starting API server");
         let api_config = config.clone();
+        let api_pollers = pollers.clone();
+        let api_retention = retention.clone();
+        let api_signals = signals.clone();
+        let api_strategies = strategies.clone();
         let handle = tokio::spawn(
-            async move { host_rest_api_server(api_config).await }.map_err(AnySignalError::from),
+            async move {
+                host_rest_api_server(
+                    api_config,
+                    api_pollers,
+                    api_retention,
+                    api_signals,
+                    api_strategies,
+                )
+                .await
+            }
+            .map_err(AnySignalError::from),
         );
         runners.push(handle);
     }
 
     if config.has_runner("coinmarketcap") {
         tracing::info!("Starting CoinMarketCap indexer");
-        let poll_duration = tokio::time::Duration::from_secs(10);
-        let config = config.clone();
-        let source =
-            FearAndGreedSignalSource::new(config.get_api_key("coinmarketcap")?, poll_duration);
-        let handle = tokio::spawn(async move { source.run_loop().await });
-        runners.push(handle);
+        let api_key = config.get_api_key("coinmarketcap")?;
+        let source = FearAndGreedSignalSource::new(api_key.clone(), live_config.clone());
+        runners.push(spawn_signal_source(source, &config, &signals, &pollers));
+        let source = BitcoinDominanceSignalSource::new(api_key, live_config.clone());
+        runners.push(spawn_signal_source(source, &config, &signals, &pollers));
     }
 
     if config.has_runner("newsapi") {
         tracing::info!("Starting NewsAPI indexer");
-        let period = tokio::time::Duration::from_secs(10);
-        let config = config.clone();
+        let source = NewsApiSignalSource::new(config.get_api_key("newsapi")?, live_config.clone());
+        runners.push(spawn_signal_source(source, &config, &signals, &pollers));
+    }
 
-        let handle = tokio::spawn(async move { run_news_fetcher(config.clone(), period).await });
-        runners.push(handle);
+    if config.has_runner("microstrategy") {
+        tracing::info!("Starting MicroStrategy purchase indexer");
+        let source = BtcPurchaseSignalSource::new(live_config.clone());
+        runners.push(spawn_signal_source(source, &config, &signals, &pollers));
+    }
+
+    if config.has_runner("youtube") {
+        tracing::info!(
+            videos = config.youtube_video_urls.len(),
+            "Starting YouTube caption indexer"
+        );
+        let api_key = config.get_api_key("youtube_data_v3")?;
+        for url in &config.youtube_video_urls {
+            let requirement = YouTubeClosedCaptionRequirement {
+                url: url.clone(),
+                language: None,
+                polling_period: Duration::from_secs(config.youtube_poll_interval_secs),
+            };
+            let source = LiveClosedCaptionSource::new(api_key.clone(), requirement);
+            runners.push(spawn_signal_source(source, &config, &signals, &pollers));
+        }
     }
 
     if config.has_runner("realtime") {
         tracing::info!("Starting realtime market state engine");
         let config = config.clone();
+        let pollers = pollers.clone();
+        let strategy_events = config.has_runner("strategy").then_some(strategy_event_tx);
         let handle = tokio::spawn(async move {
             let db = Arc::new(
                 QuestDbClient::new(&config).map_err(AnySignalError::from)?,
             );
-            let (scheduler, _event_rx) = MarketStateScheduler::new(db);
-
-            // Bridge: connects to Hyperliquid WS, seeds the engine with REST
-            // snapshots, then streams fills + asset contexts as Updates.
-            // Predicted funding polling is handled inside the bridge.
-            let update_tx = scheduler.update_sender();
+            let sink = sink::from_config(&config, db).await?;
+            tracing::info!(sink = sink.name(), "realtime engine sink");
+            let engine_cfg = MarketEngineConfig {
+                allowed_lateness: Duration::from_secs(config.realtime_allowed_lateness_secs),
+                ..MarketEngineConfig::default()
+            };
+            let (scheduler, mut event_rx) = MarketStateScheduler::new(sink, engine_cfg);
+            // drained even without strategies so sealed buckets don't pile up
             tokio::spawn(async move {
-                GuilderBridge::new(update_tx).run().await;
+                while let Some(event) = event_rx.recv().await {
+                    let Some(tx) = &strategy_events else {
+                        continue;
+                    };
+                    // strategies falling behind lose events rather than
+                    // hold sealed buckets in memory
+                    if let Err(TrySendError::Full(_)) = tx.try_send(event) {
+                        tracing::warn!("strategy runtime behind, dropping engine event");
+                    }
+                }
             });
 
+            // Bridges: one per venue. Each connects to its exchange WS, seeds
+            // the engine with REST snapshots, then streams fills + asset
+            // contexts as Updates. Predicted funding and symbol refetch run
+            // as registered pollers next to each bridge.
+            for venue in &config.realtime_venues {
+                if guilder::spawn_bridge(venue, scheduler.update_sender(), &pollers).is_none() {
+                    tracing::warn!(
+                        venue = venue.as_str(),
+                        supported = ?guilder::SUPPORTED_VENUES,
+                        "no guilder client for venue, skipping"
+                    );
+                }
+            }
+
             scheduler.run().await
         });
         runners.push(handle);
     }
 
+    if config.has_runner("retention") {
+        tracing::info!("Starting retention manager");
+        let config = config.clone();
+        let retention = retention.clone();
+        let handle = tokio::spawn(async move {
+            let db = QuestDbClient::new(&config).map_err(AnySignalError::from)?;
+            let interval = Duration::from_secs(config.retention_interval_secs);
+            retention.run_loop(db, interval).await;
+            Ok(())
+        });
+        runners.push(handle);
+    }
+
+    if config.has_runner("strategy") {
+        tracing::info!(strategies = ?config.strategies, "Starting paper-trading strategies");
+        let config = config.clone();
+        let signals = signals.clone();
+        let strategies = strategies.clone();
+        let handle = tokio::spawn(async move {
+            let db = QuestDbClient::new(&config).map_err(AnySignalError::from)?;
+            let interval = Duration::from_secs(config.strategy_signal_poll_interval_secs);
+            StrategyRuntime::new(strategy_list, strategies, signals, db, interval)
+                .run(strategy_event_rx)
+                .await
+        });
+        runners.push(handle);
+    }
+
     if config.has_runner("polygonio") {
         tracing::info!("Starting PolygonIO indexer");
-        let config = config.clone();
         let api_key = config.get_api_key("polygonio")?;
-        let handle = tokio::spawn(async move { run_polygonio_stock(api_key).await });
+        let live_config = live_config.clone();
+        let handle = tokio::spawn(async move { run_polygonio_stock(api_key, live_config).await });
         runners.push(handle);
     }
 
@@ -1,3 +1,7 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::MarketKey;
 use crate::error::AnySignalResult;
 
 /// instance is generated by strategy
@@ -12,3 +16,204 @@ pub trait Instance {
     // async fn that terminates the instance
     fn terminate(&mut self) -> impl std::future::Future<Output = AnySignalResult<()>> + Send;
 }
+
+/// Direction of a leg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    Long,
+    Short,
+}
+
+impl Side {
+    /// `1.0` for long, `-1.0` for short.
+    pub fn sign(&self) -> f64 {
+        match self {
+            Side::Long => 1.0,
+            Side::Short => -1.0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Side::Long => "long",
+            Side::Short => "short",
+        }
+    }
+}
+
+/// One position of an instance, filled on paper.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Leg {
+    pub market: MarketKey,
+    pub side: Side,
+    /// Position size in base units.
+    pub size: f64,
+    pub entry_price: f64,
+    /// Latest price the leg was marked at; the exit price once closed.
+    pub mark_price: f64,
+}
+
+impl Leg {
+    /// Unrealised (or, once closed, realised) pnl in quote currency.
+    pub fn pnl(&self) -> f64 {
+        self.side.sign() * self.size * (self.mark_price - self.entry_price)
+    }
+}
+
+/// Lifecycle of an instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceStatus {
+    Open,
+    /// Closed by its strategy.
+    Closed,
+    /// Stopped from outside, e.g. `POST /instances/{id}/terminate`.
+    Terminated,
+}
+
+impl InstanceStatus {
+    /// Value of the `status` column.
+    pub fn name(&self) -> &'static str {
+        match self {
+            InstanceStatus::Open => "open",
+            InstanceStatus::Closed => "closed",
+            InstanceStatus::Terminated => "terminated",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "open" => Some(InstanceStatus::Open),
+            "closed" => Some(InstanceStatus::Closed),
+            "terminated" => Some(InstanceStatus::Terminated),
+            _ => None,
+        }
+    }
+}
+
+/// An [`Instance`] traded on paper: its legs are filled and marked against
+/// market state, never sent to an exchange.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaperInstance {
+    pub id: u64,
+    pub strategy_id: u64,
+    pub status: InstanceStatus,
+    pub legs: Vec<Leg>,
+    /// Why it was opened, then why it was closed.
+    pub reason: String,
+    pub opened_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl PaperInstance {
+    pub fn open(
+        id: u64,
+        strategy_id: u64,
+        legs: Vec<Leg>,
+        time: DateTime<Utc>,
+        reason: String,
+    ) -> Self {
+        Self {
+            id,
+            strategy_id,
+            status: InstanceStatus::Open,
+            legs,
+            reason,
+            opened_at: time,
+            closed_at: None,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.status == InstanceStatus::Open
+    }
+
+    /// Sum of the legs' pnl.
+    pub fn pnl(&self) -> f64 {
+        self.legs.iter().map(Leg::pnl).sum()
+    }
+
+    /// Mark the legs of an open instance with `price_of` their market.
+    /// Legs without a price keep their last mark.
+    pub fn mark(&mut self, price_of: impl Fn(&MarketKey) -> Option<f64>) {
+        if !self.is_open() {
+            return;
+        }
+        for leg in &mut self.legs {
+            if let Some(price) = price_of(&leg.market) {
+                leg.mark_price = price;
+            }
+        }
+    }
+
+    /// Close at the current marks. No-op unless open.
+    pub fn close(&mut self, status: InstanceStatus, time: DateTime<Utc>, reason: String) {
+        if !self.is_open() {
+            return;
+        }
+        self.status = status;
+        self.closed_at = Some(time);
+        self.reason = reason;
+    }
+}
+
+impl Instance for PaperInstance {
+    fn strategy_id(&mut self) -> AnySignalResult<String> {
+        Ok(self.strategy_id.to_string())
+    }
+
+    fn id(&mut self) -> AnySignalResult<String> {
+        Ok(self.id.to_string())
+    }
+
+    fn current_status(&mut self) -> AnySignalResult<String> {
+        Ok(self.status.name().to_string())
+    }
+
+    /// Exits every leg at its last mark.
+    fn terminate(&mut self) -> impl std::future::Future<Output = AnySignalResult<()>> + Send {
+        let result = if self.is_open() {
+            self.close(
+                InstanceStatus::Terminated,
+                Utc::now(),
+                "terminated".to_string(),
+            );
+            Ok(())
+        } else {
+            Err(eyre::eyre!("instance {} is already {}", self.id, self.status.name()).into())
+        };
+        std::future::ready(result)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn marks_and_terminates() {
+        let btc = MarketKey::new("HlPerp", "BTC");
+        let leg = Leg {
+            market: btc.clone(),
+            side: Side::Short,
+            size: 2.0,
+            entry_price: 100.0,
+            mark_price: 100.0,
+        };
+        let mut instance = PaperInstance::open(7, 1, vec![leg], Utc::now(), "test".into());
+        instance.mark(|m| (*m == btc).then_some(90.0));
+        assert_eq!(instance.pnl(), 20.0);
+        assert_eq!(instance.current_status().unwrap(), "open");
+
+        instance.terminate().await.unwrap();
+        assert_eq!(instance.status, InstanceStatus::Terminated);
+        assert!(instance.closed_at.is_some());
+        // closed legs keep their exit price
+        instance.mark(|_| Some(50.0));
+        assert_eq!(instance.pnl(), 20.0);
+        assert!(instance.terminate().await.is_err());
+    }
+}
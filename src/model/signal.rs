@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::hash::Hash;
 
 use chrono::Utc;
@@ -35,20 +36,88 @@ pub enum SignalData {
     Binary(bool),
     Scalar(f64),
     Text(String),
-    // Json(Value), // JSON not supported by questdb
+    /// Ordered values, e.g. per-filer amounts of one SEC filing.
+    Vector(Vec<f64>),
+    /// Named fields, e.g. the attributes of a DexScreener token.
+    Structured(BTreeMap<String, FieldValue>),
+}
+
+/// One field of a [`SignalData::Structured`] signal.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Number(f64),
+    Text(String),
+}
+
+impl SignalData {
+    pub fn data_type(&self) -> SignalDataType {
+        match self {
+            SignalData::Simple => SignalDataType::Simple,
+            SignalData::Binary(_) => SignalDataType::Binary,
+            SignalData::Scalar(_) => SignalDataType::Scalar,
+            SignalData::Text(_) => SignalDataType::Text,
+            SignalData::Vector(_) => SignalDataType::Vector,
+            SignalData::Structured(_) => SignalDataType::Structured,
+        }
+    }
 }
 
 /// description of SignalData
-#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub enum SignalDataType {
     #[default]
     Simple,
     Binary,
     Scalar,
     Text,
+    Vector,
+    Structured,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+impl SignalDataType {
+    /// Lowercase name stored in `signal_info` and returned by the API.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SignalDataType::Simple => "simple",
+            SignalDataType::Binary => "binary",
+            SignalDataType::Scalar => "scalar",
+            SignalDataType::Text => "text",
+            SignalDataType::Vector => "vector",
+            SignalDataType::Structured => "structured",
+        }
+    }
+
+    /// Base name of the QuestDB table signals of this type are stored in.
+    pub fn table(&self) -> &'static str {
+        match self {
+            SignalDataType::Simple => "signal_simple",
+            SignalDataType::Binary => "signal_binary",
+            SignalDataType::Scalar => "signal_scalar",
+            SignalDataType::Text => "signal_text",
+            SignalDataType::Vector => "signal_vector",
+            SignalDataType::Structured => "signal_structured",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Simple,
+            Self::Binary,
+            Self::Scalar,
+            Self::Text,
+            Self::Vector,
+            Self::Structured,
+        ]
+        .into_iter()
+        .find(|t| t.name() == name)
+    }
+}
+
+/// What a signal is. `id` is assigned by the signal registry
+/// (`database::registry`); adapters describe their signals with
+/// [`SignalInfo::UNREGISTERED`] and register them at startup.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct SignalInfo {
     pub id: i64,
     pub signal_type: String,
@@ -57,16 +126,33 @@ pub struct SignalInfo {
     pub description: String,
     /// atomic: is raw and indivisible
     pub is_atomic: bool,
+    /// What distinguishes signals of the same type and source, e.g. the
+    /// video URL of a YouTube caption signal.
+    #[serde(default)]
+    pub parameters: BTreeMap<String, String>,
 }
 impl SignalInfo {
+    /// Id of a signal the registry has not assigned one to. Never allocated;
+    /// negative because 0 is the legacy id of `fear_and_greed`.
+    pub const UNREGISTERED: i64 = -1;
+
     pub fn dummy() -> Self {
         Self {
-            id: 0,
+            id: Self::UNREGISTERED,
             signal_type: "dummy_index".to_string(),
             data_type: SignalDataType::Scalar,
             source: "DummySource".to_string(),
             description: "Dummy Index".to_string(),
             is_atomic: true,
+            parameters: BTreeMap::new(),
         }
     }
+
+    /// Same signal regardless of id and description: type, source and
+    /// parameters match.
+    pub fn same_signal(&self, other: &SignalInfo) -> bool {
+        self.signal_type == other.signal_type
+            && self.source == other.source
+            && self.parameters == other.parameters
+    }
 }
@@ -1,6 +1,132 @@
+use chrono::{DateTime, Utc};
+
+use super::instance::{PaperInstance, Side};
+use super::signal::{Signal, SignalInfo};
+use crate::engine::{MarketKey, MarketState};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct StrategyInfo {
     pub id: u64,
     pub name: String,
     pub description: String,
     pub creator: String,
 }
+
+/// What a strategy reacts to.
+#[derive(Debug, Clone)]
+pub enum StrategyEvent {
+    /// A sealed market bucket: every tracked market as of `time`.
+    Market {
+        time: DateTime<Utc>,
+        states: Vec<MarketState>,
+    },
+    /// Fills aggregated over the minute ending at `time`. Backtests only,
+    /// for strategies whose [`Strategy::feeds`] ask for them.
+    Fills {
+        time: DateTime<Utc>,
+        fills: Vec<FillFlow>,
+    },
+    /// Order book metrics over the minute ending at `time`. Backtests only,
+    /// for strategies whose [`Strategy::feeds`] ask for them.
+    Book {
+        time: DateTime<Utc>,
+        books: Vec<BookMetrics>,
+    },
+    /// A new row of a signal the strategy subscribed to.
+    Signal { info: SignalInfo, signal: Signal },
+}
+
+impl StrategyEvent {
+    pub fn time(&self) -> DateTime<Utc> {
+        match self {
+            StrategyEvent::Market { time, .. }
+            | StrategyEvent::Fills { time, .. }
+            | StrategyEvent::Book { time, .. } => *time,
+            StrategyEvent::Signal { signal, .. } => {
+                DateTime::from_timestamp_micros(signal.timestamp_us).unwrap_or_default()
+            }
+        }
+    }
+}
+
+/// Fills of one coin, category and aggressor side over a minute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillFlow {
+    pub coin: String,
+    /// Fill direction, e.g. `Open Long` or `Liquidated Cross Long`.
+    pub category: String,
+    pub buy_side: bool,
+    pub quantity: f64,
+    pub trade_count: u64,
+}
+
+/// Top of book and depth of one coin, from minute-averaged L2 levels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookMetrics {
+    pub coin: String,
+    pub best_bid: f64,
+    pub best_ask: f64,
+    pub spread_bps: f64,
+    /// Quote value resting on the bid side over the levels kept.
+    pub bid_depth: f64,
+    pub ask_depth: f64,
+    /// `(bid_depth - ask_depth) / (bid_depth + ask_depth)`, in `[-1, 1]`.
+    pub imbalance: f64,
+}
+
+/// Events a strategy receives beyond market state and its signals.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Feeds {
+    /// [`StrategyEvent::Fills`].
+    pub fills: bool,
+    /// [`StrategyEvent::Book`].
+    pub books: bool,
+}
+
+impl Feeds {
+    /// Whether the paper-trading runtime can drive a strategy with these
+    /// feeds; it delivers neither fills nor books.
+    pub fn live(&self) -> bool {
+        !self.fills && !self.books
+    }
+}
+
+/// One leg a strategy wants opened; the broker picks the fill price.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegOrder {
+    pub market: MarketKey,
+    pub side: Side,
+    /// Position size in quote currency at the fill price.
+    pub notional: f64,
+}
+
+/// A decision taken on an event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Spawn an instance holding `legs`.
+    Open { legs: Vec<LegOrder>, reason: String },
+    /// Close one of the strategy's open instances.
+    Close { instance_id: u64, reason: String },
+}
+
+/// A trading strategy driven by market and signal events.
+///
+/// Strategies only decide; whoever drives them (the paper-trading runtime,
+/// a backtest) fills the [`Action`]s and tracks the resulting instances.
+pub trait Strategy: Send {
+    fn info(&self) -> StrategyInfo;
+
+    /// Signals to receive, as specs resolved against the signal registry.
+    fn signals(&self) -> Vec<SignalInfo> {
+        Vec::new()
+    }
+
+    /// Fill and book events to receive. Only backtests replay them, so a
+    /// strategy asking for either cannot be paper-traded.
+    fn feeds(&self) -> Feeds {
+        Feeds::default()
+    }
+
+    /// React to `event`, given the strategy's currently open instances.
+    fn on_event(&mut self, event: &StrategyEvent, open: &[PaperInstance]) -> Vec<Action>;
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::PollerConfig;
+
+/// Coarse poller state derived from the most recent attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PollerStatus {
+    /// Registered, no poll completed yet.
+    Pending,
+    /// The most recent poll succeeded.
+    Healthy,
+    /// The most recent poll failed (retrying or waiting for the next tick).
+    Failing,
+    /// The consumer went away and the poller exited.
+    Stopped,
+}
+
+/// Health snapshot of one poller.
+#[derive(Debug, Clone, Serialize)]
+pub struct PollerHealth {
+    pub name: String,
+    pub interval_ms: u64,
+    pub jitter_ms: u64,
+    pub max_retries: u32,
+    pub status: PollerStatus,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    /// Failed attempts since the last success (retries included).
+    pub consecutive_failures: u64,
+    pub total_successes: u64,
+    pub total_failures: u64,
+}
+
+/// Shared registry of poller health.
+///
+/// Cheaply cloneable — backed by an [`Arc`] so all clones share the same state.
+#[derive(Clone, Default)]
+pub struct PollerRegistry(Arc<Mutex<BTreeMap<String, PollerHealth>>>);
+
+impl PollerRegistry {
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, PollerHealth>> {
+        match self.0.lock() {
+            Ok(g) => g,
+            Err(p) => p.into_inner(),
+        }
+    }
+
+    /// Register (or reset) the entry for `cfg.name` and return a handle that
+    /// records into it.
+    pub fn register(&self, cfg: &PollerConfig) -> PollerHandle {
+        self.lock().insert(
+            cfg.name.clone(),
+            PollerHealth {
+                name: cfg.name.clone(),
+                interval_ms: cfg.interval.as_millis() as u64,
+                jitter_ms: cfg.jitter.as_millis() as u64,
+                max_retries: cfg.retry.max_retries,
+                status: PollerStatus::Pending,
+                last_success_at: None,
+                last_error: None,
+                last_error_at: None,
+                consecutive_failures: 0,
+                total_successes: 0,
+                total_failures: 0,
+            },
+        );
+        PollerHandle {
+            registry: self.clone(),
+            name: cfg.name.clone(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<PollerHealth> {
+        self.lock().get(name).cloned()
+    }
+
+    /// Snapshot of every registered poller, ordered by name.
+    pub fn list(&self) -> Vec<PollerHealth> {
+        self.lock().values().cloned().collect()
+    }
+
+    fn update(&self, name: &str, f: impl FnOnce(&mut PollerHealth)) {
+        if let Some(h) = self.lock().get_mut(name) {
+            f(h);
+        }
+    }
+}
+
+/// Write access to one poller's entry in a [`PollerRegistry`].
+pub struct PollerHandle {
+    registry: PollerRegistry,
+    name: String,
+}
+
+impl PollerHandle {
+    pub fn record_success(&self) {
+        self.registry.update(&self.name, |h| {
+            h.status = PollerStatus::Healthy;
+            h.last_success_at = Some(Utc::now());
+            h.consecutive_failures = 0;
+            h.total_successes += 1;
+        });
+    }
+
+    pub fn record_failure(&self, error: &str) {
+        self.registry.update(&self.name, |h| {
+            h.status = PollerStatus::Failing;
+            h.last_error = Some(error.to_string());
+            h.last_error_at = Some(Utc::now());
+            h.consecutive_failures += 1;
+            h.total_failures += 1;
+        });
+    }
+
+    pub fn record_interval(&self, interval: std::time::Duration) {
+        self.registry
+            .update(&self.name, |h| h.interval_ms = interval.as_millis() as u64);
+    }
+
+    pub fn record_stopped(&self) {
+        self.registry
+            .update(&self.name, |h| h.status = PollerStatus::Stopped);
+    }
+}
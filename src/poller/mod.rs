@@ -0,0 +1,332 @@
+pub mod health;
+
+pub use health::{PollerHandle, PollerHealth, PollerRegistry, PollerStatus};
+
+use std::time::Duration;
+
+use rand::Rng;
+use tokio::sync::mpsc::UnboundedSender;
+use tracing::{debug, warn};
+
+/// A REST data source polled on a fixed interval (predicted funding, the
+/// symbol list, vault TVL, …).
+///
+/// [`run_poller`] drives it with jitter and retry/backoff, forwards every
+/// successful result over a channel, and records health in a shared
+/// [`PollerRegistry`] exposed via `GET /pollers`.
+///
+/// Implementations hold their own client and any state needed between polls.
+/// Errors are plain strings, matching `guilder-abstraction`'s client API.
+#[allow(async_fn_in_trait)]
+pub trait Poller {
+    /// Value forwarded to the consumer on every successful poll.
+    type Output;
+
+    /// Fetch the current value once.
+    async fn poll(&mut self) -> Result<Self::Output, String>;
+
+    /// Interval to use instead of [`PollerConfig::interval`], re-read after
+    /// every tick so hot-reloaded settings apply.
+    fn interval(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// Where a poller's results go. Implemented for [`UnboundedSender`]; custom
+/// sinks can split one result across several consumers.
+pub trait PollerSink<T> {
+    /// Deliver one poll result. Returns `false` once the consumer is gone.
+    fn deliver(&self, value: T) -> bool;
+
+    /// `true` once the consumer is gone and polling should stop.
+    fn is_closed(&self) -> bool;
+}
+
+impl<T> PollerSink<T> for UnboundedSender<T> {
+    fn deliver(&self, value: T) -> bool {
+        self.send(value).is_ok()
+    }
+
+    fn is_closed(&self) -> bool {
+        UnboundedSender::is_closed(self)
+    }
+}
+
+/// Sink for pollers that handle their results inside [`Poller::poll`], such
+/// as writing them to QuestDB. Accepts everything and never closes.
+pub struct Discard;
+
+impl<T> PollerSink<T> for Discard {
+    fn deliver(&self, _value: T) -> bool {
+        true
+    }
+
+    fn is_closed(&self) -> bool {
+        false
+    }
+}
+
+/// Retry behaviour for a single tick: after a failed poll, retry up to
+/// `max_retries` times with exponential backoff before waiting for the next
+/// tick.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// Backoff before the first retry; doubled on every subsequent retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for a single backoff.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(15),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff before retry number `attempt` (0-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Per-poller scheduling configuration.
+#[derive(Debug, Clone)]
+pub struct PollerConfig {
+    /// Unique name shown in `GET /pollers`, e.g. `HlPerp/predicted_funding`.
+    pub name: String,
+    /// Delay between the end of one tick and the start of the next.
+    pub interval: Duration,
+    /// Uniform random delay in `[0, jitter]` added to every interval so
+    /// pollers sharing an API do not fire in lockstep.
+    pub jitter: Duration,
+    pub retry: RetryPolicy,
+    /// Poll once immediately on start instead of waiting one interval first.
+    pub poll_on_start: bool,
+}
+
+impl PollerConfig {
+    pub fn new(name: impl Into<String>, interval: Duration) -> Self {
+        Self {
+            name: name.into(),
+            interval,
+            jitter: Duration::ZERO,
+            retry: RetryPolicy::default(),
+            poll_on_start: true,
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_poll_on_start(mut self, poll_on_start: bool) -> Self {
+        self.poll_on_start = poll_on_start;
+        self
+    }
+
+    /// Interval plus a random jitter in `[0, jitter]`.
+    fn next_delay(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return self.interval;
+        }
+        let jitter_ms = rand::thread_rng().gen_range(0..=self.jitter.as_millis() as u64);
+        self.interval + Duration::from_millis(jitter_ms)
+    }
+}
+
+/// Drive `poller` until `sink` is closed.
+///
+/// Every successful [`Poller::poll`] result is delivered to `sink`. A failed poll is
+/// retried per [`PollerConfig::retry`]; once retries are exhausted the tick is
+/// skipped and the poller waits for the next one. Health is recorded in the
+/// `registry` entry registered under [`PollerConfig::name`]. A
+/// [`Poller::interval`] override replaces the configured interval.
+pub async fn run_poller<P: Poller>(
+    mut poller: P,
+    mut cfg: PollerConfig,
+    sink: impl PollerSink<P::Output>,
+    registry: PollerRegistry,
+) {
+    let health = registry.register(&cfg);
+
+    if !cfg.poll_on_start {
+        tokio::time::sleep(cfg.next_delay()).await;
+    }
+
+    while !sink.is_closed() {
+        if let Some(output) = poll_with_retry(&mut poller, &cfg, &health).await {
+            if !sink.deliver(output) {
+                break;
+            }
+        }
+        if let Some(interval) = poller.interval().filter(|i| *i != cfg.interval) {
+            cfg.interval = interval;
+            health.record_interval(interval);
+        }
+        tokio::time::sleep(cfg.next_delay()).await;
+    }
+
+    debug!(
+        poller = cfg.name.as_str(),
+        "poller consumer dropped, stopping"
+    );
+    health.record_stopped();
+}
+
+/// One tick: poll, retrying with backoff on failure.
+async fn poll_with_retry<P: Poller>(
+    poller: &mut P,
+    cfg: &PollerConfig,
+    health: &PollerHandle,
+) -> Option<P::Output> {
+    let mut attempt = 0;
+    loop {
+        match poller.poll().await {
+            Ok(output) => {
+                health.record_success();
+                return Some(output);
+            }
+            Err(e) => {
+                health.record_failure(&e);
+                if attempt >= cfg.retry.max_retries {
+                    warn!(
+                        poller = cfg.name.as_str(),
+                        attempt, "poll failed, giving up until next tick: {e}"
+                    );
+                    return None;
+                }
+                let backoff = cfg.retry.backoff(attempt);
+                warn!(
+                    poller = cfg.name.as_str(),
+                    attempt,
+                    ?backoff,
+                    "poll failed, retrying: {e}"
+                );
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails `failures` times, then yields an increasing counter.
+    struct Flaky {
+        failures: u32,
+        calls: u32,
+    }
+
+    impl Poller for Flaky {
+        type Output = u32;
+
+        async fn poll(&mut self) -> Result<u32, String> {
+            self.calls += 1;
+            if self.calls <= self.failures {
+                Err(format!("boom {}", self.calls))
+            } else {
+                Ok(self.calls)
+            }
+        }
+    }
+
+    fn fast_cfg(name: &str) -> PollerConfig {
+        PollerConfig::new(name, Duration::from_millis(5)).with_retry(RetryPolicy {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+        })
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_cap() {
+        let retry = RetryPolicy {
+            max_retries: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+        };
+        assert_eq!(retry.backoff(0), Duration::from_secs(1));
+        assert_eq!(retry.backoff(1), Duration::from_secs(2));
+        assert_eq!(retry.backoff(2), Duration::from_secs(4));
+        assert_eq!(retry.backoff(3), Duration::from_secs(5));
+        assert_eq!(retry.backoff(40), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let cfg =
+            PollerConfig::new("j", Duration::from_secs(10)).with_jitter(Duration::from_secs(2));
+        for _ in 0..100 {
+            let d = cfg.next_delay();
+            assert!(d >= Duration::from_secs(10) && d <= Duration::from_secs(12));
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_then_forwards_and_records_health() {
+        let registry = PollerRegistry::default();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let task = tokio::spawn(run_poller(
+            Flaky {
+                failures: 2,
+                calls: 0,
+            },
+            fast_cfg("flaky"),
+            tx,
+            registry.clone(),
+        ));
+
+        assert_eq!(rx.recv().await, Some(3));
+        let health = registry.get("flaky").unwrap();
+        assert_eq!(health.status, PollerStatus::Healthy);
+        assert_eq!(health.total_failures, 2);
+        assert_eq!(health.consecutive_failures, 0);
+        assert!(health.last_error.is_some());
+
+        drop(rx);
+        task.await.unwrap();
+        assert_eq!(registry.get("flaky").unwrap().status, PollerStatus::Stopped);
+    }
+
+    #[tokio::test]
+    async fn exhausted_retries_skip_the_tick() {
+        let registry = PollerRegistry::default();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        // max_retries = 2 → 3 attempts per tick; 4 failures span two ticks.
+        let task = tokio::spawn(run_poller(
+            Flaky {
+                failures: 4,
+                calls: 0,
+            },
+            fast_cfg("skip"),
+            tx,
+            registry.clone(),
+        ));
+
+        assert_eq!(rx.recv().await, Some(5));
+        assert_eq!(registry.get("skip").unwrap().total_failures, 4);
+        drop(rx);
+        task.await.unwrap();
+    }
+}
@@ -0,0 +1,638 @@
+pub mod parquet;
+pub mod postgres;
+pub mod questdb;
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::adapter::hyperliquid_s3::asset_ctxs::AssetCtxRow;
+use crate::adapter::hyperliquid_s3::market_data::L2Snapshot;
+use crate::adapter::hyperliquid_s3::node_fills_by_block::ParsedFill;
+use crate::config::Config;
+use crate::database::schema;
+use crate::database::{
+    Fill1mAggregate, FundingSpreadRow, MarketStateRow, MarketStateRt1mRow, PredictedFundingRow,
+    QuestDbClient,
+};
+use crate::error::AnySignalResult;
+
+pub use self::parquet::ParquetSink;
+pub use self::postgres::PostgresSink;
+pub use self::questdb::QuestDbSink;
+
+// ---------------------------------------------------------------------------
+// Sink trait
+// ---------------------------------------------------------------------------
+
+/// Storage backend for the rows produced by backfills and the realtime
+/// scheduler.
+///
+/// Every backend lays tables out as described by [`crate::database::schema`]
+/// and applies the dev-mode `_dev` suffix itself. Backfills ask the sink
+/// whether a partition is already present; other reads (`MarketState1mSource`,
+/// the REST API) still go to QuestDB.
+#[async_trait::async_trait]
+pub trait Sink: Send + Sync {
+    /// Backend name as configured via `SINK`.
+    fn name(&self) -> &'static str;
+
+    /// Write one batch to its table and return the number of table rows
+    /// written (after any fan-out, e.g. one `market_data` row per metric).
+    async fn write(&self, batch: RowBatch<'_>) -> AnySignalResult<usize>;
+
+    /// Whether this backend already holds any row of `partition`, so a
+    /// backfill can skip it. A table not created yet holds none.
+    async fn is_partition_present(&self, partition: &PartitionFilter) -> AnySignalResult<bool>;
+}
+
+/// The rows one backfill partition writes to `table`: those with the table's
+/// designated timestamp in `[from, to)` and every `eq` column equal to its
+/// value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionFilter {
+    /// Base table name; see [`Config::table_name`] for the dev suffix.
+    pub table: &'static str,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub eq: Vec<(&'static str, String)>,
+}
+
+impl PartitionFilter {
+    pub fn new(table: &'static str, from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        Self {
+            table,
+            from,
+            to,
+            eq: Vec::new(),
+        }
+    }
+
+    /// Only rows whose `column` is `value`.
+    pub fn eq(mut self, column: &'static str, value: &str) -> Self {
+        self.eq.push((column, value.to_string()));
+        self
+    }
+
+    /// Designated timestamp of the table, `ts` when it has no schema.
+    pub fn ts_column(&self) -> &'static str {
+        schema::table(self.table)
+            .and_then(|t| t.designated_timestamp())
+            .unwrap_or("ts")
+    }
+}
+
+/// A slice of one of the row types we persist, tagged by destination table.
+#[derive(Debug, Clone, Copy)]
+pub enum RowBatch<'a> {
+    /// Fanned out into `market_data`, one row per metric.
+    AssetCtxs(&'a [AssetCtxRow]),
+    /// The same rows as one `asset_ctx` row each.
+    AssetCtxWide(&'a [AssetCtxRow]),
+    /// Fanned out into `l2_orderbook`, one row per side and level.
+    L2Snapshots(&'a [L2Snapshot]),
+    Fills(&'a [ParsedFill]),
+    Fill1mAggregates(&'a [Fill1mAggregate]),
+    MarketState1m(&'a [MarketStateRow]),
+    MarketStateRt1m(&'a [MarketStateRt1mRow]),
+    PredictedFunding(&'a [PredictedFundingRow]),
+    FundingSpread(&'a [FundingSpreadRow]),
+}
+
+impl RowBatch<'_> {
+    /// Base table name; see [`Config::table_name`] for the dev suffix.
+    pub fn table(&self) -> &'static str {
+        match self {
+            RowBatch::AssetCtxs(_) => "market_data",
+            RowBatch::AssetCtxWide(_) => "asset_ctx",
+            RowBatch::L2Snapshots(_) => "l2_orderbook",
+            RowBatch::Fills(_) => "hyperliquid_fill",
+            RowBatch::Fill1mAggregates(_) => "hyperliquid_fill_1m_aggregate",
+            RowBatch::MarketState1m(_) => "market_state_1m",
+            RowBatch::MarketStateRt1m(_) => "market_state_rt_1m",
+            RowBatch::PredictedFunding(_) => "predicted_funding",
+            RowBatch::FundingSpread(_) => "funding_spread",
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            RowBatch::AssetCtxs(r) | RowBatch::AssetCtxWide(r) => r.is_empty(),
+            RowBatch::L2Snapshots(r) => r.is_empty(),
+            RowBatch::Fills(r) => r.is_empty(),
+            RowBatch::Fill1mAggregates(r) => r.is_empty(),
+            RowBatch::MarketState1m(r) => r.is_empty(),
+            RowBatch::MarketStateRt1m(r) => r.is_empty(),
+            RowBatch::PredictedFunding(r) => r.is_empty(),
+            RowBatch::FundingSpread(r) => r.is_empty(),
+        }
+    }
+
+    /// Flatten into table rows, with the same fan-out and column names as
+    /// the QuestDB ILP writers. Used by the non-QuestDB backends.
+    pub fn records(&self) -> Vec<Record> {
+        match self {
+            RowBatch::AssetCtxs(rows) => rows.iter().flat_map(asset_ctx_records).collect(),
+            RowBatch::AssetCtxWide(rows) => rows
+                .iter()
+                .map(|r| {
+                    Record(vec![
+                        ("ts", Value::Timestamp(r.time)),
+                        ("coin", Value::str(&r.coin)),
+                        ("market_type", Value::str(r.market_type.as_str())),
+                        ("dex", Value::non_empty(&r.dex)),
+                        ("funding", Value::F64(r.funding)),
+                        ("open_interest", Value::F64(r.open_interest)),
+                        ("prev_day_px", Value::F64(r.prev_day_px)),
+                        ("day_ntl_vlm", Value::F64(r.day_ntl_vlm)),
+                        ("premium", Value::opt_f64(r.premium)),
+                        ("oracle_px", Value::F64(r.oracle_px)),
+                        ("mark_px", Value::F64(r.mark_px)),
+                        ("mid_px", Value::opt_f64(r.mid_px)),
+                        ("impact_bid_px", Value::opt_f64(r.impact_bid_px)),
+                        ("impact_ask_px", Value::opt_f64(r.impact_ask_px)),
+                    ])
+                })
+                .collect(),
+            RowBatch::L2Snapshots(snapshots) => snapshots.iter().flat_map(l2_records).collect(),
+            RowBatch::Fills(fills) => fills
+                .iter()
+                .map(|f| {
+                    Record(vec![
+                        ("ts", Value::ts_ms(f.time_ms)),
+                        ("coin", Value::str(&f.coin)),
+                        ("wallet", Value::str(&f.wallet)),
+                        ("side", Value::str(&f.side)),
+                        ("category", Value::str(&f.category)),
+                        ("source", Value::str("HYPERLIQUID_NODE")),
+                        ("is_taker", Value::Bool(f.is_taker)),
+                        ("price", Value::F64(f.price)),
+                        ("quantity", Value::F64(f.quantity)),
+                        ("position_before", Value::F64(f.position_before)),
+                        ("realized_pnl", Value::F64(f.realized_pnl)),
+                    ])
+                })
+                .collect(),
+            RowBatch::Fill1mAggregates(rows) => rows
+                .iter()
+                .map(|r| {
+                    Record(vec![
+                        ("ts", Value::ts_ms(r.minute_ms)),
+                        ("coin", Value::str(&r.coin)),
+                        ("category", Value::str(&r.category)),
+                        ("buy_side", Value::Bool(r.buy_side)),
+                        ("quantity", Value::F64(r.quantity)),
+                        ("trade_count", Value::I64(r.trade_count)),
+                    ])
+                })
+                .collect(),
+            RowBatch::MarketState1m(rows) => rows
+                .iter()
+                .map(|r| {
+                    Record(vec![
+                        ("ts", Value::ts_ms(r.minute_ms)),
+                        ("coin", Value::str(&r.coin)),
+                        ("price_oracle", Value::F64(r.price_oracle)),
+                        ("price_mark", Value::F64(r.price_mark)),
+                        ("price_mid", Value::F64(r.price_mid)),
+                        ("open_interest", Value::F64(r.open_interest)),
+                        ("funding_rate", Value::F64(r.funding_rate)),
+                        ("volume_24h_usd", Value::F64(r.volume_24h_usd)),
+                        ("trade_volume", Value::F64(r.trade_volume)),
+                        ("trade_count", Value::I64(r.trade_count)),
+                        (
+                            "liquidation_long_volume",
+                            Value::F64(r.liquidation_long_volume),
+                        ),
+                        (
+                            "liquidation_short_volume",
+                            Value::F64(r.liquidation_short_volume),
+                        ),
+                        (
+                            "liquidation_long_count",
+                            Value::I64(r.liquidation_long_count),
+                        ),
+                        (
+                            "liquidation_short_count",
+                            Value::I64(r.liquidation_short_count),
+                        ),
+                        (
+                            "predicted_funding_rate",
+                            Value::opt_f64(r.predicted_funding_rate),
+                        ),
+                    ])
+                })
+                .collect(),
+            RowBatch::MarketStateRt1m(rows) => rows
+                .iter()
+                .map(|r| {
+                    Record(vec![
+                        ("ts", Value::ts_ms(r.minute_ms)),
+                        ("venue", Value::str(&r.venue)),
+                        ("coin", Value::str(&r.coin)),
+                        ("market_type", Value::str(&r.market_type)),
+                        ("dex", Value::non_empty(&r.dex)),
+                        ("price_oracle", Value::opt_f64(r.price_oracle)),
+                        ("price_mark", Value::F64(r.price_mark)),
+                        ("price_mid", Value::F64(r.price_mid)),
+                        ("open_interest", Value::F64(r.open_interest)),
+                        ("funding_rate", Value::F64(r.funding_rate)),
+                        ("volume_24h_usd", Value::F64(r.volume_24h_usd)),
+                        (
+                            "predicted_funding_rate",
+                            Value::opt_f64(r.predicted_funding_rate),
+                        ),
+                        ("trade_volume", Value::F64(r.trade_volume)),
+                        ("trade_count", Value::I64(r.trade_count)),
+                        (
+                            "liquidation_long_volume",
+                            Value::F64(r.liquidation_long_volume),
+                        ),
+                        (
+                            "liquidation_short_volume",
+                            Value::F64(r.liquidation_short_volume),
+                        ),
+                        (
+                            "liquidation_long_count",
+                            Value::I64(r.liquidation_long_count),
+                        ),
+                        (
+                            "liquidation_short_count",
+                            Value::I64(r.liquidation_short_count),
+                        ),
+                    ])
+                })
+                .collect(),
+            RowBatch::PredictedFunding(rows) => rows
+                .iter()
+                .map(|r| {
+                    Record(vec![
+                        ("ts", Value::ts_ms(r.minute_ms)),
+                        ("venue", Value::str(&r.venue)),
+                        ("coin", Value::str(&r.coin)),
+                        ("funding_rate", Value::F64(r.funding_rate)),
+                        ("next_funding_time", Value::ts_ms(r.next_funding_time_ms)),
+                    ])
+                })
+                .collect(),
+            RowBatch::FundingSpread(rows) => rows
+                .iter()
+                .map(|r| {
+                    Record(vec![
+                        ("ts", Value::ts_ms(r.minute_ms)),
+                        ("coin", Value::str(&r.coin)),
+                        ("venue", Value::str(&r.venue)),
+                        ("long_venue", Value::str(&r.long_venue)),
+                        ("short_venue", Value::str(&r.short_venue)),
+                        ("hl_rate_hourly", Value::F64(r.hl_rate_hourly)),
+                        ("venue_rate_hourly", Value::F64(r.venue_rate_hourly)),
+                        ("spread_hourly", Value::F64(r.spread_hourly)),
+                        ("spread_annualised", Value::F64(r.spread_annualised)),
+                        ("carry_annualised", Value::F64(r.carry_annualised)),
+                    ])
+                })
+                .collect(),
+        }
+    }
+}
+
+fn asset_ctx_records(row: &AssetCtxRow) -> Vec<Record> {
+    let metrics = [
+        ("open_interest", Some(row.open_interest)),
+        ("funding", Some(row.funding)),
+        ("mark_px", Some(row.mark_px)),
+        ("oracle_px", Some(row.oracle_px)),
+        ("prev_day_px", Some(row.prev_day_px)),
+        ("day_ntl_vlm", Some(row.day_ntl_vlm)),
+        ("mid_px", row.mid_px),
+        ("premium", row.premium),
+    ];
+    metrics
+        .into_iter()
+        .filter_map(|(category, value)| {
+            Some(Record(vec![
+                ("ts", Value::Timestamp(row.time)),
+                ("category", Value::str(category)),
+                ("ticker", Value::str(&row.coin)),
+                ("source", Value::str("HYPERLIQUID_S3")),
+                ("value", Value::F64(value?)),
+                ("market_type", Value::str(row.market_type.as_str())),
+                ("dex", Value::non_empty(&row.dex)),
+            ]))
+        })
+        .collect()
+}
+
+fn l2_records(snapshot: &L2Snapshot) -> Vec<Record> {
+    let mut out = Vec::new();
+    for (side_idx, side) in [(0usize, "bid"), (1usize, "ask")] {
+        for (level, l) in snapshot.levels()[side_idx].iter().enumerate() {
+            out.push(Record(vec![
+                ("ts", Value::ts_ms(snapshot.time_ms())),
+                ("source", Value::str("HYPERLIQUID_S3")),
+                ("ticker", Value::str(snapshot.coin())),
+                ("side", Value::str(side)),
+                ("level", Value::I64(level as i64)),
+                ("price", Value::F64(l.px.parse().unwrap_or(0.0))),
+                ("quantity", Value::F64(l.sz.parse().unwrap_or(0.0))),
+            ]));
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Generic rows
+// ---------------------------------------------------------------------------
+
+/// One cell of a [`Record`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Str(String),
+    I64(i64),
+    F64(f64),
+    Bool(bool),
+    Timestamp(DateTime<Utc>),
+}
+
+impl Value {
+    fn str(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+
+    /// `Null` for an empty string, matching the ILP writers that skip empty
+    /// symbols (e.g. `dex` on the default DEX).
+    fn non_empty(s: &str) -> Self {
+        if s.is_empty() {
+            Value::Null
+        } else {
+            Value::str(s)
+        }
+    }
+
+    fn opt_f64(v: Option<f64>) -> Self {
+        v.map_or(Value::Null, Value::F64)
+    }
+
+    fn ts_ms(ms: i64) -> Self {
+        DateTime::from_timestamp_millis(ms).map_or(Value::Null, Value::Timestamp)
+    }
+}
+
+/// One table row as `(column, value)` pairs, named as in
+/// [`crate::database::schema`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record(pub Vec<(&'static str, Value)>);
+
+impl Record {
+    /// Value of `column`, or [`Value::Null`] when the row does not set it.
+    pub fn get(&self, column: &str) -> &Value {
+        self.0
+            .iter()
+            .find(|(name, _)| *name == column)
+            .map_or(&Value::Null, |(_, v)| v)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// Build the sink selected by `config.sink`.
+///
+/// `db` is reused by the QuestDB backend so it shares the caller's ILP
+/// sender.
+pub async fn from_config(
+    config: &Config,
+    db: Arc<QuestDbClient>,
+) -> AnySignalResult<Arc<dyn Sink>> {
+    let sink: Arc<dyn Sink> = match config.sink.as_str() {
+        "questdb" => Arc::new(QuestDbSink::new(db)),
+        "parquet" => Arc::new(ParquetSink::new(&config.sink_parquet_dir, config.dev)),
+        "postgres" => {
+            let url = config
+                .sink_postgres_url
+                .as_deref()
+                .ok_or("SINK=postgres requires SINK_POSTGRES_URL")?;
+            Arc::new(PostgresSink::connect(url, config.dev).await?)
+        }
+        other => {
+            return Err(
+                format!("unknown SINK '{other}' (expected questdb, parquet or postgres)")
+                    .as_str()
+                    .into(),
+            )
+        }
+    };
+    Ok(sink)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_use_schema_columns() {
+        let rows = [MarketStateRt1mRow {
+            minute_ms: 1_748_779_200_000,
+            venue: "HlPerp".to_string(),
+            coin: "BTC".to_string(),
+            market_type: "perp".to_string(),
+            dex: String::new(),
+            price_oracle: None,
+            price_mark: 1.0,
+            price_mid: 1.0,
+            open_interest: 0.0,
+            funding_rate: 0.0,
+            volume_24h_usd: 0.0,
+            predicted_funding_rate: Some(0.0001),
+            trade_volume: 0.0,
+            trade_count: 0,
+            liquidation_long_volume: 0.0,
+            liquidation_short_volume: 0.0,
+            liquidation_long_count: 0,
+            liquidation_short_count: 0,
+        }];
+        let batch = RowBatch::MarketStateRt1m(&rows);
+        let records = batch.records();
+        assert_eq!(records.len(), 1);
+
+        let columns = schema::table(batch.table()).unwrap().columns();
+        for (name, _) in &records[0].0 {
+            assert!(
+                columns.iter().any(|c| c.name == *name),
+                "{name} not in schema"
+            );
+        }
+        assert_eq!(records[0].get("dex"), &Value::Null);
+        assert_eq!(records[0].get("price_oracle"), &Value::Null);
+        assert_eq!(
+            records[0].get("ts"),
+            &Value::Timestamp(DateTime::from_timestamp_millis(1_748_779_200_000).unwrap())
+        );
+    }
+
+    #[test]
+    fn every_batch_writes_its_schema_columns() {
+        // the optional prices are borrowed strings, as in the archive CSV
+        let ctx: AssetCtxRow = serde_json::from_str(
+            r#"{"time": "2025-06-01T12:00:00Z", "coin": "BTC", "funding": 0.0001,
+                "open_interest": 1.0, "prev_day_px": 1.0, "day_ntl_vlm": 1.0,
+                "premium": "0.0002", "oracle_px": 1.0, "mark_px": 1.0, "mid_px": "1.0",
+                "impact_bid_px": "1.0", "impact_ask_px": "1.0"}"#,
+        )
+        .unwrap();
+        let snapshot: L2Snapshot = serde_json::from_value(serde_json::json!({
+            "time": "2025-06-01T12:00:00.000000000",
+            "raw": {"data": {
+                "coin": "BTC",
+                "time": 1_748_779_200_000i64,
+                "levels": [[{"px": "1.0", "sz": "2.0", "n": 1}], [{"px": "1.1", "sz": "3.0", "n": 2}]],
+            }},
+        }))
+        .unwrap();
+        let fill = ParsedFill {
+            wallet: "0xabc".to_string(),
+            coin: "BTC".to_string(),
+            time_ms: 1_748_779_200_000,
+            side: "buy".to_string(),
+            category: "Open Long".to_string(),
+            is_taker: true,
+            price: 1.0,
+            quantity: 1.0,
+            position_before: 0.0,
+            realized_pnl: 0.0,
+        };
+        let aggregate = Fill1mAggregate {
+            minute_ms: 1_748_779_200_000,
+            coin: "BTC".to_string(),
+            category: "Buy".to_string(),
+            buy_side: true,
+            quantity: 1.0,
+            trade_count: 1,
+        };
+        let state = MarketStateRow {
+            minute_ms: 1_748_779_200_000,
+            coin: "BTC".to_string(),
+            price_oracle: 1.0,
+            price_mark: 1.0,
+            price_mid: 1.0,
+            open_interest: 0.0,
+            funding_rate: 0.0,
+            volume_24h_usd: 0.0,
+            trade_volume: 0.0,
+            trade_count: 0,
+            liquidation_long_volume: 0.0,
+            liquidation_short_volume: 0.0,
+            liquidation_long_count: 0,
+            liquidation_short_count: 0,
+            predicted_funding_rate: Some(0.0001),
+        };
+        let state_rt = MarketStateRt1mRow {
+            minute_ms: 1_748_779_200_000,
+            venue: "HlPerp".to_string(),
+            coin: "BTC".to_string(),
+            market_type: "perp".to_string(),
+            dex: String::new(),
+            price_oracle: Some(1.0),
+            price_mark: 1.0,
+            price_mid: 1.0,
+            open_interest: 0.0,
+            funding_rate: 0.0,
+            volume_24h_usd: 0.0,
+            predicted_funding_rate: Some(0.0001),
+            trade_volume: 0.0,
+            trade_count: 0,
+            liquidation_long_volume: 0.0,
+            liquidation_short_volume: 0.0,
+            liquidation_long_count: 0,
+            liquidation_short_count: 0,
+        };
+        let predicted = PredictedFundingRow {
+            minute_ms: 1_748_779_200_000,
+            venue: "BinPerp".to_string(),
+            coin: "BTC".to_string(),
+            funding_rate: 0.0001,
+            next_funding_time_ms: 1_748_793_600_000,
+        };
+        let spread = FundingSpreadRow {
+            minute_ms: 1_748_779_200_000,
+            coin: "BTC".to_string(),
+            venue: "BinPerp".to_string(),
+            hl_rate_hourly: 0.0,
+            venue_rate_hourly: 0.0,
+            spread_hourly: 0.0,
+            spread_annualised: 0.0,
+            long_venue: "HlPerp".to_string(),
+            short_venue: "BinPerp".to_string(),
+            carry_annualised: 0.0,
+        };
+
+        let (ctxs, snapshots, fills) = ([ctx], [snapshot], [fill]);
+        let (aggregates, states, states_rt) = ([aggregate], [state], [state_rt]);
+        let (predicted, spreads) = ([predicted], [spread]);
+        let batches = [
+            RowBatch::AssetCtxs(&ctxs),
+            RowBatch::AssetCtxWide(&ctxs),
+            RowBatch::L2Snapshots(&snapshots),
+            RowBatch::Fills(&fills),
+            RowBatch::Fill1mAggregates(&aggregates),
+            RowBatch::MarketState1m(&states),
+            RowBatch::MarketStateRt1m(&states_rt),
+            RowBatch::PredictedFunding(&predicted),
+            RowBatch::FundingSpread(&spreads),
+        ];
+        for batch in batches {
+            let table = batch.table();
+            let mut want: Vec<&str> = schema::table(table)
+                .unwrap_or_else(|| panic!("no schema for {table}"))
+                .columns()
+                .iter()
+                .map(|c| c.name)
+                .collect();
+            want.sort_unstable();
+            let records = batch.records();
+            assert!(!records.is_empty(), "{table}");
+            for record in &records {
+                let mut got: Vec<&str> = record.0.iter().map(|(name, _)| *name).collect();
+                got.sort_unstable();
+                assert_eq!(got, want, "{table}");
+            }
+        }
+    }
+
+    #[test]
+    fn asset_ctx_wide_is_one_row_per_coin() {
+        let row: AssetCtxRow = serde_json::from_value(serde_json::json!({
+            "time": "2025-06-01T12:00:00Z",
+            "coin": "BTC",
+            "funding": 0.0001,
+            "open_interest": 1.0,
+            "prev_day_px": 1.0,
+            "day_ntl_vlm": 1.0,
+            "oracle_px": 1.0,
+            "mark_px": 1.0,
+        }))
+        .unwrap();
+        let rows = [row];
+        let batch = RowBatch::AssetCtxWide(&rows);
+        let records = batch.records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(RowBatch::AssetCtxs(&rows).records().len(), 6);
+
+        let columns = schema::table(batch.table()).unwrap().columns();
+        assert_eq!(records[0].0.len(), columns.len());
+        for (name, _) in &records[0].0 {
+            assert!(
+                columns.iter().any(|c| c.name == *name),
+                "{name} not in schema"
+            );
+        }
+        assert_eq!(records[0].get("impact_bid_px"), &Value::Null);
+    }
+}
@@ -0,0 +1,448 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    BooleanBuilder, Float64Builder, Int32Builder, Int64Builder, StringBuilder,
+    TimestampMicrosecondBuilder,
+};
+use arrow_array::{Array, ArrayRef, RecordBatch, StringArray, TimestampMicrosecondArray};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::NaiveDate;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::{ArrowWriter, ProjectionMask};
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+
+use super::{PartitionFilter, Record, RowBatch, Sink, Value};
+use crate::database::schema::{self, Column, ColumnType};
+use crate::error::AnySignalResult;
+
+/// Writes Parquet files on local disk, one directory per table and date:
+///
+/// `<root>/<table>/date=YYYY-MM-DD/part-<micros>-<seq>.parquet`
+///
+/// Every write adds new part files — nothing is rewritten. Backfills skip
+/// partitions these files already hold, but a forced re-run duplicates rows;
+/// dedup at read time (e.g. on the table's `dedup_keys`) when that matters.
+pub struct ParquetSink {
+    root: PathBuf,
+    dev: bool,
+    /// Disambiguates part files written within the same microsecond.
+    seq: AtomicU64,
+}
+
+impl ParquetSink {
+    pub fn new(root: impl AsRef<Path>, dev: bool) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            dev,
+            seq: AtomicU64::new(0),
+        }
+    }
+
+    /// `table` with the dev suffix when in dev mode; also its directory.
+    fn table_name(&self, table: &str) -> String {
+        if self.dev {
+            format!("{table}_dev")
+        } else {
+            table.to_string()
+        }
+    }
+
+    /// One Arrow batch per date of `batch`, with the part file it goes to.
+    /// Every record needs its table's designated timestamp.
+    fn part_files(&self, batch: RowBatch<'_>) -> AnySignalResult<Vec<(PathBuf, RecordBatch)>> {
+        let table = schema::table(batch.table())
+            .ok_or_else(|| eyre::eyre!("no schema for table '{}'", batch.table()))?;
+        let name = self.table_name(table.name);
+        let ts_column = table.designated_timestamp().unwrap_or("ts");
+        let columns = table.columns();
+        let schema = arrow_schema(&columns);
+
+        let records = batch.records();
+        let mut by_date: BTreeMap<NaiveDate, Vec<&Record>> = BTreeMap::new();
+        for record in &records {
+            let Value::Timestamp(ts) = record.get(ts_column) else {
+                return Err(eyre::eyre!("{name}: record without a '{ts_column}' timestamp").into());
+            };
+            by_date.entry(ts.date_naive()).or_default().push(record);
+        }
+
+        let mut files = Vec::with_capacity(by_date.len());
+        for (date, records) in by_date {
+            let path = self
+                .root
+                .join(&name)
+                .join(format!("date={date}"))
+                .join(format!(
+                    "part-{}-{}.parquet",
+                    chrono::Utc::now().timestamp_micros(),
+                    self.seq.fetch_add(1, Ordering::Relaxed)
+                ));
+            let batch = record_batch(schema.clone(), &columns, &records)
+                .map_err(|e| eyre::eyre!("{name}: {e}"))?;
+            files.push((path, batch));
+        }
+        Ok(files)
+    }
+}
+
+#[async_trait::async_trait]
+impl Sink for ParquetSink {
+    fn name(&self) -> &'static str {
+        "parquet"
+    }
+
+    async fn write(&self, batch: RowBatch<'_>) -> AnySignalResult<usize> {
+        if batch.is_empty() {
+            return Ok(0);
+        }
+        let files = self.part_files(batch)?;
+        let written = files.iter().map(|(_, batch)| batch.num_rows()).sum();
+        tokio::task::spawn_blocking(move || -> AnySignalResult<()> {
+            for (path, batch) in files {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir)
+                        .map_err(|e| eyre::eyre!("create {}: {e}", dir.display()))?;
+                }
+                write_file(&path, batch)
+                    .map_err(|e| eyre::eyre!("write {}: {e}", path.display()))?;
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| eyre::eyre!("parquet writer task: {e}"))??;
+        Ok(written)
+    }
+
+    async fn is_partition_present(&self, partition: &PartitionFilter) -> AnySignalResult<bool> {
+        let dir = self.root.join(self.table_name(partition.table));
+        let partition = partition.clone();
+        tokio::task::spawn_blocking(move || partition_present(&dir, &partition))
+            .await
+            .map_err(|e| eyre::eyre!("parquet reader task: {e}"))?
+    }
+}
+
+/// Whether any part file under the table directory `dir` has a row of
+/// `partition`. Only the date directories the range spans are read, and of
+/// those files only the timestamp and filtered columns.
+fn partition_present(dir: &Path, partition: &PartitionFilter) -> AnySignalResult<bool> {
+    if partition.from >= partition.to {
+        return Ok(false);
+    }
+    let last = (partition.to - chrono::Duration::microseconds(1)).date_naive();
+    for date in partition.from.date_naive().iter_days() {
+        if date > last {
+            break;
+        }
+        let day = dir.join(format!("date={date}"));
+        let entries = match std::fs::read_dir(&day) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(eyre::eyre!("read {}: {e}", day.display()).into()),
+        };
+        for entry in entries {
+            let path = entry
+                .map_err(|e| eyre::eyre!("read {}: {e}", day.display()))?
+                .path();
+            if path.extension().is_none_or(|ext| ext != "parquet") {
+                continue;
+            }
+            if file_has_rows(&path, partition)
+                .map_err(|e| eyre::eyre!("read {}: {e}", path.display()))?
+            {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+fn file_has_rows(path: &Path, partition: &PartitionFilter) -> Result<bool, ParquetError> {
+    let (from, to) = (
+        partition.from.timestamp_micros(),
+        partition.to.timestamp_micros(),
+    );
+    let ts_column = partition.ts_column();
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+    let columns = std::iter::once(ts_column).chain(partition.eq.iter().map(|(c, _)| *c));
+    let mask = ProjectionMask::columns(builder.parquet_schema(), columns);
+    for batch in builder.with_projection(mask).build()? {
+        let batch = batch?;
+        let Some(ts) = batch
+            .column_by_name(ts_column)
+            .and_then(|c| c.as_any().downcast_ref::<TimestampMicrosecondArray>())
+        else {
+            return Ok(false);
+        };
+        // a file without one of the filtered columns holds none of its rows
+        let Some(eq) = partition
+            .eq
+            .iter()
+            .map(|(column, value)| {
+                let array = batch
+                    .column_by_name(column)?
+                    .as_any()
+                    .downcast_ref::<StringArray>()?;
+                Some((array, value.as_str()))
+            })
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Ok(false);
+        };
+        let matches = |row: usize| {
+            ts.is_valid(row)
+                && (from..to).contains(&ts.value(row))
+                && eq
+                    .iter()
+                    .all(|(array, value)| array.is_valid(row) && array.value(row) == *value)
+        };
+        if (0..batch.num_rows()).any(matches) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn write_file(path: &Path, batch: RecordBatch) -> Result<(), parquet::errors::ParquetError> {
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let file = File::create(path)?;
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Arrow conversion
+// ---------------------------------------------------------------------------
+
+/// Arrow type for a QuestDB column. Symbols become plain UTF-8 strings and
+/// timestamps are UTC microseconds, QuestDB's native resolution.
+pub fn arrow_type(ty: ColumnType) -> DataType {
+    match ty {
+        ColumnType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        ColumnType::Symbol { .. } | ColumnType::String => DataType::Utf8,
+        ColumnType::Boolean => DataType::Boolean,
+        ColumnType::Int => DataType::Int32,
+        ColumnType::Long => DataType::Int64,
+        ColumnType::Double => DataType::Float64,
+    }
+}
+
+/// Nullable Arrow schema for `columns`, in order.
+pub fn arrow_schema(columns: &[Column]) -> SchemaRef {
+    Arc::new(Schema::new(
+        columns
+            .iter()
+            .map(|c| Field::new(c.name, arrow_type(c.ty), true))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Build one Arrow batch from `records`, taking each of `columns` by name;
+/// columns a record does not set are null.
+pub fn record_batch(
+    schema: SchemaRef,
+    columns: &[Column],
+    records: &[&Record],
+) -> Result<RecordBatch, ArrowError> {
+    let arrays: Vec<ArrayRef> = columns
+        .iter()
+        .map(|c| column_array(c, records))
+        .collect::<Result<_, _>>()?;
+    RecordBatch::try_new(schema, arrays)
+}
+
+fn column_array(column: &Column, records: &[&Record]) -> Result<ArrayRef, ArrowError> {
+    let values = records.iter().map(|r| r.get(column.name));
+    let mismatch = |v: &Value| {
+        ArrowError::InvalidArgumentError(format!(
+            "column '{}' ({}) cannot hold {v:?}",
+            column.name,
+            column.ty.name()
+        ))
+    };
+    let array: ArrayRef = match column.ty {
+        ColumnType::Timestamp => {
+            let mut b =
+                TimestampMicrosecondBuilder::with_capacity(records.len()).with_timezone("UTC");
+            for v in values {
+                match v {
+                    Value::Timestamp(t) => b.append_value(t.timestamp_micros()),
+                    Value::Null => b.append_null(),
+                    other => return Err(mismatch(other)),
+                }
+            }
+            Arc::new(b.finish())
+        }
+        ColumnType::Symbol { .. } | ColumnType::String => {
+            let mut b = StringBuilder::new();
+            for v in values {
+                match v {
+                    Value::Str(s) => b.append_value(s),
+                    Value::Null => b.append_null(),
+                    other => return Err(mismatch(other)),
+                }
+            }
+            Arc::new(b.finish())
+        }
+        ColumnType::Boolean => {
+            let mut b = BooleanBuilder::with_capacity(records.len());
+            for v in values {
+                match v {
+                    Value::Bool(x) => b.append_value(*x),
+                    Value::Null => b.append_null(),
+                    other => return Err(mismatch(other)),
+                }
+            }
+            Arc::new(b.finish())
+        }
+        ColumnType::Int => {
+            let mut b = Int32Builder::with_capacity(records.len());
+            for v in values {
+                match v {
+                    Value::I64(x) => {
+                        b.append_value(i32::try_from(*x).map_err(|_| mismatch(&Value::I64(*x)))?)
+                    }
+                    Value::Null => b.append_null(),
+                    other => return Err(mismatch(other)),
+                }
+            }
+            Arc::new(b.finish())
+        }
+        ColumnType::Long => {
+            let mut b = Int64Builder::with_capacity(records.len());
+            for v in values {
+                match v {
+                    Value::I64(x) => b.append_value(*x),
+                    Value::Null => b.append_null(),
+                    other => return Err(mismatch(other)),
+                }
+            }
+            Arc::new(b.finish())
+        }
+        ColumnType::Double => {
+            let mut b = Float64Builder::with_capacity(records.len());
+            for v in values {
+                match v {
+                    Value::F64(x) => b.append_value(*x),
+                    Value::Null => b.append_null(),
+                    other => return Err(mismatch(other)),
+                }
+            }
+            Arc::new(b.finish())
+        }
+    };
+    Ok(array)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Fill1mAggregate;
+    use chrono::DateTime;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    #[tokio::test]
+    async fn writes_one_file_per_table_and_date() {
+        let root = std::env::temp_dir().join(format!(
+            "anysignal-parquet-sink-{}",
+            chrono::Utc::now().timestamp_micros()
+        ));
+        let sink = ParquetSink::new(&root, true);
+        let row = |minute_ms, coin: &str| Fill1mAggregate {
+            minute_ms,
+            coin: coin.to_string(),
+            category: "Buy".to_string(),
+            buy_side: true,
+            quantity: 1.5,
+            trade_count: 3,
+        };
+        let rows = [
+            row(1_748_822_340_000, "BTC"), // 2025-06-01T23:59
+            row(1_748_822_400_000, "BTC"), // 2025-06-02T00:00
+            row(1_748_822_400_000, "ETH"),
+        ];
+
+        let written = sink.write(RowBatch::Fill1mAggregates(&rows)).await.unwrap();
+        assert_eq!(written, 3);
+
+        let table_dir = root.join("hyperliquid_fill_1m_aggregate_dev");
+        let mut day_rows = Vec::new();
+        for date in ["2025-06-01", "2025-06-02"] {
+            let dir = table_dir.join(format!("date={date}"));
+            let files: Vec<_> = std::fs::read_dir(&dir).unwrap().collect();
+            assert_eq!(files.len(), 1, "{date}");
+            let file = File::open(files[0].as_ref().unwrap().path()).unwrap();
+            let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+                .unwrap()
+                .build()
+                .unwrap();
+            day_rows.push(reader.map(|b| b.unwrap().num_rows()).sum::<usize>());
+        }
+        assert_eq!(day_rows, [1, 2]);
+
+        // a record without its timestamp fails the write
+        let undated = [row(i64::MAX, "BTC")];
+        assert!(sink
+            .write(RowBatch::Fill1mAggregates(&undated))
+            .await
+            .is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn finds_partitions_it_holds() {
+        let root = std::env::temp_dir().join(format!(
+            "anysignal-parquet-presence-{}",
+            chrono::Utc::now().timestamp_micros()
+        ));
+        let sink = ParquetSink::new(&root, false);
+        let hour = |h| {
+            let start = DateTime::parse_from_rfc3339(&format!("2025-06-01T{h:02}:00:00Z"))
+                .unwrap()
+                .to_utc();
+            PartitionFilter::new(
+                "hyperliquid_fill_1m_aggregate",
+                start,
+                start + chrono::Duration::hours(1),
+            )
+        };
+        // nothing written yet
+        assert!(!sink.is_partition_present(&hour(12)).await.unwrap());
+
+        let rows = [Fill1mAggregate {
+            minute_ms: 1_748_781_000_000, // 2025-06-01T12:30
+            coin: "BTC".to_string(),
+            category: "Buy".to_string(),
+            buy_side: true,
+            quantity: 1.5,
+            trade_count: 3,
+        }];
+        sink.write(RowBatch::Fill1mAggregates(&rows)).await.unwrap();
+
+        assert!(sink.is_partition_present(&hour(12)).await.unwrap());
+        assert!(!sink.is_partition_present(&hour(13)).await.unwrap());
+        let coin = |c| hour(12).eq("coin", c);
+        assert!(sink.is_partition_present(&coin("BTC")).await.unwrap());
+        assert!(!sink.is_partition_present(&coin("ETH")).await.unwrap());
+        // a column the table lacks matches nothing
+        let unknown = hour(12).eq("ticker", "BTC");
+        assert!(!sink.is_partition_present(&unknown).await.unwrap());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use super::{PartitionFilter, RowBatch, Sink};
+use crate::database::query::{Query, ReadPath};
+use crate::database::{
+    insert_asset_ctx_wide, insert_asset_ctxs, insert_funding_spread,
+    insert_hyperliquid_fill_1m_aggregate, insert_hyperliquid_fills, insert_l2_snapshots,
+    insert_market_state_1m, insert_market_state_rt_1m, insert_predicted_funding, QuestDbClient,
+};
+use crate::error::AnySignalResult;
+
+/// Writes over QuestDB ILP with the `insert_*` writers in
+/// [`crate::database`], through [`QuestDbClient::write_rows`]. The default sink.
+pub struct QuestDbSink {
+    db: Arc<QuestDbClient>,
+}
+
+impl QuestDbSink {
+    pub fn new(db: Arc<QuestDbClient>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl Sink for QuestDbSink {
+    fn name(&self) -> &'static str {
+        "questdb"
+    }
+
+    async fn write(&self, batch: RowBatch<'_>) -> AnySignalResult<usize> {
+        if batch.is_empty() {
+            return Ok(0);
+        }
+        let table = self.db.table_name(batch.table());
+        // encoded in chunks here, flushed on a blocking thread through a
+        // pooled sender; rejected buffers land in the spool
+        let db = &self.db;
+        let rows = match batch {
+            RowBatch::AssetCtxs(rows) => db.write_rows(&table, rows, insert_asset_ctxs).await,
+            RowBatch::AssetCtxWide(rows) => {
+                db.write_rows(&table, rows, insert_asset_ctx_wide).await
+            }
+            RowBatch::L2Snapshots(rows) => db.write_rows(&table, rows, insert_l2_snapshots).await,
+            RowBatch::Fills(rows) => db.write_rows(&table, rows, insert_hyperliquid_fills).await,
+            RowBatch::Fill1mAggregates(rows) => {
+                db.write_rows(&table, rows, insert_hyperliquid_fill_1m_aggregate)
+                    .await
+            }
+            RowBatch::MarketState1m(rows) => {
+                db.write_rows(&table, rows, insert_market_state_1m).await
+            }
+            RowBatch::MarketStateRt1m(rows) => {
+                db.write_rows(&table, rows, insert_market_state_rt_1m).await
+            }
+            RowBatch::PredictedFunding(rows) => {
+                db.write_rows(&table, rows, insert_predicted_funding).await
+            }
+            RowBatch::FundingSpread(rows) => {
+                db.write_rows(&table, rows, insert_funding_spread).await
+            }
+        }?;
+        Ok(rows)
+    }
+
+    async fn is_partition_present(&self, partition: &PartitionFilter) -> AnySignalResult<bool> {
+        let mut query = Query::count().from(&self.db.table_name(partition.table));
+        for (column, value) in &partition.eq {
+            query = query.eq(column, value);
+        }
+        let query = query
+            .time_range(partition.ts_column(), partition.from, partition.to)
+            .via(ReadPath::PgWire);
+        Ok(self.db.count(&query).await? > 0)
+    }
+}
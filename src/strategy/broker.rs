@@ -0,0 +1,65 @@
+use crate::engine::MarketKey;
+use crate::model::instance::Leg;
+use crate::model::strategy::LegOrder;
+
+/// Fill every order at `price_of` its market. All or nothing: an order
+/// without a usable price or notional fails the whole instance.
+pub fn fill(
+    orders: &[LegOrder],
+    price_of: impl Fn(&MarketKey) -> Option<f64>,
+) -> Result<Vec<Leg>, String> {
+    if orders.is_empty() {
+        return Err("an instance needs at least one leg".to_string());
+    }
+    orders
+        .iter()
+        .map(|order| {
+            let market = &order.market;
+            if !(order.notional.is_finite() && order.notional > 0.0) {
+                return Err(format!(
+                    "{}/{}: notional must be positive",
+                    market.venue, market.coin
+                ));
+            }
+            let price = price_of(market)
+                .filter(|p| p.is_finite() && *p > 0.0)
+                .ok_or_else(|| format!("{}/{}: no price to fill at", market.venue, market.coin))?;
+            Ok(Leg {
+                market: market.clone(),
+                side: order.side,
+                size: order.notional / price,
+                entry_price: price,
+                mark_price: price,
+            })
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::instance::Side;
+
+    #[test]
+    fn fills_all_legs_or_none() {
+        let btc = MarketKey::new("HlPerp", "BTC");
+        let eth = MarketKey::new("HlPerp", "ETH");
+        let order = |market: &MarketKey, notional| LegOrder {
+            market: market.clone(),
+            side: Side::Long,
+            notional,
+        };
+        let price_of = |m: &MarketKey| (*m == btc).then_some(50_000.0);
+
+        let legs = fill(&[order(&btc, 1_000.0)], price_of).unwrap();
+        assert_eq!(legs[0].size, 0.02);
+        assert_eq!(legs[0].entry_price, 50_000.0);
+        assert!(fill(&[order(&btc, 1_000.0), order(&eth, 1_000.0)], price_of).is_err());
+        assert!(fill(&[order(&btc, 0.0)], price_of).is_err());
+        assert!(fill(&[], price_of).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+
+use crate::adapter::coinmarketcap::fear_and_greed;
+use crate::engine::{MarketKey, MarketState, MarketType};
+use crate::model::instance::{PaperInstance, Side};
+use crate::model::signal::{SignalData, SignalInfo};
+use crate::model::strategy::{Action, LegOrder, Strategy, StrategyEvent, StrategyInfo};
+
+/// Names `STRATEGIES` may list.
+pub const STRATEGIES: &[&str] = &["funding_carry", "fear_greed_contrarian"];
+
+/// Build the built-in strategy `name`.
+pub fn build(name: &str) -> Option<Box<dyn Strategy>> {
+    match name {
+        "funding_carry" => Some(Box::new(FundingCarry)),
+        "fear_greed_contrarian" => Some(Box::new(FearGreedContrarian)),
+        _ => None,
+    }
+}
+
+/// Quote notional of every leg the built-ins open.
+const NOTIONAL: f64 = 1_000.0;
+/// Venue the built-ins trade on.
+const VENUE: &str = "HlPerp";
+
+// ---------------------------------------------------------------------------
+// funding_carry
+// ---------------------------------------------------------------------------
+
+/// Hourly funding rate at which a carry position is opened (~88% a year).
+const CARRY_ENTRY_RATE: f64 = 0.0001;
+/// Hourly funding rate below which it is closed again.
+const CARRY_EXIT_RATE: f64 = 0.00002;
+/// Most carry positions held at once.
+const CARRY_MAX_OPEN: usize = 5;
+
+/// Collects extreme funding: shorts perps paying longs heavily and longs
+/// perps paying shorts, unhedged, until the rate normalises or flips.
+#[derive(Debug, Default)]
+pub struct FundingCarry;
+
+impl FundingCarry {
+    fn side(rate: f64) -> Side {
+        if rate > 0.0 {
+            Side::Short
+        } else {
+            Side::Long
+        }
+    }
+}
+
+impl Strategy for FundingCarry {
+    fn info(&self) -> StrategyInfo {
+        StrategyInfo {
+            id: 1,
+            name: "funding_carry".to_string(),
+            description: format!(
+                "Unhedged {VENUE} perp position against funding above {CARRY_ENTRY_RATE} an \
+                 hour, closed once it falls below {CARRY_EXIT_RATE} or flips"
+            ),
+            creator: "anysignal".to_string(),
+        }
+    }
+
+    fn on_event(&mut self, event: &StrategyEvent, open: &[PaperInstance]) -> Vec<Action> {
+        let StrategyEvent::Market { states, .. } = event else {
+            return Vec::new();
+        };
+        let rate_of = |key: &MarketKey| {
+            states
+                .iter()
+                .find(|s| s.venue == key.venue && s.coin == key.coin)
+                .and_then(|s| s.funding_rate)
+        };
+        let mut actions = Vec::new();
+        let mut held = HashSet::new();
+        for instance in open {
+            let Some(leg) = instance.legs.first() else {
+                continue;
+            };
+            held.insert(leg.market.clone());
+            let Some(rate) = rate_of(&leg.market) else {
+                continue;
+            };
+            if rate.abs() < CARRY_EXIT_RATE || Self::side(rate) != leg.side {
+                actions.push(Action::Close {
+                    instance_id: instance.id,
+                    reason: format!("funding {rate} back to normal"),
+                });
+            }
+        }
+        let slots = CARRY_MAX_OPEN.saturating_sub(open.len());
+        let mut candidates: Vec<(&MarketState, f64)> = states
+            .iter()
+            .filter(|s| s.venue == VENUE && s.market_type == MarketType::Perp)
+            .filter(|s| !held.contains(&s.key()))
+            .filter_map(|s| Some((s, s.funding_rate?)))
+            .filter(|(_, rate)| rate.abs() >= CARRY_ENTRY_RATE)
+            .collect();
+        candidates.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+        for (state, rate) in candidates.into_iter().take(slots) {
+            actions.push(Action::Open {
+                legs: vec![LegOrder {
+                    market: state.key(),
+                    side: Self::side(rate),
+                    notional: NOTIONAL,
+                }],
+                reason: format!("{} funding {rate}", state.coin),
+            });
+        }
+        actions
+    }
+}
+
+// ---------------------------------------------------------------------------
+// fear_greed_contrarian
+// ---------------------------------------------------------------------------
+
+/// Index value at or below which BTC is bought ("extreme fear").
+const FEAR: f64 = 25.0;
+/// Index value at or above which the position is closed ("greed").
+const GREED: f64 = 75.0;
+
+/// Buys BTC when the fear and greed index shows extreme fear and sells once
+/// it turns greedy.
+#[derive(Debug, Default)]
+pub struct FearGreedContrarian;
+
+impl Strategy for FearGreedContrarian {
+    fn info(&self) -> StrategyInfo {
+        StrategyInfo {
+            id: 2,
+            name: "fear_greed_contrarian".to_string(),
+            description: format!(
+                "Long BTC on {VENUE} from a fear and greed index of {FEAR} or below until \
+                 it reaches {GREED}"
+            ),
+            creator: "anysignal".to_string(),
+        }
+    }
+
+    fn signals(&self) -> Vec<SignalInfo> {
+        vec![fear_and_greed::signal_info()]
+    }
+
+    fn on_event(&mut self, event: &StrategyEvent, open: &[PaperInstance]) -> Vec<Action> {
+        let StrategyEvent::Signal { signal, .. } = event else {
+            return Vec::new();
+        };
+        let SignalData::Scalar(index) = signal.data else {
+            return Vec::new();
+        };
+        if index <= FEAR && open.is_empty() {
+            return vec![Action::Open {
+                legs: vec![LegOrder {
+                    market: MarketKey::new(VENUE, "BTC"),
+                    side: Side::Long,
+                    notional: NOTIONAL,
+                }],
+                reason: format!("fear and greed at {index}"),
+            }];
+        }
+        if index >= GREED {
+            return open
+                .iter()
+                .map(|i| Action::Close {
+                    instance_id: i.id,
+                    reason: format!("fear and greed at {index}"),
+                })
+                .collect();
+        }
+        Vec::new()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::model::instance::Leg;
+    use crate::model::signal::Signal;
+
+    fn state(coin: &str, funding_rate: f64) -> MarketState {
+        let mut state = MarketState::new(&MarketKey::new(VENUE, coin));
+        state.funding_rate = Some(funding_rate);
+        state
+    }
+
+    fn instance(id: u64, coin: &str, side: Side) -> PaperInstance {
+        let leg = Leg {
+            market: MarketKey::new(VENUE, coin),
+            side,
+            size: 1.0,
+            entry_price: 1.0,
+            mark_price: 1.0,
+        };
+        PaperInstance::open(id, 1, vec![leg], Utc::now(), String::new())
+    }
+
+    #[test]
+    fn funding_carry_opens_and_closes() {
+        let event = StrategyEvent::Market {
+            time: Utc::now(),
+            states: vec![
+                state("BTC", 0.00001),
+                state("DOGE", 0.0003),
+                state("PEPE", -0.0002),
+                state("ETH", 0.0002),
+            ],
+        };
+        let open = [
+            instance(1, "ETH", Side::Short),
+            instance(2, "BTC", Side::Short),
+        ];
+        let actions = FundingCarry.on_event(&event, &open);
+        assert!(matches!(&actions[0], Action::Close { instance_id: 2, .. }));
+        let opened: Vec<(String, Side)> = actions
+            .iter()
+            .filter_map(|a| match a {
+                Action::Open { legs, .. } => Some((legs[0].market.coin.clone(), legs[0].side)),
+                Action::Close { .. } => None,
+            })
+            .collect();
+        // strongest funding first; ETH is already held
+        assert_eq!(
+            opened,
+            [
+                ("DOGE".to_string(), Side::Short),
+                ("PEPE".to_string(), Side::Long)
+            ]
+        );
+    }
+
+    #[test]
+    fn fear_greed_contrarian_trades_the_extremes() {
+        let event = |value| StrategyEvent::Signal {
+            info: fear_and_greed::signal_info(),
+            signal: Signal::dummy_scalar(value),
+        };
+        let mut strategy = FearGreedContrarian;
+        assert!(matches!(
+            strategy.on_event(&event(20.0), &[])[..],
+            [Action::Open { .. }]
+        ));
+        let open = [instance(4, "BTC", Side::Long)];
+        assert!(strategy.on_event(&event(20.0), &open).is_empty());
+        assert!(strategy.on_event(&event(50.0), &open).is_empty());
+        assert_eq!(
+            strategy.on_event(&event(80.0), &open),
+            [Action::Close {
+                instance_id: 4,
+                reason: "fear and greed at 80".to_string()
+            }]
+        );
+    }
+}
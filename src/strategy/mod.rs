@@ -0,0 +1,11 @@
+/// fills leg orders on paper
+pub mod broker;
+/// strategies shipped with the service
+pub mod builtin;
+/// running strategies and their instances, shared with the API
+pub mod registry;
+/// drives strategies from engine events and signals
+pub mod runtime;
+
+pub use registry::StrategyRegistry;
+pub use runtime::StrategyRuntime;
@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::database::query::Query;
+use crate::database::{insert_strategy_instance, QuestDbClient};
+use crate::engine::MarketKey;
+use crate::error::{AnySignalError, AnySignalResult};
+use crate::model::instance::{Instance, InstanceStatus, Leg, PaperInstance};
+use crate::model::strategy::StrategyInfo;
+
+/// Table every instance change is recorded in.
+pub const TABLE: &str = "strategy_instance";
+
+/// One `strategy_instance` row as read back from QuestDB.
+#[derive(Debug, Deserialize)]
+struct InstanceRow {
+    instance_id: i64,
+    strategy_id: i64,
+    status: String,
+    legs: Option<String>,
+    reason: Option<String>,
+    opened_at: DateTime<Utc>,
+    closed_at: Option<DateTime<Utc>>,
+}
+
+impl InstanceRow {
+    fn into_instance(self) -> Result<PaperInstance, String> {
+        let status = InstanceStatus::from_name(&self.status).ok_or_else(|| {
+            format!(
+                "instance {}: unknown status {:?}",
+                self.instance_id, self.status
+            )
+        })?;
+        let legs: Vec<Leg> = match self.legs.as_deref() {
+            None | Some("") => Vec::new(),
+            Some(json) => serde_json::from_str(json)
+                .map_err(|e| format!("instance {}: bad legs: {e}", self.instance_id))?,
+        };
+        Ok(PaperInstance {
+            id: self.instance_id as u64,
+            strategy_id: self.strategy_id as u64,
+            status,
+            legs,
+            reason: self.reason.unwrap_or_default(),
+            opened_at: self.opened_at,
+            closed_at: self.closed_at,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    strategies: BTreeMap<u64, StrategyInfo>,
+    instances: BTreeMap<u64, PaperInstance>,
+    /// Highest instance id handed out or loaded.
+    last_id: u64,
+    /// Whether [`TABLE`] has been read; until then no id is handed out, as
+    /// it could collide with a stored instance.
+    loaded: bool,
+}
+
+/// Strategies running in this process and every instance they spawned,
+/// loaded from [`TABLE`] at startup so ids keep increasing across restarts.
+/// Cheaply cloneable — shared between the `strategy` runner and the API.
+#[derive(Clone, Default)]
+pub struct StrategyRegistry(Arc<Mutex<Inner>>);
+
+impl StrategyRegistry {
+    /// A registry of `strategies` whose history is not loaded yet: it opens
+    /// no instances until [`load_history`](Self::load_history) succeeds.
+    pub fn new(strategies: &[StrategyInfo]) -> Self {
+        let registry = Self::default();
+        registry.lock().strategies = strategies.iter().map(|s| (s.id, s.clone())).collect();
+        registry
+    }
+
+    /// A registry of `strategies` holding the latest row of every stored
+    /// instance.
+    pub async fn load(db: &QuestDbClient, strategies: &[StrategyInfo]) -> AnySignalResult<Self> {
+        let registry = Self::new(strategies);
+        registry.load_history(db).await?;
+        Ok(registry)
+    }
+
+    /// Read the latest row of every stored instance, after which ids continue
+    /// from the highest one. A missing table is an empty history.
+    pub async fn load_history(&self, db: &QuestDbClient) -> AnySignalResult<()> {
+        let query = Query::select(&[
+            "instance_id",
+            "strategy_id",
+            "status",
+            "legs",
+            "reason",
+            "opened_at",
+            "closed_at",
+        ])
+        .from(&db.table_name(TABLE))
+        .order_by("ts");
+        let rows: Vec<InstanceRow> = match db.query(&query).await {
+            Ok(rows) => rows,
+            Err(AnySignalError::QuestQuery(e)) if e.is_missing_table() => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        let mut inner = self.lock();
+        for row in rows {
+            match row.into_instance() {
+                Ok(instance) => {
+                    inner.last_id = inner.last_id.max(instance.id);
+                    inner.instances.insert(instance.id, instance);
+                }
+                Err(e) => tracing::warn!(error = e.as_str(), "strategy registry: skipping row"),
+            }
+        }
+        inner.loaded = true;
+        Ok(())
+    }
+
+    /// Whether the stored history has been loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.lock().loaded
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.0.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Running strategies, by id.
+    pub fn strategies(&self) -> Vec<StrategyInfo> {
+        self.lock().strategies.values().cloned().collect()
+    }
+
+    /// Every instance, by id.
+    pub fn instances(&self) -> Vec<PaperInstance> {
+        self.lock().instances.values().cloned().collect()
+    }
+
+    pub fn get(&self, id: u64) -> Option<PaperInstance> {
+        self.lock().instances.get(&id).cloned()
+    }
+
+    /// Open instances of `strategy_id`, by id.
+    pub fn open_instances(&self, strategy_id: u64) -> Vec<PaperInstance> {
+        self.lock()
+            .instances
+            .values()
+            .filter(|i| i.strategy_id == strategy_id && i.is_open())
+            .cloned()
+            .collect()
+    }
+
+    /// Record a new open instance under the next id. `None` until the
+    /// history is loaded.
+    pub fn open(
+        &self,
+        strategy_id: u64,
+        legs: Vec<Leg>,
+        time: DateTime<Utc>,
+        reason: String,
+    ) -> Option<PaperInstance> {
+        let mut inner = self.lock();
+        if !inner.loaded {
+            return None;
+        }
+        inner.last_id += 1;
+        let instance = PaperInstance::open(inner.last_id, strategy_id, legs, time, reason);
+        inner.instances.insert(instance.id, instance.clone());
+        Some(instance)
+    }
+
+    /// Mark every open instance with `price_of` its legs' markets.
+    pub fn mark(&self, price_of: impl Fn(&MarketKey) -> Option<f64>) {
+        for instance in self.lock().instances.values_mut() {
+            instance.mark(&price_of);
+        }
+    }
+
+    /// Close instance `id` of `strategy_id` at its marks. `None` unless it
+    /// is one of that strategy's open instances.
+    pub fn close(
+        &self,
+        strategy_id: u64,
+        id: u64,
+        time: DateTime<Utc>,
+        reason: String,
+    ) -> Option<PaperInstance> {
+        let mut inner = self.lock();
+        let instance = inner
+            .instances
+            .get_mut(&id)
+            .filter(|i| i.strategy_id == strategy_id && i.is_open())?;
+        instance.close(InstanceStatus::Closed, time, reason);
+        Some(instance.clone())
+    }
+
+    /// [`Instance::terminate`] instance `id` and record it in [`TABLE`].
+    /// `None` for an unknown id; an instance that is no longer open is an
+    /// error.
+    pub async fn terminate(
+        &self,
+        db: &QuestDbClient,
+        id: u64,
+    ) -> AnySignalResult<Option<PaperInstance>> {
+        let Some(mut instance) = self.get(id) else {
+            return Ok(None);
+        };
+        instance.terminate().await?;
+        {
+            let mut inner = self.lock();
+            match inner.instances.get_mut(&id) {
+                // the runtime may have closed it meanwhile
+                Some(current) if !current.is_open() => {
+                    return Err(
+                        eyre::eyre!("instance {id} is already {}", current.status.name()).into(),
+                    )
+                }
+                Some(current) => current.clone_from(&instance),
+                None => return Ok(None),
+            }
+        }
+        save(db, &instance).await?;
+        tracing::info!(id, pnl = instance.pnl(), "strategy instance terminated");
+        Ok(Some(instance))
+    }
+}
+
+/// Write the current state of `instance` to [`TABLE`].
+pub async fn save(db: &QuestDbClient, instance: &PaperInstance) -> AnySignalResult<()> {
+    db.write_rows(
+        &db.table_name(TABLE),
+        std::slice::from_ref(instance),
+        insert_strategy_instance,
+    )
+    .await?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::model::instance::Side;
+
+    fn leg() -> Leg {
+        Leg {
+            market: MarketKey::new("HlPerp", "BTC"),
+            side: Side::Long,
+            size: 1.0,
+            entry_price: 100.0,
+            mark_price: 100.0,
+        }
+    }
+
+    #[test]
+    fn allocates_ids_and_closes_own_instances() {
+        let registry = StrategyRegistry::default();
+        registry.lock().loaded = true;
+        let leg = leg();
+        let btc = leg.market.clone();
+        let now = Utc::now();
+        let a = registry
+            .open(1, vec![leg.clone()], now, "a".into())
+            .unwrap();
+        let b = registry.open(2, vec![leg], now, "b".into()).unwrap();
+        assert_eq!((a.id, b.id), (1, 2));
+        assert_eq!(registry.open_instances(1), std::slice::from_ref(&a));
+
+        registry.mark(|m| (*m == btc).then_some(110.0));
+        // another strategy's instance is left alone
+        assert_eq!(registry.close(2, a.id, now, "x".into()), None);
+        let closed = registry.close(1, a.id, now, "exit".into()).unwrap();
+        assert_eq!(closed.status, InstanceStatus::Closed);
+        assert_eq!(closed.pnl(), 10.0);
+        assert!(registry.open_instances(1).is_empty());
+        assert_eq!(registry.close(1, a.id, now, "again".into()), None);
+    }
+
+    #[tokio::test]
+    async fn failed_load_never_allocates_ids() {
+        let mut config = Config::from_env();
+        // nothing listens on port 1
+        config.questdb_addr = "127.0.0.1:1".to_string();
+        config.questdb_pg_addr = "127.0.0.1:1".to_string();
+        config.questdb_spool_dir = String::new();
+        let db = QuestDbClient::new(&config).unwrap();
+
+        let registry = StrategyRegistry::new(&[]);
+        assert!(registry.load_history(&db).await.is_err());
+        assert!(!registry.is_loaded());
+        assert_eq!(registry.open(1, vec![leg()], Utc::now(), "a".into()), None);
+        assert!(registry.instances().is_empty());
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::mpsc::Receiver;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+use super::broker;
+use super::registry::{save, StrategyRegistry};
+use crate::database::registry::SignalRegistry;
+use crate::database::signal_data::signal_query;
+use crate::database::{select_signal_db, QuestDbClient};
+use crate::engine::{Event, MarketKey, MarketState};
+use crate::error::AnySignalResult;
+use crate::model::signal::{Signal, SignalInfo};
+use crate::model::strategy::{Action, Strategy, StrategyEvent};
+use crate::poller::RetryPolicy;
+
+/// Engine events buffered for the runtime; more are dropped (see `main`).
+pub const EVENT_CAPACITY: usize = 1_024;
+
+/// Rows read per signal and query; a poll pages through its window.
+const SIGNAL_POLL_LIMIT: u64 = 1_000;
+
+/// How far back each poll looks, by row timestamp. A day covers daily rows
+/// stamped at midnight (e.g. `fear_and_greed`) and rows stored late.
+const SIGNAL_LOOKBACK: chrono::TimeDelta = chrono::TimeDelta::days(1);
+
+/// Backoff between attempts to load the stored instances; `max_retries` is
+/// unused, loading is retried until it succeeds.
+const LOAD_RETRY: RetryPolicy = RetryPolicy {
+    max_retries: u32::MAX,
+    initial_backoff: Duration::from_secs(1),
+    max_backoff: Duration::from_secs(60),
+};
+
+/// Paper-trades `strategies` against live market state.
+///
+/// Every sealed bucket of the [`crate::engine::MarketEngine`] marks the open
+/// instances and is handed to every strategy; subscribed signals are polled
+/// from QuestDB and every row stamped within [`SIGNAL_LOOKBACK`] is delivered
+/// once, in the order it is found.
+/// Orders fill at the latest `price_mid`, and every instance opened or closed
+/// is written to `strategy_instance`.
+pub struct StrategyRuntime {
+    strategies: Vec<Box<dyn Strategy>>,
+    registry: StrategyRegistry,
+    signals: SignalRegistry,
+    db: QuestDbClient,
+    /// Latest `price_mid` per market.
+    marks: HashMap<MarketKey, f64>,
+    /// Rows delivered that are still within the lookback, per signal id.
+    delivered: HashMap<i64, HashSet<Signal>>,
+    signal_poll_interval: Duration,
+}
+
+impl StrategyRuntime {
+    pub fn new(
+        strategies: Vec<Box<dyn Strategy>>,
+        registry: StrategyRegistry,
+        signals: SignalRegistry,
+        db: QuestDbClient,
+        signal_poll_interval: Duration,
+    ) -> Self {
+        Self {
+            strategies,
+            registry,
+            signals,
+            db,
+            marks: HashMap::new(),
+            delivered: HashMap::new(),
+            signal_poll_interval,
+        }
+    }
+
+    /// Run until the engine's event channel closes.
+    ///
+    /// Strategies start once the stored instances are loaded; until then
+    /// new ids could collide with stored ones.
+    pub async fn run(mut self, mut events: Receiver<Event>) -> AnySignalResult<()> {
+        load_history(&self.registry, &self.db).await;
+        let mut interval = tokio::time::interval(self.signal_poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Some(Event::BucketSealed { states, timestamp, .. }) => {
+                        self.on_market(timestamp, states).await;
+                    }
+                    Some(Event::Snapshot { states, .. }) => self.update_marks(&states),
+                    None => {
+                        info!("market engine event channel closed, stopping strategies");
+                        break;
+                    }
+                },
+                _ = interval.tick() => self.poll_signals().await,
+            }
+        }
+        Ok(())
+    }
+
+    fn update_marks(&mut self, states: &[MarketState]) {
+        for state in states {
+            if let Some(price) = state.price_mid.filter(|p| p.is_finite() && *p > 0.0) {
+                self.marks.insert(state.key(), price);
+            }
+        }
+        let marks = &self.marks;
+        self.registry.mark(|m| marks.get(m).copied());
+    }
+
+    async fn on_market(&mut self, time: DateTime<Utc>, states: Vec<MarketState>) {
+        self.update_marks(&states);
+        let event = StrategyEvent::Market { time, states };
+        for i in 0..self.strategies.len() {
+            self.dispatch(i, &event).await;
+        }
+    }
+
+    /// Deliver the rows of every subscribed signal within the lookback that
+    /// were not delivered yet.
+    async fn poll_signals(&mut self) {
+        let mut subscribed: BTreeMap<i64, (SignalInfo, Vec<usize>)> = BTreeMap::new();
+        for (i, strategy) in self.strategies.iter().enumerate() {
+            for spec in strategy.signals() {
+                // registered by its adapter, possibly after we started
+                let Some(info) = self.signals.find(&spec) else {
+                    continue;
+                };
+                subscribed
+                    .entry(info.id)
+                    .or_insert_with(|| (info, Vec::new()))
+                    .1
+                    .push(i);
+            }
+        }
+        let now = Utc::now();
+        let window = now - SIGNAL_LOOKBACK;
+        for (id, (info, subscribers)) in subscribed {
+            self.delivered
+                .entry(id)
+                .or_default()
+                .retain(|s| s.timestamp_us >= window.timestamp_micros());
+            let mut from = window;
+            loop {
+                let query = signal_query(&self.db, &info)
+                    .time_range("timestamp", from, now)
+                    .limit(SIGNAL_POLL_LIMIT);
+                let rows = match select_signal_db(&self.db, info.data_type, &query).await {
+                    Ok(rows) => rows,
+                    Err(e) => {
+                        warn!(error = %e, signal = id, "strategy signal poll failed");
+                        break;
+                    }
+                };
+                // a full page continues from its last timestamp, which may
+                // have more rows; the ones already seen are skipped
+                let next = rows
+                    .last()
+                    .and_then(|s| DateTime::from_timestamp_micros(s.timestamp_us))
+                    .filter(|last| rows.len() as u64 >= SIGNAL_POLL_LIMIT && *last > from);
+                for signal in rows {
+                    if !self.delivered.entry(id).or_default().insert(signal.clone()) {
+                        continue;
+                    }
+                    let event = StrategyEvent::Signal {
+                        info: info.clone(),
+                        signal,
+                    };
+                    for i in &subscribers {
+                        self.dispatch(*i, &event).await;
+                    }
+                }
+                match next {
+                    Some(last) => from = last,
+                    None => break,
+                }
+            }
+        }
+    }
+
+    /// Hand `event` to strategy `i` and carry out its actions.
+    async fn dispatch(&mut self, i: usize, event: &StrategyEvent) {
+        let strategy_id = self.strategies[i].info().id;
+        let open = self.registry.open_instances(strategy_id);
+        let actions = self.strategies[i].on_event(event, &open);
+        for action in actions {
+            let instance = match action {
+                Action::Open { legs, reason } => {
+                    match broker::fill(&legs, |m| self.marks.get(m).copied()) {
+                        Ok(legs) => self.registry.open(strategy_id, legs, Utc::now(), reason),
+                        Err(e) => {
+                            warn!(strategy_id, error = e.as_str(), "strategy order not filled");
+                            None
+                        }
+                    }
+                }
+                Action::Close {
+                    instance_id,
+                    reason,
+                } => self
+                    .registry
+                    .close(strategy_id, instance_id, Utc::now(), reason),
+            };
+            let Some(instance) = instance else {
+                continue;
+            };
+            info!(
+                strategy_id,
+                id = instance.id,
+                status = instance.status.name(),
+                pnl = instance.pnl(),
+                reason = instance.reason.as_str(),
+                "strategy instance updated"
+            );
+            if let Err(e) = save(&self.db, &instance).await {
+                warn!(error = %e, id = instance.id, "strategy instance not stored");
+            }
+        }
+    }
+}
+
+/// Load the history of `registry`, retrying with [`LOAD_RETRY`] backoff.
+async fn load_history(registry: &StrategyRegistry, db: &QuestDbClient) {
+    let mut attempt = 0;
+    while !registry.is_loaded() {
+        match registry.load_history(db).await {
+            Ok(()) => info!(
+                instances = registry.instances().len(),
+                "strategy instances loaded"
+            ),
+            Err(e) => {
+                let backoff = LOAD_RETRY.backoff(attempt);
+                warn!(error = %e, ?backoff, "strategy instances not loaded, retrying");
+                tokio::time::sleep(backoff).await;
+                attempt = attempt.saturating_add(1);
+            }
+        }
+    }
+}